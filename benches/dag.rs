@@ -1,41 +1,146 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-
-// Placeholder: Define or import your DAG structures and functions here
-// For example:
-// use icn_runtime::dag::Dag; // Assuming Dag is in icn_runtime
-
-fn bench_dag_node_creation(c: &mut Criterion) {
-    c.bench_function("dag_node_create", |b| {
-        b.iter(|| {
-            // Placeholder: Replace with actual DAG node creation logic
-            // let node = Dag::new_node(black_box(some_data));
-            black_box(0); // Replace with your actual operation
-        })
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use icn_core_types::DidKey;
+use icn_types::dag::memory::MemoryDagStore;
+use icn_types::dag::{DagNodeBuilder, DagPayload, DagStore, SharedDagStore, SignedDagNode};
+use icn_types::Cid;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use tokio::sync::Mutex;
+
+type MutexStore = Arc<Mutex<Box<dyn DagStore + Send + Sync>>>;
+
+/// Nodes in the pre-populated DAG
+const DAG_SIZE: usize = 1_000;
+/// Reads performed by each concurrent reader per iteration
+const READS_PER_TASK: usize = 200;
+
+fn signed_node(key: &DidKey, parents: Vec<Cid>, seq: usize) -> SignedDagNode {
+    let node = DagNodeBuilder::new()
+        .with_payload(DagPayload::Raw(seq.to_be_bytes().to_vec()))
+        .with_parents(parents)
+        .with_author(key.did().clone())
+        .with_federation_id("bench-federation".to_string())
+        .build()
+        .expect("Failed to build node");
+    let node_bytes = serde_ipld_dagcbor::to_vec(&node).unwrap();
+    SignedDagNode {
+        signature: key.sign(&node_bytes),
+        node,
+        cid: None,
+    }
+}
+
+/// Build an in-memory store holding a chain of `DAG_SIZE` nodes
+fn populated_store(rt: &Runtime) -> (MemoryDagStore, Arc<Vec<Cid>>) {
+    let key = DidKey::new();
+    let mut store = MemoryDagStore::new();
+    let cids = rt.block_on(async {
+        let mut cids: Vec<Cid> = Vec::with_capacity(DAG_SIZE);
+        for seq in 0..DAG_SIZE {
+            let parents = cids.last().cloned().into_iter().collect();
+            cids.push(store.add_node(signed_node(&key, parents, seq)).await.unwrap());
+        }
+        cids
     });
+    (store, Arc::new(cids))
+}
+
+async fn concurrent_reads(store: &SharedDagStore, cids: &Arc<Vec<Cid>>, readers: usize) {
+    let tasks: Vec<_> = (0..readers)
+        .map(|reader| {
+            let store = store.clone();
+            let cids = Arc::clone(cids);
+            tokio::spawn(async move {
+                for i in 0..READS_PER_TASK {
+                    let cid = &cids[(reader * 31 + i * 7) % cids.len()];
+                    black_box(store.get_node(cid).await.unwrap());
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
 }
 
-fn bench_dag_traversal(c: &mut Criterion) {
-    // Placeholder: Set up a DAG for traversal
-    // let dag = /* setup_some_dag() */ ;
-    c.bench_function("dag_traverse", |b| {
-        b.iter(|| {
-            // Placeholder: Replace with actual DAG traversal logic
-            // dag.traverse(black_box(start_node_cid));
-            black_box(0); // Replace with your actual operation
+/// Same workload behind a single Mutex, as SharedDagStore used to be, for comparison
+async fn concurrent_reads_mutex(store: &MutexStore, cids: &Arc<Vec<Cid>>, readers: usize) {
+    let tasks: Vec<_> = (0..readers)
+        .map(|reader| {
+            let store = Arc::clone(store);
+            let cids = Arc::clone(cids);
+            tokio::spawn(async move {
+                for i in 0..READS_PER_TASK {
+                    let cid = &cids[(reader * 31 + i * 7) % cids.len()];
+                    let guard = store.lock().await;
+                    black_box(guard.get_node(cid).await.unwrap());
+                }
+            })
         })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+/// Read throughput of `get_node` with an increasing number of concurrent readers
+fn bench_concurrent_reads(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let (memory_store, cids) = populated_store(&rt);
+    let shared = SharedDagStore::new(Box::new(memory_store.clone()));
+    let mutex: MutexStore = Arc::new(Mutex::new(Box::new(memory_store)));
+
+    let mut group = c.benchmark_group("dag_concurrent_reads");
+    for readers in [1usize, 2, 4, 8, 16] {
+        group.throughput(Throughput::Elements((readers * READS_PER_TASK) as u64));
+        group.bench_with_input(BenchmarkId::new("shared_rwlock", readers), &readers, |b, &readers| {
+            b.iter(|| rt.block_on(concurrent_reads(&shared, &cids, readers)));
+        });
+        group.bench_with_input(BenchmarkId::new("mutex_baseline", readers), &readers, |b, &readers| {
+            b.iter(|| rt.block_on(concurrent_reads_mutex(&mutex, &cids, readers)));
+        });
+    }
+    group.finish();
+}
+
+/// Read throughput while a writer keeps appending to the DAG
+fn bench_reads_with_writer(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let (memory_store, cids) = populated_store(&rt);
+    let store = SharedDagStore::new(Box::new(memory_store));
+    let key = DidKey::new();
+
+    let mut group = c.benchmark_group("dag_reads_with_writer");
+    for readers in [1usize, 4, 16] {
+        group.throughput(Throughput::Elements((readers * READS_PER_TASK) as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(readers), &readers, |b, &readers| {
+            let mut seq = DAG_SIZE;
+            b.iter(|| {
+                rt.block_on(async {
+                    let writer_store = store.clone();
+                    let parent = cids[seq % cids.len()].clone();
+                    let node = signed_node(&key, vec![parent], seq);
+                    seq += 1;
+                    let writer = tokio::spawn(async move { writer_store.add_node(node).await.unwrap() });
+                    concurrent_reads(&store, &cids, readers).await;
+                    writer.await.unwrap();
+                })
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Tip lookups, which every scheduler round performs
+fn bench_get_tips(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let (memory_store, _cids) = populated_store(&rt);
+    let store = SharedDagStore::new(Box::new(memory_store));
+
+    c.bench_function("dag_get_tips", |b| {
+        b.iter(|| rt.block_on(async { black_box(store.get_tips().await.unwrap()) }));
     });
 }
 
-// Placeholder: The prompt also mentioned "Quorum verification"
-// fn bench_quorum_verification(c: &mut Criterion) {
-//     // Placeholder: Set up data for quorum verification
-//     c.bench_function("quorum_verify", |b| {
-//         b.iter(|| {
-//             // Placeholder: Replace with actual quorum verification logic
-//             black_box(0); // Replace with your actual operation
-//         })
-//     });
-// }
-
-criterion_group!(benches, bench_dag_node_creation, bench_dag_traversal /*, bench_quorum_verification */);
-criterion_main!(benches); 
\ No newline at end of file
+criterion_group!(benches, bench_concurrent_reads, bench_reads_with_writer, bench_get_tips);
+criterion_main!(benches);
@@ -0,0 +1,87 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use icn_core_types::DidKey;
+use icn_types::dag::{DagNodeBuilder, DagPayload, DagStore, SignedDagNode};
+use icn_types::{Cid, RocksDbDagStore};
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::runtime::Runtime;
+
+/// Distinct authors in the imported DAG, as in a small federation export
+const AUTHORS: usize = 16;
+
+/// Build a signed DAG of `size` nodes: a chain where every 8th node also merges in
+/// the node 8 steps back, so imports exercise multi-parent tip updates.
+fn export(size: usize) -> Vec<SignedDagNode> {
+    let keys: Vec<DidKey> = (0..AUTHORS).map(|_| DidKey::new()).collect();
+    let mut cids: Vec<Cid> = Vec::with_capacity(size);
+    let mut nodes = Vec::with_capacity(size);
+    for seq in 0..size {
+        let key = &keys[seq % AUTHORS];
+        let mut parents: Vec<Cid> = cids.last().cloned().into_iter().collect();
+        if seq % 8 == 0 && seq >= 8 {
+            parents.push(cids[seq - 8].clone());
+        }
+        let node = DagNodeBuilder::new()
+            .with_payload(DagPayload::Raw(seq.to_be_bytes().to_vec()))
+            .with_parents(parents)
+            .with_author(key.did().clone())
+            .with_federation_id("bench-federation".to_string())
+            .build()
+            .expect("Failed to build node");
+        let node_bytes = serde_ipld_dagcbor::to_vec(&node).unwrap();
+        let signed = SignedDagNode {
+            signature: key.sign(&node_bytes),
+            node,
+            cid: None,
+        };
+        cids.push(signed.calculate_cid().unwrap());
+        nodes.push(signed);
+    }
+    nodes
+}
+
+fn empty_store() -> (TempDir, RocksDbDagStore) {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let store = RocksDbDagStore::open(dir.path()).expect("Failed to open RocksDB store");
+    (dir, store)
+}
+
+/// Importing an export node by node versus in a single `add_nodes` batch.
+///
+/// The per-node path rewrites the author and payload-type index lists on every
+/// insert, so expect it to take minutes per iteration at 100k nodes.
+fn bench_import(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("dag_rocksdb_import");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(60));
+    for size in [10_000usize, 100_000] {
+        let nodes = export(size);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::new("add_node", size), &nodes, |b, nodes| {
+            b.iter_batched(
+                || (empty_store(), nodes.clone()),
+                |((_dir, mut store), nodes)| {
+                    rt.block_on(async {
+                        for node in nodes {
+                            store.add_node(node).await.unwrap();
+                        }
+                    })
+                },
+                BatchSize::PerIteration,
+            );
+        });
+        group.bench_with_input(BenchmarkId::new("add_nodes", size), &nodes, |b, nodes| {
+            b.iter_batched(
+                || (empty_store(), nodes.clone()),
+                |((_dir, mut store), nodes)| rt.block_on(async { store.add_nodes(nodes).await.unwrap() }),
+                BatchSize::PerIteration,
+            );
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_import);
+criterion_main!(benches);
@@ -0,0 +1,313 @@
+//! Semantic analysis of CCL statements before lowering.
+//!
+//! Resolves every name against its lexical scope, type checks statements and
+//! expressions, and checks the ranges of literals and of amounts that are known
+//! at compile time. Problems are collected as located `Diagnostic`s rather than
+//! stopping at the first, so an author can fix them in one pass.
+
+use crate::policy::parse_did;
+use icn_ccl_parser::{BinaryOp, CclExpr, CclStmt, Diagnostic, Span, Spanned, UnaryOp};
+use std::collections::HashMap;
+
+/// Resource types the runtime ledger knows by name; any other name is metered
+/// as a custom resource. Mirrors `ResourceType::from_str` in `icn-economics`.
+pub const RESOURCE_TYPES: &[&str] = &["compute_unit", "storage_mb", "bandwidth_mb", "gpu_minute", "governance_point", "credit"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ty {
+    Int,
+    Bool,
+    /// Builtins called for their effect, such as `log`
+    Unit,
+    /// An expression that has already been reported, so it raises no further errors
+    Error,
+}
+
+impl Ty {
+    fn name(self) -> &'static str {
+        match self {
+            Ty::Int => "int",
+            Ty::Bool => "bool",
+            Ty::Unit => "()",
+            Ty::Error => "{error}",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Binding {
+    ty: Ty,
+    mutable: bool,
+}
+
+/// Check a sequence of statements that runs as one program, such as the top
+/// level of a file or a proposal's `on_pass` body
+pub(crate) fn check_stmts(stmts: &[CclStmt]) -> Vec<Diagnostic> {
+    let mut checker = Checker { scopes: Vec::new(), diagnostics: Vec::new() };
+    checker.block(stmts);
+    checker.diagnostics
+}
+
+struct Checker {
+    scopes: Vec<HashMap<String, Binding>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker {
+    fn block(&mut self, stmts: &[CclStmt]) {
+        self.scopes.push(HashMap::new());
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+        self.scopes.pop();
+    }
+
+    fn stmt(&mut self, stmt: &CclStmt) {
+        match stmt {
+            CclStmt::PerformMeteredAction { resource, amount } => {
+                self.resource_type(resource);
+                self.amount(amount, "amount", i64::MAX as i128);
+            }
+            CclStmt::MintToken { token, amount } => {
+                self.resource_type(token);
+                self.amount(amount, "amount", i64::MAX as i128);
+            }
+            CclStmt::TransferResource { token, to, amount } => {
+                self.resource_type(token);
+                if let Err(message) = parse_did(to) {
+                    self.error(message, Some(to.span), "transfers must go to a did:key DID");
+                }
+                self.amount(amount, "amount", i64::MAX as i128);
+            }
+            CclStmt::AnchorData { bytes, .. } => {
+                // The byte count is passed to the host as an i32
+                self.amount(bytes, "bytes", i32::MAX as i128);
+            }
+            CclStmt::Let { name, value } => {
+                let ty = self.value(value);
+                self.scopes
+                    .last_mut()
+                    .expect("INTERNAL_ERROR: no open scope")
+                    .insert(name.node.clone(), Binding { ty, mutable: true });
+            }
+            CclStmt::Assign { name, value } => {
+                let Some(binding) = self.lookup(name, name.span) else {
+                    self.expr(value);
+                    return;
+                };
+                if !binding.mutable {
+                    self.error(
+                        format!("cannot assign to loop variable '{}'", name),
+                        Some(name.span),
+                        "loop variables are fixed for each iteration",
+                    );
+                }
+                self.expect(value, binding.ty, &format!("assignment to '{}'", name));
+            }
+            CclStmt::If { cond, then_body, else_body } => {
+                self.expect(cond, Ty::Bool, "if condition");
+                self.block(then_body);
+                self.block(else_body);
+            }
+            CclStmt::For { var, start, end, body } => {
+                self.expect(start, Ty::Int, "loop start");
+                self.expect(end, Ty::Int, "loop end");
+                self.scopes.push(HashMap::from([(var.node.clone(), Binding { ty: Ty::Int, mutable: false })]));
+                self.block(body);
+                self.scopes.pop();
+            }
+            CclStmt::Expr(expr) => {
+                self.expr(expr);
+            }
+        }
+    }
+
+    fn resource_type(&mut self, resource: &Spanned<String>) {
+        if !RESOURCE_TYPES.contains(&resource.as_str()) {
+            let diagnostic = Diagnostic::warning(format!("unknown resource type '{}'", resource), Some(resource.span))
+                .with_label("metered as a custom resource")
+                .with_note(format!("known resource types are {}", RESOURCE_TYPES.join(", ")));
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    /// An `int` between zero and `max` wherever its value is known at compile time
+    fn amount(&mut self, expr: &CclExpr, what: &str, max: i128) {
+        if self.expect(expr, Ty::Int, what) != Ty::Int {
+            return;
+        }
+        match constant(expr) {
+            Some(value) if value < 0 => {
+                self.error(format!("{} must not be negative, but '{}' is {}", what, expr, value), expr.span(), "negative");
+            }
+            Some(value) if value > max => {
+                self.error(
+                    format!("{} must be at most {}, but '{}' is {}", what, max, expr, value),
+                    expr.span(),
+                    "out of range",
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// Check an expression that must produce a value
+    fn value(&mut self, expr: &CclExpr) -> Ty {
+        match self.expr(expr) {
+            Ty::Unit => {
+                self.error(format!("'{}' does not produce a value", expr), expr.span(), "has no value");
+                Ty::Error
+            }
+            ty => ty,
+        }
+    }
+
+    /// Check that `expr` has type `expected`, returning its type
+    fn expect(&mut self, expr: &CclExpr, expected: Ty, what: &str) -> Ty {
+        let ty = self.expr(expr);
+        if ty != expected && ty != Ty::Error && expected != Ty::Error {
+            self.error(
+                format!("{} must be {}, but '{}' is {}", what, expected.name(), expr, ty.name()),
+                expr.span(),
+                format!("expected {}", expected.name()),
+            );
+            return Ty::Error;
+        }
+        ty
+    }
+
+    fn expr(&mut self, expr: &CclExpr) -> Ty {
+        self.expr_at(expr, None)
+    }
+
+    /// `span` is that of the nearest enclosing `Located` node
+    fn expr_at(&mut self, expr: &CclExpr, span: Option<Span>) -> Ty {
+        match expr {
+            CclExpr::Located { span, expr } => self.expr_at(expr, Some(*span)),
+            CclExpr::Int(value) => {
+                if *value > i64::MAX as u128 {
+                    self.error(format!("integer {} does not fit in 64 bits", value), span, "out of range");
+                    return Ty::Error;
+                }
+                Ty::Int
+            }
+            CclExpr::Bool(_) => Ty::Bool,
+            CclExpr::Str(_) => {
+                self.error(format!("string {} can only be passed to a builtin", expr), span, "not allowed here");
+                Ty::Error
+            }
+            CclExpr::Var(name) => self.lookup(name, span.unwrap_or_default()).map_or(Ty::Error, |binding| binding.ty),
+            CclExpr::Unary { op: UnaryOp::Neg, expr: operand } => {
+                self.expect(operand, Ty::Int, "operand of '-'");
+                Ty::Int
+            }
+            CclExpr::Unary { op: UnaryOp::Not, expr: operand } => {
+                self.expect(operand, Ty::Bool, "operand of '!'");
+                Ty::Bool
+            }
+            CclExpr::Binary { op: op @ (BinaryOp::And | BinaryOp::Or), lhs, rhs } => {
+                let what = format!("operand of '{}'", op.symbol());
+                self.expect(lhs, Ty::Bool, &what);
+                self.expect(rhs, Ty::Bool, &what);
+                Ty::Bool
+            }
+            CclExpr::Binary { op, lhs, rhs } => {
+                let ty = self.value(lhs);
+                if self.expect(rhs, ty, &format!("right operand of '{}'", op.symbol())) == Ty::Error || ty == Ty::Error {
+                    return Ty::Error;
+                }
+                if matches!(op, BinaryOp::Div | BinaryOp::Rem) && constant(rhs) == Some(0) {
+                    self.error(format!("division by zero in '{}'", expr), rhs.span(), "this is zero");
+                }
+                match (op, ty) {
+                    (BinaryOp::Eq | BinaryOp::Ne, Ty::Int | Ty::Bool) => Ty::Bool,
+                    (BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge, Ty::Int) => Ty::Bool,
+                    (BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem, Ty::Int) => Ty::Int,
+                    _ => {
+                        self.error(
+                            format!("operator '{}' cannot be applied to {} in '{}'", op.symbol(), ty.name(), expr),
+                            span,
+                            format!("not defined for {}", ty.name()),
+                        );
+                        Ty::Error
+                    }
+                }
+            }
+            CclExpr::Call { name, args } => self.call(name, args, span),
+        }
+    }
+
+    /// Builtins: `log(string)`, `min(int, int)`, `max(int, int)` and `abs(int)`
+    fn call(&mut self, name: &str, args: &[CclExpr], span: Option<Span>) -> Ty {
+        let arity = match name {
+            "log" | "abs" => 1,
+            "min" | "max" => 2,
+            _ => {
+                self.error(format!("unknown function '{}'", name), span, "not a builtin");
+                args.iter().for_each(|arg| {
+                    self.expr(arg);
+                });
+                return Ty::Error;
+            }
+        };
+        if args.len() != arity {
+            self.error(
+                format!("'{}' takes {} argument(s), got {}", name, arity, args.len()),
+                span,
+                format!("expected {} argument(s)", arity),
+            );
+            return Ty::Error;
+        }
+
+        if name == "log" {
+            if !matches!(args[0].unlocated(), CclExpr::Str(_)) {
+                self.error(format!("'log' takes a string literal, got '{}'", args[0]), args[0].span(), "expected a string");
+            }
+            return Ty::Unit;
+        }
+        let what = format!("argument of '{}'", name);
+        for arg in args {
+            self.expect(arg, Ty::Int, &what);
+        }
+        Ty::Int
+    }
+
+    fn lookup(&mut self, name: &str, span: Span) -> Option<Binding> {
+        let binding = self.scopes.iter().rev().find_map(|scope| scope.get(name).copied());
+        if binding.is_none() {
+            self.error(format!("unknown variable '{}'", name), Some(span), "not defined in this scope");
+        }
+        binding
+    }
+
+    fn error(&mut self, message: String, span: Option<Span>, label: impl Into<String>) {
+        self.diagnostics.push(Diagnostic::error(message, span).with_label(label));
+    }
+}
+
+/// Value of an integer expression made only of literals and builtins, if it
+/// has one; `None` also covers overflow and division by zero
+fn constant(expr: &CclExpr) -> Option<i128> {
+    match expr.unlocated() {
+        CclExpr::Int(value) => i128::try_from(*value).ok(),
+        CclExpr::Unary { op: UnaryOp::Neg, expr } => constant(expr)?.checked_neg(),
+        CclExpr::Binary { op, lhs, rhs } => {
+            let (lhs, rhs) = (constant(lhs)?, constant(rhs)?);
+            match op {
+                BinaryOp::Add => lhs.checked_add(rhs),
+                BinaryOp::Sub => lhs.checked_sub(rhs),
+                BinaryOp::Mul => lhs.checked_mul(rhs),
+                BinaryOp::Div => lhs.checked_div(rhs),
+                BinaryOp::Rem => lhs.checked_rem(rhs),
+                _ => None,
+            }
+        }
+        CclExpr::Call { name, args } => match (name.as_str(), args.as_slice()) {
+            ("abs", [x]) => constant(x)?.checked_abs(),
+            ("min", [a, b]) => Some(constant(a)?.min(constant(b)?)),
+            ("max", [a, b]) => Some(constant(a)?.max(constant(b)?)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,397 @@
+//! Lowering of CCL statements and expressions into the body of `_start`.
+//!
+//! Programs are expected to have passed `check` first; the errors raised here
+//! only guard against lowering an unchecked AST.
+//!
+//! Integers are `i64` and booleans `i32`. Arithmetic wraps and division by zero
+//! traps, as in Wasm. Every `let` gets its own local, and `for` loops evaluate
+//! both bounds once and do not allow assignment to the loop variable, so every
+//! loop runs a number of iterations fixed at entry.
+
+use crate::{CompileError, StringPool, HOST_ANCHOR_TO_DAG, HOST_CHECK_RESOURCE_AUTHORIZATION, HOST_LOG_MESSAGE, HOST_RECORD_RESOURCE_USAGE, TOKEN_CREDIT, TOKEN_TRANSFER};
+use icn_ccl_parser::{BinaryOp, CclExpr, CclStmt, UnaryOp};
+use std::collections::HashMap;
+use wasm_encoder::{BlockType, Function, Instruction, ValType};
+
+/// Type of a lowered CCL expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ty {
+    Int,
+    Bool,
+    /// Builtins called for their effect, such as `log`
+    Unit,
+}
+
+impl Ty {
+    fn name(self) -> &'static str {
+        match self {
+            Ty::Int => "int",
+            Ty::Bool => "bool",
+            Ty::Unit => "()",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Binding {
+    local: u32,
+    ty: Ty,
+    mutable: bool,
+}
+
+/// Builds a single function body, tracking its locals and lexical scopes
+pub(crate) struct FunctionBuilder<'p> {
+    pool: &'p mut StringPool,
+    locals: Vec<ValType>,
+    scopes: Vec<HashMap<String, Binding>>,
+    body: Vec<Instruction<'static>>,
+}
+
+impl<'p> FunctionBuilder<'p> {
+    pub(crate) fn new(pool: &'p mut StringPool) -> Self {
+        Self {
+            pool,
+            locals: Vec::new(),
+            scopes: vec![HashMap::new()],
+            body: Vec::new(),
+        }
+    }
+
+    pub(crate) fn finish(self) -> Function {
+        let mut f = Function::new(self.locals.into_iter().map(|ty| (1, ty)));
+        for instruction in &self.body {
+            f.instruction(instruction);
+        }
+        f.instruction(&Instruction::End);
+        f
+    }
+
+    pub(crate) fn lower_block(&mut self, stmts: &[CclStmt]) -> Result<(), CompileError> {
+        self.scopes.push(HashMap::new());
+        let result = stmts.iter().try_for_each(|stmt| self.lower_stmt(stmt));
+        self.scopes.pop();
+        result
+    }
+
+    fn lower_stmt(&mut self, stmt: &CclStmt) -> Result<(), CompileError> {
+        match stmt {
+            CclStmt::PerformMeteredAction { resource, amount } => {
+                self.log(&format!("PerformMeteredAction for resource: {}", resource));
+
+                self.expect(amount, Ty::Int, "amount")?;
+                let amount_local = self.local(ValType::I64);
+                self.emit(Instruction::LocalSet(amount_local));
+
+                let (tok_ptr, tok_len) = self.pool.intern(resource);
+                self.emit(Instruction::I32Const(tok_ptr as i32));
+                self.emit(Instruction::I32Const(tok_len as i32));
+                self.emit(Instruction::LocalGet(amount_local));
+                self.emit(Instruction::Call(HOST_CHECK_RESOURCE_AUTHORIZATION));
+                self.emit(Instruction::Drop);
+
+                self.emit(Instruction::I32Const(tok_ptr as i32));
+                self.emit(Instruction::I32Const(tok_len as i32));
+                self.emit(Instruction::LocalGet(amount_local));
+                self.emit(Instruction::Call(HOST_RECORD_RESOURCE_USAGE));
+            }
+            CclStmt::MintToken { token, amount } => {
+                self.log(&format!("MintToken: {}", token));
+
+                let (tok_ptr, tok_len) = self.pool.intern(token);
+                self.emit(Instruction::I32Const(tok_ptr as i32));
+                self.emit(Instruction::I32Const(tok_len as i32));
+                self.expect(amount, Ty::Int, "amount")?;
+                self.emit(Instruction::Call(TOKEN_CREDIT));
+                self.trap_on_error();
+            }
+            CclStmt::TransferResource { token, to, amount } => {
+                self.log(&format!("TransferResource: {} to {}", token, to));
+
+                let (tok_ptr, tok_len) = self.pool.intern(token);
+                let (to_ptr, to_len) = self.pool.intern(to);
+                self.emit(Instruction::I32Const(tok_ptr as i32));
+                self.emit(Instruction::I32Const(tok_len as i32));
+                self.emit(Instruction::I32Const(to_ptr as i32));
+                self.emit(Instruction::I32Const(to_len as i32));
+                self.expect(amount, Ty::Int, "amount")?;
+                self.emit(Instruction::Call(TOKEN_TRANSFER));
+                self.trap_on_error();
+            }
+            CclStmt::AnchorData { cid, bytes } => {
+                self.log(&format!("AnchorData for cid: {}", cid));
+
+                let (cid_ptr, cid_len) = self.pool.intern(cid);
+                self.emit(Instruction::I32Const(cid_ptr as i32));
+                self.emit(Instruction::I32Const(cid_len as i32));
+                self.expect(bytes, Ty::Int, "bytes")?;
+                self.emit(Instruction::I32WrapI64);
+                self.emit(Instruction::Call(HOST_ANCHOR_TO_DAG));
+            }
+            CclStmt::Let { name, value } => {
+                let ty = self.lower_value(value)?;
+                let local = self.local(val_type(ty));
+                self.emit(Instruction::LocalSet(local));
+                self.bind(name, Binding { local, ty, mutable: true });
+            }
+            CclStmt::Assign { name, value } => {
+                let binding = self.lookup(name)?;
+                if !binding.mutable {
+                    return Err(lowering(format!("cannot assign to loop variable '{}'", name)));
+                }
+                self.expect(value, binding.ty, &format!("assignment to '{}'", name))?;
+                self.emit(Instruction::LocalSet(binding.local));
+            }
+            CclStmt::If { cond, then_body, else_body } => {
+                self.expect(cond, Ty::Bool, "if condition")?;
+                self.emit(Instruction::If(BlockType::Empty));
+                self.lower_block(then_body)?;
+                if !else_body.is_empty() {
+                    self.emit(Instruction::Else);
+                    self.lower_block(else_body)?;
+                }
+                self.emit(Instruction::End);
+            }
+            CclStmt::For { var, start, end, body } => {
+                self.expect(start, Ty::Int, "loop start")?;
+                let counter = self.local(ValType::I64);
+                self.emit(Instruction::LocalSet(counter));
+                self.expect(end, Ty::Int, "loop end")?;
+                let limit = self.local(ValType::I64);
+                self.emit(Instruction::LocalSet(limit));
+
+                self.emit(Instruction::Block(BlockType::Empty));
+                self.emit(Instruction::Loop(BlockType::Empty));
+                self.emit(Instruction::LocalGet(counter));
+                self.emit(Instruction::LocalGet(limit));
+                self.emit(Instruction::I64GeS);
+                self.emit(Instruction::BrIf(1));
+
+                self.scopes.push(HashMap::new());
+                self.bind(var, Binding { local: counter, ty: Ty::Int, mutable: false });
+                let result = self.lower_block(body);
+                self.scopes.pop();
+                result?;
+
+                self.emit(Instruction::LocalGet(counter));
+                self.emit(Instruction::I64Const(1));
+                self.emit(Instruction::I64Add);
+                self.emit(Instruction::LocalSet(counter));
+                self.emit(Instruction::Br(0));
+                self.emit(Instruction::End);
+                self.emit(Instruction::End);
+            }
+            CclStmt::Expr(expr) => {
+                if self.lower_expr(expr)? != Ty::Unit {
+                    self.emit(Instruction::Drop);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Lower an expression that must produce a value
+    fn lower_value(&mut self, expr: &CclExpr) -> Result<Ty, CompileError> {
+        match self.lower_expr(expr)? {
+            Ty::Unit => Err(lowering(format!("'{}' does not produce a value", expr))),
+            ty => Ok(ty),
+        }
+    }
+
+    fn expect(&mut self, expr: &CclExpr, expected: Ty, what: &str) -> Result<(), CompileError> {
+        let ty = self.lower_expr(expr)?;
+        if ty != expected {
+            return Err(lowering(format!(
+                "{} must be {}, but '{}' is {}",
+                what,
+                expected.name(),
+                expr,
+                ty.name()
+            )));
+        }
+        Ok(())
+    }
+
+    fn lower_expr(&mut self, expr: &CclExpr) -> Result<Ty, CompileError> {
+        match expr {
+            CclExpr::Int(value) => {
+                let value = i64::try_from(*value)
+                    .map_err(|_| lowering(format!("integer {} does not fit in 64 bits", value)))?;
+                self.emit(Instruction::I64Const(value));
+                Ok(Ty::Int)
+            }
+            CclExpr::Bool(value) => {
+                self.emit(Instruction::I32Const(*value as i32));
+                Ok(Ty::Bool)
+            }
+            CclExpr::Str(_) => Err(lowering(format!("string {} can only be passed to a builtin", expr))),
+            CclExpr::Var(name) => {
+                let binding = self.lookup(name)?;
+                self.emit(Instruction::LocalGet(binding.local));
+                Ok(binding.ty)
+            }
+            CclExpr::Unary { op: UnaryOp::Neg, expr: operand } => {
+                self.emit(Instruction::I64Const(0));
+                self.expect(operand, Ty::Int, "operand of '-'")?;
+                self.emit(Instruction::I64Sub);
+                Ok(Ty::Int)
+            }
+            CclExpr::Unary { op: UnaryOp::Not, expr: operand } => {
+                self.expect(operand, Ty::Bool, "operand of '!'")?;
+                self.emit(Instruction::I32Eqz);
+                Ok(Ty::Bool)
+            }
+            CclExpr::Binary { op: op @ (BinaryOp::And | BinaryOp::Or), lhs, rhs } => {
+                // Short-circuit: the right operand only runs when it decides the result
+                let what = format!("operand of '{}'", op.symbol());
+                self.expect(lhs, Ty::Bool, &what)?;
+                self.emit(Instruction::If(BlockType::Result(ValType::I32)));
+                if *op == BinaryOp::And {
+                    self.expect(rhs, Ty::Bool, &what)?;
+                    self.emit(Instruction::Else);
+                    self.emit(Instruction::I32Const(0));
+                } else {
+                    self.emit(Instruction::I32Const(1));
+                    self.emit(Instruction::Else);
+                    self.expect(rhs, Ty::Bool, &what)?;
+                }
+                self.emit(Instruction::End);
+                Ok(Ty::Bool)
+            }
+            CclExpr::Binary { op, lhs, rhs } => {
+                let ty = self.lower_value(lhs)?;
+                self.expect(rhs, ty, &format!("right operand of '{}'", op.symbol()))?;
+                let (instruction, result) = match (op, ty) {
+                    (BinaryOp::Eq, Ty::Int) => (Instruction::I64Eq, Ty::Bool),
+                    (BinaryOp::Ne, Ty::Int) => (Instruction::I64Ne, Ty::Bool),
+                    (BinaryOp::Eq, Ty::Bool) => (Instruction::I32Eq, Ty::Bool),
+                    (BinaryOp::Ne, Ty::Bool) => (Instruction::I32Ne, Ty::Bool),
+                    (BinaryOp::Lt, Ty::Int) => (Instruction::I64LtS, Ty::Bool),
+                    (BinaryOp::Le, Ty::Int) => (Instruction::I64LeS, Ty::Bool),
+                    (BinaryOp::Gt, Ty::Int) => (Instruction::I64GtS, Ty::Bool),
+                    (BinaryOp::Ge, Ty::Int) => (Instruction::I64GeS, Ty::Bool),
+                    (BinaryOp::Add, Ty::Int) => (Instruction::I64Add, Ty::Int),
+                    (BinaryOp::Sub, Ty::Int) => (Instruction::I64Sub, Ty::Int),
+                    (BinaryOp::Mul, Ty::Int) => (Instruction::I64Mul, Ty::Int),
+                    (BinaryOp::Div, Ty::Int) => (Instruction::I64DivS, Ty::Int),
+                    (BinaryOp::Rem, Ty::Int) => (Instruction::I64RemS, Ty::Int),
+                    _ => {
+                        return Err(lowering(format!(
+                            "operator '{}' cannot be applied to {} in '{}'",
+                            op.symbol(),
+                            ty.name(),
+                            expr
+                        )))
+                    }
+                };
+                self.emit(instruction);
+                Ok(result)
+            }
+            CclExpr::Call { name, args } => self.lower_call(name, args),
+            CclExpr::Located { expr, .. } => self.lower_expr(expr),
+        }
+    }
+
+    /// Builtins: `log(string)`, `min(int, int)`, `max(int, int)` and `abs(int)`
+    fn lower_call(&mut self, name: &str, args: &[CclExpr]) -> Result<Ty, CompileError> {
+        let arity = match name {
+            "log" | "abs" => 1,
+            "min" | "max" => 2,
+            _ => return Err(lowering(format!("unknown function '{}'", name))),
+        };
+        if args.len() != arity {
+            return Err(lowering(format!("'{}' takes {} argument(s), got {}", name, arity, args.len())));
+        }
+
+        match name {
+            "log" => match args[0].unlocated() {
+                CclExpr::Str(message) => {
+                    self.log(message);
+                    Ok(Ty::Unit)
+                }
+                other => Err(lowering(format!("'log' takes a string literal, got '{}'", other))),
+            },
+            "abs" => {
+                // select(-x, x, x < 0)
+                self.expect(&args[0], Ty::Int, "argument of 'abs'")?;
+                let x = self.local(ValType::I64);
+                self.emit(Instruction::LocalSet(x));
+                self.emit(Instruction::I64Const(0));
+                self.emit(Instruction::LocalGet(x));
+                self.emit(Instruction::I64Sub);
+                self.emit(Instruction::LocalGet(x));
+                self.emit(Instruction::LocalGet(x));
+                self.emit(Instruction::I64Const(0));
+                self.emit(Instruction::I64LtS);
+                self.emit(Instruction::Select);
+                Ok(Ty::Int)
+            }
+            _ => {
+                // select(a, b, a < b) for min, select(a, b, a > b) for max
+                let what = format!("argument of '{}'", name);
+                self.expect(&args[0], Ty::Int, &what)?;
+                let a = self.local(ValType::I64);
+                self.emit(Instruction::LocalSet(a));
+                self.expect(&args[1], Ty::Int, &what)?;
+                let b = self.local(ValType::I64);
+                self.emit(Instruction::LocalSet(b));
+                self.emit(Instruction::LocalGet(a));
+                self.emit(Instruction::LocalGet(b));
+                self.emit(Instruction::LocalGet(a));
+                self.emit(Instruction::LocalGet(b));
+                self.emit(if name == "min" { Instruction::I64LtS } else { Instruction::I64GtS });
+                self.emit(Instruction::Select);
+                Ok(Ty::Int)
+            }
+        }
+    }
+
+    fn log(&mut self, message: &str) {
+        let (msg_ptr, msg_len) = self.pool.intern(message);
+        self.emit(Instruction::I32Const(msg_ptr as i32));
+        self.emit(Instruction::I32Const(msg_len as i32));
+        self.emit(Instruction::Call(HOST_LOG_MESSAGE));
+    }
+
+    /// Trap if the ledger call just made returned an error code, as failing to
+    /// record metered usage does
+    fn trap_on_error(&mut self) {
+        self.emit(Instruction::If(BlockType::Empty));
+        self.emit(Instruction::Unreachable);
+        self.emit(Instruction::End);
+    }
+
+    fn emit(&mut self, instruction: Instruction<'static>) {
+        self.body.push(instruction);
+    }
+
+    fn local(&mut self, ty: ValType) -> u32 {
+        self.locals.push(ty);
+        (self.locals.len() - 1) as u32
+    }
+
+    fn bind(&mut self, name: &str, binding: Binding) {
+        self.scopes
+            .last_mut()
+            .expect("INTERNAL_ERROR: no open scope")
+            .insert(name.to_string(), binding);
+    }
+
+    fn lookup(&self, name: &str) -> Result<Binding, CompileError> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+            .ok_or_else(|| lowering(format!("unknown variable '{}'", name)))
+    }
+}
+
+fn val_type(ty: Ty) -> ValType {
+    match ty {
+        Ty::Int => ValType::I64,
+        Ty::Bool | Ty::Unit => ValType::I32,
+    }
+}
+
+fn lowering(message: String) -> CompileError {
+    CompileError::Lowering(message)
+}
@@ -1,25 +1,48 @@
 #![deny(unsafe_code)]
 
-use icn_ccl_parser::{parse_ccl, CclModule, CclStmt};
+mod check;
+mod codegen;
+mod policy;
+
+use codegen::FunctionBuilder;
+use icn_ccl_parser::{parse_ccl, CclStmt};
 use sha2::{Digest, Sha256};
+pub use check::RESOURCE_TYPES;
+pub use icn_ccl_parser::{CclDuration, CclThreshold, Diagnostic, Severity, Span};
+pub use policy::{PolicyConfig, PolicyDid, PolicyRule, PolicyScope};
 use wasm_encoder::{
-    CodeSection, ConstExpr, DataSection, EntityType, Function, FunctionSection, ImportSection, Instruction, MemorySection, MemoryType, Module,
-    TypeSection,
+    CodeSection, ConstExpr, DataSection, EntityType, ExportKind, ExportSection, FunctionSection, ImportSection, MemorySection, MemoryType,
+    Module, TypeSection,
 };
 
 /// Order must match `icn-runtime` host-function registration.
 /// Signatures based on user proposal:
 /// 0: host_log_message(ptr: i32, len: i32)
-/// 1: host_anchor_to_dag(bytes: i32) -> For now, will pass ptr/len for consistency if logging CID
+/// 1: host_anchor_to_dag(cid_ptr: i32, cid_len: i32, bytes: i32)
 /// 2: host_check_resource_authorization(token_ptr: i32, token_len: i32, amount: i64) -> i32
 /// 3: host_record_resource_usage(token_ptr: i32, token_len: i32, amount: i64)
+/// 4: token_credit(token_ptr: i32, token_len: i32, amount: i64) -> i32
+/// 5: token_transfer(token_ptr: i32, token_len: i32, dest_ptr: i32, dest_len: i32, amount: i64) -> i32
 const ABI: &[(&str, &str)] = &[
     ("icn", "host_log_message"),                 // 0
     ("icn", "host_anchor_to_dag"),               // 1
     ("icn", "host_check_resource_authorization"),// 2
     ("icn", "host_record_resource_usage"),       // 3
+    ("env", "token_credit"),                     // 4
+    ("env", "token_transfer"),                   // 5
 ];
 
+// Function indices of the `ABI` imports
+pub(crate) const HOST_LOG_MESSAGE: u32 = 0;
+pub(crate) const HOST_ANCHOR_TO_DAG: u32 = 1;
+pub(crate) const HOST_CHECK_RESOURCE_AUTHORIZATION: u32 = 2;
+pub(crate) const HOST_RECORD_RESOURCE_USAGE: u32 = 3;
+pub(crate) const TOKEN_CREDIT: u32 = 4;
+pub(crate) const TOKEN_TRANSFER: u32 = 5;
+
+/// Name of the exported function that runs the contract's statements in order
+pub const ENTRY_POINT: &str = "_start";
+
 /// Returned by `compile()` – deterministic Wasm plus its SHA-256 hex digest.
 #[derive(Debug)]
 pub struct WasmArtifact {
@@ -28,7 +51,7 @@ pub struct WasmArtifact {
 }
 
 // Helper for string constants in the Wasm data section
-struct StringPool {
+pub(crate) struct StringPool {
     data: DataSection,
     offset: u32, 
     active_segment_data: Vec<u8>, 
@@ -43,7 +66,7 @@ impl StringPool {
         }
     }
 
-    fn intern(&mut self, s: &str) -> (u32, u32) {
+    pub(crate) fn intern(&mut self, s: &str) -> (u32, u32) {
         let ptr = self.offset;
         let bytes = s.as_bytes();
         self.active_segment_data.extend_from_slice(bytes);
@@ -63,9 +86,71 @@ impl StringPool {
     }
 }
 
+/// Returned by `compile_proposals()` – a proposal's voting rules plus its
+/// `on_pass` body, compiled like a top-level program.
+#[derive(Debug)]
+pub struct ProposalArtifact {
+    pub title:     String,
+    pub threshold: CclThreshold,
+    pub duration:  CclDuration,
+    pub scope:     String,
+    pub on_pass:   WasmArtifact,
+}
+
+/// Check all of `source` – top-level statements and every `proposal` body –
+/// without compiling it. A syntax error is reported as the only diagnostic.
+pub fn check(source: &str) -> Vec<Diagnostic> {
+    match parse_ccl(source) {
+        Ok(ast) => std::iter::once(&ast.stmts)
+            .chain(ast.proposals.iter().map(|proposal| &proposal.on_pass))
+            .flat_map(|stmts| check::check_stmts(stmts))
+            .collect(),
+        Err(e) => vec![e.diagnostic()],
+    }
+}
+
+/// Compile the top-level statements of `source`; `proposal` blocks are left to
+/// `compile_proposals()`.
 pub fn compile(source: &str, caller_scope: &str) -> Result<WasmArtifact, CompileError> {
     let ast = parse_ccl(source)?;
-    let wasm_bytes = lower_to_wasm(&ast, caller_scope)?;
+    artifact(&ast.stmts, caller_scope)
+}
+
+/// Compile every `proposal` block in `source`, in source order. Each `on_pass`
+/// body becomes its own module, run with the proposal's scope as caller scope.
+pub fn compile_proposals(source: &str) -> Result<Vec<ProposalArtifact>, CompileError> {
+    let ast = parse_ccl(source)?;
+    ast.proposals
+        .into_iter()
+        .map(|proposal| {
+            Ok(ProposalArtifact {
+                on_pass:   artifact(&proposal.on_pass, &proposal.scope)?,
+                title:     proposal.title,
+                threshold: proposal.threshold,
+                duration:  proposal.duration,
+                scope:     proposal.scope,
+            })
+        })
+        .collect()
+}
+
+/// Compile the single `policy` block in `source` into the configuration for
+/// `scope_id`, checking its DIDs and rules along the way.
+pub fn compile_policy(source: &str, scope_type: PolicyScope, scope_id: &str) -> Result<PolicyConfig, CompileError> {
+    let ast = parse_ccl(source)?;
+    match ast.policies.as_slice() {
+        [policy] => policy::lower_policy(policy, scope_type, scope_id),
+        [] => Err(CompileError::Policy("no policy block found".into())),
+        _ => Err(CompileError::Policy(format!("expected one policy block, found {}", ast.policies.len()))),
+    }
+}
+
+fn artifact(stmts: &[CclStmt], caller_scope: &str) -> Result<WasmArtifact, CompileError> {
+    let diagnostics = check::check_stmts(stmts);
+    if diagnostics.iter().any(Diagnostic::is_error) {
+        return Err(CompileError::Check(diagnostics));
+    }
+    let wasm_bytes = lower_to_wasm(stmts, caller_scope)?;
     let hash_hex = format!("{:x}", Sha256::digest(&wasm_bytes));
     Ok(WasmArtifact {
         wasm: wasm_bytes,
@@ -76,7 +161,7 @@ pub fn compile(source: &str, caller_scope: &str) -> Result<WasmArtifact, Compile
 // ──────────────────────────────────────────────────────────────────────────────
 // lowering: AST ➜ minimal Wasm module
 // ──────────────────────────────────────────────────────────────────────────────
-fn lower_to_wasm(ast: &CclModule, _scope: &str) -> Result<Vec<u8>, CompileError> {
+fn lower_to_wasm(stmts: &[CclStmt], _scope: &str) -> Result<Vec<u8>, CompileError> {
     let mut module = Module::new();
     let mut string_pool = StringPool::new();
 
@@ -91,13 +176,18 @@ fn lower_to_wasm(ast: &CclModule, _scope: &str) -> Result<Vec<u8>, CompileError>
     // Type 2: (ptr: i32, len: i32, amount: i64) -> i32
     let type_idx_ptr_len_i64_i32 = types.len();
     types.function([wasm_encoder::ValType::I32, wasm_encoder::ValType::I32, wasm_encoder::ValType::I64], [wasm_encoder::ValType::I32]);
-    // Type 3: (val: i32) -> ()  (For host_anchor_to_dag if it takes bytes directly)
-    let type_idx_i32_void = types.len();
-    types.function([wasm_encoder::ValType::I32], []);
+    // Type 3: (cid_ptr: i32, cid_len: i32, bytes: i32) -> () (for host_anchor_to_dag)
+    let type_idx_ptr_len_i32_void = types.len();
+    types.function([wasm_encoder::ValType::I32, wasm_encoder::ValType::I32, wasm_encoder::ValType::I32], []);
      // Type 4: (ptr: i32, len: i32, amount: i64) -> () (for record_resource_usage, assuming no return needed for Call)
     let type_idx_ptr_len_i64_void = types.len();
     types.function([wasm_encoder::ValType::I32, wasm_encoder::ValType::I32, wasm_encoder::ValType::I64], []);
-
+    // Type 5: (token_ptr: i32, token_len: i32, dest_ptr: i32, dest_len: i32, amount: i64) -> i32 (for token_transfer)
+    let type_idx_ptr_len_ptr_len_i64_i32 = types.len();
+    types.function(
+        [wasm_encoder::ValType::I32, wasm_encoder::ValType::I32, wasm_encoder::ValType::I32, wasm_encoder::ValType::I32, wasm_encoder::ValType::I64],
+        [wasm_encoder::ValType::I32],
+    );
 
     module.section(&types);
 
@@ -105,18 +195,19 @@ fn lower_to_wasm(ast: &CclModule, _scope: &str) -> Result<Vec<u8>, CompileError>
     let mut imports = ImportSection::new();
     // ABI: (module, name, type_index_for_signature)
     imports.import(ABI[0].0, ABI[0].1, EntityType::Function(type_idx_ptr_len_void)); // host_log_message
-    imports.import(ABI[1].0, ABI[1].1, EntityType::Function(type_idx_i32_void));   // host_anchor_to_dag (assuming i32 bytes for now)
+    imports.import(ABI[1].0, ABI[1].1, EntityType::Function(type_idx_ptr_len_i32_void)); // host_anchor_to_dag
     imports.import(ABI[2].0, ABI[2].1, EntityType::Function(type_idx_ptr_len_i64_i32)); // host_check_resource_authorization
     imports.import(ABI[3].0, ABI[3].1, EntityType::Function(type_idx_ptr_len_i64_void)); // host_record_resource_usage
+    imports.import(ABI[4].0, ABI[4].1, EntityType::Function(type_idx_ptr_len_i64_i32)); // token_credit
+    imports.import(ABI[5].0, ABI[5].1, EntityType::Function(type_idx_ptr_len_ptr_len_i64_i32)); // token_transfer
     module.section(&imports);
 
     // ---------- section: functions ---------------------------------------------
+    // A single `_start` runs every statement, so bindings and control flow can
+    // span statements
     let mut func_sec = FunctionSection::new();
-    // Create placeholder for function bodies that will be defined in the Code section
-    for _stmt in &ast.stmts {
-        func_sec.function(type_idx_void_void); 
-    }
-    module.section(&func_sec); // Moved func_sec here
+    func_sec.function(type_idx_void_void);
+    module.section(&func_sec);
 
     // ---------- section: memory ----------------------------------------------
     let mut mem_sec = MemorySection::new();
@@ -127,60 +218,19 @@ fn lower_to_wasm(ast: &CclModule, _scope: &str) -> Result<Vec<u8>, CompileError>
         shared: false,
     });
     module.section(&mem_sec);
+
+    // ---------- section: exports ---------------------------------------------
+    let mut exports = ExportSection::new();
+    exports.export(ENTRY_POINT, ExportKind::Func, ABI.len() as u32);
+    exports.export("memory", ExportKind::Memory, 0);
+    module.section(&exports);
     
     // ---------- section: code ------------------------------------------------
+    let mut builder = FunctionBuilder::new(&mut string_pool);
+    builder.lower_block(stmts)?;
     let mut code_sec = CodeSection::new();
-    for stmt in &ast.stmts {
-        let mut f = Function::new(vec![]); 
-        match stmt {
-            CclStmt::PerformMeteredAction { resource, amount } => {
-                let log_msg = format!("PerformMeteredAction for resource: {}", resource);
-                let (msg_ptr, msg_len) = string_pool.intern(&log_msg);
-                f.instruction(&Instruction::I32Const(msg_ptr as i32));
-                f.instruction(&Instruction::I32Const(msg_len as i32));
-                f.instruction(&Instruction::Call(0)); 
-
-                let (tok_ptr, tok_len) = string_pool.intern(resource);
-                f.instruction(&Instruction::I32Const(tok_ptr as i32));
-                f.instruction(&Instruction::I32Const(tok_len as i32));
-                f.instruction(&Instruction::I64Const(*amount as i64));
-                f.instruction(&Instruction::Call(2)); 
-                f.instruction(&Instruction::Drop);    
-
-                f.instruction(&Instruction::I32Const(tok_ptr as i32)); 
-                f.instruction(&Instruction::I32Const(tok_len as i32));
-                f.instruction(&Instruction::I64Const(*amount as i64));
-                f.instruction(&Instruction::Call(3)); 
-            }
-            CclStmt::MintToken { token, amount } => {
-                let log_msg = format!("MintToken: {} amount: {}", token, amount);
-                let (msg_ptr, msg_len) = string_pool.intern(&log_msg);
-                f.instruction(&Instruction::I32Const(msg_ptr as i32));
-                f.instruction(&Instruction::I32Const(msg_len as i32));
-                f.instruction(&Instruction::Call(0)); 
-            }
-            CclStmt::TransferResource { token, to, amount } => {
-                let log_msg = format!("TransferResource: {} to {} amount: {}", token, to, amount);
-                let (msg_ptr, msg_len) = string_pool.intern(&log_msg);
-                f.instruction(&Instruction::I32Const(msg_ptr as i32));
-                f.instruction(&Instruction::I32Const(msg_len as i32));
-                f.instruction(&Instruction::Call(0));
-            }
-            CclStmt::AnchorData { cid, bytes } => {
-                let log_msg = format!("AnchorData for cid: {}", cid);
-                let (msg_ptr, msg_len) = string_pool.intern(&log_msg);
-                f.instruction(&Instruction::I32Const(msg_ptr as i32));
-                f.instruction(&Instruction::I32Const(msg_len as i32));
-                f.instruction(&Instruction::Call(0)); 
-
-                f.instruction(&Instruction::I32Const(*bytes as i32)); 
-                f.instruction(&Instruction::Call(1)); 
-            }
-        }
-        f.instruction(&Instruction::End);
-        code_sec.function(&f);
-    }
-    module.section(&code_sec); // Moved code_sec here
+    code_sec.function(&builder.finish());
+    module.section(&code_sec);
 
     // ---------- section: data ------------------------------------------------
     string_pool.finalize_segment();
@@ -196,4 +246,20 @@ pub enum CompileError {
     Parse(#[from] icn_ccl_parser::CclError),
     #[error("lowering error: {0}")]
     Lowering(String),
+    #[error("policy error: {0}")]
+    Policy(String),
+    /// Semantic errors, along with any warnings found alongside them
+    #[error("{}", .0.iter().filter(|d| d.is_error()).map(ToString::to_string).collect::<Vec<_>>().join("\n"))]
+    Check(Vec<Diagnostic>),
+}
+
+impl CompileError {
+    /// Everything to show the author, located where possible
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            CompileError::Parse(e) => vec![e.diagnostic()],
+            CompileError::Check(diagnostics) => diagnostics.clone(),
+            other => vec![Diagnostic::error(other.to_string(), None)],
+        }
+    }
 } 
\ No newline at end of file
@@ -0,0 +1,161 @@
+//! `policy { allow .. }` blocks ➜ scope policy configuration.
+//!
+//! `PolicyConfig`, `PolicyRule`, `PolicyScope` and `PolicyDid` serialize exactly
+//! like `ScopePolicyConfig`, `PolicyRule`, `NodeScope` and `Did` in `icn-types`,
+//! so `PolicyConfig::to_json_string()` can be used as the `proposed_policy` of a
+//! `PolicyUpdateProposal` or deserialized as a `ScopePolicyConfig`.
+
+use crate::CompileError;
+use icn_ccl_parser::{CclPolicy, CclPolicyRule};
+use multibase::Base;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// Multicodec prefix of an Ed25519 public key in a `did:key`
+const ED25519_MULTICODEC_PREFIX: [u8; 2] = [0xed, 0x01];
+const ED25519_KEY_LENGTH: usize = 32;
+
+/// Mirrors `NodeScope` in `icn-types`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PolicyScope {
+    Cooperative,
+    Community,
+    Federation,
+}
+
+impl FromStr for PolicyScope {
+    type Err = CompileError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "cooperative" | "coop" => Ok(PolicyScope::Cooperative),
+            "community"            => Ok(PolicyScope::Community),
+            "federation"           => Ok(PolicyScope::Federation),
+            _ => Err(CompileError::Policy(format!("unknown scope type '{}'", s))),
+        }
+    }
+}
+
+/// Mirrors `Did` in `icn-types`: the Ed25519 public key of a `did:key`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PolicyDid {
+    pub public_key_bytes: Vec<u8>,
+}
+
+impl FromStr for PolicyDid {
+    type Err = CompileError;
+
+    fn from_str(did: &str) -> Result<Self, Self::Err> {
+        parse_did(did).map_err(CompileError::Policy)
+    }
+}
+
+/// Parse a `did:key` DID, describing what is wrong with it otherwise
+pub(crate) fn parse_did(did: &str) -> Result<PolicyDid, String> {
+    let invalid = |reason: &str| format!("invalid DID '{}': {}", did, reason);
+    let encoded = did.strip_prefix("did:key:").ok_or_else(|| invalid("only did:key is supported"))?;
+    let (base, decoded) = multibase::decode(encoded).map_err(|e| invalid(&e.to_string()))?;
+    if base != Base::Base58Btc {
+        return Err(invalid("expected base58btc encoding"));
+    }
+    let key = decoded.strip_prefix(&ED25519_MULTICODEC_PREFIX[..]).ok_or_else(|| invalid("not an Ed25519 key"))?;
+    if key.len() != ED25519_KEY_LENGTH {
+        return Err(invalid(&format!("expected a {}-byte key, got {}", ED25519_KEY_LENGTH, key.len())));
+    }
+    Ok(PolicyDid { public_key_bytes: key.to_vec() })
+}
+
+/// Renders the `did:key:z…` form
+impl fmt::Display for PolicyDid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut prefixed = ED25519_MULTICODEC_PREFIX.to_vec();
+        prefixed.extend_from_slice(&self.public_key_bytes);
+        write!(f, "did:key:{}", multibase::encode(Base::Base58Btc, prefixed))
+    }
+}
+
+/// Mirrors `PolicyRule` in `icn-types`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyRule {
+    pub action_type:         String,
+    pub required_membership: Option<String>,
+    pub allowed_dids:        Option<Vec<PolicyDid>>,
+}
+
+/// Mirrors `ScopePolicyConfig` in `icn-types`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyConfig {
+    pub scope_type:      PolicyScope,
+    pub scope_id:        String,
+    pub allowed_actions: Vec<PolicyRule>,
+}
+
+impl PolicyConfig {
+    /// Check the rules are unambiguous: every action is named once, and no
+    /// rule names an empty scope or an empty or repeated DID allowlist.
+    pub fn validate(&self) -> Result<(), CompileError> {
+        if self.scope_id.is_empty() {
+            return Err(CompileError::Policy("scope id must not be empty".into()));
+        }
+        let mut actions = HashSet::new();
+        for rule in &self.allowed_actions {
+            if rule.action_type.is_empty() {
+                return Err(CompileError::Policy("action names must not be empty".into()));
+            }
+            // Only the first rule for an action is ever consulted
+            if !actions.insert(rule.action_type.as_str()) {
+                return Err(CompileError::Policy(format!("action '{}' is allowed more than once", rule.action_type)));
+            }
+            if rule.required_membership.as_deref() == Some("") {
+                return Err(CompileError::Policy(format!("members_of for '{}' names no scope", rule.action_type)));
+            }
+            if let Some(dids) = &rule.allowed_dids {
+                if dids.is_empty() {
+                    return Err(CompileError::Policy(format!("dids for '{}' lists no DIDs", rule.action_type)));
+                }
+                let mut seen = HashSet::new();
+                if let Some(repeated) = dids.iter().find(|did| !seen.insert(*did)) {
+                    return Err(CompileError::Policy(format!("{} is listed twice for '{}'", repeated, rule.action_type)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The JSON stored as a `PolicyUpdateProposal`'s `proposed_policy`
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(self).expect("policy config always serializes")
+    }
+
+    /// Parse and validate a `ScopePolicyConfig` JSON string
+    pub fn from_json_string(json: &str) -> Result<Self, CompileError> {
+        let config: PolicyConfig = serde_json::from_str(json)
+            .map_err(|e| CompileError::Policy(format!("invalid policy JSON: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+pub(crate) fn lower_policy(policy: &CclPolicy, scope_type: PolicyScope, scope_id: &str) -> Result<PolicyConfig, CompileError> {
+    let config = PolicyConfig {
+        scope_type,
+        scope_id: scope_id.to_string(),
+        allowed_actions: policy.rules.iter().map(lower_rule).collect::<Result<_, _>>()?,
+    };
+    config.validate()?;
+    Ok(config)
+}
+
+fn lower_rule(rule: &CclPolicyRule) -> Result<PolicyRule, CompileError> {
+    let allowed_dids = match &rule.allowed_dids {
+        Some(dids) => Some(dids.iter().map(|did| did.parse()).collect::<Result<_, _>>()?),
+        None => None,
+    };
+    Ok(PolicyRule {
+        action_type: rule.action_type.clone(),
+        required_membership: rule.required_membership.clone(),
+        allowed_dids,
+    })
+}
@@ -0,0 +1,85 @@
+use icn_ccl_compiler::{check, compile, PolicyDid, Severity};
+
+/// `line:col: severity: message` for each diagnostic
+fn diagnose(src: &str) -> Vec<String> {
+    check(src).iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn diagnostics_point_at_the_offending_source() {
+    let src = "let fee = 10\nif fee > 5 {\n    fee = fee + bonus\n}\n";
+    assert_eq!(diagnose(src), vec!["3:17: error: unknown variable 'bonus'"]);
+
+    let rendered = check(src)[0].render(src, "bylaws.ccl");
+    assert_eq!(
+        rendered,
+        "error: unknown variable 'bonus'\n \
+         --> bylaws.ccl:3:17\n  \
+         |\n\
+         3 |     fee = fee + bonus\n  \
+         |                 ^^^^^ not defined in this scope\n"
+    );
+}
+
+#[test]
+fn every_problem_is_reported() {
+    let src = r#"
+        transfer_resource {
+            token  = "credit"
+            to     = "did:coop:bob"
+            amount = 9223372036854775807 + 1
+        }
+        anchor_data {
+            cid   = "bafy"
+            bytes = -1
+        }
+        let ratio = 10 / (5 - 5)
+        for i in 0..3 { i = true }
+    "#;
+
+    assert_eq!(diagnose(src), vec![
+        "4:22: error: invalid DID 'did:coop:bob': only did:key is supported",
+        "5:22: error: amount must be at most 9223372036854775807, but '9223372036854775807 + 1' is 9223372036854775808",
+        "9:21: error: bytes must not be negative, but '-1' is -1",
+        "11:27: error: division by zero in '10 / (5 - 5)'",
+        "12:25: error: cannot assign to loop variable 'i'",
+        "12:29: error: assignment to 'i' must be int, but 'true' is bool",
+    ]);
+}
+
+#[test]
+fn unknown_resource_types_only_warn() {
+    let src = r#"perform_metered_action { resource_type = "compute_fuel" amount = 1 }"#;
+    let diagnostics = check(src);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert_eq!(diagnostics[0].message, "unknown resource type 'compute_fuel'");
+    assert!(compile(src, "coop-x").is_ok());
+
+    let did = PolicyDid { public_key_bytes: vec![4; 32] };
+    let src = format!(r#"transfer_resource {{ token = "credit" to = "{}" amount = 5 }}"#, did);
+    assert!(check(&src).is_empty());
+}
+
+#[test]
+fn syntax_errors_are_located() {
+    let src = "let x = 1\nlet y = * 2\n";
+    let diagnostics = check(src);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].to_string(), "2:9: error: syntax error: expected an expression");
+    assert!(diagnostics[0].render(src, "x.ccl").contains("2 | let y = * 2\n  |         ^"));
+}
+
+#[test]
+fn proposal_bodies_are_checked_separately() {
+    let src = r#"
+        let dues = 5
+        proposal "Spend" {
+            threshold = majority
+            duration  = 1d
+            scope     = "coop-x"
+            on_pass { mint_token { token = "credit" amount = dues } }
+        }
+    "#;
+    assert_eq!(diagnose(src), vec!["7:62: error: unknown variable 'dues'"]);
+}
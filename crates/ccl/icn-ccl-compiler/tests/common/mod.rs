@@ -0,0 +1,82 @@
+//! Runs compiled CCL against stub host functions that record what they were asked to do.
+#![allow(dead_code)]
+
+use icn_ccl_compiler::{compile, ENTRY_POINT};
+use wasmi::{Caller, Engine, Extern, Linker, Module, Store};
+
+/// Host calls made by a compiled contract
+#[derive(Debug, Default)]
+pub struct Host {
+    pub logs: Vec<String>,
+    pub usage: Vec<(String, i64)>,
+    pub anchored: Vec<(String, i32)>,
+    pub minted: Vec<(String, i64)>,
+    pub transfers: Vec<(String, String, i64)>,
+}
+
+fn read_string(caller: &Caller<'_, Host>, ptr: i32, len: i32) -> String {
+    let memory = caller.get_export("memory").and_then(Extern::into_memory).expect("memory export");
+    let mut buf = vec![0; len as usize];
+    memory.read(caller, ptr as usize, &mut buf).expect("read guest memory");
+    String::from_utf8(buf).expect("utf-8 string")
+}
+
+/// Compile the top-level statements of `src` and run them
+pub fn run(src: &str) -> Host {
+    run_wasm(&compile(src, "did:coop:alice").expect("compile").wasm)
+}
+
+/// Run a compiled module's entry point against stub host functions
+pub fn run_wasm(wasm: &[u8]) -> Host {
+    let engine = Engine::default();
+    let module = Module::new(&engine, wasm).expect("valid wasm");
+    let mut store = Store::new(&engine, Host::default());
+    let mut linker = <Linker<Host>>::new(&engine);
+    linker
+        .func_wrap("icn", "host_log_message", |caller: Caller<'_, Host>, ptr: i32, len: i32| {
+            let message = read_string(&caller, ptr, len);
+            let mut caller = caller;
+            caller.data_mut().logs.push(message);
+        })
+        .unwrap()
+        .func_wrap("icn", "host_anchor_to_dag", |caller: Caller<'_, Host>, ptr: i32, len: i32, bytes: i32| {
+            let cid = read_string(&caller, ptr, len);
+            let mut caller = caller;
+            caller.data_mut().anchored.push((cid, bytes));
+        })
+        .unwrap()
+        .func_wrap("icn", "host_check_resource_authorization", |_: Caller<'_, Host>, _: i32, _: i32, _: i64| -> i32 { 1 })
+        .unwrap()
+        .func_wrap("icn", "host_record_resource_usage", |caller: Caller<'_, Host>, ptr: i32, len: i32, amount: i64| {
+            let token = read_string(&caller, ptr, len);
+            let mut caller = caller;
+            caller.data_mut().usage.push((token, amount));
+        })
+        .unwrap()
+        .func_wrap("env", "token_credit", |caller: Caller<'_, Host>, ptr: i32, len: i32, amount: i64| -> i32 {
+            let token = read_string(&caller, ptr, len);
+            let mut caller = caller;
+            caller.data_mut().minted.push((token, amount));
+            0
+        })
+        .unwrap()
+        .func_wrap(
+            "env",
+            "token_transfer",
+            |caller: Caller<'_, Host>, ptr: i32, len: i32, dest_ptr: i32, dest_len: i32, amount: i64| -> i32 {
+                let token = read_string(&caller, ptr, len);
+                let to = read_string(&caller, dest_ptr, dest_len);
+                let mut caller = caller;
+                caller.data_mut().transfers.push((token, to, amount));
+                0
+            },
+        )
+        .unwrap();
+    let instance = linker.instantiate(&mut store, &module).unwrap().start(&mut store).unwrap();
+    instance
+        .get_typed_func::<(), ()>(&store, ENTRY_POINT)
+        .unwrap()
+        .call(&mut store, ())
+        .expect("contract trapped");
+    store.into_data()
+}
@@ -17,6 +17,6 @@ fn compile_anchor_data_hash_stable() {
     // ✱ 2. golden hash – to be filled in after first run
     assert_eq!(
         art.hash_hex,
-        "80167470ee173847a5b0b6825edba4bc6ac973a5f86dc0a6f05d0cdf184858e7"
+        "9e0118a11a245dd111ae99005b7352c0b7dab1390436eb0d7ae55f2da8b87a55"
     );
 } 
\ No newline at end of file
@@ -0,0 +1,125 @@
+mod common;
+
+use common::run;
+use icn_ccl_compiler::{compile, CompileError, PolicyDid};
+
+fn check_error(src: &str) -> String {
+    match compile(src, "did:coop:alice") {
+        Err(CompileError::Check(diagnostics)) => diagnostics[0].message.clone(),
+        other => panic!("Expected a check error, got {:?}", other.map(|artifact| artifact.hash_hex)),
+    }
+}
+
+#[test]
+fn computed_amounts_reach_the_host() {
+    let host = run(r#"
+        let members = 4
+        let fee = members * 25 - 10 / 3
+        perform_metered_action {
+            resource_type = "compute_fuel"
+            amount = fee
+        }
+        anchor_data {
+            cid   = "bafy123"
+            bytes = max(fee, 512) % 100 + abs(-1)
+        }
+    "#);
+
+    assert_eq!(host.usage, vec![("compute_fuel".to_string(), 97)]);
+    assert_eq!(host.anchored, vec![("bafy123".to_string(), 13)]);
+}
+
+#[test]
+fn computed_mint_and_transfer_amounts_reach_the_ledger() {
+    let to = PolicyDid { public_key_bytes: vec![4; 32] }.to_string();
+    let host = run(&format!(
+        r#"
+        let base = 100
+        let members = 3
+        mint_token {{
+            token  = "credit"
+            amount = base * (members + 1)
+        }}
+        for i in 1..3 {{
+            transfer_resource {{
+                token  = "credit"
+                to     = "{}"
+                amount = base / i
+            }}
+        }}
+    "#,
+        to
+    ));
+
+    assert_eq!(host.minted, vec![("credit".to_string(), 400)]);
+    assert_eq!(
+        host.transfers,
+        vec![("credit".to_string(), to.clone(), 100), ("credit".to_string(), to, 50)]
+    );
+}
+
+#[test]
+fn for_loop_accumulates() {
+    let host = run(r#"
+        let total = 0
+        for i in 1..5 {
+            total = total + i
+        }
+        for i in 10..0 {
+            total = 1000
+        }
+        perform_metered_action {
+            resource_type = "sum"
+            amount = total
+        }
+    "#);
+
+    assert_eq!(host.usage, vec![("sum".to_string(), 10)]);
+}
+
+#[test]
+fn if_else_chains_branch() {
+    let host = run(r#"
+        for n in 0..4 {
+            if n == 0 {
+                log("zero")
+            } else if n % 2 == 1 && !(n > 2) {
+                log("odd")
+            } else if n > 100 || n == 2 {
+                log("two")
+            } else {
+                log("other")
+            }
+        }
+    "#);
+
+    assert_eq!(host.logs, vec!["zero", "odd", "two", "other"]);
+}
+
+#[test]
+fn bindings_are_block_scoped() {
+    let host = run(r#"
+        let x = 1
+        if true {
+            let x = 50
+            x = x + 1
+            perform_metered_action { resource_type = "inner" amount = x }
+        }
+        perform_metered_action { resource_type = "outer" amount = x }
+    "#);
+
+    assert_eq!(host.usage, vec![("inner".to_string(), 51), ("outer".to_string(), 1)]);
+    assert!(check_error("if true { let y = 1 } y = 2").contains("unknown variable 'y'"));
+}
+
+#[test]
+fn ill_typed_programs_are_rejected() {
+    assert!(check_error("let x = 1 + true").contains("right operand of '+'"));
+    assert!(check_error("if 1 { }").contains("if condition must be bool"));
+    assert!(check_error("let b = true b = 3").contains("assignment to 'b' must be bool"));
+    assert!(check_error("for i in 0..3 { i = 0 }").contains("cannot assign to loop variable 'i'"));
+    assert!(check_error("let x = log(\"hi\")").contains("does not produce a value"));
+    assert!(check_error("let x = pow(2, 3)").contains("unknown function 'pow'"));
+    assert!(check_error("let x = min(1)").contains("takes 2 argument(s)"));
+    assert!(check_error("let x = 9223372036854775808").contains("does not fit in 64 bits"));
+}
@@ -0,0 +1,78 @@
+use icn_ccl_compiler::{compile_policy, CompileError, PolicyConfig, PolicyDid, PolicyScope};
+use serde_json::json;
+
+fn did(byte: u8) -> PolicyDid {
+    PolicyDid { public_key_bytes: vec![byte; 32] }
+}
+
+fn policy_error(src: &str) -> String {
+    match compile_policy(src, PolicyScope::Cooperative, "coop-x") {
+        Err(CompileError::Policy(message)) => message,
+        other => panic!("Expected a policy error, got {:?}", other),
+    }
+}
+
+#[test]
+fn policy_compiles_to_scope_policy_config() {
+    let src = format!(
+        r#"
+        policy {{
+            allow "submit_proposal" to members_of("coop-x");
+            allow "mint_token" to dids("{}", "{}");
+            allow "anchor_data" to anyone;
+        }}
+        "#,
+        did(1),
+        did(2)
+    );
+
+    let config = compile_policy(&src, PolicyScope::Cooperative, "coop-x").expect("compile");
+
+    // Same shape as `icn_types::ScopePolicyConfig`
+    let expected = json!({
+        "scope_type": "Cooperative",
+        "scope_id": "coop-x",
+        "allowed_actions": [
+            { "action_type": "submit_proposal", "required_membership": "coop-x", "allowed_dids": null },
+            {
+                "action_type": "mint_token",
+                "required_membership": null,
+                "allowed_dids": [{ "public_key_bytes": vec![1; 32] }, { "public_key_bytes": vec![2; 32] }],
+            },
+            { "action_type": "anchor_data", "required_membership": null, "allowed_dids": null },
+        ],
+    });
+    assert_eq!(serde_json::to_value(&config).unwrap(), expected);
+}
+
+#[test]
+fn policy_json_round_trips() {
+    let src = format!(r#"policy {{ allow "vote" to members_of("fed-1") and dids("{}") }}"#, did(9));
+    let config = compile_policy(&src, PolicyScope::Federation, "fed-1").expect("compile");
+
+    let json = config.to_json_string();
+    assert_eq!(PolicyConfig::from_json_string(&json).expect("parse"), config);
+
+    let rule = &config.allowed_actions[0];
+    assert_eq!(rule.allowed_dids.as_ref().unwrap()[0].to_string().parse::<PolicyDid>().unwrap(), did(9));
+}
+
+#[test]
+fn invalid_policies_are_rejected() {
+    assert!(policy_error(r#"allow_nothing = 1"#).contains("no policy block"));
+    assert!(policy_error(r#"policy { } policy { }"#).contains("expected one policy block, found 2"));
+    assert!(policy_error(r#"policy { allow "vote" to anyone allow "vote" to anyone }"#)
+        .contains("action 'vote' is allowed more than once"));
+    assert!(policy_error(r#"policy { allow "" to anyone }"#).contains("must not be empty"));
+    assert!(policy_error(r#"policy { allow "vote" to members_of("") }"#).contains("names no scope"));
+    assert!(policy_error(r#"policy { allow "vote" to dids("did:coop:alice") }"#).contains("only did:key is supported"));
+    assert!(policy_error(r#"policy { allow "vote" to dids("did:key:z1") }"#).contains("invalid DID"));
+
+    let twice = format!(r#"policy {{ allow "vote" to dids("{}", "{}") }}"#, did(3), did(3));
+    assert!(policy_error(&twice).contains("listed twice"));
+
+    assert!(matches!(
+        PolicyConfig::from_json_string(r#"{"scope_type":"Cooperative","scope_id":"","allowed_actions":[]}"#),
+        Err(CompileError::Policy(_))
+    ));
+}
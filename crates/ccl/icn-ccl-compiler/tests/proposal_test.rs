@@ -0,0 +1,79 @@
+mod common;
+
+use common::run_wasm;
+use icn_ccl_compiler::{compile, compile_proposals, CclDuration, CclThreshold};
+
+const BYLAWS: &str = r#"
+    # Dues are paid by every member, whatever the outcome of the vote
+    perform_metered_action {
+        resource_type = "dues"
+        amount = 5
+    }
+
+    proposal "Raise the compute allowance" {
+        threshold = 66%
+        duration  = 7d
+        scope     = "coop-x"
+        on_pass {
+            let members = 12
+            perform_metered_action {
+                resource_type = "compute_fuel"
+                amount = members * 100
+            }
+        }
+    }
+
+    proposal "Dissolve the working group" {
+        threshold = unanimous
+        duration  = 500 blocks
+        scope     = "coop-x/wg"
+        on_pass {
+            log("dissolved")
+        }
+    }
+"#;
+
+#[test]
+fn proposals_compile_with_their_voting_rules() {
+    let proposals = compile_proposals(BYLAWS).expect("compile");
+    assert_eq!(proposals.len(), 2);
+
+    assert_eq!(proposals[0].title, "Raise the compute allowance");
+    assert_eq!(proposals[0].threshold, CclThreshold::Percentage(66));
+    assert_eq!(proposals[0].duration, CclDuration::TimeBased(7 * 24 * 60 * 60));
+    assert_eq!(proposals[0].scope, "coop-x");
+
+    assert_eq!(proposals[1].threshold, CclThreshold::Unanimous);
+    assert_eq!(proposals[1].duration, CclDuration::BlockBased(500));
+    assert_ne!(proposals[0].on_pass.hash_hex, proposals[1].on_pass.hash_hex);
+}
+
+#[test]
+fn on_pass_runs_only_its_own_body() {
+    let proposals = compile_proposals(BYLAWS).expect("compile");
+
+    let host = run_wasm(&proposals[0].on_pass.wasm);
+    assert_eq!(host.usage, vec![("compute_fuel".to_string(), 1200)]);
+
+    let host = run_wasm(&proposals[1].on_pass.wasm);
+    assert_eq!(host.logs, vec!["dissolved"]);
+    assert!(host.usage.is_empty());
+
+    // Top-level statements compile without the proposals
+    let top_level = compile(BYLAWS, "coop-x").expect("compile");
+    let host = run_wasm(&top_level.wasm);
+    assert_eq!(host.usage, vec![("dues".to_string(), 5)]);
+}
+
+#[test]
+fn on_pass_bodies_are_checked() {
+    let src = r#"
+        proposal "Broken" {
+            threshold = majority
+            duration  = open_ended
+            scope     = "coop-x"
+            on_pass { total = 1 }
+        }
+    "#;
+    assert!(compile_proposals(src).unwrap_err().to_string().contains("unknown variable 'total'"));
+}
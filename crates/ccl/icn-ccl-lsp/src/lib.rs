@@ -0,0 +1,158 @@
+//! Editor support for CCL, kept free of any transport so it can be tested
+//! directly: `main.rs` wires these functions to a stdio language server.
+//!
+//! LSP positions count UTF-16 code units from the start of a zero-based line,
+//! while CCL spans are byte offsets, so every conversion goes through
+//! [`position`] and [`offset`].
+
+use icn_ccl_compiler::{check, Severity, RESOURCE_TYPES};
+use icn_ccl_parser::format_ccl;
+use lsp_types::{
+    CompletionItem, CompletionItemKind, Diagnostic, DiagnosticSeverity, Documentation, Hover, HoverContents,
+    MarkupContent, MarkupKind, Position, Range, TextEdit,
+};
+
+/// Keywords and builtins with the markdown shown when hovering over them
+pub const KEYWORD_DOCS: &[(&str, &str)] = &[
+    ("perform_metered_action", "```ccl\nperform_metered_action { resource_type = \"compute_unit\" amount = 10 }\n```\nCharge `amount` units of `resource_type` to the caller's scope; aborts the program if the scope has too little left."),
+    ("mint_token", "```ccl\nmint_token { token = \"credit\" amount = 100 }\n```\nCreate `amount` new units of `token` in the caller's scope."),
+    ("transfer_resource", "```ccl\ntransfer_resource { token = \"credit\" to = \"did:key:z6Mk…\" amount = 5 }\n```\nMove `amount` units of `token` from the caller to the `did:key` DID `to`."),
+    ("anchor_data", "```ccl\nanchor_data { cid = \"bafy…\" bytes = 512 }\n```\nAnchor a DAG node referencing `cid`, metering `bytes` of storage."),
+    ("let", "```ccl\nlet name = expr\n```\nBind a new integer or boolean variable in the current block."),
+    ("if", "```ccl\nif cond { … } else if cond { … } else { … }\n```\nRun a block when a boolean condition holds."),
+    ("else", "```ccl\nif cond { … } else { … }\n```\nThe block run when the condition of an `if` does not hold."),
+    ("for", "```ccl\nfor i in start..end { … }\n```\nRun a block once for each integer from `start` up to, but not including, `end`. `i` cannot be assigned to."),
+    ("proposal", "```ccl\nproposal \"Title\" { threshold = majority duration = 7d scope = \"coop-x\" on_pass { … } }\n```\nA governance proposal whose `on_pass` body runs once it is accepted."),
+    ("threshold", "Votes a proposal needs to pass: `majority`, `unanimous` or a percentage such as `60%`."),
+    ("duration", "How long voting stays open: `7d`, `12h`, `30m`, `3600s`, `100 blocks` or `open_ended`."),
+    ("scope", "The cooperative, community or federation a proposal belongs to."),
+    ("on_pass", "Statements run when the proposal passes."),
+    ("policy", "```ccl\npolicy { allow \"mint_token\" to members_of(\"coop-x\"); }\n```\nWho may perform each action in a scope; compiles to a scope policy configuration."),
+    ("allow", "```ccl\nallow \"action\" to anyone\nallow \"action\" to members_of(\"scope\") and dids(\"did:key:…\")\n```\nGrant an action to everyone, or to those matching all of the listed principals."),
+    ("members_of", "Members of the named scope."),
+    ("dids", "Any of the listed `did:key` DIDs."),
+    ("anyone", "Every caller, with no membership or DID requirement."),
+    ("log", "```ccl\nlog(\"message\")\n```\nWrite a message to the runtime log."),
+    ("min", "```ccl\nmin(a, b)\n```\nThe smaller of two integers."),
+    ("max", "```ccl\nmax(a, b)\n```\nThe larger of two integers."),
+    ("abs", "```ccl\nabs(x)\n```\nThe absolute value of an integer."),
+];
+
+/// Byte offset ➜ LSP position
+pub fn position(text: &str, offset: usize) -> Position {
+    let offset = offset.min(text.len());
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: before[line_start..].encode_utf16().count() as u32,
+    }
+}
+
+/// LSP position ➜ byte offset, clamped to the end of its line
+pub fn offset(text: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match text[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return text.len(),
+        }
+    }
+    let line = text[line_start..].split('\n').next().unwrap_or("");
+    let mut units = 0;
+    for (i, c) in line.char_indices() {
+        if units >= position.character as usize {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    line_start + line.len()
+}
+
+/// Syntax and semantic problems in a document
+pub fn diagnostics(text: &str) -> Vec<Diagnostic> {
+    check(text)
+        .into_iter()
+        .map(|diagnostic| {
+            let range = diagnostic
+                .span
+                .map(|span| Range::new(position(text, span.start), position(text, span.end.max(span.start))))
+                .unwrap_or_default();
+            let mut message = diagnostic.message;
+            for note in &diagnostic.notes {
+                message.push_str(&format!("\nnote: {}", note));
+            }
+            Diagnostic {
+                range,
+                severity: Some(match diagnostic.severity {
+                    Severity::Error => DiagnosticSeverity::ERROR,
+                    Severity::Warning => DiagnosticSeverity::WARNING,
+                }),
+                source: Some("ccl".to_string()),
+                message,
+                ..Diagnostic::default()
+            }
+        })
+        .collect()
+}
+
+/// Docs for the keyword or builtin under the cursor
+pub fn hover(text: &str, position: Position) -> Option<Hover> {
+    let at = offset(text, position);
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let start = text[..at].rfind(|c: char| !is_word(c)).map_or(0, |i| i + 1);
+    let end = text[at..].find(|c: char| !is_word(c)).map_or(text.len(), |i| at + i);
+    let word = &text[start..end];
+    let (_, docs) = KEYWORD_DOCS.iter().find(|(keyword, _)| *keyword == word)?;
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value: docs.to_string() }),
+        range: Some(Range::new(self::position(text, start), self::position(text, end))),
+    })
+}
+
+/// Resource types inside the string of a `resource_type` or `token`, and
+/// keywords anywhere else
+pub fn completion(text: &str, position: Position) -> Vec<CompletionItem> {
+    let at = offset(text, position);
+    let line = &text[text[..at].rfind('\n').map_or(0, |i| i + 1)..at];
+
+    if line.matches('"').count() % 2 == 1 {
+        let before_string = line[..line.rfind('"').unwrap_or(0)].trim_end();
+        let key = before_string.strip_suffix('=').unwrap_or("").trim_end();
+        if !(key.ends_with("resource_type") || key.ends_with("token")) {
+            return Vec::new();
+        }
+        return RESOURCE_TYPES
+            .iter()
+            .map(|resource| CompletionItem {
+                label: resource.to_string(),
+                kind: Some(CompletionItemKind::ENUM_MEMBER),
+                detail: Some("resource type".to_string()),
+                ..CompletionItem::default()
+            })
+            .collect();
+    }
+
+    KEYWORD_DOCS
+        .iter()
+        .map(|(keyword, docs)| CompletionItem {
+            label: keyword.to_string(),
+            kind: Some(CompletionItemKind::KEYWORD),
+            documentation: Some(Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: docs.to_string(),
+            })),
+            ..CompletionItem::default()
+        })
+        .collect()
+}
+
+/// One edit replacing the whole document with its canonical form; `None` if
+/// it does not parse
+pub fn formatting(text: &str) -> Option<Vec<TextEdit>> {
+    let formatted = format_ccl(text).ok()?;
+    if formatted == text {
+        return Some(Vec::new());
+    }
+    Some(vec![TextEdit { range: Range::new(Position::new(0, 0), position(text, text.len())), new_text: formatted }])
+}
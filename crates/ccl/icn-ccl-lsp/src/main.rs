@@ -0,0 +1,199 @@
+//! `icn-ccl-lsp`: a CCL language server speaking LSP over stdio.
+//!
+//! Documents are synced in full; diagnostics are republished whenever one is
+//! opened or changed.
+
+use icn_ccl_lsp::{completion, diagnostics, formatting, hover};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{Completion, Formatting, HoverRequest, Request as _};
+use lsp_types::{
+    CompletionOptions, CompletionParams, CompletionResponse, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, DocumentFormattingParams, HoverParams, HoverProviderCapability, OneOf,
+    PublishDiagnosticsParams, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::error::Error;
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+fn main() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["\"".to_string()]),
+            ..CompletionOptions::default()
+        }),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        ..ServerCapabilities::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    Server { connection: &connection, documents: HashMap::new() }.run()?;
+    io_threads.join()?;
+    Ok(())
+}
+
+struct Server<'c> {
+    connection: &'c Connection,
+    documents: HashMap<Url, String>,
+}
+
+impl Server<'_> {
+    fn run(mut self) -> Result<()> {
+        for message in &self.connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    self.request(request)?;
+                }
+                Message::Notification(notification) => self.notification(notification)?,
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn request(&self, request: Request) -> Result<()> {
+        let id = request.id.clone();
+        let response = self.respond(request).unwrap_or_else(|e| {
+            Response::new_err(id, ErrorCode::InvalidParams as i32, format!("invalid params: {}", e))
+        });
+        self.connection.sender.send(Message::Response(response))?;
+        Ok(())
+    }
+
+    fn respond(&self, request: Request) -> serde_json::Result<Response> {
+        let id = request.id;
+        let response = match request.method.as_str() {
+            HoverRequest::METHOD => {
+                let params: HoverParams = serde_json::from_value(request.params)?;
+                let position = params.text_document_position_params;
+                let result = self.document(&position.text_document.uri).and_then(|text| hover(text, position.position));
+                Response::new_ok(id, result)
+            }
+            Completion::METHOD => {
+                let params: CompletionParams = serde_json::from_value(request.params)?;
+                let position = params.text_document_position;
+                let items = self.document(&position.text_document.uri).map(|text| completion(text, position.position));
+                Response::new_ok(id, items.map(CompletionResponse::Array))
+            }
+            Formatting::METHOD => {
+                let params: DocumentFormattingParams = serde_json::from_value(request.params)?;
+                Response::new_ok(id, self.document(&params.text_document.uri).and_then(formatting))
+            }
+            method => {
+                log::warn!("unhandled request {}", method);
+                Response::new_err(id, ErrorCode::MethodNotFound as i32, format!("unknown method {}", method))
+            }
+        };
+        Ok(response)
+    }
+
+    fn notification(&mut self, notification: Notification) -> Result<()> {
+        let Notification { method, params } = notification;
+        match method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Some(params) = notification_params::<DidOpenTextDocumentParams>(&method, params) else {
+                    return Ok(());
+                };
+                self.update(params.text_document.uri, params.text_document.text, Some(params.text_document.version))
+            }
+            DidChangeTextDocument::METHOD => {
+                let Some(params) = notification_params::<DidChangeTextDocumentParams>(&method, params) else {
+                    return Ok(());
+                };
+                // Full sync: the last change holds the whole document
+                match params.content_changes.into_iter().last() {
+                    Some(change) => self.update(params.text_document.uri, change.text, Some(params.text_document.version)),
+                    None => Ok(()),
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let Some(params) = notification_params::<DidCloseTextDocumentParams>(&method, params) else {
+                    return Ok(());
+                };
+                self.documents.remove(&params.text_document.uri);
+                self.publish(params.text_document.uri, Vec::new(), None)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn update(&mut self, uri: Url, text: String, version: Option<i32>) -> Result<()> {
+        let found = diagnostics(&text);
+        self.documents.insert(uri.clone(), text);
+        self.publish(uri, found, version)
+    }
+
+    fn publish(&self, uri: Url, diagnostics: Vec<lsp_types::Diagnostic>, version: Option<i32>) -> Result<()> {
+        let params = PublishDiagnosticsParams { uri, diagnostics, version };
+        let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+        self.connection.sender.send(Message::Notification(notification))?;
+        Ok(())
+    }
+
+    fn document(&self, uri: &Url) -> Option<&str> {
+        self.documents.get(uri).map(String::as_str)
+    }
+}
+
+/// Notifications get no reply, so ones with malformed params are logged and dropped
+fn notification_params<P: DeserializeOwned>(method: &str, params: serde_json::Value) -> Option<P> {
+    match serde_json::from_value(params) {
+        Ok(params) => Some(params),
+        Err(e) => {
+            log::warn!("ignoring {} with invalid params: {}", method, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_server::RequestId;
+
+    #[test]
+    fn invalid_params_do_not_stop_the_server() {
+        let (server, client) = Connection::memory();
+        let handle = std::thread::spawn(move || Server { connection: &server, documents: HashMap::new() }.run());
+
+        let bad_hover = Request::new(RequestId::from(1), HoverRequest::METHOD.to_string(), serde_json::json!({}));
+        client.sender.send(Message::Request(bad_hover)).unwrap();
+        match client.receiver.recv().unwrap() {
+            Message::Response(response) => {
+                assert_eq!(response.id, RequestId::from(1));
+                assert_eq!(response.error.unwrap().code, ErrorCode::InvalidParams as i32);
+            }
+            other => panic!("expected a response, got {:?}", other),
+        }
+
+        let bad_open = Notification::new(DidOpenTextDocument::METHOD.to_string(), serde_json::json!({ "uri": 1 }));
+        client.sender.send(Message::Notification(bad_open)).unwrap();
+
+        let formatting = Request::new(
+            RequestId::from(2),
+            Formatting::METHOD.to_string(),
+            serde_json::json!({ "textDocument": { "uri": "file:///policy.ccl" }, "options": { "tabSize": 4, "insertSpaces": true } }),
+        );
+        client.sender.send(Message::Request(formatting)).unwrap();
+        match client.receiver.recv().unwrap() {
+            Message::Response(response) => {
+                assert_eq!(response.id, RequestId::from(2));
+                assert!(response.error.is_none());
+            }
+            other => panic!("expected a response, got {:?}", other),
+        }
+
+        drop(client);
+        handle.join().unwrap().unwrap();
+    }
+}
@@ -0,0 +1,69 @@
+use icn_ccl_lsp::{completion, diagnostics, formatting, hover, offset, position};
+use lsp_types::{DiagnosticSeverity, HoverContents, Position, Range};
+
+#[test]
+fn positions_count_utf16_units_per_line() {
+    let text = "log(\"é😀\")\nlet x = 1\n";
+    assert_eq!(position(text, 0), Position::new(0, 0));
+    // `é` is one UTF-16 unit, `😀` two
+    assert_eq!(position(text, text.find(')').unwrap()), Position::new(0, 9));
+    assert_eq!(position(text, text.find('x').unwrap()), Position::new(1, 4));
+    assert_eq!(offset(text, Position::new(0, 9)), text.find(')').unwrap());
+    assert_eq!(offset(text, Position::new(1, 99)), text.len() - 1);
+}
+
+#[test]
+fn diagnostics_are_ranged_and_graded() {
+    let text = "let fee = 10\nfee = fee + bonus\nmint_token { token = \"fuel\" amount = fee }\n";
+    let found = diagnostics(text);
+    assert_eq!(found.len(), 2);
+
+    assert_eq!(found[0].severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(found[0].message, "unknown variable 'bonus'");
+    assert_eq!(found[0].range, Range::new(Position::new(1, 12), Position::new(1, 17)));
+
+    assert_eq!(found[1].severity, Some(DiagnosticSeverity::WARNING));
+    assert!(found[1].message.starts_with("unknown resource type 'fuel'\nnote: known resource types are compute_unit"));
+    assert_eq!(found[1].source.as_deref(), Some("ccl"));
+}
+
+#[test]
+fn hover_documents_keywords() {
+    let text = "let x = 1\nmint_token { token = \"credit\" amount = x }\n";
+    let docs = hover(text, Position::new(1, 3)).expect("hover on mint_token");
+    let HoverContents::Markup(markup) = docs.contents else { panic!("expected markdown") };
+    assert!(markup.value.contains("Create `amount` new units"));
+    assert_eq!(docs.range, Some(Range::new(Position::new(1, 0), Position::new(1, 10))));
+
+    assert!(hover(text, Position::new(0, 0)).is_some());
+    // Plain variables have no docs
+    assert!(hover(text, Position::new(0, 4)).is_none());
+}
+
+#[test]
+fn completion_offers_resource_types_inside_their_strings() {
+    let text = "perform_metered_action { resource_type = \"gp\" amount = 1 }";
+    let inside = Position::new(0, text.find("gp").unwrap() as u32);
+    let labels: Vec<_> = completion(text, inside).into_iter().map(|item| item.label).collect();
+    assert!(labels.contains(&"compute_unit".to_string()));
+    assert!(labels.contains(&"gpu_minute".to_string()));
+
+    let text = "anchor_data { cid = \"ba";
+    assert!(completion(text, Position::new(0, text.len() as u32)).is_empty());
+
+    let keywords: Vec<_> = completion("", Position::new(0, 0)).into_iter().map(|item| item.label).collect();
+    assert!(keywords.contains(&"transfer_resource".to_string()));
+    assert!(keywords.contains(&"policy".to_string()));
+}
+
+#[test]
+fn formatting_replaces_the_whole_document() {
+    let text = "let x=1\nif x>0 { log(\"pos\") }";
+    let edits = formatting(text).expect("valid source formats");
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].range, Range::new(Position::new(0, 0), Position::new(1, 21)));
+    assert_eq!(edits[0].new_text, "let x = 1\nif x > 0 {\n    log(\"pos\")\n}\n");
+
+    assert_eq!(formatting(&edits[0].new_text), Some(Vec::new()));
+    assert_eq!(formatting("let x ="), None);
+}
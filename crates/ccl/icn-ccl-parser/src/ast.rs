@@ -1,17 +1,314 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Where a node came from: a byte range of the source, plus the 1-based line and
+/// column it starts at. Spans never take part in equality, so the same program
+/// laid out differently parses to equal ASTs.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end:   usize,
+    pub line:  usize,
+    pub col:   usize,
+}
+
+impl PartialEq for Span {
+    fn eq(&self, _other: &Span) -> bool {
+        true
+    }
+}
+
+impl Eq for Span {}
+
+/// A value with the span it was parsed from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Spanned<T> {
+    pub node: T,
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    pub fn new(node: T, span: Span) -> Self {
+        Self { node, span }
+    }
+}
+
+impl<T> Deref for Spanned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.node
+    }
+}
+
+impl<T> From<T> for Spanned<T> {
+    fn from(node: T) -> Self {
+        Self::new(node, Span::default())
+    }
+}
+
+impl From<&str> for Spanned<String> {
+    fn from(node: &str) -> Self {
+        Self::new(node.to_string(), Span::default())
+    }
+}
+
+impl PartialEq<str> for Spanned<String> {
+    fn eq(&self, other: &str) -> bool {
+        self.node == other
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Spanned<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.node.fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CclModule {
     pub stmts: Vec<CclStmt>,
+    pub proposals: Vec<CclProposal>,
+    pub policies: Vec<CclPolicy>,
+}
+
+/// A `proposal "title" { .. }` template: who votes, how long, what it takes to
+/// pass, and the statements to run once it has
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CclProposal {
+    pub title:     String,
+    pub threshold: CclThreshold,
+    pub duration:  CclDuration,
+    /// Scope whose members are eligible to vote
+    pub scope:     String,
+    pub on_pass:   Vec<CclStmt>,
+}
+
+/// Mirrors `VotingThreshold` in `icn-identity-core`, which serializes the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CclThreshold {
+    Majority,
+    /// 1 to 100
+    Percentage(u8),
+    Unanimous,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Mirrors `VotingDuration` in `icn-identity-core`, which serializes the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CclDuration {
+    /// Seconds
+    TimeBased(u64),
+    BlockBased(u64),
+    OpenEnded,
+}
+
+/// A `policy { allow .. }` block: who may perform which actions in a scope
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CclPolicy {
+    pub rules: Vec<CclPolicyRule>,
+}
+
+/// `allow "action" to ..`; fields follow `PolicyRule` in `icn-types`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CclPolicyRule {
+    pub action_type:         String,
+    /// From `members_of("scope")`
+    pub required_membership: Option<String>,
+    /// From `dids("did:key:…", ..)`, as written
+    pub allowed_dids:        Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum CclStmt {
-    PerformMeteredAction { resource: String, amount: u64 },
-    MintToken            { token:    String, amount: u128 },
-    TransferResource     { token:    String, to:     String, amount: u128 },
-    AnchorData           { cid:      String, bytes:  u64 },
-    // …extend…
+    PerformMeteredAction { resource: Spanned<String>, amount: CclExpr },
+    MintToken            { token:    Spanned<String>, amount: CclExpr },
+    TransferResource     { token:    Spanned<String>, to:     Spanned<String>, amount: CclExpr },
+    AnchorData           { cid:      Spanned<String>, bytes:  CclExpr },
+    /// `let name = value`
+    Let    { name: Spanned<String>, value: CclExpr },
+    /// `name = value`, where `name` is an earlier `let` binding
+    Assign { name: Spanned<String>, value: CclExpr },
+    /// `if cond { .. } else { .. }`; an `else if` is an `If` alone in `else_body`
+    If     { cond: CclExpr, then_body: Vec<CclStmt>, else_body: Vec<CclStmt> },
+    /// `for var in start..end { .. }`, with both bounds evaluated once
+    For    { var: Spanned<String>, start: CclExpr, end: CclExpr, body: Vec<CclStmt> },
+    /// A builtin call evaluated for its effect, e.g. `log("…")`
+    Expr(CclExpr),
 }
 
-pub type CclExpr = String; // placeholder for future expression grammar 
\ No newline at end of file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum CclExpr {
+    Int(u128),
+    Bool(bool),
+    Str(String),
+    Var(String),
+    Unary  { op: UnaryOp,  expr: Box<CclExpr> },
+    Binary { op: BinaryOp, lhs: Box<CclExpr>, rhs: Box<CclExpr> },
+    Call   { name: String, args: Vec<CclExpr> },
+    /// The parser wraps every expression it builds in one of these; it is
+    /// transparent to equality and `Display`
+    Located { span: Span, expr: Box<CclExpr> },
+}
+
+impl CclExpr {
+    /// The expression under any `Located` wrappers
+    pub fn unlocated(&self) -> &CclExpr {
+        match self {
+            CclExpr::Located { expr, .. } => expr.unlocated(),
+            expr => expr,
+        }
+    }
+
+    /// Span of the outermost `Located` wrapper, if any
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            CclExpr::Located { span, .. } => Some(*span),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for CclExpr {
+    fn eq(&self, other: &CclExpr) -> bool {
+        match (self.unlocated(), other.unlocated()) {
+            (CclExpr::Int(a), CclExpr::Int(b)) => a == b,
+            (CclExpr::Bool(a), CclExpr::Bool(b)) => a == b,
+            (CclExpr::Str(a), CclExpr::Str(b)) => a == b,
+            (CclExpr::Var(a), CclExpr::Var(b)) => a == b,
+            (CclExpr::Unary { op: a, expr: x }, CclExpr::Unary { op: b, expr: y }) => a == b && x == y,
+            (CclExpr::Binary { op: a, lhs: l1, rhs: r1 }, CclExpr::Binary { op: b, lhs: l2, rhs: r2 }) => {
+                a == b && l1 == l2 && r1 == r2
+            }
+            (CclExpr::Call { name: a, args: x }, CclExpr::Call { name: b, args: y }) => a == b && x == y,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl BinaryOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Or  => "||",
+            BinaryOp::And => "&&",
+            BinaryOp::Eq  => "==",
+            BinaryOp::Ne  => "!=",
+            BinaryOp::Lt  => "<",
+            BinaryOp::Le  => "<=",
+            BinaryOp::Gt  => ">",
+            BinaryOp::Ge  => ">=",
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+        }
+    }
+
+    /// Binding strength, matching the grammar's Pratt parser (higher binds tighter)
+    pub fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::And => 2,
+            BinaryOp::Eq | BinaryOp::Ne => 3,
+            BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => 4,
+            BinaryOp::Add | BinaryOp::Sub => 5,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => 6,
+        }
+    }
+}
+
+/// Renders the expression as CCL source, parenthesising only where precedence requires
+impl fmt::Display for CclExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CclExpr::Int(value) => write!(f, "{}", value),
+            CclExpr::Bool(value) => write!(f, "{}", value),
+            CclExpr::Str(value) => write!(f, "\"{}\"", value),
+            CclExpr::Var(name) => write!(f, "{}", name),
+            CclExpr::Unary { op, expr } => {
+                let symbol = match op {
+                    UnaryOp::Neg => "-",
+                    UnaryOp::Not => "!",
+                };
+                match expr.unlocated() {
+                    CclExpr::Binary { .. } => write!(f, "{}({})", symbol, expr),
+                    _ => write!(f, "{}{}", symbol, expr),
+                }
+            }
+            CclExpr::Binary { op, lhs, rhs } => {
+                // Operators are left-associative, so only a right operand of equal
+                // precedence needs parentheses
+                let wrap = |expr: &CclExpr, right: bool| match expr.unlocated() {
+                    CclExpr::Binary { op: inner, .. } => {
+                        inner.precedence() < op.precedence() || (right && inner.precedence() == op.precedence())
+                    }
+                    _ => false,
+                };
+                if wrap(lhs, false) { write!(f, "({})", lhs)? } else { write!(f, "{}", lhs)? }
+                write!(f, " {} ", op.symbol())?;
+                if wrap(rhs, true) { write!(f, "({})", rhs) } else { write!(f, "{}", rhs) }
+            }
+            CclExpr::Call { name, args } => {
+                write!(f, "{}(", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+            CclExpr::Located { expr, .. } => write!(f, "{}", expr),
+        }
+    }
+}
+
+/// Renders the policy as CCL source that parses back to the same policy
+impl fmt::Display for CclPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "policy {{")?;
+        for rule in &self.rules {
+            write!(f, "    allow \"{}\" to ", rule.action_type)?;
+            let mut principals = Vec::new();
+            if let Some(scope) = &rule.required_membership {
+                principals.push(format!("members_of(\"{}\")", scope));
+            }
+            if let Some(dids) = &rule.allowed_dids {
+                let quoted: Vec<_> = dids.iter().map(|did| format!("\"{}\"", did)).collect();
+                principals.push(format!("dids({})", quoted.join(", ")));
+            }
+            if principals.is_empty() {
+                writeln!(f, "anyone;")?;
+            } else {
+                writeln!(f, "{};", principals.join(" and "))?;
+            }
+        }
+        write!(f, "}}")
+    }
+}
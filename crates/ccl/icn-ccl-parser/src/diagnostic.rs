@@ -0,0 +1,120 @@
+//! Located errors and warnings about CCL source, rendered the way rustc does:
+//!
+//! ```text
+//! error: unknown variable 'members'
+//!  --> bylaws.ccl:4:18
+//!   |
+//! 4 |         amount = members * 100
+//!   |                  ^^^^^^^ not defined in this scope
+//! ```
+
+use crate::ast::Span;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message:  String,
+    /// `None` for problems with no single place in the source
+    pub span:     Option<Span>,
+    /// Shown next to the underlined source
+    pub label:    Option<String>,
+    pub notes:    Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, span: Option<Span>) -> Self {
+        Self { severity: Severity::Error, message: message.into(), span, label: None, notes: Vec::new() }
+    }
+
+    pub fn warning(message: impl Into<String>, span: Option<Span>) -> Self {
+        Self { severity: Severity::Warning, ..Self::error(message, span) }
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Render with the offending line of `source` underlined; `origin` names the
+    /// source, usually its path
+    pub fn render(&self, source: &str, origin: &str) -> String {
+        let mut out = format!("{}: {}\n", self.severity, self.message);
+        let Some(span) = self.span else {
+            out.push_str(&format!(" --> {}\n", origin));
+            for note in &self.notes {
+                out.push_str(&format!("  = note: {}\n", note));
+            }
+            return out;
+        };
+
+        let line_no = span.line.to_string();
+        let gutter = " ".repeat(line_no.len());
+        let line = source.lines().nth(span.line.saturating_sub(1)).unwrap_or("");
+        out.push_str(&format!("{}--> {}:{}:{}\n", gutter, origin, span.line, span.col));
+        out.push_str(&format!("{} |\n", gutter));
+        out.push_str(&format!("{} | {}\n", line_no, line));
+
+        // Keep tabs so the underline lines up with the source above it
+        let indent: String = line
+            .chars()
+            .take(span.col.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let rest = line.chars().count().saturating_sub(span.col.saturating_sub(1));
+        let width = source
+            .get(span.start..span.end)
+            .map(|text| text.lines().next().unwrap_or("").chars().count())
+            .unwrap_or(0)
+            .min(rest)
+            .max(1);
+        out.push_str(&format!("{} | {}{}", gutter, indent, "^".repeat(width)));
+        if let Some(label) = &self.label {
+            out.push_str(&format!(" {}", label));
+        }
+        out.push('\n');
+
+        if !self.notes.is_empty() {
+            out.push_str(&format!("{} |\n", gutter));
+        }
+        for note in &self.notes {
+            out.push_str(&format!("{} = note: {}\n", gutter, note));
+        }
+        out
+    }
+}
+
+/// `line:col: severity: message`, or just `severity: message` without a span
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(span) = self.span {
+            write!(f, "{}:{}: ", span.line, span.col)?;
+        }
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
@@ -0,0 +1,328 @@
+//! Canonical formatting of CCL source, driven by the pest parse tree.
+//!
+//! Layout is fixed: four-space indentation, one statement per line, `=` aligned
+//! within each block of `key = value` lines and single spaces around binary
+//! operators. Runs of blank lines collapse to one. The grammar discards
+//! comments, so they are recovered from the source text and re-attached where
+//! they were: on their own line before the item that follows them, or at the
+//! end of the line they trailed.
+
+use crate::{syntax_diagnostic, CclError, CclParser, Rule};
+use pest::iterators::Pair;
+use pest::Parser;
+use std::collections::VecDeque;
+
+const INDENT: &str = "    ";
+
+/// Format `source` canonically; fails only if it does not parse
+pub fn format_ccl(source: &str) -> Result<String, CclError> {
+    let file = CclParser::parse(Rule::file, source)
+        .map_err(syntax_diagnostic)?
+        .next()
+        .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: file rule should always be present".to_string()))?;
+
+    let mut formatter = Formatter {
+        source,
+        comments: scan_comments(source),
+        lines: Vec::new(),
+        depth: 0,
+        last_end: 0,
+        block_start: true,
+    };
+    for pair in file.into_inner() {
+        match pair.as_rule() {
+            Rule::stmt => formatter.stmt(pair),
+            Rule::proposal => formatter.proposal(pair),
+            Rule::policy => formatter.policy(pair),
+            _ => {}
+        }
+    }
+    formatter.leading(source.len());
+
+    let mut out = formatter.lines.join("\n");
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+struct Comment {
+    start: usize,
+    text: String,
+}
+
+/// Every `#` comment outside a string literal, in source order
+fn scan_comments(source: &str) -> VecDeque<Comment> {
+    let mut comments = VecDeque::new();
+    let mut in_string = false;
+    let mut pos = 0;
+    while let Some(offset) = source[pos..].find(['"', '#']) {
+        let i = pos + offset;
+        if source[i..].starts_with('"') {
+            in_string = !in_string;
+            pos = i + 1;
+        } else if in_string {
+            pos = i + 1;
+        } else {
+            let end = source[i..].find('\n').map_or(source.len(), |n| i + n);
+            comments.push_back(Comment { start: i, text: source[i..end].trim_end().to_string() });
+            pos = end;
+        }
+    }
+    comments
+}
+
+struct Formatter<'s> {
+    source: &'s str,
+    comments: VecDeque<Comment>,
+    lines: Vec<String>,
+    depth: usize,
+    /// End of the last source text written out
+    last_end: usize,
+    /// Nothing written since the last `{`, so no blank line is wanted
+    block_start: bool,
+}
+
+impl Formatter<'_> {
+    /// Write out the comments before `start`, keeping one blank line wherever
+    /// the source had any
+    fn leading(&mut self, start: usize) {
+        while self.comments.front().is_some_and(|comment| comment.start < start) {
+            let comment = self.comments.pop_front().expect("front was checked");
+            let gap = &self.source[self.last_end.min(comment.start)..comment.start];
+            if gap.contains('\n') || self.lines.is_empty() {
+                self.blank_line_if(gap);
+                self.line(comment.text);
+            } else {
+                let last = self.lines.last_mut().expect("lines is not empty");
+                last.push_str("  ");
+                last.push_str(&comment.text);
+            }
+            self.last_end = self.last_end.max(comment.start + 1);
+            self.block_start = false;
+        }
+        let gap = &self.source[self.last_end.min(start)..start];
+        self.blank_line_if(gap);
+    }
+
+    fn blank_line_if(&mut self, gap: &str) {
+        if gap.matches('\n').count() > 1 && !self.block_start {
+            self.lines.push(String::new());
+            self.block_start = true;
+        }
+    }
+
+    fn line(&mut self, text: String) {
+        self.lines.push(format!("{}{}", INDENT.repeat(self.depth), text));
+    }
+
+    /// A single-line item spanning `start..end` in the source
+    fn item(&mut self, start: usize, end: usize, text: String) {
+        self.leading(start);
+        self.line(text);
+        self.last_end = self.content_end(start, end);
+        self.block_start = false;
+    }
+
+    /// Pest spans run on over the whitespace and comments that follow an
+    /// expression; this is where the item's own text stops
+    fn content_end(&self, start: usize, end: usize) -> usize {
+        let mut end = start + self.source[start..end].trim_end().len();
+        while let Some(comment) = self.comments.iter().find(|c| c.start >= start && c.start + c.text.len() == end) {
+            end = start + self.source[start..comment.start].trim_end().len();
+        }
+        end
+    }
+
+    /// `header {` for an item starting at `start` whose `{` is at `brace`
+    fn open(&mut self, start: usize, brace: usize, header: String) {
+        self.leading(start);
+        self.line(format!("{} {{", header));
+        self.enter(brace);
+    }
+
+    /// `} else .. {`, continuing the line of the previous closing brace
+    fn reopen(&mut self, brace: usize, header: &str) {
+        let last = self.lines.last_mut().expect("an if precedes its else");
+        last.push_str(&format!(" {} {{", header));
+        self.enter(brace);
+    }
+
+    fn enter(&mut self, brace: usize) {
+        self.last_end = brace + 1;
+        self.depth += 1;
+        self.block_start = true;
+    }
+
+    /// The `}` ending at `end`; an empty block closes on its opening line
+    fn close(&mut self, end: usize) {
+        self.leading(end - 1);
+        self.depth -= 1;
+        match self.lines.last_mut() {
+            Some(last) if self.block_start && last.ends_with('{') => last.push('}'),
+            _ => self.line("}".to_string()),
+        }
+        self.last_end = end;
+        self.block_start = false;
+    }
+
+    fn brace_after(&self, pos: usize) -> usize {
+        pos + self.source[pos..].find('{').expect("INTERNAL_ERROR: block without '{'")
+    }
+
+    fn stmt(&mut self, pair: Pair<Rule>) {
+        let Some(inner) = pair.into_inner().next() else { return };
+        let (start, end) = (inner.as_span().start(), inner.as_span().end());
+        match inner.as_rule() {
+            Rule::perform_metered_action | Rule::mint_token | Rule::transfer_resource | Rule::anchor_data => {
+                let header = inner.as_str().split(|c: char| c.is_whitespace() || c == '{').next().unwrap_or("").to_string();
+                self.open(start, self.brace_after(start), header);
+                self.kv_lines(inner.into_inner().collect());
+                self.close(end);
+            }
+            Rule::let_stmt | Rule::assign_stmt => {
+                let keyword = if inner.as_rule() == Rule::let_stmt { "let " } else { "" };
+                let mut parts = inner.into_inner();
+                let name = parts.next().map_or("", |name| name.as_str());
+                let value = parts.next().map(expr).unwrap_or_default();
+                self.item(start, end, format!("{}{} = {}", keyword, name, value));
+            }
+            Rule::call_stmt => {
+                let text = inner.into_inner().next().map(call).unwrap_or_default();
+                self.item(start, end, text);
+            }
+            Rule::if_stmt => self.if_stmt(inner, false),
+            Rule::for_stmt => {
+                let mut parts = inner.into_inner();
+                let var = parts.next().map_or("", |var| var.as_str());
+                let from = parts.next().map(expr).unwrap_or_default();
+                let to = parts.next().map(expr).unwrap_or_default();
+                if let Some(block) = parts.next() {
+                    self.open(start, block.as_span().start(), format!("for {} in {}..{}", var, from, to));
+                    self.block_body(block);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn if_stmt(&mut self, pair: Pair<Rule>, chained: bool) {
+        let start = pair.as_span().start();
+        let mut parts = pair.into_inner();
+        let (Some(cond), Some(block)) = (parts.next(), parts.next()) else { return };
+        let header = format!("if {}", expr(cond));
+        if chained {
+            self.reopen(block.as_span().start(), &format!("else {}", header));
+        } else {
+            self.open(start, block.as_span().start(), header);
+        }
+        self.block_body(block);
+
+        match parts.next() {
+            Some(other) if other.as_rule() == Rule::if_stmt => self.if_stmt(other, true),
+            Some(block) => {
+                self.reopen(block.as_span().start(), "else");
+                self.block_body(block);
+            }
+            None => {}
+        }
+    }
+
+    /// The statements of a `block` whose `{` has already been written
+    fn block_body(&mut self, block: Pair<Rule>) {
+        let end = block.as_span().end();
+        for stmt in block.into_inner() {
+            self.stmt(stmt);
+        }
+        self.close(end);
+    }
+
+    /// `key = value` lines, with the `=` signs aligned
+    fn kv_lines(&mut self, pairs: Vec<Pair<Rule>>) {
+        let width = pairs.iter().map(|pair| kv_key(pair).len()).max().unwrap_or(0);
+        for pair in pairs {
+            let (start, end) = (pair.as_span().start(), pair.as_span().end());
+            let key = kv_key(&pair);
+            let value = pair.into_inner().next().map(kv_value).unwrap_or_default();
+            self.item(start, end, format!("{:<width$} = {}", key, value, width = width));
+        }
+    }
+
+    fn proposal(&mut self, pair: Pair<Rule>) {
+        let (start, end) = (pair.as_span().start(), pair.as_span().end());
+        let mut parts = pair.into_inner();
+        let Some(title) = parts.next() else { return };
+        self.open(start, self.brace_after(title.as_span().end()), format!("proposal {}", title.as_str()));
+
+        let (kvs, rest): (Vec<_>, Vec<_>) = parts.partition(|part| part.as_rule() != Rule::on_pass);
+        self.kv_lines(kvs);
+        for on_pass in rest {
+            if let Some(block) = on_pass.into_inner().next() {
+                self.open(block.as_span().start(), block.as_span().start(), "on_pass".to_string());
+                self.block_body(block);
+            }
+        }
+        self.close(end);
+    }
+
+    fn policy(&mut self, pair: Pair<Rule>) {
+        let (start, end) = (pair.as_span().start(), pair.as_span().end());
+        self.open(start, self.brace_after(start), "policy".to_string());
+        for rule in pair.into_inner() {
+            let (rule_start, rule_end) = (rule.as_span().start(), rule.as_span().end());
+            let mut parts = rule.into_inner();
+            let action = parts.next().map_or("", |action| action.as_str());
+            let principals: Vec<_> = parts
+                .map(|principal| match principal.as_rule() {
+                    Rule::anyone => "anyone".to_string(),
+                    rule => {
+                        let name = if rule == Rule::dids { "dids" } else { "members_of" };
+                        let args: Vec<_> = principal.into_inner().map(|arg| arg.as_str()).collect();
+                        format!("{}({})", name, args.join(", "))
+                    }
+                })
+                .collect();
+            self.item(rule_start, rule_end, format!("allow {} to {};", action, principals.join(" and ")));
+        }
+        self.close(end);
+    }
+}
+
+/// `kv_resource_type` ➜ `resource_type`
+fn kv_key(pair: &Pair<Rule>) -> String {
+    format!("{:?}", pair.as_rule()).trim_start_matches("kv_").to_string()
+}
+
+fn kv_value(value: Pair<Rule>) -> String {
+    match value.as_rule() {
+        Rule::expr => expr(value),
+        Rule::percentage => format!("{}%", value.into_inner().next().map_or("", |n| n.as_str())),
+        Rule::blocks => format!("{} blocks", value.into_inner().next().map_or("", |n| n.as_str())),
+        _ => value.as_str().to_string(),
+    }
+}
+
+fn expr(pair: Pair<Rule>) -> String {
+    let mut out = String::new();
+    for part in pair.into_inner() {
+        match part.as_rule() {
+            Rule::neg => out.push('-'),
+            Rule::not => out.push('!'),
+            Rule::expr => out.push_str(&format!("({})", expr(part))),
+            Rule::call => out.push_str(&call(part)),
+            Rule::or | Rule::and | Rule::eq | Rule::ne | Rule::le | Rule::ge | Rule::lt | Rule::gt
+            | Rule::add | Rule::sub | Rule::mul | Rule::div | Rule::rem => {
+                out.push_str(&format!(" {} ", part.as_str()));
+            }
+            _ => out.push_str(part.as_str()),
+        }
+    }
+    out
+}
+
+fn call(pair: Pair<Rule>) -> String {
+    let mut parts = pair.into_inner();
+    let name = parts.next().map_or("", |name| name.as_str());
+    let args: Vec<_> = parts.map(expr).collect();
+    format!("{}({})", name, args.join(", "))
+}
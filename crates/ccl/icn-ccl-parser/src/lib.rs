@@ -1,11 +1,21 @@
 #![deny(unsafe_code)]
 
 pub mod ast;
-pub use ast::{CclModule, CclStmt};
+pub mod diagnostic;
+pub mod format;
+pub use ast::{
+    BinaryOp, CclDuration, CclExpr, CclModule, CclPolicy, CclPolicyRule, CclProposal, CclStmt, CclThreshold, Span, Spanned,
+    UnaryOp,
+};
+pub use diagnostic::{Diagnostic, Severity};
+pub use format::format_ccl;
 
+use pest::error::{InputLocation, LineColLocation};
 use pest::iterators::Pair;
+use pest::pratt_parser::{Assoc, Op, PrattParser};
 use pest::Parser;
 use pest_derive::Parser;
+use std::sync::OnceLock;
 use thiserror::Error;
 
 #[derive(Parser)]
@@ -14,7 +24,7 @@ pub struct CclParser;
 
 pub fn parse_ccl(source: &str) -> Result<CclModule, CclError> {
     let mut pairs = CclParser::parse(Rule::file, source)
-        .map_err(|e| CclError::Syntax(e.to_string()))?;
+        .map_err(syntax_diagnostic)?;
 
     let file_pair = pairs.next().expect("INTERNAL_ERROR: file rule should always be present");
     if file_pair.as_rule() != Rule::file {
@@ -22,46 +32,316 @@ pub fn parse_ccl(source: &str) -> Result<CclModule, CclError> {
     }
 
     let mut stmts = Vec::new();
+    let mut proposals = Vec::new();
+    let mut policies = Vec::new();
     for pair_in_file in file_pair.into_inner() {
         match pair_in_file.as_rule() {
             Rule::stmt => {
-                let actual_stmt_pair = pair_in_file.into_inner().next()
-                    .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: Empty stmt rule encountered".to_string()))?;
-                
-                match actual_stmt_pair.as_rule() {
-                    Rule::perform_metered_action => {
-                        stmts.push(parse_metered_action(actual_stmt_pair)?);
-                    }
-                    Rule::mint_token => {
-                        stmts.push(parse_mint_token(actual_stmt_pair)?);
-                    }
-                    Rule::transfer_resource => {
-                        stmts.push(parse_transfer_resource(actual_stmt_pair)?);
-                    }
-                    Rule::anchor_data => {
-                        stmts.push(parse_anchor_data(actual_stmt_pair)?);
-                    }
-                    _ => {
-                        return Err(CclError::Syntax(format!(
-                            "Unexpected rule {:?} inside stmt. Expected a specific statement type.",
-                            actual_stmt_pair.as_rule()
-                        )));
-                    }
-                }
+                stmts.push(parse_stmt(pair_in_file)?);
+            }
+            Rule::proposal => {
+                proposals.push(parse_proposal(pair_in_file)?);
+            }
+            Rule::policy => {
+                policies.push(parse_policy(pair_in_file)?);
             }
             Rule::EOI => {
                 break;
             }
             _ => {
                 return Err(CclError::Syntax(format!(
-                    "Unexpected rule {:?} directly inside file. Expected stmt or EOI.",
+                    "Unexpected rule {:?} directly inside file. Expected proposal, policy, stmt or EOI.",
                     pair_in_file.as_rule()
                 )));
             }
         }
     }
 
-    Ok(CclModule { stmts })
+    Ok(CclModule { stmts, proposals, policies })
+}
+
+fn parse_stmt(pair: Pair<Rule>) -> Result<CclStmt, CclError> {
+    let actual_stmt_pair = pair.into_inner().next()
+        .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: Empty stmt rule encountered".to_string()))?;
+
+    match actual_stmt_pair.as_rule() {
+        Rule::perform_metered_action => parse_metered_action(actual_stmt_pair),
+        Rule::mint_token             => parse_mint_token(actual_stmt_pair),
+        Rule::transfer_resource      => parse_transfer_resource(actual_stmt_pair),
+        Rule::anchor_data            => parse_anchor_data(actual_stmt_pair),
+        Rule::let_stmt               => parse_binding(actual_stmt_pair, true),
+        Rule::assign_stmt            => parse_binding(actual_stmt_pair, false),
+        Rule::if_stmt                => parse_if(actual_stmt_pair),
+        Rule::for_stmt               => parse_for(actual_stmt_pair),
+        Rule::call_stmt => {
+            let call_pair = actual_stmt_pair.into_inner().next()
+                .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: call_stmt missing call".to_string()))?;
+            Ok(CclStmt::Expr(parse_call(call_pair)?))
+        }
+        _ => Err(CclError::Syntax(format!(
+            "Unexpected rule {:?} inside stmt. Expected a specific statement type.",
+            actual_stmt_pair.as_rule()
+        ))),
+    }
+}
+
+fn parse_proposal(pair: Pair<Rule>) -> Result<CclProposal, CclError> {
+    let mut inner = pair.into_inner();
+    let title = unquote(inner.next()
+        .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: proposal missing title".to_string()))?
+        .as_str());
+
+    let mut threshold = None;
+    let mut duration  = None;
+    let mut scope     = None;
+    let mut on_pass   = None;
+
+    for inner_kv_pair in inner {
+        let rule = inner_kv_pair.as_rule();
+        let value_pair = inner_kv_pair.into_inner().next()
+            .ok_or_else(|| CclError::Syntax(format!("INTERNAL_ERROR: {:?} missing value", rule)))?;
+        match rule {
+            Rule::kv_threshold => threshold = Some(parse_threshold(value_pair)?),
+            Rule::kv_duration  => duration  = Some(parse_duration(value_pair)?),
+            Rule::kv_scope     => scope     = Some(unquote(value_pair.as_str())),
+            Rule::on_pass      => on_pass   = Some(parse_block(value_pair)?),
+            _ => {
+                return Err(CclError::Syntax(format!(
+                    "Unexpected rule {:?} inside proposal. Expected kv_threshold, kv_duration, kv_scope or on_pass.",
+                    rule
+                )));
+            }
+        }
+    }
+
+    Ok(CclProposal {
+        threshold: threshold.ok_or_else(|| CclError::Semantic(format!("Missing 'threshold' in proposal '{}'.", title)))?,
+        duration:  duration .ok_or_else(|| CclError::Semantic(format!("Missing 'duration' in proposal '{}'.", title)))?,
+        scope:     scope    .ok_or_else(|| CclError::Semantic(format!("Missing 'scope' in proposal '{}'.", title)))?,
+        on_pass:   on_pass  .ok_or_else(|| CclError::Semantic(format!("Missing 'on_pass' in proposal '{}'.", title)))?,
+        title,
+    })
+}
+
+fn parse_threshold(pair: Pair<Rule>) -> Result<CclThreshold, CclError> {
+    match pair.as_rule() {
+        Rule::majority  => Ok(CclThreshold::Majority),
+        Rule::unanimous => Ok(CclThreshold::Unanimous),
+        Rule::percentage => {
+            let span = span_of(&pair);
+            let lit_str = pair.into_inner().as_str();
+            match lit_str.parse::<u8>() {
+                Ok(percent) if (1..=100).contains(&percent) => Ok(CclThreshold::Percentage(percent)),
+                _ => Err(located(format!("Invalid threshold '{}%': must be between 1% and 100%.", lit_str), span)),
+            }
+        }
+        rule => Err(CclError::Syntax(format!("INTERNAL_ERROR: Unexpected threshold rule {:?}", rule))),
+    }
+}
+
+fn parse_duration(pair: Pair<Rule>) -> Result<CclDuration, CclError> {
+    let rule = pair.as_rule();
+    if rule == Rule::open_ended {
+        return Ok(CclDuration::OpenEnded);
+    }
+
+    let span = span_of(&pair);
+    let mut inner = pair.into_inner();
+    let lit_str = inner.next()
+        .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: duration missing int_lit".to_string()))?
+        .as_str();
+    let count = lit_str.parse::<u64>()
+        .map_err(|_| located(format!("Invalid duration '{}': not a valid u64 integer.", lit_str), span))?;
+
+    match rule {
+        Rule::blocks => Ok(CclDuration::BlockBased(count)),
+        Rule::time_span => {
+            let unit_seconds = match inner.next().map(|unit| unit.as_str()) {
+                Some("m") => 60,
+                Some("h") => 60 * 60,
+                Some("d") => 24 * 60 * 60,
+                _ => 1,
+            };
+            count.checked_mul(unit_seconds)
+                .map(CclDuration::TimeBased)
+                .ok_or_else(|| located(format!("Invalid duration '{}': too long.", lit_str), span))
+        }
+        rule => Err(CclError::Syntax(format!("INTERNAL_ERROR: Unexpected duration rule {:?}", rule))),
+    }
+}
+
+fn parse_policy(pair: Pair<Rule>) -> Result<CclPolicy, CclError> {
+    let rules = pair.into_inner().map(parse_allow_rule).collect::<Result<_, _>>()?;
+    Ok(CclPolicy { rules })
+}
+
+fn parse_allow_rule(pair: Pair<Rule>) -> Result<CclPolicyRule, CclError> {
+    let mut inner = pair.into_inner();
+    let action_type = unquote(inner.next()
+        .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: allow_rule missing action".to_string()))?
+        .as_str());
+
+    let mut required_membership = None;
+    let mut allowed_dids = None;
+
+    for principal_pair in inner {
+        let rule = principal_pair.as_rule();
+        let span = span_of(&principal_pair);
+        let mut values = principal_pair.into_inner().map(|lit| unquote(lit.as_str()));
+        let duplicate = match rule {
+            Rule::anyone => false,
+            Rule::members_of => required_membership.replace(values.next().unwrap_or_default()).is_some(),
+            Rule::dids       => allowed_dids.replace(values.collect::<Vec<_>>()).is_some(),
+            _ => {
+                return Err(CclError::Syntax(format!(
+                    "Unexpected rule {:?} inside allow. Expected anyone, members_of or dids.",
+                    rule
+                )));
+            }
+        };
+        if duplicate {
+            return Err(located(format!(
+                "'{}' given more than once for action '{}'.",
+                if rule == Rule::dids { "dids" } else { "members_of" },
+                action_type
+            ), span));
+        }
+    }
+
+    Ok(CclPolicyRule { action_type, required_membership, allowed_dids })
+}
+
+fn parse_block(pair: Pair<Rule>) -> Result<Vec<CclStmt>, CclError> {
+    pair.into_inner().map(parse_stmt).collect()
+}
+
+fn parse_binding(pair: Pair<Rule>, is_let: bool) -> Result<CclStmt, CclError> {
+    let mut inner = pair.into_inner();
+    let name = spanned_str(&inner.next()
+        .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: binding missing ident".to_string()))?);
+    let value = parse_expr(inner.next()
+        .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: binding missing expr".to_string()))?)?;
+
+    Ok(if is_let { CclStmt::Let { name, value } } else { CclStmt::Assign { name, value } })
+}
+
+fn parse_if(pair: Pair<Rule>) -> Result<CclStmt, CclError> {
+    let mut inner = pair.into_inner();
+    let cond = parse_expr(inner.next()
+        .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: if_stmt missing condition".to_string()))?)?;
+    let then_body = parse_block(inner.next()
+        .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: if_stmt missing block".to_string()))?)?;
+    let else_body = match inner.next() {
+        None => Vec::new(),
+        Some(else_pair) if else_pair.as_rule() == Rule::if_stmt => vec![parse_if(else_pair)?],
+        Some(else_pair) => parse_block(else_pair)?,
+    };
+
+    Ok(CclStmt::If { cond, then_body, else_body })
+}
+
+fn parse_for(pair: Pair<Rule>) -> Result<CclStmt, CclError> {
+    let mut inner = pair.into_inner();
+    let mut next = |what: &str| inner.next()
+        .ok_or_else(|| CclError::Syntax(format!("INTERNAL_ERROR: for_stmt missing {}", what)));
+    let var   = spanned_str(&next("ident")?);
+    let start = parse_expr(next("start")?)?;
+    let end   = parse_expr(next("end")?)?;
+    let body  = parse_block(next("block")?)?;
+
+    Ok(CclStmt::For { var, start, end, body })
+}
+
+fn pratt_parser() -> &'static PrattParser<Rule> {
+    static PRATT: OnceLock<PrattParser<Rule>> = OnceLock::new();
+    // Keep in sync with `BinaryOp::precedence`
+    PRATT.get_or_init(|| {
+        PrattParser::new()
+            .op(Op::infix(Rule::or, Assoc::Left))
+            .op(Op::infix(Rule::and, Assoc::Left))
+            .op(Op::infix(Rule::eq, Assoc::Left) | Op::infix(Rule::ne, Assoc::Left))
+            .op(Op::infix(Rule::lt, Assoc::Left)
+                | Op::infix(Rule::le, Assoc::Left)
+                | Op::infix(Rule::gt, Assoc::Left)
+                | Op::infix(Rule::ge, Assoc::Left))
+            .op(Op::infix(Rule::add, Assoc::Left) | Op::infix(Rule::sub, Assoc::Left))
+            .op(Op::infix(Rule::mul, Assoc::Left) | Op::infix(Rule::div, Assoc::Left) | Op::infix(Rule::rem, Assoc::Left))
+            .op(Op::prefix(Rule::neg) | Op::prefix(Rule::not))
+    })
+}
+
+fn parse_expr(pair: Pair<Rule>) -> Result<CclExpr, CclError> {
+    pratt_parser()
+        .map_primary(|primary| {
+            let span = span_of(&primary);
+            let expr = match primary.as_rule() {
+                Rule::int_lit => {
+                    let lit_str = primary.as_str();
+                    lit_str.parse::<u128>().map(CclExpr::Int).map_err(|_| {
+                        located(format!("Invalid integer '{}': not a valid u128 integer.", lit_str), span)
+                    })?
+                }
+                Rule::bool_lit   => CclExpr::Bool(primary.as_str() == "true"),
+                Rule::string_lit => CclExpr::Str(unquote(primary.as_str())),
+                Rule::ident      => CclExpr::Var(primary.as_str().to_string()),
+                Rule::call       => parse_call(primary)?,
+                // Already located; keep the span of the parenthesised expression
+                Rule::expr       => return parse_expr(primary),
+                rule => return Err(CclError::Syntax(format!("INTERNAL_ERROR: Unexpected rule {:?} in expression", rule))),
+            };
+            Ok(CclExpr::Located { span, expr: Box::new(expr) })
+        })
+        .map_prefix(|op, expr| {
+            let expr = expr?;
+            let span = join(span_of(&op), expr.span());
+            let op = match op.as_rule() {
+                Rule::neg => UnaryOp::Neg,
+                _ => UnaryOp::Not,
+            };
+            let expr = CclExpr::Unary { op, expr: Box::new(expr) };
+            Ok(CclExpr::Located { span, expr: Box::new(expr) })
+        })
+        .map_infix(|lhs, op, rhs| {
+            let (lhs, rhs) = (lhs?, rhs?);
+            let span = join(lhs.span().unwrap_or_else(|| span_of(&op)), rhs.span());
+            let op = match op.as_rule() {
+                Rule::or  => BinaryOp::Or,
+                Rule::and => BinaryOp::And,
+                Rule::eq  => BinaryOp::Eq,
+                Rule::ne  => BinaryOp::Ne,
+                Rule::lt  => BinaryOp::Lt,
+                Rule::le  => BinaryOp::Le,
+                Rule::gt  => BinaryOp::Gt,
+                Rule::ge  => BinaryOp::Ge,
+                Rule::add => BinaryOp::Add,
+                Rule::sub => BinaryOp::Sub,
+                Rule::mul => BinaryOp::Mul,
+                Rule::div => BinaryOp::Div,
+                Rule::rem => BinaryOp::Rem,
+                rule => return Err(CclError::Syntax(format!("INTERNAL_ERROR: Unexpected operator {:?}", rule))),
+            };
+            let expr = CclExpr::Binary { op, lhs: Box::new(lhs), rhs: Box::new(rhs) };
+            Ok(CclExpr::Located { span, expr: Box::new(expr) })
+        })
+        .parse(pair.into_inner())
+}
+
+fn parse_call(pair: Pair<Rule>) -> Result<CclExpr, CclError> {
+    let mut inner = pair.into_inner();
+    let name = inner.next()
+        .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: call missing ident".to_string()))?
+        .as_str()
+        .to_string();
+    let args = inner.map(parse_expr).collect::<Result<_, _>>()?;
+
+    Ok(CclExpr::Call { name, args })
+}
+
+/// Parse the expression of a `kv_*` rule such as `kv_amount`
+fn parse_kv_expr(pair: Pair<Rule>, key: &str) -> Result<CclExpr, CclError> {
+    let expr_pair = pair.into_inner().next()
+        .ok_or_else(|| CclError::Syntax(format!("INTERNAL_ERROR: kv_{} missing expr", key)))?;
+    parse_expr(expr_pair)
 }
 
 fn parse_metered_action(pair: Pair<Rule>) -> Result<CclStmt, CclError> {
@@ -73,15 +353,10 @@ fn parse_metered_action(pair: Pair<Rule>) -> Result<CclStmt, CclError> {
             Rule::kv_resource_type => {
                 let string_lit_pair = inner_kv_pair.into_inner().next()
                     .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: kv_resource_type missing string_lit".to_string()))?;
-                resource = Some(unquote(string_lit_pair.as_str()));
+                resource = Some(spanned_str(&string_lit_pair));
             }
             Rule::kv_amount => {
-                let int_lit_pair = inner_kv_pair.into_inner().next()
-                    .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: kv_amount missing int_lit".to_string()))?;
-                let lit_str = int_lit_pair.as_str();
-                amount = Some(lit_str.parse::<u64>().map_err(|_| {
-                    CclError::Semantic(format!("Invalid amount '{}': not a valid u64 integer.", lit_str))
-                })?);
+                amount = Some(parse_kv_expr(inner_kv_pair, "amount")?);
             }
             _ => {
                  return Err(CclError::Syntax(format!(
@@ -107,15 +382,10 @@ fn parse_mint_token(pair: Pair<Rule>) -> Result<CclStmt, CclError> {
             Rule::kv_token => {
                 let string_lit_pair = inner_kv_pair.into_inner().next()
                     .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: kv_token missing string_lit".to_string()))?;
-                token = Some(unquote(string_lit_pair.as_str()));
+                token = Some(spanned_str(&string_lit_pair));
             }
             Rule::kv_amount => {
-                let int_lit_pair = inner_kv_pair.into_inner().next()
-                    .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: kv_amount missing int_lit".to_string()))?;
-                let lit_str = int_lit_pair.as_str();
-                amount = Some(lit_str.parse::<u128>().map_err(|_| {
-                    CclError::Semantic(format!("Invalid amount '{}': not a valid u128 integer.", lit_str))
-                })?);
+                amount = Some(parse_kv_expr(inner_kv_pair, "amount")?);
             }
             _ => {
                 return Err(CclError::Syntax(format!(
@@ -142,20 +412,15 @@ fn parse_transfer_resource(pair: Pair<Rule>) -> Result<CclStmt, CclError> {
             Rule::kv_token => {
                 let string_lit_pair = inner_kv_pair.into_inner().next()
                     .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: kv_token missing string_lit".to_string()))?;
-                token = Some(unquote(string_lit_pair.as_str()));
+                token = Some(spanned_str(&string_lit_pair));
             }
             Rule::kv_to => {
                 let string_lit_pair = inner_kv_pair.into_inner().next()
                     .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: kv_to missing string_lit".to_string()))?;
-                to = Some(unquote(string_lit_pair.as_str()));
+                to = Some(spanned_str(&string_lit_pair));
             }
             Rule::kv_amount => {
-                let int_lit_pair = inner_kv_pair.into_inner().next()
-                    .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: kv_amount missing int_lit".to_string()))?;
-                let lit_str = int_lit_pair.as_str();
-                amount = Some(lit_str.parse::<u128>().map_err(|_| {
-                    CclError::Semantic(format!("Invalid amount '{}': not a valid u128 integer.", lit_str))
-                })?);
+                amount = Some(parse_kv_expr(inner_kv_pair, "amount")?);
             }
             _ => {
                 return Err(CclError::Syntax(format!(
@@ -182,15 +447,10 @@ fn parse_anchor_data(pair: Pair<Rule>) -> Result<CclStmt, CclError> {
             Rule::kv_cid => {
                 let string_lit_pair = inner_kv_pair.into_inner().next()
                     .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: kv_cid missing string_lit".to_string()))?;
-                cid = Some(unquote(string_lit_pair.as_str()));
+                cid = Some(spanned_str(&string_lit_pair));
             }
             Rule::kv_bytes => {
-                let int_lit_pair = inner_kv_pair.into_inner().next()
-                    .ok_or_else(|| CclError::Syntax("INTERNAL_ERROR: kv_bytes missing int_lit".to_string()))?;
-                let lit_str = int_lit_pair.as_str();
-                bytes = Some(lit_str.parse::<u64>().map_err(|_| {
-                    CclError::Semantic(format!("Invalid bytes '{}': not a valid u64 integer.", lit_str))
-                })?);
+                bytes = Some(parse_kv_expr(inner_kv_pair, "bytes")?);
             }
             _ => {
                 return Err(CclError::Syntax(format!(
@@ -211,10 +471,67 @@ fn unquote(s: &str) -> String {
     s.trim_matches('"').to_string()
 }
 
+fn span_of(pair: &Pair<Rule>) -> Span {
+    let span = pair.as_span();
+    let (line, col) = span.start_pos().line_col();
+    Span { start: span.start(), end: span.end(), line, col }
+}
+
+/// From the start of `first` to the end of `last`
+fn join(first: Span, last: Option<Span>) -> Span {
+    Span { end: last.map_or(first.end, |last| last.end), ..first }
+}
+
+/// A string literal or identifier, unquoted, with its span
+fn spanned_str(pair: &Pair<Rule>) -> Spanned<String> {
+    Spanned::new(unquote(pair.as_str()), span_of(pair))
+}
+
+fn located(message: String, span: Span) -> CclError {
+    CclError::Located(Diagnostic::error(message, Some(span)))
+}
+
+/// Turn a pest error into a located diagnostic, with rule names a CCL author would recognise
+fn syntax_diagnostic(error: pest::error::Error<Rule>) -> CclError {
+    let (start, end) = match error.location {
+        InputLocation::Pos(pos) => (pos, pos),
+        InputLocation::Span(span) => span,
+    };
+    let (line, col) = match error.line_col {
+        LineColLocation::Pos(pos) | LineColLocation::Span(pos, _) => pos,
+    };
+    let error = error.renamed_rules(|rule| match rule {
+        Rule::EOI        => "end of input".to_string(),
+        Rule::stmt       => "a statement".to_string(),
+        Rule::block      => "a block".to_string(),
+        Rule::expr       => "an expression".to_string(),
+        Rule::ident      => "a name".to_string(),
+        Rule::int_lit    => "an integer".to_string(),
+        Rule::bool_lit   => "a boolean".to_string(),
+        Rule::string_lit => "a string".to_string(),
+        rule             => format!("{:?}", rule),
+    });
+    let diagnostic = Diagnostic::error(format!("syntax error: {}", error.variant.message()), Some(Span { start, end, line, col }));
+    CclError::Located(diagnostic)
+}
+
 #[derive(Error, Debug)]
 pub enum CclError {
     #[error("syntax error: {0}")]
     Syntax(String),
     #[error("semantic error: {0}")]
     Semantic(String),
+    /// An error at a known place in the source
+    #[error("{0}")]
+    Located(Diagnostic),
+}
+
+impl CclError {
+    /// This error as a diagnostic, without a span unless it is `Located`
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            CclError::Located(diagnostic) => diagnostic.clone(),
+            other => Diagnostic::error(other.to_string(), None),
+        }
+    }
 } 
\ No newline at end of file
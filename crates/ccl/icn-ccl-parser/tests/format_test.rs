@@ -0,0 +1,98 @@
+use icn_ccl_parser::{format_ccl, parse_ccl};
+
+/// Formatting must be stable and must not change what the source means
+fn assert_canonical(src: &str, expected: &str) {
+    let formatted = format_ccl(src).expect("format failed");
+    assert_eq!(formatted, expected);
+    assert_eq!(format_ccl(&formatted).expect("reformat failed"), formatted);
+    assert_eq!(parse_ccl(&formatted).expect("formatted source parses"), parse_ccl(src).expect("source parses"));
+}
+
+#[test]
+fn statements_are_laid_out_canonically() {
+    let src = r#"let   fee=10
+let bonus = -(fee+2)*3
+if fee>5&&!false{ fee = fee+bonus } else if fee == 0 {} else { log("none") }
+for i in 0 .. 3 { mint_token { token="credit" amount=i } }
+perform_metered_action { resource_type = "compute_unit"
+   amount = max(fee, 1) }"#;
+
+    assert_canonical(src, r#"let fee = 10
+let bonus = -(fee + 2) * 3
+if fee > 5 && !false {
+    fee = fee + bonus
+} else if fee == 0 {} else {
+    log("none")
+}
+for i in 0..3 {
+    mint_token {
+        token  = "credit"
+        amount = i
+    }
+}
+perform_metered_action {
+    resource_type = "compute_unit"
+    amount        = max(fee, 1)
+}
+"#);
+}
+
+#[test]
+fn proposals_and_policies_are_laid_out_canonically() {
+    let src = r#"proposal "Spend {x}" { threshold = 60 % duration = 100 blocks scope = "coop-x"
+  on_pass { anchor_data { cid = "bafy" bytes = 10 } } }
+policy {
+  allow "mint_token" to members_of("coop-x") and dids("did:key:a","did:key:b")
+  allow "log" to anyone;
+}"#;
+
+    assert_canonical(src, r#"proposal "Spend {x}" {
+    threshold = 60%
+    duration  = 100 blocks
+    scope     = "coop-x"
+    on_pass {
+        anchor_data {
+            cid   = "bafy"
+            bytes = 10
+        }
+    }
+}
+policy {
+    allow "mint_token" to members_of("coop-x") and dids("did:key:a", "did:key:b");
+    allow "log" to anyone;
+}
+"#);
+}
+
+#[test]
+fn comments_and_blank_lines_are_preserved() {
+    let src = r#"
+# Bylaws for coop-x
+let fee = 10   # base fee
+
+
+
+if fee > 5 {
+  # never waived
+      log("fee # is charged")
+  # end of branch
+}
+# trailer"#;
+
+    assert_canonical(src, r#"# Bylaws for coop-x
+let fee = 10  # base fee
+
+if fee > 5 {
+    # never waived
+    log("fee # is charged")
+    # end of branch
+}
+# trailer
+"#);
+}
+
+#[test]
+fn invalid_source_is_not_formatted() {
+    let err = format_ccl("let x = * 2").unwrap_err();
+    assert!(err.to_string().contains("expected an expression"), "{}", err);
+}
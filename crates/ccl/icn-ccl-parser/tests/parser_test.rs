@@ -1,4 +1,4 @@
-use icn_ccl_parser::parse_ccl;
+use icn_ccl_parser::{parse_ccl, BinaryOp, CclDuration, CclError, CclExpr, CclPolicyRule, CclStmt, CclThreshold, UnaryOp};
 
 #[test]
 fn parse_minimal_metered_action() {
@@ -12,11 +12,11 @@ fn parse_minimal_metered_action() {
     let module = parse_ccl(source).expect("parse failed");
 
     assert_eq!(module.stmts.len(), 1);
-    if let Some(stmt) = module.stmts.get(0) {
+    if let Some(stmt) = module.stmts.first() {
         match stmt {
             icn_ccl_parser::CclStmt::PerformMeteredAction { resource, amount } => {
                 assert_eq!(resource, "compute_fuel");
-                assert_eq!(*amount, 42);
+                assert_eq!(*amount, CclExpr::Int(42));
             }
             other => panic!("Unexpected statement: {:?}", other),
         }
@@ -36,7 +36,7 @@ fn parse_mint_token() {
     match &module.stmts[0] {
         icn_ccl_parser::CclStmt::MintToken { token, amount } => {
             assert_eq!(token, "coop_credit");
-            assert_eq!(*amount, 1_000);
+            assert_eq!(*amount, CclExpr::Int(1_000));
         }
         _ => panic!("wrong stmt"),
     }
@@ -57,7 +57,7 @@ fn parse_transfer_resource() {
         icn_ccl_parser::CclStmt::TransferResource { token, to, amount } => {
             assert_eq!(token, "mesh_gpu_hours");
             assert_eq!(to, "did:coop:alice");
-            assert_eq!(*amount, 10);
+            assert_eq!(*amount, CclExpr::Int(10));
         }
         _ => panic!("wrong stmt"),
     }
@@ -76,8 +76,222 @@ fn parse_anchor_data() {
     match &module.stmts[0] {
         icn_ccl_parser::CclStmt::AnchorData { cid, bytes } => {
             assert_eq!(cid, "bafybeigdyrzt...");
-            assert_eq!(*bytes, 2048);
+            assert_eq!(*bytes, CclExpr::Int(2048));
         }
         _ => panic!("wrong stmt"),
     }
-} 
\ No newline at end of file
+}
+
+fn var(name: &str) -> Box<CclExpr> {
+    Box::new(CclExpr::Var(name.to_string()))
+}
+
+fn int(value: u128) -> Box<CclExpr> {
+    Box::new(CclExpr::Int(value))
+}
+
+#[test]
+fn parse_precedence_and_associativity() {
+    let module = parse_ccl("let x = 1 + 2 * 3 - 4 < 5 && !done").unwrap();
+    let expected = CclExpr::Binary {
+        op: BinaryOp::And,
+        lhs: Box::new(CclExpr::Binary {
+            op: BinaryOp::Lt,
+            lhs: Box::new(CclExpr::Binary {
+                op: BinaryOp::Sub,
+                lhs: Box::new(CclExpr::Binary {
+                    op: BinaryOp::Add,
+                    lhs: int(1),
+                    rhs: Box::new(CclExpr::Binary { op: BinaryOp::Mul, lhs: int(2), rhs: int(3) }),
+                }),
+                rhs: int(4),
+            }),
+            rhs: int(5),
+        }),
+        rhs: Box::new(CclExpr::Unary { op: UnaryOp::Not, expr: var("done") }),
+    };
+    assert_eq!(module.stmts, vec![CclStmt::Let { name: "x".into(), value: expected }]);
+}
+
+#[test]
+fn parse_computed_amount() {
+    let src = r#"
+        let base = 100
+        mint_token {
+            token  = "coop_credit"
+            amount = base * (members + 1)
+        }
+    "#;
+
+    let module = parse_ccl(src).unwrap();
+    match &module.stmts[1] {
+        CclStmt::MintToken { amount, .. } => assert_eq!(amount.to_string(), "base * (members + 1)"),
+        other => panic!("Unexpected statement: {:?}", other),
+    }
+}
+
+#[test]
+fn parse_control_flow() {
+    let src = r#"
+        let total = 0
+        for i in 0..n {
+            if i % 2 == 0 {
+                total = total + i
+            } else if i > 7 {
+                log("big")
+            } else {
+                total = max(total, i)
+            }
+        }
+    "#;
+
+    let module = parse_ccl(src).unwrap();
+    assert_eq!(module.stmts.len(), 2);
+    let CclStmt::For { var, start, end, body } = &module.stmts[1] else {
+        panic!("Expected a for loop, got {:?}", module.stmts[1]);
+    };
+    assert_eq!(var, "i");
+    assert_eq!(*start, CclExpr::Int(0));
+    assert_eq!(*end, CclExpr::Var("n".into()));
+
+    let CclStmt::If { then_body, else_body, .. } = &body[0] else {
+        panic!("Expected an if, got {:?}", body[0]);
+    };
+    assert!(matches!(&then_body[0], CclStmt::Assign { name, .. } if name == "total"));
+    let CclStmt::If { then_body, else_body, .. } = &else_body[0] else {
+        panic!("Expected an else if, got {:?}", else_body[0]);
+    };
+    assert_eq!(then_body[0], CclStmt::Expr(CclExpr::Call { name: "log".into(), args: vec![CclExpr::Str("big".into())] }));
+    assert_eq!(else_body[0], CclStmt::Assign {
+        name: "total".into(),
+        value: CclExpr::Call { name: "max".into(), args: vec![CclExpr::Var("total".into()), CclExpr::Var("i".into())] },
+    });
+}
+
+#[test]
+fn keywords_are_not_identifier_prefixes() {
+    let module = parse_ccl("letter = 1\nformat = iffy").unwrap();
+    assert_eq!(module.stmts, vec![
+        CclStmt::Assign { name: "letter".into(), value: CclExpr::Int(1) },
+        CclStmt::Assign { name: "format".into(), value: CclExpr::Var("iffy".into()) },
+    ]);
+    assert!(parse_ccl("let if = 1").is_err());
+}
+
+#[test]
+fn expression_display_round_trips() {
+    for src in ["a - (b - c)", "(a + b) * c", "-(a + 1) / 2", "!(x && y) || z", "max(a, b % 3) >= 10"] {
+        let module = parse_ccl(&format!("let v = {}", src)).unwrap();
+        let CclStmt::Let { value, .. } = &module.stmts[0] else { unreachable!() };
+        assert_eq!(value.to_string(), src);
+    }
+}
+
+#[test]
+fn parse_proposal() {
+    let src = r#"
+        proposal "Admit new members" {
+            threshold = majority
+            duration  = 12h
+            scope     = "coop-x"
+            on_pass {
+                mint_token { token = "membership" amount = 1 }
+            }
+        }
+        anchor_data { cid = "bafy" bytes = 1 }
+    "#;
+
+    let module = parse_ccl(src).unwrap();
+    assert_eq!(module.stmts.len(), 1);
+    assert_eq!(module.proposals.len(), 1);
+    let proposal = &module.proposals[0];
+    assert_eq!(proposal.title, "Admit new members");
+    assert_eq!(proposal.threshold, CclThreshold::Majority);
+    assert_eq!(proposal.duration, CclDuration::TimeBased(12 * 60 * 60));
+    assert_eq!(proposal.scope, "coop-x");
+    assert_eq!(proposal.on_pass, vec![CclStmt::MintToken { token: "membership".into(), amount: CclExpr::Int(1) }]);
+}
+
+#[test]
+fn parse_proposal_voting_rules() {
+    let rules = |threshold: &str, duration: &str| {
+        let src = format!(
+            r#"proposal "p" {{ threshold = {} duration = {} scope = "s" on_pass {{ }} }}"#,
+            threshold, duration
+        );
+        parse_ccl(&src).map(|module| (module.proposals[0].threshold, module.proposals[0].duration))
+    };
+
+    assert_eq!(rules("75%", "3600s").unwrap(), (CclThreshold::Percentage(75), CclDuration::TimeBased(3600)));
+    assert_eq!(rules("unanimous", "30m").unwrap(), (CclThreshold::Unanimous, CclDuration::TimeBased(1800)));
+    assert_eq!(rules("100%", "10 blocks").unwrap(), (CclThreshold::Percentage(100), CclDuration::BlockBased(10)));
+    assert_eq!(rules("1%", "open_ended").unwrap(), (CclThreshold::Percentage(1), CclDuration::OpenEnded));
+
+    assert!(rules("0%", "1d").is_err());
+    assert!(rules("101%", "1d").is_err());
+    assert!(rules("majority", "1 d").is_err());
+    assert!(rules("majority", "1w").is_err());
+}
+
+#[test]
+fn voting_rules_serialize_like_identity_core() {
+    assert_eq!(serde_json::to_string(&CclThreshold::Percentage(60)).unwrap(), r#"{"percentage":60}"#);
+    assert_eq!(serde_json::to_string(&CclDuration::TimeBased(60)).unwrap(), r#"{"timeBased":60}"#);
+    assert_eq!(serde_json::to_string(&CclDuration::OpenEnded).unwrap(), r#""openEnded""#);
+}
+
+#[test]
+fn parse_policy() {
+    let src = r#"
+        policy {
+            allow "submit_proposal" to members_of("coop-x");
+            allow "mint_token" to dids("did:key:a", "did:key:b") and members_of("coop-x")
+            allow "read" to anyone;
+        }
+        let policy_version = 2
+    "#;
+
+    let module = parse_ccl(src).expect("parse failed");
+    assert_eq!(module.policies.len(), 1);
+    assert_eq!(module.stmts.len(), 1);
+    assert_eq!(module.policies[0].rules, vec![
+        CclPolicyRule {
+            action_type: "submit_proposal".into(),
+            required_membership: Some("coop-x".into()),
+            allowed_dids: None,
+        },
+        CclPolicyRule {
+            action_type: "mint_token".into(),
+            required_membership: Some("coop-x".into()),
+            allowed_dids: Some(vec!["did:key:a".into(), "did:key:b".into()]),
+        },
+        CclPolicyRule { action_type: "read".into(), required_membership: None, allowed_dids: None },
+    ]);
+
+    // Display renders source that parses back to the same policy
+    let rendered = module.policies[0].to_string();
+    assert_eq!(parse_ccl(&rendered).expect("reparse failed").policies, module.policies);
+
+    assert!(parse_ccl(r#"policy { allow "x" to members_of("a") and members_of("b") }"#).is_err());
+    assert!(parse_ccl(r#"policy { allow "x" to anyone and dids("did:key:a") }"#).is_err());
+    assert!(parse_ccl(r#"policy { allow "x" }"#).is_err());
+}
+
+#[test]
+fn nodes_carry_their_source_spans() {
+    let src = "let total = 1\nmint_token {\n    token  = \"credit\"\n    amount = total * 2\n}";
+    let module = parse_ccl(src).unwrap();
+
+    let CclStmt::MintToken { token, amount } = &module.stmts[1] else { unreachable!() };
+    assert_eq!((token.span.line, token.span.col), (3, 14));
+    assert_eq!(&src[token.span.start..token.span.end], "\"credit\"");
+
+    let span = amount.span().expect("expressions are located");
+    assert_eq!((span.line, span.col), (4, 14));
+    assert_eq!(&src[span.start..span.end], "total * 2");
+
+    match parse_ccl("let x = (1 +\n)") {
+        Err(CclError::Located(diagnostic)) => assert_eq!((diagnostic.span.unwrap().line, diagnostic.span.unwrap().col), (2, 1)),
+        other => panic!("Expected a located syntax error, got {:?}", other),
+    }
+}
@@ -154,8 +154,9 @@ impl<'de> Deserialize<'de> for Cid {
     where
         D: Deserializer<'de>,
     {
-        let bytes = Vec::<u8>::deserialize(deserializer)?;
-        ExternalCid::try_from(bytes)
+        // ByteBuf accepts both byte strings (CBOR) and sequences (JSON)
+        let bytes = serde_bytes::ByteBuf::deserialize(deserializer)?;
+        ExternalCid::try_from(bytes.into_vec())
             .map(Cid)
             .map_err(serde::de::Error::custom)
     }
@@ -36,7 +36,7 @@ impl Did {
     pub fn to_string(&self) -> String {
         let mut prefixed_key = vec![0xed, 0x01]; 
         prefixed_key.extend_from_slice(&self.public_key_bytes);
-        format!("did:key:z{}", multibase::encode(multibase::Base::Base58Btc, prefixed_key))
+        format!("did:key:{}", multibase::encode(multibase::Base::Base58Btc, prefixed_key))
     }
     pub fn public_key_bytes(&self) -> &[u8] {
         &self.public_key_bytes
@@ -46,12 +46,17 @@ impl Did {
         VerifyingKey::from_bytes(key_bytes)
     }
 
-    /// Parse a DID string (e.g., did:key:z...) into a Did object
+    /// Parse a DID string (e.g., did:key:z...) into a Did object.
+    ///
+    /// Also accepts the legacy `did:key:zz…` form that `to_string` used to
+    /// produce, so DIDs stored before the fix still parse. An Ed25519 `did:key`
+    /// always encodes to `z6Mk…`, so a doubled `z` is never ambiguous.
     pub fn from_string(did_str: &str) -> Result<Self, DidParseError> {
         if !did_str.starts_with("did:key:") {
             return Err(DidParseError::UnsupportedDidMethod(did_str.to_string()));
         }
         let encoded_key = &did_str[8..];
+        let encoded_key = encoded_key.strip_prefix('z').filter(|rest| rest.starts_with('z')).unwrap_or(encoded_key);
 
         let (base, decoded_bytes) = multibase::decode(encoded_key)?;
         if base != Base::Base58Btc {
@@ -0,0 +1,26 @@
+use ed25519_dalek::SigningKey;
+use icn_core_types::Did;
+use rand::rngs::OsRng;
+
+fn random_did() -> Did {
+    Did::new(&SigningKey::generate(&mut OsRng).verifying_key())
+}
+
+#[test]
+fn did_round_trips_through_its_string_form() {
+    let did = random_did();
+    let encoded = did.to_string();
+
+    assert!(encoded.starts_with("did:key:z6Mk"), "{}", encoded);
+    assert_eq!(Did::from_string(&encoded).unwrap(), did);
+    assert_eq!(encoded.parse::<Did>().unwrap(), did);
+}
+
+#[test]
+fn legacy_double_z_form_still_parses() {
+    let did = random_did();
+    let legacy = did.to_string().replacen("did:key:z", "did:key:zz", 1);
+
+    assert_eq!(Did::from_string(&legacy).unwrap(), did);
+    assert!(Did::from_string("did:key:zzz6Mk").is_err());
+}
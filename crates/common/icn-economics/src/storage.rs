@@ -439,4 +439,27 @@ mod tests {
         let history = store.get_transaction_history("test-coop").await.unwrap();
         assert_eq!(history.len(), 1);
     }
-} 
\ No newline at end of file
+    
+    #[test]
+    async fn test_reversal_restores_balances() {
+        let store = InMemoryTokenStore::new();
+        let did = icn_identity_core::did::DidKey::new().did().clone();
+        store.credit("source-coop", ResourceType::ComputeUnit, 100).await.unwrap();
+        
+        let transfer = ResourceTransaction::new_transfer(
+            ResourceType::ComputeUnit,
+            40,
+            "source-coop",
+            "dest-coop",
+            "test-federation",
+            did,
+        );
+        store.apply_transaction(&transfer).await.unwrap();
+        store.apply_transaction(&transfer.reversal()).await.unwrap();
+        
+        let source_balance = store.get_balance("source-coop", &ResourceType::ComputeUnit).await.unwrap();
+        let dest_balance = store.get_balance("dest-coop", &ResourceType::ComputeUnit).await.unwrap();
+        assert_eq!(source_balance, 100);
+        assert_eq!(dest_balance, 0);
+    }
+}
@@ -50,6 +50,23 @@ pub enum ResourceType {
     Custom(String),
 }
 
+impl std::str::FromStr for ResourceType {
+    type Err = std::convert::Infallible;
+
+    /// Parse a resource type from its snake_case name; unknown names become `Custom`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "compute_unit" => ResourceType::ComputeUnit,
+            "storage_mb" => ResourceType::StorageMb,
+            "bandwidth_mb" => ResourceType::BandwidthMb,
+            "gpu_minute" => ResourceType::GpuMinute,
+            "governance_point" => ResourceType::GovernancePoint,
+            "credit" => ResourceType::Credit,
+            other => ResourceType::Custom(other.to_string()),
+        })
+    }
+}
+
 /// Base token representing a digital asset within the ICN
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceToken {
@@ -229,6 +246,13 @@ mod tests {
         assert!(result);
     }
     
+    #[test]
+    fn test_resource_type_from_name() {
+        assert_eq!("compute_unit".parse::<ResourceType>().unwrap(), ResourceType::ComputeUnit);
+        assert_eq!("gpu_minute".parse::<ResourceType>().unwrap(), ResourceType::GpuMinute);
+        assert_eq!("seed_library".parse::<ResourceType>().unwrap(), ResourceType::Custom("seed_library".to_string()));
+    }
+    
     #[test]
     fn test_token_expiration() {
         // Create a token that's already expired
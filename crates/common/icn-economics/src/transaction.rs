@@ -163,6 +163,26 @@ impl ResourceTransaction {
         self.dag_anchors.push(cid);
     }
     
+    /// The transaction that undoes this one, e.g. to compensate for an aborted execution
+    pub fn reversal(&self) -> Self {
+        let transaction_type = match self.transaction_type {
+            TransactionType::Debit => TransactionType::Credit,
+            TransactionType::Credit => TransactionType::Debit,
+            TransactionType::Transfer => TransactionType::Transfer,
+            TransactionType::Mint => TransactionType::Burn,
+            TransactionType::Burn => TransactionType::Mint,
+        };
+        Self {
+            transaction_type,
+            source_id: self.destination_id.clone(),
+            destination_id: self.source_id.clone(),
+            timestamp: Utc::now(),
+            dag_anchors: Vec::new(),
+            id: None,
+            ..self.clone()
+        }
+    }
+    
     /// Validate that the transaction has all required fields
     pub fn validate(&self) -> Result<(), TransactionError> {
         match self.transaction_type {
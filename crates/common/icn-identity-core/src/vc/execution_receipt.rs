@@ -149,6 +149,24 @@ pub enum ExecutionStatus {
     
     /// Execution was canceled
     Canceled,
+    
+    /// Execution exhausted its fuel budget
+    #[serde(rename = "out_of_fuel")]
+    OutOfFuel,
+    
+    /// Execution exceeded its wall-clock time limit
+    Timeout,
+    
+    /// Execution tried to grow memory beyond its limit
+    #[serde(rename = "memory_limit")]
+    MemoryLimit,
+    
+    /// Execution tried to grow a table beyond its limit
+    #[serde(rename = "table_limit")]
+    TableLimit,
+    
+    /// Execution was not authorized by the scope's policy or module allowlist
+    Denied,
 }
 
 /// The ExecutionReceipt Verifiable Credential structure
@@ -0,0 +1,486 @@
+//! Reusable conformance checks for `DagStore` implementations.
+//!
+//! Every backend (in-memory, RocksDB, SQLite, wrappers and mocks) is expected to
+//! behave identically with respect to tips, ordering, path finding, the author and
+//! payload-type indexes, branch verification, duplicate inserts, missing parents,
+//! bulk inserts and the change feed.
+//! A backend opts in by handing a store factory to [`run_all`]; individual checks
+//! are public so they can also be run one by one, e.g. from property-based tests
+//! via [`check_random_dag`].
+//!
+//! The checks panic on the first violation, like ordinary test assertions.
+
+use crate::dag::{DagCursor, DagError, DagFeedEvent, DagFeedFilter, DagNodeBuilder, DagPayload, DagStore, DagSubscription, PublicKeyResolver, SignedDagNode};
+use crate::{Cid, Did};
+use ed25519_dalek::VerifyingKey;
+use icn_core_types::DidKey;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// Federation ID used for all nodes created by the conformance checks
+pub const CONFORMANCE_FEDERATION_ID: &str = "conformance-federation";
+
+/// Builds uniquely-identified signed nodes and resolves the keys that signed them.
+pub struct NodeFactory {
+    keys: Vec<DidKey>,
+    counter: u64,
+}
+
+impl NodeFactory {
+    /// Create a factory with `authors` distinct signing identities
+    pub fn new(authors: usize) -> Self {
+        Self {
+            keys: (0..authors.max(1)).map(|_| DidKey::new()).collect(),
+            counter: 0,
+        }
+    }
+
+    /// DID of the author at `index`
+    pub fn author(&self, index: usize) -> &Did {
+        self.keys[index % self.keys.len()].did()
+    }
+
+    /// Build a node signed by the author at `author`.
+    ///
+    /// Every node gets a unique payload so no two nodes share a CID by accident.
+    pub fn node(&mut self, author: usize, parents: Vec<Cid>, payload: DagPayload) -> SignedDagNode {
+        self.counter += 1;
+        let key = &self.keys[author % self.keys.len()];
+        let node = DagNodeBuilder::new()
+            .with_payload(payload)
+            .with_parents(parents)
+            .with_author(key.did().clone())
+            .with_federation_id(CONFORMANCE_FEDERATION_ID.to_string())
+            .with_label(format!("conformance-{}", self.counter))
+            .build()
+            .expect("conformance node should build");
+        let node_bytes = serde_ipld_dagcbor::to_vec(&node).expect("conformance node should serialize");
+        SignedDagNode {
+            signature: key.sign(&node_bytes),
+            node,
+            cid: None,
+        }
+    }
+
+    /// Build a raw-payload node signed by the author at `author`
+    pub fn raw(&mut self, author: usize, parents: Vec<Cid>) -> SignedDagNode {
+        let payload = DagPayload::Raw(self.counter.to_be_bytes().to_vec());
+        self.node(author, parents, payload)
+    }
+
+    /// Build a node whose signature was produced by a key other than its author's
+    pub fn forged(&mut self, author: usize, parents: Vec<Cid>) -> SignedDagNode {
+        let mut node = self.raw(author, parents);
+        let node_bytes = serde_ipld_dagcbor::to_vec(&node.node).expect("conformance node should serialize");
+        node.signature = DidKey::new().sign(&node_bytes);
+        node
+    }
+}
+
+impl PublicKeyResolver for NodeFactory {
+    fn resolve(&self, did: &Did) -> Result<VerifyingKey, DagError> {
+        self.keys
+            .iter()
+            .find(|key| key.did() == did)
+            .map(|key| *key.verifying_key())
+            .ok_or_else(|| DagError::PublicKeyResolutionError(did.clone(), "Unknown conformance author".to_string()))
+    }
+}
+
+fn cid_of(node: &SignedDagNode) -> Cid {
+    node.calculate_cid().expect("stored node should have a computable CID")
+}
+
+fn cid_set(nodes: &[SignedDagNode]) -> HashSet<Cid> {
+    nodes.iter().map(cid_of).collect()
+}
+
+async fn add<S: DagStore + Send + Sync>(store: &mut S, node: SignedDagNode) -> Cid {
+    store.add_node(node).await.expect("add_node should accept a node whose parents exist")
+}
+
+async fn tip_set<S: DagStore + Send + Sync>(store: &S) -> HashSet<Cid> {
+    store.get_tips().await.expect("get_tips should succeed").into_iter().collect()
+}
+
+/// Assert that `ordered` lists every node after all of its parents.
+pub fn assert_topological(ordered: &[SignedDagNode]) {
+    let mut seen = HashSet::new();
+    for node in ordered {
+        for parent in &node.node.parents {
+            assert!(
+                seen.contains(parent),
+                "node {} was ordered before its parent {}",
+                cid_of(node),
+                parent
+            );
+        }
+        assert!(seen.insert(cid_of(node)), "node {} was returned twice", cid_of(node));
+    }
+}
+
+/// Run every conformance check, each against a fresh store from `make_store`.
+pub async fn run_all<S, F>(mut make_store: F)
+where
+    S: DagStore + Send + Sync,
+    F: FnMut() -> S,
+{
+    check_empty_store(&mut make_store()).await;
+    check_tips(&mut make_store()).await;
+    check_ordering(&mut make_store()).await;
+    check_find_path(&mut make_store()).await;
+    check_author_index(&mut make_store()).await;
+    check_payload_type_index(&mut make_store()).await;
+    check_verify_branch(&mut make_store()).await;
+    check_duplicate_insert(&mut make_store()).await;
+    check_missing_parent(&mut make_store()).await;
+    check_bulk_insert(&mut make_store()).await;
+    check_subscription(&mut make_store()).await;
+    for shape in sample_shapes() {
+        check_random_dag(&mut make_store(), &shape).await;
+    }
+}
+
+/// A new store has no tips and no nodes.
+pub async fn check_empty_store<S: DagStore + Send + Sync>(store: &mut S) {
+    assert!(tip_set(store).await.is_empty(), "empty store should have no tips");
+    assert!(
+        store.get_ordered_nodes().await.expect("get_ordered_nodes should succeed").is_empty(),
+        "empty store should have no nodes"
+    );
+}
+
+/// Tips are exactly the nodes without children, across forks and merges.
+pub async fn check_tips<S: DagStore + Send + Sync>(store: &mut S) {
+    let mut factory = NodeFactory::new(1);
+
+    let genesis = add(store, factory.raw(0, vec![])).await;
+    assert_eq!(tip_set(store).await, HashSet::from([genesis.clone()]));
+
+    let left = add(store, factory.raw(0, vec![genesis.clone()])).await;
+    let right = add(store, factory.raw(0, vec![genesis.clone()])).await;
+    assert_eq!(tip_set(store).await, HashSet::from([left.clone(), right.clone()]));
+
+    let merge = add(store, factory.raw(0, vec![left, right])).await;
+    assert_eq!(tip_set(store).await, HashSet::from([merge]));
+}
+
+/// `get_ordered_nodes` returns every node exactly once, parents first.
+pub async fn check_ordering<S: DagStore + Send + Sync>(store: &mut S) {
+    let mut factory = NodeFactory::new(1);
+
+    let genesis = add(store, factory.raw(0, vec![])).await;
+    let a = add(store, factory.raw(0, vec![genesis.clone()])).await;
+    let b = add(store, factory.raw(0, vec![genesis.clone()])).await;
+    let c = add(store, factory.raw(0, vec![a.clone(), b.clone()])).await;
+    let d = add(store, factory.raw(0, vec![c.clone(), genesis.clone()])).await;
+
+    let ordered = store.get_ordered_nodes().await.expect("get_ordered_nodes should succeed");
+    assert_topological(&ordered);
+    assert_eq!(cid_set(&ordered), HashSet::from([genesis, a, b, c, d]));
+}
+
+/// `find_path` walks from a node towards its ancestors.
+pub async fn check_find_path<S: DagStore + Send + Sync>(store: &mut S) {
+    let mut factory = NodeFactory::new(1);
+
+    let genesis = add(store, factory.raw(0, vec![])).await;
+    let a = add(store, factory.raw(0, vec![genesis.clone()])).await;
+    let b = add(store, factory.raw(0, vec![a.clone()])).await;
+    let sibling = add(store, factory.raw(0, vec![genesis.clone()])).await;
+
+    let path: Vec<Cid> = store.find_path(&b, &genesis).await.expect("find_path should succeed").iter().map(cid_of).collect();
+    assert_eq!(path, vec![b.clone(), a.clone(), genesis.clone()]);
+
+    let to_self: Vec<Cid> = store.find_path(&a, &a).await.expect("find_path should succeed").iter().map(cid_of).collect();
+    assert_eq!(to_self, vec![a.clone()]);
+
+    assert!(
+        store.find_path(&genesis, &b).await.expect("find_path should succeed").is_empty(),
+        "there is no path from an ancestor to its descendant"
+    );
+    assert!(
+        store.find_path(&b, &sibling).await.expect("find_path should succeed").is_empty(),
+        "there is no path between sibling branches"
+    );
+}
+
+/// `get_nodes_by_author` returns exactly the nodes signed by that author.
+pub async fn check_author_index<S: DagStore + Send + Sync>(store: &mut S) {
+    let mut factory = NodeFactory::new(3);
+
+    let genesis = add(store, factory.raw(0, vec![])).await;
+    let mut expected: HashMap<usize, HashSet<Cid>> = HashMap::from([(0, HashSet::from([genesis.clone()]))]);
+    for i in 0..6 {
+        let author = i % 2;
+        let cid = add(store, factory.raw(author, vec![genesis.clone()])).await;
+        expected.entry(author).or_default().insert(cid);
+    }
+
+    for author in 0..2 {
+        let nodes = store.get_nodes_by_author(factory.author(author)).await.expect("get_nodes_by_author should succeed");
+        assert_eq!(nodes.len(), expected[&author].len(), "author index should not contain duplicates");
+        assert_eq!(cid_set(&nodes), expected[&author]);
+    }
+    assert!(
+        store.get_nodes_by_author(factory.author(2)).await.expect("get_nodes_by_author should succeed").is_empty(),
+        "an author without nodes should have an empty index"
+    );
+}
+
+/// `get_nodes_by_payload_type` indexes raw, json and reference payloads.
+pub async fn check_payload_type_index<S: DagStore + Send + Sync>(store: &mut S) {
+    let mut factory = NodeFactory::new(1);
+
+    let genesis = add(store, factory.raw(0, vec![])).await;
+    let raw = add(store, factory.raw(0, vec![genesis.clone()])).await;
+    let json = add(store, factory.node(0, vec![genesis.clone()], DagPayload::Json(serde_json::json!({ "kind": "conformance" })))).await;
+    let reference = add(store, factory.node(0, vec![json.clone()], DagPayload::Reference(raw.clone()))).await;
+
+    assert_eq!(cid_set(&by_payload_type(store, "raw").await), HashSet::from([genesis, raw]));
+    assert_eq!(cid_set(&by_payload_type(store, "json").await), HashSet::from([json]));
+    assert_eq!(cid_set(&by_payload_type(store, "reference").await), HashSet::from([reference]));
+    assert!(by_payload_type(store, "no-such-type").await.is_empty());
+}
+
+async fn by_payload_type<S: DagStore + Send + Sync>(store: &S, payload_type: &str) -> Vec<SignedDagNode> {
+    store.get_nodes_by_payload_type(payload_type).await.expect("get_nodes_by_payload_type should succeed")
+}
+
+/// `verify_branch` accepts correctly signed branches and rejects forged or unresolvable ones.
+pub async fn check_verify_branch<S: DagStore + Send + Sync>(store: &mut S) {
+    let mut factory = NodeFactory::new(2);
+
+    let genesis = add(store, factory.raw(0, vec![])).await;
+    let a = add(store, factory.raw(1, vec![genesis.clone()])).await;
+    let b = add(store, factory.raw(0, vec![a.clone(), genesis.clone()])).await;
+    store.verify_branch(&b, &factory).await.expect("a correctly signed branch should verify");
+
+    let forged = add(store, factory.forged(1, vec![b.clone()])).await;
+    assert!(
+        store.verify_branch(&forged, &factory).await.is_err(),
+        "a branch containing a forged signature should not verify"
+    );
+    store.verify_branch(&b, &factory).await.expect("the branch below a forged node should still verify");
+
+    let strangers = NodeFactory::new(1);
+    assert!(
+        store.verify_branch(&b, &strangers).await.is_err(),
+        "a branch whose authors cannot be resolved should not verify"
+    );
+}
+
+/// Adding a node twice returns the same CID and leaves the store unchanged.
+pub async fn check_duplicate_insert<S: DagStore + Send + Sync>(store: &mut S) {
+    let mut factory = NodeFactory::new(1);
+
+    let genesis_node = factory.raw(0, vec![]);
+    let genesis = add(store, genesis_node.clone()).await;
+    let child = add(store, factory.raw(0, vec![genesis.clone()])).await;
+
+    assert_eq!(add(store, genesis_node).await, genesis, "re-adding a node should return its CID");
+    assert_eq!(tip_set(store).await, HashSet::from([child]), "re-adding a parent should not make it a tip again");
+
+    let ordered = store.get_ordered_nodes().await.expect("get_ordered_nodes should succeed");
+    assert_eq!(ordered.len(), 2);
+    assert_eq!(
+        store.get_nodes_by_author(factory.author(0)).await.expect("get_nodes_by_author should succeed").len(),
+        2,
+        "re-adding a node should not duplicate index entries"
+    );
+}
+
+/// Nodes referencing unknown parents are rejected without modifying the store.
+pub async fn check_missing_parent<S: DagStore + Send + Sync>(store: &mut S) {
+    let mut factory = NodeFactory::new(1);
+
+    let genesis = add(store, factory.raw(0, vec![])).await;
+    let unknown = Cid::from_bytes(b"conformance: not in the store").expect("CID should build");
+
+    match store.add_node(factory.raw(0, vec![genesis.clone(), unknown.clone()])).await {
+        Err(DagError::ParentNotFound { parent, .. }) => assert_eq!(parent, unknown),
+        other => panic!("expected ParentNotFound, got {:?}", other.map(|cid| cid.to_string())),
+    }
+    assert_eq!(tip_set(store).await, HashSet::from([genesis]));
+    assert_eq!(store.get_ordered_nodes().await.expect("get_ordered_nodes should succeed").len(), 1);
+}
+
+/// `add_nodes` accepts parents from earlier in the same batch, skips nodes that
+/// are already stored, maintains tips and indexes like `add_node`, and writes
+/// nothing when a parent is missing.
+pub async fn check_bulk_insert<S: DagStore + Send + Sync>(store: &mut S) {
+    let mut factory = NodeFactory::new(2);
+
+    let genesis = add(store, factory.raw(0, vec![])).await;
+
+    let left = factory.raw(0, vec![genesis.clone()]);
+    let left_cid = left.calculate_cid().expect("CID should compute");
+    let right = factory.raw(1, vec![genesis.clone()]);
+    let right_cid = right.calculate_cid().expect("CID should compute");
+    let merge = factory.raw(1, vec![left_cid.clone(), right_cid.clone()]);
+    let merge_cid = merge.calculate_cid().expect("CID should compute");
+    let stored_genesis = stored_copy(store, &genesis).await;
+
+    let batch = vec![stored_genesis, left.clone(), right, left, merge];
+    let cids = store.add_nodes(batch).await.expect("add_nodes should accept a valid batch");
+    assert_eq!(
+        cids,
+        vec![genesis.clone(), left_cid.clone(), right_cid.clone(), left_cid.clone(), merge_cid.clone()],
+        "add_nodes should return CIDs in input order"
+    );
+
+    assert_eq!(tip_set(store).await, HashSet::from([merge_cid.clone()]));
+    let ordered = store.get_ordered_nodes().await.expect("get_ordered_nodes should succeed");
+    assert_eq!(ordered.len(), 4, "duplicates in a batch should be stored once");
+    assert_topological(&ordered);
+    let by_author = store.get_nodes_by_author(factory.author(1)).await.expect("get_nodes_by_author should succeed");
+    assert_eq!(cid_set(&by_author), HashSet::from([right_cid, merge_cid.clone()]));
+    let raw = store.get_nodes_by_payload_type("raw").await.expect("get_nodes_by_payload_type should succeed");
+    assert_eq!(raw.len(), 4, "payload type index should not contain duplicates");
+
+    // A batch whose parent is missing is rejected as a whole
+    let unknown = Cid::from_bytes(b"conformance: not in the store").expect("CID should build");
+    let valid = factory.raw(0, vec![merge_cid.clone()]);
+    let valid_cid = valid.calculate_cid().expect("CID should compute");
+    let orphan = factory.raw(0, vec![valid_cid.clone(), unknown.clone()]);
+    match store.add_nodes(vec![valid, orphan]).await {
+        Err(DagError::ParentNotFound { parent, .. }) => assert_eq!(parent, unknown),
+        other => panic!("expected ParentNotFound, got {:?}", other.map(|cids| cids.len())),
+    }
+    assert!(
+        matches!(store.get_node(&valid_cid).await, Err(DagError::NodeNotFound(_))),
+        "no node of a rejected batch should be stored"
+    );
+    assert_eq!(tip_set(store).await, HashSet::from([merge_cid]));
+}
+
+/// Subscriptions replay stored nodes in insertion order, honour filters, resume
+/// from a cursor and then deliver live inserts.
+pub async fn check_subscription<S: DagStore + Send + Sync>(store: &mut S) {
+    let mut factory = NodeFactory::new(2);
+
+    let genesis = add(store, factory.raw(0, vec![])).await;
+    let second = add(store, factory.raw(1, vec![genesis.clone()])).await;
+    let json = factory.node(0, vec![second.clone()], DagPayload::Json(serde_json::json!({ "kind": "conformance" })));
+    let json_label = json.node.metadata.label.clone().expect("conformance nodes are labelled");
+    let third = add(store, json).await;
+
+    // Replay of everything, in insertion order with increasing cursors
+    let mut all = subscribe(store, DagFeedFilter::new(), None).await;
+    let replayed = [next_event(&mut all).await, next_event(&mut all).await, next_event(&mut all).await];
+    assert_eq!(
+        replayed.iter().map(|event| event.cid.clone()).collect::<Vec<_>>(),
+        vec![genesis.clone(), second.clone(), third.clone()],
+        "subscription should replay nodes in insertion order"
+    );
+    assert!(
+        replayed.windows(2).all(|pair| pair[0].cursor < pair[1].cursor),
+        "cursors should increase with every insert"
+    );
+    assert_eq!(all.cursor(), replayed[2].cursor);
+
+    // Resuming from a cursor skips everything up to and including it
+    let mut resumed = subscribe(store, DagFeedFilter::new(), Some(replayed[0].cursor)).await;
+    assert_eq!(next_event(&mut resumed).await.cid, second);
+    assert_eq!(next_event(&mut resumed).await.cid, third);
+
+    // Filters
+    let mut by_author = subscribe(store, DagFeedFilter::new().with_author(factory.author(1).clone()), None).await;
+    assert_eq!(next_event(&mut by_author).await.cid, second);
+    // Payload kinds are named as in the payload type index
+    let mut by_kind = subscribe(store, DagFeedFilter::new().with_payload_kind("json"), None).await;
+    assert_eq!(next_event(&mut by_kind).await.cid, third);
+    assert_eq!(cid_set(&by_payload_type(store, "json").await), HashSet::from([third.clone()]));
+    let mut by_label = subscribe(store, DagFeedFilter::new().with_label(json_label), None).await;
+    assert_eq!(next_event(&mut by_label).await.cid, third);
+
+    // Live inserts reach existing subscriptions, filtered ones included
+    let live = add(store, factory.raw(1, vec![third.clone()])).await;
+    let live_event = next_event(&mut all).await;
+    assert_eq!(live_event.cid, live);
+    assert!(live_event.cursor > replayed[2].cursor);
+    assert_eq!(next_event(&mut by_author).await.cid, live);
+
+    // Duplicate inserts are not new events
+    add(store, stored_copy(store, &live).await).await;
+    let after = add(store, factory.raw(0, vec![live.clone()])).await;
+    assert_eq!(next_event(&mut all).await.cid, after, "a duplicate insert should not be published");
+}
+
+/// Build a DAG from `shape` and check every store invariant against it.
+///
+/// `shape[i]` lists the parents of node `i` as indexes into `shape`; out-of-range
+/// indexes are reduced modulo `i`, so any `Vec<Vec<usize>>` describes a valid DAG.
+/// Node 0 and nodes with an empty parent list are roots. Authors alternate between
+/// two identities.
+pub async fn check_random_dag<S: DagStore + Send + Sync>(store: &mut S, shape: &[Vec<usize>]) {
+    let mut factory = NodeFactory::new(2);
+    let mut cids: Vec<Cid> = Vec::with_capacity(shape.len());
+    let mut parents_of: Vec<Vec<usize>> = Vec::with_capacity(shape.len());
+    let mut has_children = vec![false; shape.len()];
+
+    for (i, raw_parents) in shape.iter().enumerate() {
+        let mut parents: Vec<usize> = if i == 0 { Vec::new() } else { raw_parents.iter().map(|p| p % i).collect() };
+        parents.sort_unstable();
+        parents.dedup();
+        for &p in &parents {
+            has_children[p] = true;
+        }
+        let node = factory.raw(i % 2, parents.iter().map(|&p| cids[p].clone()).collect());
+        cids.push(add(store, node).await);
+        parents_of.push(parents);
+    }
+
+    let expected_tips: HashSet<Cid> = cids.iter().zip(&has_children).filter(|(_, &c)| !c).map(|(cid, _)| cid.clone()).collect();
+    assert_eq!(tip_set(store).await, expected_tips);
+
+    let ordered = store.get_ordered_nodes().await.expect("get_ordered_nodes should succeed");
+    assert_topological(&ordered);
+    assert_eq!(cid_set(&ordered), cids.iter().cloned().collect::<HashSet<_>>());
+
+    for author in 0..2 {
+        let expected: HashSet<Cid> = cids.iter().enumerate().filter(|(i, _)| i % 2 == author).map(|(_, cid)| cid.clone()).collect();
+        let nodes = store.get_nodes_by_author(factory.author(author)).await.expect("get_nodes_by_author should succeed");
+        assert_eq!(cid_set(&nodes), expected);
+    }
+
+    // Every node with parents has a path back to its first parent's ancestry
+    for (i, parents) in parents_of.iter().enumerate() {
+        if let Some(&first) = parents.first() {
+            let path = store.find_path(&cids[i], &cids[first]).await.expect("find_path should succeed");
+            assert_eq!(path.len(), 2, "a node and its direct parent should be one hop apart");
+            assert_eq!(cid_of(&path[0]), cids[i]);
+            assert_eq!(cid_of(&path[1]), cids[first]);
+        }
+    }
+
+    for tip in &expected_tips {
+        store.verify_branch(tip, &factory).await.expect("every generated branch should verify");
+    }
+}
+
+/// A few fixed DAG shapes (chain, wide fan-out, diamond lattice) used by [`run_all`].
+pub fn sample_shapes() -> Vec<Vec<Vec<usize>>> {
+    let chain = (0..16).map(|i| if i == 0 { vec![] } else { vec![i - 1] }).collect();
+    let fan_out = (0..16).map(|i| if i == 0 { vec![] } else { vec![0] }).collect();
+    let lattice = (0..16).map(|i| if i < 2 { vec![] } else { vec![i - 1, i - 2] }).collect();
+    vec![chain, fan_out, lattice]
+}
+
+async fn subscribe<S: DagStore + Send + Sync>(store: &S, filter: DagFeedFilter, from: Option<DagCursor>) -> DagSubscription {
+    store.subscribe(filter, from).await.expect("subscribe should succeed")
+}
+
+async fn next_event(subscription: &mut DagSubscription) -> DagFeedEvent {
+    tokio::time::timeout(Duration::from_secs(5), subscription.next())
+        .await
+        .expect("subscription should deliver an event")
+        .expect("subscription should not be closed")
+        .expect("subscription should not lag")
+}
+
+async fn stored_copy<S: DagStore + Send + Sync>(store: &S, cid: &Cid) -> SignedDagNode {
+    let mut node = store.get_node(cid).await.expect("get_node should succeed");
+    node.cid = None;
+    node
+}
@@ -0,0 +1,200 @@
+//! Change feed for DAG stores.
+//!
+//! Stores assign every newly inserted node a monotonically increasing sequence
+//! number and publish it on a [`DagFeed`]. Consumers call
+//! [`DagStore::subscribe`](crate::dag::DagStore::subscribe) with a
+//! [`DagFeedFilter`] and an optional [`DagCursor`]; the returned
+//! [`DagSubscription`] first replays matching nodes stored after the cursor and
+//! then yields live inserts. Remembering the cursor of the last event seen lets a
+//! consumer resume after a restart without rescanning the whole DAG.
+
+use crate::dag::{DagError, NodeScope, SignedDagNode};
+use crate::{Cid, Did};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use tokio::sync::broadcast;
+
+/// Number of live events buffered per subscriber before it is considered lagged
+const FEED_CAPACITY: usize = 1024;
+
+/// Position in a store's insertion sequence.
+///
+/// A cursor identifies the last event a consumer has seen; subscribing with it
+/// delivers only nodes inserted afterwards.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DagCursor(pub u64);
+
+impl DagCursor {
+    /// Cursor positioned before the first node of the store
+    pub const START: DagCursor = DagCursor(0);
+}
+
+/// A node inserted into a store, as delivered to subscribers
+#[derive(Clone, Debug)]
+pub struct DagFeedEvent {
+    /// Position of this node in the store's insertion sequence
+    pub cursor: DagCursor,
+    /// CID of the inserted node
+    pub cid: Cid,
+    /// The inserted node
+    pub node: SignedDagNode,
+}
+
+/// Selects which inserted nodes a subscription yields.
+///
+/// Each populated criterion must match (AND); within a criterion any listed value
+/// may match (OR). An empty filter matches every node.
+#[derive(Clone, Debug, Default)]
+pub struct DagFeedFilter {
+    labels: Vec<String>,
+    payload_kinds: Vec<String>,
+    scopes: Vec<(NodeScope, Option<String>)>,
+    authors: Vec<Did>,
+}
+
+impl DagFeedFilter {
+    /// Create a filter that matches every node
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match nodes carrying this metadata label
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.labels.push(label.into());
+        self
+    }
+
+    /// Match nodes whose payload is of this kind (see [`DagPayload::type_key`])
+    pub fn with_payload_kind(mut self, kind: impl Into<String>) -> Self {
+        self.payload_kinds.push(kind.into());
+        self
+    }
+
+    /// Match nodes in this scope, optionally restricted to a single scope ID
+    pub fn with_scope(mut self, scope: NodeScope, scope_id: Option<String>) -> Self {
+        self.scopes.push((scope, scope_id));
+        self
+    }
+
+    /// Match nodes created by this author
+    pub fn with_author(mut self, author: Did) -> Self {
+        self.authors.push(author);
+        self
+    }
+
+    /// Check whether a node passes this filter
+    pub fn matches(&self, node: &SignedDagNode) -> bool {
+        let metadata = &node.node.metadata;
+        let label_ok = self.labels.is_empty()
+            || metadata.label.as_ref().map_or(false, |label| self.labels.contains(label));
+        let kind_ok = self.payload_kinds.is_empty()
+            || self.payload_kinds.iter().any(|kind| kind == node.node.payload.type_key());
+        let scope_ok = self.scopes.is_empty()
+            || self.scopes.iter().any(|(scope, scope_id)| {
+                *scope == metadata.scope && (scope_id.is_none() || *scope_id == metadata.scope_id)
+            });
+        let author_ok = self.authors.is_empty() || self.authors.contains(&node.node.author);
+        label_ok && kind_ok && scope_ok && author_ok
+    }
+}
+
+/// Publishing side of a store's change feed.
+///
+/// Stores keep one of these and call [`DagFeed::publish`] after each insert has
+/// been committed.
+#[derive(Clone, Debug)]
+pub struct DagFeed {
+    sender: broadcast::Sender<DagFeedEvent>,
+}
+
+impl Default for DagFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DagFeed {
+    /// Create a feed with no subscribers
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(FEED_CAPACITY);
+        Self { sender }
+    }
+
+    /// Publish a committed insert to live subscribers
+    pub fn publish(&self, cursor: DagCursor, cid: Cid, node: SignedDagNode) {
+        // No receivers is not an error: nobody is subscribed yet
+        let _ = self.sender.send(DagFeedEvent { cursor, cid, node });
+    }
+
+    /// Register a live receiver.
+    ///
+    /// Stores must call this *before* loading the replay backlog so that no insert
+    /// falls between the two; [`DagSubscription`] drops any duplicates.
+    pub fn receiver(&self) -> broadcast::Receiver<DagFeedEvent> {
+        self.sender.subscribe()
+    }
+}
+
+/// A stream of inserted nodes matching a filter, starting after a cursor.
+pub struct DagSubscription {
+    filter: DagFeedFilter,
+    backlog: VecDeque<DagFeedEvent>,
+    live: broadcast::Receiver<DagFeedEvent>,
+    position: DagCursor,
+}
+
+impl DagSubscription {
+    /// Build a subscription from a live receiver and the stored nodes after `from`.
+    ///
+    /// `backlog` must be in cursor order; it is filtered here.
+    pub fn new(
+        filter: DagFeedFilter,
+        from: DagCursor,
+        backlog: Vec<DagFeedEvent>,
+        live: broadcast::Receiver<DagFeedEvent>,
+    ) -> Self {
+        Self {
+            filter,
+            backlog: backlog.into_iter().filter(|event| event.cursor > from).collect(),
+            live,
+            position: from,
+        }
+    }
+
+    /// Cursor of the last event this subscription has passed over.
+    ///
+    /// Subscribing again from this cursor resumes where this subscription stopped.
+    pub fn cursor(&self) -> DagCursor {
+        self.position
+    }
+
+    /// Wait for the next matching node.
+    ///
+    /// Returns `None` once the store has been dropped. If the consumer falls more
+    /// than the feed capacity behind, `DagError::SubscriptionLagged` is returned
+    /// with the cursor to resubscribe from.
+    pub async fn next(&mut self) -> Option<Result<DagFeedEvent, DagError>> {
+        while let Some(event) = self.backlog.pop_front() {
+            self.position = event.cursor;
+            if self.filter.matches(&event.node) {
+                return Some(Ok(event));
+            }
+        }
+
+        loop {
+            match self.live.recv().await {
+                Ok(event) if event.cursor <= self.position => continue, // Already replayed
+                Ok(event) => {
+                    self.position = event.cursor;
+                    if self.filter.matches(&event.node) {
+                        return Some(Ok(event));
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => {
+                    return Some(Err(DagError::SubscriptionLagged(self.position)));
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
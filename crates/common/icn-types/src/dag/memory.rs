@@ -1,5 +1,7 @@
 use crate::Cid;
 use crate::dag::{DagError, DagStore, SignedDagNode, PublicKeyResolver};
+#[cfg(feature = "async")]
+use crate::dag::feed::{DagCursor, DagFeed, DagFeedEvent, DagFeedFilter, DagSubscription};
 use crate::Did;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
@@ -19,6 +21,11 @@ pub struct MemoryDagStore {
     author_nodes: Arc<RwLock<HashMap<String, HashSet<String>>>>,
     /// Map of payload type -> Set of node CIDs
     payload_types: Arc<RwLock<HashMap<String, HashSet<String>>>>,
+    /// Node CIDs in insertion order; a node's cursor is its index + 1
+    insertion_log: Arc<RwLock<Vec<String>>>,
+    /// Change feed for subscribers
+    #[cfg(feature = "async")]
+    feed: DagFeed,
 }
 
 impl MemoryDagStore {
@@ -30,6 +37,9 @@ impl MemoryDagStore {
             children: Arc::new(RwLock::new(HashMap::new())),
             author_nodes: Arc::new(RwLock::new(HashMap::new())),
             payload_types: Arc::new(RwLock::new(HashMap::new())),
+            insertion_log: Arc::new(RwLock::new(Vec::new())),
+            #[cfg(feature = "async")]
+            feed: DagFeed::new(),
         }
     }
     
@@ -37,17 +47,6 @@ impl MemoryDagStore {
     fn cid_to_key(cid: &Cid) -> String {
         cid.to_string()
     }
-    
-    /// Get the payload type as a string
-    fn get_payload_type(node: &SignedDagNode) -> String {
-        match &node.node.payload {
-            crate::dag::DagPayload::Raw(_) => "raw".to_string(),
-            crate::dag::DagPayload::Json(_) => "json".to_string(),
-            crate::dag::DagPayload::Reference(_) => "reference".to_string(),
-            crate::dag::DagPayload::TrustBundle(_) => "trustbundle".to_string(),
-            crate::dag::DagPayload::ExecutionReceipt(_) => "receipt".to_string(),
-        }
-    }
 }
 
 // Asynchronous implementation ONLY
@@ -65,6 +64,7 @@ impl DagStore for MemoryDagStore {
         let mut children = self.children.write().await;
         let mut author_nodes = self.author_nodes.write().await;
         let mut payload_types = self.payload_types.write().await;
+        let mut insertion_log = self.insertion_log.write().await;
         
         // Check if the node already exists
         if nodes.contains_key(&cid_key) {
@@ -81,6 +81,7 @@ impl DagStore for MemoryDagStore {
                 drop(children);
                 drop(author_nodes);
                 drop(payload_types);
+                drop(insertion_log);
                 return Err(DagError::ParentNotFound { child: cid.clone(), parent: parent_cid.clone() });
             }
         }
@@ -109,11 +110,16 @@ impl DagStore for MemoryDagStore {
             .insert(cid_key.clone());
         
         // Update payload_types
-        let payload_type = Self::get_payload_type(&node);
+        let payload_type = node.node.payload.type_key().to_string();
         payload_types
             .entry(payload_type)
             .or_insert_with(HashSet::new)
-            .insert(cid_key);
+            .insert(cid_key.clone());
+        
+        // Record the insert and notify subscribers while still holding the locks,
+        // so events are published in cursor order
+        insertion_log.push(cid_key);
+        self.feed.publish(DagCursor(insertion_log.len() as u64), cid.clone(), node);
             
         // Locks are dropped automatically when guards go out of scope
         Ok(cid)
@@ -134,8 +140,9 @@ impl DagStore for MemoryDagStore {
         let tips = self.tips.read().await;
         
         // Attempt to convert keys back to Cids
+        // Keys are CID strings, so parse them rather than hashing them
         let result: Result<Vec<Cid>, _> = tips.iter()
-            .map(|key| Cid::from_bytes(key.as_bytes())) // Use from_bytes
+            .map(|key| key.parse::<Cid>())
             .collect();
             
         result.map_err(|e| DagError::CidError(format!("Failed to parse CID from key: {}", e)))
@@ -330,13 +337,39 @@ impl DagStore for MemoryDagStore {
     }
 
     async fn verify_branch(&self, tip: &Cid, resolver: &(dyn PublicKeyResolver + Send + Sync)) -> Result<(), DagError> {
-        // TODO: Implement async verification logic. This will likely involve cloning node data under lock,
-        // then performing the traversal and signature checks outside the lock.
-        // Need to be careful about how the resolver is used (can it be called concurrently?).
-        let _tip = tip; // Mark as used
-        let _resolver = resolver; // Mark as used
-        println!("Warning: MemoryDagStore::verify_branch is not fully implemented.");
-        Ok(()) // Assume valid for now
+        // Collect the branch under the read lock, then check signatures without holding it
+        let branch = {
+            let nodes_guard = self.nodes.read().await;
+            let tip_key = Self::cid_to_key(tip);
+            if !nodes_guard.contains_key(&tip_key) {
+                return Err(DagError::NodeNotFound(tip.clone()));
+            }
+
+            let mut branch = Vec::new();
+            let mut visited = HashSet::new();
+            let mut queue = VecDeque::new();
+            queue.push_back(tip.clone());
+            visited.insert(tip_key);
+
+            while let Some(cid) = queue.pop_front() {
+                let node = nodes_guard
+                    .get(&Self::cid_to_key(&cid))
+                    .cloned()
+                    .ok_or_else(|| DagError::MissingParent(cid.clone()))?;
+                for parent_cid in &node.node.parents {
+                    if visited.insert(Self::cid_to_key(parent_cid)) {
+                        queue.push_back(parent_cid.clone());
+                    }
+                }
+                branch.push((cid, node));
+            }
+            branch
+        };
+
+        for (cid, node) in branch {
+            node.verify(&cid, resolver)?;
+        }
+        Ok(())
     }
 
     #[cfg(feature = "async")]
@@ -351,4 +384,26 @@ impl DagStore for MemoryDagStore {
     fn get_data(&self, _cid: &Cid) -> Result<Option<Vec<u8>>, DagError> {
         unimplemented!("get_data not yet implemented for MemoryDagStore")
     }
+
+    #[cfg(feature = "async")]
+    async fn subscribe(&self, filter: DagFeedFilter, from: Option<DagCursor>) -> Result<DagSubscription, DagError> {
+        let from = from.unwrap_or(DagCursor::START);
+        // Register for live events before reading the backlog so nothing is missed
+        let live = self.feed.receiver();
+        
+        let nodes = self.nodes.read().await;
+        let insertion_log = self.insertion_log.read().await;
+        let backlog = insertion_log.iter()
+            .enumerate()
+            .skip(from.0 as usize)
+            .map(|(index, key)| {
+                let cid = key.parse::<Cid>()
+                    .map_err(|e| DagError::CidError(format!("Failed to parse CID from key {}: {}", key, e)))?;
+                let node = nodes.get(key).cloned().ok_or_else(|| DagError::NodeNotFound(cid.clone()))?;
+                Ok(DagFeedEvent { cursor: DagCursor(index as u64 + 1), cid, node })
+            })
+            .collect::<Result<Vec<_>, DagError>>()?;
+        
+        Ok(DagSubscription::new(filter, from, backlog, live))
+    }
 } 
\ No newline at end of file
@@ -5,7 +5,7 @@ use chrono::{DateTime, Utc};
 use ed25519_dalek::Signature;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use ed25519_dalek::VerifyingKey;
+use ed25519_dalek::{Verifier, VerifyingKey};
 use std::fmt;
 use std::sync::Arc;
 // Removed specific imports, use full path in #[from]
@@ -16,6 +16,14 @@ use std::sync::Arc;
 #[cfg(feature = "persistence")]
 pub mod rocksdb;
 
+// Include the SQLite implementation
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+// Reusable conformance checks for DagStore implementations
+#[cfg(feature = "conformance")]
+pub mod conformance;
+
 // Include the in-memory implementation
 pub mod memory;
 
@@ -30,6 +38,13 @@ mod tests;
 #[cfg(all(test, feature = "async"))]
 mod tests_async;
 
+// Change feed / subscription support
+#[cfg(feature = "async")]
+pub mod feed;
+
+#[cfg(feature = "async")]
+pub use feed::{DagCursor, DagFeed, DagFeedEvent, DagFeedFilter, DagSubscription};
+
 // Re-export sync types for easier access
 pub use sync::{DAGSyncBundle, DAGSyncService, FederationPeer, SyncError, VerificationResult};
 
@@ -64,8 +79,12 @@ pub enum DagError {
     PublicKeyResolutionError(Did, String),
     #[error("Storage error: {0}")]
     StorageError(String),
+    #[cfg(feature = "persistence")]
     #[error("RocksDB error: {0}")]
     RocksDbError(#[from] ::rocksdb::Error),
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite error: {0}")]
+    SqliteError(#[from] ::rusqlite::Error),
     #[error("Join error from background task: {0}")]
     JoinError(#[from] tokio::task::JoinError),
     #[error("CID calculation or parsing error: {0}")]
@@ -76,6 +95,9 @@ pub enum DagError {
     MissingParent(Cid),
     #[error("Policy error: {0}")]
     PolicyError(#[from] crate::PolicyError),
+    #[cfg(feature = "async")]
+    #[error("Subscription fell behind the change feed; resubscribe from {0:?}")]
+    SubscriptionLagged(feed::DagCursor),
 }
 
 /// Trait for resolving DIDs to public verifying keys
@@ -145,6 +167,20 @@ pub enum DagPayload {
     ExecutionReceipt(Cid),
 }
 
+impl DagPayload {
+    /// Name of this payload's type in the stores' payload type index, as
+    /// accepted by `get_nodes_by_payload_type` and `DagFeedFilter::with_payload_kind`
+    pub fn type_key(&self) -> &'static str {
+        match self {
+            DagPayload::Raw(_) => "raw",
+            DagPayload::Json(_) => "json",
+            DagPayload::Reference(_) => "reference",
+            DagPayload::TrustBundle(_) => "TrustBundle",
+            DagPayload::ExecutionReceipt(_) => "ExecutionReceipt",
+        }
+    }
+}
+
 /// Represents a single node in the Directed Acyclic Graph
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct DagNode {
@@ -158,12 +194,20 @@ pub struct DagNode {
     pub metadata: DagNodeMetadata,
 }
 
+impl DagNode {
+    /// Canonical DAG-CBOR serialization of this node; its CID and signature are computed over these bytes
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, DagError> {
+        serde_ipld_dagcbor::to_vec(self).map_err(|e| DagError::SerializationError(e.to_string()))
+    }
+}
+
 /// A signed DAG node ready for inclusion in the graph
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SignedDagNode {
     /// The unsigned DAG node
     pub node: DagNode,
     /// The author's signature over the canonical serialization of the node
+    #[serde(with = "signature_bytes")]
     pub signature: Signature,
     /// The computed CID for this node (derived from its contents)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -173,12 +217,8 @@ pub struct SignedDagNode {
 impl SignedDagNode {
     /// Calculate the CID for this node based on its canonical serialization (DAG-CBOR)
     pub fn calculate_cid(&self) -> Result<Cid, DagError> {
-        // Serialize the inner node using DAG-CBOR for canonical representation
-        let canonical_node_bytes = serde_ipld_dagcbor::to_vec(&self.node)
-            .map_err(|e| DagError::SerializationError(e.to_string()))?;
-            
         // Calculate CID using the canonical DAG-CBOR bytes
-        Cid::from_bytes(&canonical_node_bytes)
+        Cid::from_bytes(&self.node.canonical_bytes()?)
             .map_err(|e| DagError::CidError(e.to_string()))
     }
     
@@ -193,6 +233,17 @@ impl SignedDagNode {
         }
     }
     
+    /// Verify this node against the CID it is stored under and its author's public key.
+    pub fn verify(&self, expected_cid: &Cid, resolver: &(dyn PublicKeyResolver + Send + Sync)) -> Result<(), DagError> {
+        if &self.calculate_cid()? != expected_cid {
+            return Err(DagError::CidMismatch(expected_cid.clone()));
+        }
+        let verifying_key = resolver.resolve(&self.node.author)?;
+        verifying_key
+            .verify(&self.node.canonical_bytes()?, &self.signature)
+            .map_err(|_| DagError::InvalidSignature(expected_cid.clone()))
+    }
+    
     /// Create an AnchorRef from this node
     pub fn to_anchor_ref(&mut self) -> Result<AnchorRef, DagError> {
         let cid = self.ensure_cid()?;
@@ -210,6 +261,25 @@ impl SignedDagNode {
     }
 }
 
+/// Serde helpers that encode signatures as a byte string.
+///
+/// `ed25519_dalek::Signature` serializes as bytes but deserializes as a tuple, which
+/// does not round-trip through DAG-CBOR. Accepting any byte buffer on the way back in
+/// keeps both the CBOR and JSON encodings readable.
+mod signature_bytes {
+    use ed25519_dalek::Signature;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(signature: &Signature, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&signature.to_bytes())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Signature, D::Error> {
+        let bytes = serde_bytes::ByteBuf::deserialize(deserializer)?;
+        Signature::from_slice(&bytes).map_err(serde::de::Error::custom)
+    }
+}
+
 /// Trait defining the interface for DAG storage backends
 #[cfg_attr(feature = "async", async_trait::async_trait)]
 pub trait DagStore {
@@ -220,6 +290,62 @@ pub trait DagStore {
     #[cfg(not(feature = "async"))]
     fn add_node(&mut self, node: SignedDagNode) -> Result<Cid, DagError>;
     
+    /// Add a batch of signed nodes, returning their CIDs in input order.
+    ///
+    /// Each node's parents must already be stored or appear earlier in the batch;
+    /// otherwise `ParentNotFound` is returned before anything is written. Nodes that
+    /// are already stored are skipped. Backends override this to write the whole
+    /// batch at once; the default adds the nodes one by one after validation.
+    #[cfg(feature = "async")]
+    async fn add_nodes(&mut self, mut nodes: Vec<SignedDagNode>) -> Result<Vec<Cid>, DagError> {
+        let mut batch_cids = std::collections::HashSet::new();
+        for node in nodes.iter_mut() {
+            let cid = node.ensure_cid()?;
+            for parent in &node.node.parents {
+                if batch_cids.contains(parent) {
+                    continue;
+                }
+                match self.get_node(parent).await {
+                    Ok(_) => {}
+                    Err(DagError::NodeNotFound(_)) => {
+                        return Err(DagError::ParentNotFound { child: cid, parent: parent.clone() });
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            batch_cids.insert(cid);
+        }
+        
+        let mut cids = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            cids.push(self.add_node(node).await?);
+        }
+        Ok(cids)
+    }
+    
+    #[cfg(not(feature = "async"))]
+    fn add_nodes(&mut self, mut nodes: Vec<SignedDagNode>) -> Result<Vec<Cid>, DagError> {
+        let mut batch_cids = std::collections::HashSet::new();
+        for node in nodes.iter_mut() {
+            let cid = node.ensure_cid()?;
+            for parent in &node.node.parents {
+                if batch_cids.contains(parent) {
+                    continue;
+                }
+                match self.get_node(parent) {
+                    Ok(_) => {}
+                    Err(DagError::NodeNotFound(_)) => {
+                        return Err(DagError::ParentNotFound { child: cid, parent: parent.clone() });
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            batch_cids.insert(cid);
+        }
+        
+        nodes.into_iter().map(|node| self.add_node(node)).collect()
+    }
+    
     /// Retrieve a node by its CID
     #[cfg(feature = "async")]
     async fn get_node(&self, cid: &Cid) -> Result<SignedDagNode, DagError>;
@@ -276,6 +402,15 @@ pub trait DagStore {
     
     #[cfg(not(feature = "async"))]
     fn verify_branch(&self, tip: &Cid, resolver: &(dyn PublicKeyResolver + Send + Sync)) -> Result<(), DagError>;
+    
+    /// Subscribe to nodes matching `filter` that are inserted after `from`.
+    /// With `from = None` all stored nodes are replayed before live inserts.
+    /// Stores without a change feed return an error.
+    #[cfg(feature = "async")]
+    async fn subscribe(&self, filter: DagFeedFilter, from: Option<DagCursor>) -> Result<DagSubscription, DagError> {
+        let _ = (filter, from);
+        Err(DagError::StorageError("This DAG store does not support subscriptions".to_string()))
+    }
 }
 
 /// Builder for creating new DAG nodes
@@ -375,32 +510,36 @@ impl DagNodeBuilder {
     }
 }
 
-/// A wrapper for DagStore that provides shared mutable access
+/// A wrapper for DagStore that provides shared mutable access.
+///
+/// Reads take a shared lock and run concurrently with each other; only `add_node`
+/// takes the exclusive lock. Backends are expected to be safe for concurrent reads
+/// through `&self` (all in-tree stores use interior locking or a thread-safe DB handle).
 #[derive(Clone)]
 pub struct SharedDagStore {
-    inner: Arc<tokio::sync::Mutex<Box<dyn DagStore + Send + Sync>>>,
+    inner: Arc<tokio::sync::RwLock<Box<dyn DagStore + Send + Sync>>>,
 }
 
 impl SharedDagStore {
     /// Create a new SharedDagStore from a boxed DagStore
     pub fn new(store: Box<dyn DagStore + Send + Sync>) -> Self {
         Self {
-            inner: Arc::new(tokio::sync::Mutex::new(store)),
+            inner: Arc::new(tokio::sync::RwLock::new(store)),
         }
     }
     
     /// Deprecated: Attempt to create a SharedDagStore from an existing Arc<Box<dyn DagStore>>.
     /// This is generally unsafe if the Arc is shared, as it requires exclusive ownership
-    /// to place the Box inside the Mutex for the SharedDagStore.
+    /// to place the Box inside the RwLock for the SharedDagStore.
     /// Use `SharedDagStore::new` instead.
     #[deprecated = "Use SharedDagStore::new instead. Creating from a potentially shared Arc is problematic."]
     pub fn from_arc(store: Arc<Box<dyn DagStore + Send + Sync>>) -> Result<Self, DagError> {
         match Arc::try_unwrap(store) {
             Ok(boxed) => Ok(Self {
-                inner: Arc::new(tokio::sync::Mutex::new(boxed)),
+                inner: Arc::new(tokio::sync::RwLock::new(boxed)),
             }),
             Err(_) => {
-                // We cannot safely create a SharedDagStore (with internal Mutex for mutation)
+                // We cannot safely create a SharedDagStore (with internal RwLock for mutation)
                 // from an Arc that is already shared elsewhere, as we don't have exclusive ownership.
                 // Returning an error is safer than the previous ClonedDagStore hack.
                 Err(DagError::StorageError("Cannot create SharedDagStore from a shared Arc<Box<dyn DagStore>>. Use ::new().".to_string()))
@@ -410,55 +549,67 @@ impl SharedDagStore {
     
     /// Add a node to the DAG store with shared mutable access
     pub async fn add_node(&self, node: SignedDagNode) -> Result<Cid, DagError> {
-        let mut store = self.inner.lock().await;
+        let mut store = self.inner.write().await;
         store.add_node(node).await
     }
     
+    /// Add a batch of nodes to the DAG store with shared mutable access
+    pub async fn add_nodes(&self, nodes: Vec<SignedDagNode>) -> Result<Vec<Cid>, DagError> {
+        let mut store = self.inner.write().await;
+        store.add_nodes(nodes).await
+    }
+    
     /// Get a node from the DAG store
     pub async fn get_node(&self, cid: &Cid) -> Result<SignedDagNode, DagError> {
-        let store = self.inner.lock().await;
+        let store = self.inner.read().await;
         store.get_node(cid).await
     }
     
     /// Get raw data from the DAG store
     pub async fn get_data(&self, cid: &Cid) -> Result<Option<Vec<u8>>, DagError> {
-        let store = self.inner.lock().await;
+        let store = self.inner.read().await;
         store.get_data(cid).await
     }
     
     /// Get tip CIDs from the DAG store
     pub async fn get_tips(&self) -> Result<Vec<Cid>, DagError> {
-        let store = self.inner.lock().await;
+        let store = self.inner.read().await;
         store.get_tips().await
     }
     
     /// Get ordered nodes from the DAG store
     pub async fn get_ordered_nodes(&self) -> Result<Vec<SignedDagNode>, DagError> {
-        let store = self.inner.lock().await;
+        let store = self.inner.read().await;
         store.get_ordered_nodes().await
     }
     
     /// Get nodes by author
     pub async fn get_nodes_by_author(&self, author: &Did) -> Result<Vec<SignedDagNode>, DagError> {
-        let store = self.inner.lock().await;
+        let store = self.inner.read().await;
         store.get_nodes_by_author(author).await
     }
     
     /// Get nodes by payload type
     pub async fn get_nodes_by_payload_type(&self, payload_type: &str) -> Result<Vec<SignedDagNode>, DagError> {
-        let store = self.inner.lock().await;
+        let store = self.inner.read().await;
         store.get_nodes_by_payload_type(payload_type).await
     }
     
     /// Find a path between nodes
     pub async fn find_path(&self, from: &Cid, to: &Cid) -> Result<Vec<SignedDagNode>, DagError> {
-        let store = self.inner.lock().await;
+        let store = self.inner.read().await;
         store.find_path(from, to).await
     }
     
     /// Verify a branch of the DAG
     pub async fn verify_branch(&self, tip: &Cid, resolver: &(dyn PublicKeyResolver + Send + Sync)) -> Result<(), DagError> {
-        let store = self.inner.lock().await;
+        let store = self.inner.read().await;
         store.verify_branch(tip, resolver).await
     }
+    
+    /// Subscribe to nodes inserted into the DAG store
+    pub async fn subscribe(&self, filter: DagFeedFilter, from: Option<DagCursor>) -> Result<DagSubscription, DagError> {
+        let store = self.inner.read().await;
+        store.subscribe(filter, from).await
+    }
 } 
\ No newline at end of file
@@ -2,10 +2,13 @@
 
 use crate::Cid;
 use crate::dag::{DagError, DagStore, SignedDagNode, PublicKeyResolver};
+#[cfg(feature = "async")]
+use crate::dag::feed::{DagCursor, DagFeed, DagFeedEvent, DagFeedFilter, DagSubscription};
 use crate::Did;
 use rocksdb::{ColumnFamily, ColumnFamilyDescriptor, Options, DB, WriteBatch};
 use std::collections::{HashSet, HashMap, VecDeque};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 use async_trait::async_trait;
 
@@ -20,6 +23,12 @@ lazy_static! {
         vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0]
     ).unwrap();
 
+    static ref DAG_ADD_NODES_DURATION: Histogram = register_histogram!(
+        "dag_add_nodes_duration_seconds",
+        "Time taken to bulk-import a batch of nodes into the RocksDB DAG store",
+        vec![0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0]
+    ).unwrap();
+
     static ref DAG_VERIFY_BRANCH_DURATION: Histogram = register_histogram!(
         "dag_verify_branch_duration_seconds",
         "Time taken to verify a DAG branch in RocksDB"
@@ -49,12 +58,20 @@ const CF_TIPS: &str = "tips";
 const CF_CHILDREN: &str = "children";
 const CF_AUTHORS: &str = "authors";
 const CF_PAYLOAD_TYPES: &str = "payload_types";
+/// Insertion sequence (big-endian u64 -> node CID bytes), used as the change feed cursor.
+/// Nodes written before this column family existed are not replayed to subscribers.
+const CF_SEQUENCE: &str = "sequence";
 
 /// RocksDB-based implementation of the DagStore trait
 pub struct RocksDbDagStore {
     db: Arc<DB>,
     // Cache of nodes that have children (not tips)
     non_tips: Arc<RwLock<HashSet<Vec<u8>>>>,
+    // Last sequence number assigned to an inserted node
+    last_seq: Arc<AtomicU64>,
+    // Change feed for subscribers
+    #[cfg(feature = "async")]
+    feed: DagFeed,
 }
 
 impl RocksDbDagStore {
@@ -71,6 +88,7 @@ impl RocksDbDagStore {
             ColumnFamilyDescriptor::new(CF_CHILDREN, Options::default()),
             ColumnFamilyDescriptor::new(CF_AUTHORS, Options::default()),
             ColumnFamilyDescriptor::new(CF_PAYLOAD_TYPES, Options::default()),
+            ColumnFamilyDescriptor::new(CF_SEQUENCE, Options::default()),
         ];
 
         // Open the database
@@ -80,17 +98,67 @@ impl RocksDbDagStore {
         let store = Self {
             db: Arc::new(db),
             non_tips: Arc::new(RwLock::new(HashSet::new())),
+            last_seq: Arc::new(AtomicU64::new(0)),
+            #[cfg(feature = "async")]
+            feed: DagFeed::new(),
         };
 
         // Initialize the non_tips cache
         store.initialize_non_tips_cache()?;
         
+        // Resume the insertion sequence after the last recorded node
+        store.initialize_last_seq()?;
+        
         // Initialize total nodes gauge (approximation on open)
         store.update_nodes_total_gauge()?; 
 
         Ok(store)
     }
 
+    /// Load the last assigned sequence number from the sequence column family
+    fn initialize_last_seq(&self) -> Result<(), DagError> {
+        let cf_sequence = self.cf_handle(CF_SEQUENCE)?;
+        let last = match self.db.iterator_cf(cf_sequence, rocksdb::IteratorMode::End).next() {
+            Some(entry) => {
+                let (key, _) = entry?;
+                Self::key_to_seq(&key)?
+            }
+            None => 0,
+        };
+        self.last_seq.store(last, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Merge new CID keys into the DAG-CBOR list stored under `key`, staging the result in `batch`
+    fn merge_cid_list(
+        db: &DB,
+        batch: &mut WriteBatch,
+        cf: &ColumnFamily,
+        key: &[u8],
+        additions: Vec<Vec<u8>>,
+    ) -> Result<(), DagError> {
+        let mut list: Vec<Vec<u8>> = match db.get_cf(cf, key)? {
+            Some(bytes) => Self::deserialize_cid_list(&bytes)?,
+            None => Vec::new(),
+        };
+        let mut present: HashSet<Vec<u8>> = list.iter().cloned().collect();
+        for addition in additions {
+            if present.insert(addition.clone()) {
+                list.push(addition);
+            }
+        }
+        batch.put_cf(cf, key, Self::serialize_cid_list(&list)?);
+        Ok(())
+    }
+
+    /// Decode a big-endian sequence key
+    fn key_to_seq(key: &[u8]) -> Result<u64, DagError> {
+        let bytes: [u8; 8] = key
+            .try_into()
+            .map_err(|_| DagError::StorageError(format!("Invalid sequence key length: {}", key.len())))?;
+        Ok(u64::from_be_bytes(bytes))
+    }
+
     /// Initialize the cache of non-tip nodes
     fn initialize_non_tips_cache(&self) -> Result<(), DagError> {
         let cf_children = self
@@ -242,13 +310,7 @@ impl RocksDbDagStore {
     #[allow(dead_code)]
     fn update_payload_types(&self, node: &SignedDagNode) -> Result<(), DagError> {
         let cf_payload_types = self.cf_handle(CF_PAYLOAD_TYPES)?;
-        let payload_type = match &node.node.payload {
-            crate::dag::DagPayload::Raw(_) => "raw",
-            crate::dag::DagPayload::Json(_) => "json",
-            crate::dag::DagPayload::Reference(_) => "reference",
-            crate::dag::DagPayload::TrustBundle(_) => "trustbundle",
-            crate::dag::DagPayload::ExecutionReceipt(_) => "receipt",
-        };
+        let payload_type = node.node.payload.type_key();
         let payload_key = payload_type.as_bytes();
         let node_cid = node.cid.as_ref().unwrap(); // Safe because we ensure CID is computed before adding
         let node_key = Self::cid_to_key(node_cid);
@@ -296,14 +358,23 @@ impl DagStore for RocksDbDagStore {
         let node_cid = node.ensure_cid()?; 
         let node_key = Self::cid_to_key(&node_cid);
         
-        // Check if node already exists to avoid re-incrementing counter etc.
+        // Re-adding an existing node is a no-op; rewriting it would restore it as a tip
         let cf_nodes = self.cf_handle(CF_NODES)?;
-        let node_exists = self.db.get_cf(cf_nodes, &node_key)?.is_some();
+        if self.db.get_cf(cf_nodes, &node_key)?.is_some() {
+            return Ok(node_cid);
+        }
+        for parent_cid in &node.node.parents {
+            if self.db.get_cf(cf_nodes, Self::cid_to_key(parent_cid))?.is_none() {
+                return Err(DagError::ParentNotFound { child: node_cid.clone(), parent: parent_cid.clone() });
+            }
+        }
 
         let node_bytes = Self::serialize_node(&node)?;
 
+        let seq = self.last_seq.load(Ordering::SeqCst) + 1;
         let mut batch = WriteBatch::default();
         batch.put_cf(cf_nodes, &node_key, &node_bytes);
+        batch.put_cf(self.cf_handle(CF_SEQUENCE)?, seq.to_be_bytes(), &node_key);
         
         // 2. Update tips
         let cf_tips = self.cf_handle(CF_TIPS)?;
@@ -358,14 +429,7 @@ impl DagStore for RocksDbDagStore {
 
         // 5. Update payload type index
         let cf_payload_types = self.cf_handle(CF_PAYLOAD_TYPES)?;
-        let payload_type_str = match &node.node.payload {
-            crate::dag::DagPayload::Raw(_) => "raw",
-            crate::dag::DagPayload::Json(_) => "json",
-            crate::dag::DagPayload::Reference(_) => "reference",
-            crate::dag::DagPayload::TrustBundle(_) => "TrustBundle",
-            crate::dag::DagPayload::ExecutionReceipt(_) => "ExecutionReceipt",
-            // Add other types as needed
-        };
+        let payload_type_str = node.node.payload.type_key();
         let payload_type_key = payload_type_str.as_bytes().to_vec();
         // Get existing nodes list (Read operation, outside batch)
         let existing_payload_nodes = self.db.get_cf(cf_payload_types, &payload_type_key)
@@ -400,10 +464,8 @@ impl DagStore for RocksDbDagStore {
             }
         } // Lock guard dropped here
 
-        // Increment node count only if it was a new node
-        if !node_exists {
-             DAG_NODES_TOTAL.inc();
-        }
+        DAG_NODES_TOTAL.inc();
+        self.last_seq.store(seq, Ordering::SeqCst);
 
         // Note: Updating tip count here accurately is complex.
         // It depends on whether parents were already tips.
@@ -673,12 +735,22 @@ impl DagStore for RocksDbDagStore {
         let node_key = Self::cid_to_key(&node_cid);
         let node_bytes = Self::serialize_node(&node)?;
         
-        // Check existence before spawn_blocking to correctly update counter
-        let node_exists = {
+        // Re-adding an existing node is a no-op; rewriting it would restore it as a tip
+        {
             let cf_nodes = self.cf_handle(CF_NODES)?;
-            self.db.get_cf(cf_nodes, &node_key)?.is_some()
-        };
+            if self.db.get_cf(cf_nodes, &node_key)?.is_some() {
+                return Ok(node_cid);
+            }
+            for parent_cid in &node.node.parents {
+                if self.db.get_cf(cf_nodes, Self::cid_to_key(parent_cid))?.is_none() {
+                    return Err(DagError::ParentNotFound { child: node_cid.clone(), parent: parent_cid.clone() });
+                }
+            }
+        }
 
+        // Sequence numbers are only handed out under `&mut self`, so they are published in order
+        let seq = self.last_seq.load(Ordering::SeqCst) + 1;
+        let event_node = node.clone();
         let db_clone = Arc::clone(&self.db);
         
         let batch_result = tokio::task::spawn_blocking(move || {
@@ -695,9 +767,12 @@ impl DagStore for RocksDbDagStore {
                  .ok_or_else(|| DagError::StorageError("Authors CF not found".to_string()))?;
             let cf_payload_types = db_clone.cf_handle(CF_PAYLOAD_TYPES)
                  .ok_or_else(|| DagError::StorageError("PayloadTypes CF not found".to_string()))?;
+            let cf_sequence = db_clone.cf_handle(CF_SEQUENCE)
+                 .ok_or_else(|| DagError::StorageError("Sequence CF not found".to_string()))?;
 
-            // 1. Add node data
+            // 1. Add node data and its insertion sequence number
             batch.put_cf(cf_nodes, &node_key, &node_bytes);
+            batch.put_cf(cf_sequence, seq.to_be_bytes(), &node_key);
 
             // 2. Update tips
             batch.put_cf(cf_tips, &node_key, &[1]);
@@ -739,14 +814,7 @@ impl DagStore for RocksDbDagStore {
             batch.put_cf(cf_authors, &author_key, &serialized_author_nodes);
 
              // 5. Update payload type index (using DAG-CBOR)
-            let payload_type_str = match &node.node.payload {
-                crate::dag::DagPayload::Raw(_) => "raw",
-                crate::dag::DagPayload::Json(_) => "json",
-                crate::dag::DagPayload::Reference(_) => "reference",
-                crate::dag::DagPayload::TrustBundle(_) => "TrustBundle",
-                crate::dag::DagPayload::ExecutionReceipt(_) => "ExecutionReceipt",
-            };
-            let payload_type_key = payload_type_str.as_bytes().to_vec();
+            let payload_type_key = node.node.payload.type_key().as_bytes().to_vec();
             let existing_payload_nodes = db_clone.get_cf(cf_payload_types, &payload_type_key)
                  .map_err(|e| DagError::StorageError(format!("Failed to get payload type nodes: {}", e)))?;
             let mut payload_nodes: Vec<Vec<u8>> = match existing_payload_nodes {
@@ -781,15 +849,140 @@ impl DagStore for RocksDbDagStore {
             }
         } // Lock guard dropped here
 
-        // Increment node count only if it was a new node
-        if !node_exists {
-             DAG_NODES_TOTAL.inc();
-        }
+        DAG_NODES_TOTAL.inc();
         // Deferring tip count update
 
+        self.last_seq.store(seq, Ordering::SeqCst);
+        self.feed.publish(DagCursor(seq), node_cid.clone(), event_node);
+
         Ok(node_cid)
     }
 
+    /// Bulk import: validates the whole batch, then writes every node and index
+    /// update in a single `WriteBatch`. Index lists are read and rewritten once per
+    /// key rather than once per node, and the tip set is updated once.
+    async fn add_nodes(&mut self, mut nodes: Vec<SignedDagNode>) -> Result<Vec<Cid>, DagError> {
+        let _timer = DAG_ADD_NODES_DURATION.start_timer();
+
+        let mut cids = Vec::with_capacity(nodes.len());
+        for node in nodes.iter_mut() {
+            cids.push(node.ensure_cid()?);
+        }
+
+        let first_seq = self.last_seq.load(Ordering::SeqCst) + 1;
+        let db_clone = Arc::clone(&self.db);
+        let cids_for_batch = cids.clone();
+
+        let (inserted, parent_keys) = tokio::task::spawn_blocking(move || {
+            let cf_nodes = db_clone.cf_handle(CF_NODES)
+                .ok_or_else(|| DagError::StorageError("Nodes CF not found".to_string()))?;
+            let cf_tips = db_clone.cf_handle(CF_TIPS)
+                .ok_or_else(|| DagError::StorageError("Tips CF not found".to_string()))?;
+            let cf_children = db_clone.cf_handle(CF_CHILDREN)
+                .ok_or_else(|| DagError::StorageError("Children CF not found".to_string()))?;
+            let cf_authors = db_clone.cf_handle(CF_AUTHORS)
+                .ok_or_else(|| DagError::StorageError("Authors CF not found".to_string()))?;
+            let cf_payload_types = db_clone.cf_handle(CF_PAYLOAD_TYPES)
+                .ok_or_else(|| DagError::StorageError("PayloadTypes CF not found".to_string()))?;
+            let cf_sequence = db_clone.cf_handle(CF_SEQUENCE)
+                .ok_or_else(|| DagError::StorageError("Sequence CF not found".to_string()))?;
+
+            // 1. Validate: skip stored or repeated nodes, and require every parent to be
+            //    stored already or to appear earlier in the batch
+            let mut batch_keys: HashSet<Vec<u8>> = HashSet::new();
+            let mut inserted: Vec<(u64, Cid, SignedDagNode)> = Vec::new();
+            for (node, node_cid) in nodes.into_iter().zip(cids_for_batch) {
+                let node_key = Self::cid_to_key(&node_cid);
+                if batch_keys.contains(&node_key) || db_clone.get_cf(cf_nodes, &node_key)?.is_some() {
+                    continue;
+                }
+                for parent_cid in &node.node.parents {
+                    let parent_key = Self::cid_to_key(parent_cid);
+                    if !batch_keys.contains(&parent_key) && db_clone.get_cf(cf_nodes, &parent_key)?.is_none() {
+                        return Err(DagError::ParentNotFound { child: node_cid.clone(), parent: parent_cid.clone() });
+                    }
+                }
+                batch_keys.insert(node_key);
+                let seq = first_seq + inserted.len() as u64;
+                inserted.push((seq, node_cid, node));
+            }
+
+            // 2. Stage nodes and collect index additions per key
+            let mut batch = WriteBatch::default();
+            let mut children: HashMap<Vec<u8>, Vec<Vec<u8>>> = HashMap::new();
+            let mut authors: HashMap<Vec<u8>, Vec<Vec<u8>>> = HashMap::new();
+            let mut payload_types: HashMap<Vec<u8>, Vec<Vec<u8>>> = HashMap::new();
+            let mut parent_keys: HashSet<Vec<u8>> = HashSet::new();
+            for (seq, node_cid, node) in &inserted {
+                let node_key = Self::cid_to_key(node_cid);
+                batch.put_cf(cf_nodes, &node_key, Self::serialize_node(node)?);
+                batch.put_cf(cf_sequence, seq.to_be_bytes(), &node_key);
+                for parent_cid in &node.node.parents {
+                    let parent_key = Self::cid_to_key(parent_cid);
+                    children.entry(parent_key.clone()).or_default().push(node_key.clone());
+                    parent_keys.insert(parent_key);
+                }
+                authors
+                    .entry(node.node.author.to_string().into_bytes())
+                    .or_default()
+                    .push(node_key.clone());
+                payload_types
+                    .entry(node.node.payload.type_key().as_bytes().to_vec())
+                    .or_default()
+                    .push(node_key);
+            }
+
+            // 3. Update tips once: new nodes without children in the batch become tips,
+            //    every referenced parent stops being one
+            for (_, node_cid, _) in &inserted {
+                let node_key = Self::cid_to_key(node_cid);
+                if !parent_keys.contains(&node_key) {
+                    batch.put_cf(cf_tips, &node_key, &[1]);
+                }
+            }
+            for parent_key in &parent_keys {
+                batch.delete_cf(cf_tips, parent_key);
+            }
+
+            // 4. Merge index lists, one read and one write per key
+            for (parent_key, additions) in children {
+                Self::merge_cid_list(&db_clone, &mut batch, cf_children, &parent_key, additions)?;
+            }
+            for (author_key, additions) in authors {
+                Self::merge_cid_list(&db_clone, &mut batch, cf_authors, &author_key, additions)?;
+            }
+            for (payload_type_key, additions) in payload_types {
+                Self::merge_cid_list(&db_clone, &mut batch, cf_payload_types, &payload_type_key, additions)?;
+            }
+
+            // --- Commit Batch ---
+            db_clone.write(batch).map_err(|e| {
+                DagError::StorageError(format!("Atomic batch write failed: {}", e))
+            })?;
+
+            Ok::<_, DagError>((inserted, parent_keys))
+        }).await.map_err(DagError::from)??;
+
+        // --- Update In-Memory Cache (After successful commit) ---
+        {
+            let mut non_tips = self
+                .non_tips
+                .write()
+                .map_err(|e| DagError::StorageError(format!("Failed to acquire write lock for cache: {}", e)))?;
+            non_tips.extend(parent_keys);
+        }
+
+        DAG_NODES_TOTAL.add(inserted.len() as i64);
+        if let Some((last_seq, _, _)) = inserted.last() {
+            self.last_seq.store(*last_seq, Ordering::SeqCst);
+        }
+        for (seq, node_cid, node) in inserted {
+            self.feed.publish(DagCursor(seq), node_cid, node);
+        }
+
+        Ok(cids)
+    }
+
     async fn get_node(&self, cid: &Cid) -> Result<SignedDagNode, DagError> {
         let cid_clone = cid.clone();
         let db_clone = self.db.clone();
@@ -838,21 +1031,45 @@ impl DagStore for RocksDbDagStore {
         Ok(tips)
     }
 
-    async fn verify_branch(&self, tip: &Cid, _resolver: &(dyn PublicKeyResolver + Send + Sync)) -> Result<(), DagError> {
+    async fn verify_branch(&self, tip: &Cid, resolver: &(dyn PublicKeyResolver + Send + Sync)) -> Result<(), DagError> {
         let _timer = DAG_VERIFY_BRANCH_DURATION.start_timer(); // Start timing
         
-        let _tip_clone = tip.clone();
-        let _db_clone = self.db.clone();
-        // Pass resolver appropriately, assuming it's Send + Sync
-        // If resolver itself is not Send/Sync, need to handle differently (e.g., Arc<Mutex<...>>)
-        // Commenting out spawn_blocking call as resolver handling is unclear
-        /*
-        let verification_result = tokio::task::spawn_blocking(move || {
-            // ... blocking verification logic using resolver ...
+        let tip_clone = tip.clone();
+        let db_clone = self.db.clone();
+        // Load the branch in a blocking task; signatures are checked afterwards so the
+        // (non-'static) resolver never has to move into the blocking pool.
+        let branch_result = tokio::task::spawn_blocking(move || {
+            let cf_nodes = db_clone.cf_handle(CF_NODES)
+                .ok_or_else(|| DagError::StorageError(format!("CF not found: {}", CF_NODES)))?;
+
+            let mut branch = Vec::new();
+            let mut visited = HashSet::new();
+            let mut queue = VecDeque::new();
+            queue.push_back(tip_clone.clone());
+            visited.insert(tip_clone.clone());
+
+            while let Some(cid) = queue.pop_front() {
+                let node_bytes = match db_clone.get_cf(cf_nodes, Self::cid_to_key(&cid))? {
+                    Some(bytes) => bytes,
+                    None if cid == tip_clone => return Err(DagError::NodeNotFound(cid)),
+                    None => return Err(DagError::MissingParent(cid)),
+                };
+                let signed_node = Self::deserialize_node(&node_bytes)?;
+                for parent_cid in &signed_node.node.parents {
+                    if visited.insert(parent_cid.clone()) {
+                        queue.push_back(parent_cid.clone());
+                    }
+                }
+                branch.push((cid, signed_node));
+            }
+            Ok::<_, DagError>(branch)
         }).await;
-        */
-        // Using placeholder result until spawn_blocking is fixed
-        let verification_result: Result<Result<(), DagError>, tokio::task::JoinError> = Ok(Ok(()));
+
+        let verification_result = branch_result.map(|loaded| {
+            loaded.and_then(|branch| {
+                branch.iter().try_for_each(|(cid, node)| node.verify(cid, resolver))
+            })
+        });
 
         // Match on the outer JoinError first, then the inner verification Result
         match verification_result {
@@ -1129,4 +1346,36 @@ impl DagStore for RocksDbDagStore {
     async fn get_data(&self, _cid: &Cid) -> Result<Option<Vec<u8>>, DagError> {
         unimplemented!("get_data not yet implemented for RocksDbDagStore (async)")
     }
+
+    async fn subscribe(&self, filter: DagFeedFilter, from: Option<DagCursor>) -> Result<DagSubscription, DagError> {
+        let from = from.unwrap_or(DagCursor::START);
+        // Register for live events before reading the backlog so nothing is missed
+        let live = self.feed.receiver();
+        let db_clone = self.db.clone();
+
+        let backlog = tokio::task::spawn_blocking(move || {
+            let cf_sequence = db_clone.cf_handle(CF_SEQUENCE)
+                .ok_or_else(|| DagError::StorageError(format!("CF not found: {}", CF_SEQUENCE)))?;
+            let cf_nodes = db_clone.cf_handle(CF_NODES)
+                .ok_or_else(|| DagError::StorageError(format!("CF not found: {}", CF_NODES)))?;
+
+            let start = (from.0 + 1).to_be_bytes();
+            let mut backlog = Vec::new();
+            for entry in db_clone.iterator_cf(cf_sequence, rocksdb::IteratorMode::From(&start, rocksdb::Direction::Forward)) {
+                let (seq_key, node_key) = entry?;
+                let cid = Cid::try_from(node_key.as_ref())
+                    .map_err(|e| DagError::CidError(format!("Invalid CID bytes in sequence index: {}", e)))?;
+                let node_bytes = db_clone.get_cf(cf_nodes, &node_key)?
+                    .ok_or_else(|| DagError::NodeNotFound(cid.clone()))?;
+                backlog.push(DagFeedEvent {
+                    cursor: DagCursor(Self::key_to_seq(&seq_key)?),
+                    cid,
+                    node: Self::deserialize_node(&node_bytes)?,
+                });
+            }
+            Ok::<_, DagError>(backlog)
+        }).await.map_err(DagError::from)??;
+
+        Ok(DagSubscription::new(filter, from, backlog, live))
+    }
 } 
\ No newline at end of file
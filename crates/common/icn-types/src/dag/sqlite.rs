@@ -0,0 +1,386 @@
+#![cfg(feature = "sqlite")]
+
+use crate::Cid;
+use crate::dag::{DagError, DagNode, DagStore, SignedDagNode, PublicKeyResolver};
+use crate::dag::feed::{DagCursor, DagFeed, DagFeedEvent, DagFeedFilter, DagSubscription};
+use crate::Did;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use ed25519_dalek::Signature;
+
+/// Schema for the SQLite DAG store.
+///
+/// `nodes.node` holds the canonical DAG-CBOR encoding of the unsigned node (the bytes
+/// the CID and signature are computed over) and `nodes.signature` the raw signature.
+/// `nodes.seq` records insertion order and doubles as the change feed cursor. Since
+/// parents must be present before a child is inserted, ordering by `seq` is always
+/// a valid topological order.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS nodes (
+        seq          INTEGER PRIMARY KEY AUTOINCREMENT,
+        cid          BLOB NOT NULL UNIQUE,
+        node         BLOB NOT NULL,
+        signature    BLOB NOT NULL,
+        author       TEXT NOT NULL,
+        payload_type TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS children (
+        parent BLOB NOT NULL,
+        child  BLOB NOT NULL,
+        PRIMARY KEY (parent, child)
+    );
+    CREATE TABLE IF NOT EXISTS tips (
+        cid BLOB PRIMARY KEY
+    );
+    CREATE INDEX IF NOT EXISTS idx_nodes_author ON nodes(author);
+    CREATE INDEX IF NOT EXISTS idx_nodes_payload_type ON nodes(payload_type);
+";
+
+/// SQLite-based implementation of the DagStore trait.
+///
+/// A lighter alternative to `RocksDbDagStore` for small deployments and for
+/// embedding (e.g. in the wallet). All indexes (tips, children, authors and
+/// payload types) are kept in the same database file.
+#[derive(Clone)]
+pub struct SqliteDagStore {
+    conn: Arc<Mutex<Connection>>,
+    feed: DagFeed,
+}
+
+impl SqliteDagStore {
+    /// Open a SQLite database at the specified path, creating it if it doesn't exist
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, DagError> {
+        let conn = Connection::open(path)
+            .map_err(|e| DagError::StorageError(format!("Failed to open SQLite database: {}", e)))?;
+        Self::from_connection(conn)
+    }
+
+    /// Open a transient, in-memory SQLite database
+    pub fn open_in_memory() -> Result<Self, DagError> {
+        let conn = Connection::open_in_memory()
+            .map_err(|e| DagError::StorageError(format!("Failed to open in-memory SQLite database: {}", e)))?;
+        Self::from_connection(conn)
+    }
+
+    fn from_connection(conn: Connection) -> Result<Self, DagError> {
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            feed: DagFeed::new(),
+        })
+    }
+
+    /// Run a closure against the connection on the blocking thread pool
+    async fn with_conn<F, T>(&self, f: F) -> Result<T, DagError>
+    where
+        F: FnOnce(&mut Connection) -> Result<T, DagError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || {
+            let mut guard = conn
+                .lock()
+                .map_err(|e| DagError::StorageError(format!("Failed to acquire SQLite connection lock: {}", e)))?;
+            f(&mut guard)
+        })
+        .await
+        .map_err(DagError::JoinError)?
+    }
+
+    /// Serialize the unsigned part of a DAG node to canonical DAG-CBOR bytes
+    fn serialize_node(node: &SignedDagNode) -> Result<Vec<u8>, DagError> {
+        serde_ipld_dagcbor::to_vec(&node.node)
+            .map_err(|e| DagError::SerializationError(format!("DAG-CBOR serialization error (node): {}", e)))
+    }
+
+    /// Rebuild a signed DAG node from its stored columns
+    fn deserialize_node(cid: Cid, node_bytes: &[u8], signature_bytes: &[u8]) -> Result<SignedDagNode, DagError> {
+        let node: DagNode = serde_ipld_dagcbor::from_slice(node_bytes)
+            .map_err(|e| DagError::SerializationError(format!("DAG-CBOR deserialization error (node): {}", e)))?;
+        let signature = Signature::from_slice(signature_bytes)
+            .map_err(|e| DagError::SerializationError(format!("Invalid stored signature for {}: {}", cid, e)))?;
+        Ok(SignedDagNode {
+            node,
+            signature,
+            cid: Some(cid),
+        })
+    }
+
+    /// Read the `(cid, node, signature)` columns of a row
+    fn node_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+        Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+    }
+
+    /// Decode the columns read by `node_from_row` into a signed DAG node
+    fn decode_row((cid_key, node_bytes, signature_bytes): (Vec<u8>, Vec<u8>, Vec<u8>)) -> Result<SignedDagNode, DagError> {
+        Self::deserialize_node(Self::key_to_cid(&cid_key)?, &node_bytes, &signature_bytes)
+    }
+
+    /// Serialize a CID to use as a key
+    fn cid_to_key(cid: &Cid) -> Vec<u8> {
+        cid.to_bytes()
+    }
+
+    /// Parse a CID back from its key bytes
+    fn key_to_cid(key: &[u8]) -> Result<Cid, DagError> {
+        Cid::try_from(key).map_err(|e| DagError::CidError(format!("Invalid CID bytes in SQLite store: {}", e)))
+    }
+
+    fn load_node(conn: &Connection, cid: &Cid) -> Result<Option<SignedDagNode>, DagError> {
+        let row = conn
+            .query_row(
+                "SELECT cid, node, signature FROM nodes WHERE cid = ?1",
+                params![Self::cid_to_key(cid)],
+                Self::node_from_row,
+            )
+            .optional()?;
+        row.map(Self::decode_row).transpose()
+    }
+
+    fn load_nodes(conn: &Connection, sql: &str, args: impl rusqlite::Params) -> Result<Vec<SignedDagNode>, DagError> {
+        let mut stmt = conn.prepare_cached(sql)?;
+        let rows = stmt.query_map(args, Self::node_from_row)?;
+        let mut nodes = Vec::new();
+        for row in rows {
+            nodes.push(Self::decode_row(row?)?);
+        }
+        Ok(nodes)
+    }
+}
+
+#[async_trait]
+impl DagStore for SqliteDagStore {
+    async fn add_node(&mut self, mut node: SignedDagNode) -> Result<Cid, DagError> {
+        let node_cid = node.ensure_cid()?;
+        let node_key = Self::cid_to_key(&node_cid);
+        let node_bytes = Self::serialize_node(&node)?;
+        let signature_bytes = node.signature.to_bytes().to_vec();
+        let author = node.node.author.to_string();
+        let payload_type = node.node.payload.type_key();
+        let feed = self.feed.clone();
+
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+
+            let exists = tx
+                .query_row("SELECT 1 FROM nodes WHERE cid = ?1", params![node_key], |_| Ok(()))
+                .optional()?
+                .is_some();
+            if exists {
+                return Ok(node_cid);
+            }
+
+            for parent_cid in &node.node.parents {
+                let parent_exists = tx
+                    .query_row("SELECT 1 FROM nodes WHERE cid = ?1", params![Self::cid_to_key(parent_cid)], |_| Ok(()))
+                    .optional()?
+                    .is_some();
+                if !parent_exists {
+                    return Err(DagError::ParentNotFound { child: node_cid.clone(), parent: parent_cid.clone() });
+                }
+            }
+
+            tx.execute(
+                "INSERT INTO nodes (cid, node, signature, author, payload_type) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![node_key, node_bytes, signature_bytes, author, payload_type],
+            )?;
+            let seq = tx.last_insert_rowid();
+            tx.execute("INSERT OR IGNORE INTO tips (cid) VALUES (?1)", params![node_key])?;
+            for parent_cid in &node.node.parents {
+                let parent_key = Self::cid_to_key(parent_cid);
+                tx.execute("DELETE FROM tips WHERE cid = ?1", params![parent_key])?;
+                tx.execute(
+                    "INSERT OR IGNORE INTO children (parent, child) VALUES (?1, ?2)",
+                    params![parent_key, node_key],
+                )?;
+            }
+
+            tx.commit()?;
+            // Publish while still holding the connection so events go out in seq order
+            feed.publish(DagCursor(seq as u64), node_cid.clone(), node);
+            Ok(node_cid)
+        })
+        .await
+    }
+
+    async fn get_node(&self, cid: &Cid) -> Result<SignedDagNode, DagError> {
+        let cid = cid.clone();
+        self.with_conn(move |conn| {
+            Self::load_node(conn, &cid)?.ok_or(DagError::NodeNotFound(cid))
+        })
+        .await
+    }
+
+    async fn get_data(&self, cid: &Cid) -> Result<Option<Vec<u8>>, DagError> {
+        let key = Self::cid_to_key(cid);
+        self.with_conn(move |conn| {
+            let data = conn
+                .query_row("SELECT node FROM nodes WHERE cid = ?1", params![key], |row| row.get(0))
+                .optional()?;
+            Ok(data)
+        })
+        .await
+    }
+
+    async fn get_tips(&self) -> Result<Vec<Cid>, DagError> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare_cached("SELECT cid FROM tips")?;
+            let rows = stmt.query_map([], |row| row.get::<_, Vec<u8>>(0))?;
+            let mut tips = Vec::new();
+            for row in rows {
+                tips.push(Self::key_to_cid(&row?)?);
+            }
+            Ok(tips)
+        })
+        .await
+    }
+
+    async fn get_ordered_nodes(&self) -> Result<Vec<SignedDagNode>, DagError> {
+        self.with_conn(|conn| {
+            // Insertion order is topological because parents are required to exist first
+            Self::load_nodes(conn, "SELECT cid, node, signature FROM nodes ORDER BY seq", [])
+        })
+        .await
+    }
+
+    async fn get_nodes_by_author(&self, author: &Did) -> Result<Vec<SignedDagNode>, DagError> {
+        let author_key = author.to_string();
+        self.with_conn(move |conn| {
+            Self::load_nodes(conn, "SELECT cid, node, signature FROM nodes WHERE author = ?1 ORDER BY seq", params![author_key])
+        })
+            .await
+    }
+
+    async fn get_nodes_by_payload_type(&self, payload_type: &str) -> Result<Vec<SignedDagNode>, DagError> {
+        let payload_key = payload_type.to_string();
+        self.with_conn(move |conn| {
+            Self::load_nodes(conn, "SELECT cid, node, signature FROM nodes WHERE payload_type = ?1 ORDER BY seq", params![payload_key])
+        })
+            .await
+    }
+
+    async fn find_path(&self, from: &Cid, to: &Cid) -> Result<Vec<SignedDagNode>, DagError> {
+        let from_cid = from.clone();
+        let to_cid = to.clone();
+
+        self.with_conn(move |conn| {
+            let mut queue = VecDeque::new();
+            let mut visited = HashSet::new();
+            // Stores child -> immediate parent relationship discovered during BFS from 'from'
+            let mut predecessors = HashMap::new();
+            let mut loaded = HashMap::new();
+
+            queue.push_back(from_cid.clone());
+            visited.insert(from_cid.clone());
+            let mut target_found = false;
+
+            while let Some(current_cid) = queue.pop_front() {
+                let signed_node = Self::load_node(conn, &current_cid)?
+                    .ok_or_else(|| DagError::NodeNotFound(current_cid.clone()))?;
+                let parents = signed_node.node.parents.clone();
+                loaded.insert(current_cid.clone(), signed_node);
+
+                if current_cid == to_cid {
+                    target_found = true;
+                    break;
+                }
+
+                for parent_cid in parents {
+                    if visited.insert(parent_cid.clone()) {
+                        predecessors.insert(parent_cid.clone(), current_cid.clone());
+                        queue.push_back(parent_cid);
+                    }
+                }
+            }
+
+            if !target_found {
+                return Ok(Vec::new());
+            }
+
+            let mut path_nodes = VecDeque::new();
+            let mut current = to_cid.clone();
+            loop {
+                let node = loaded
+                    .remove(&current)
+                    .ok_or_else(|| DagError::StorageError("Path reconstruction failed: node not loaded".to_string()))?;
+                path_nodes.push_front(node);
+                if current == from_cid {
+                    break;
+                }
+                current = predecessors
+                    .get(&current)
+                    .cloned()
+                    .ok_or_else(|| DagError::StorageError("Path reconstruction failed: predecessor not found".to_string()))?;
+            }
+
+            Ok(path_nodes.into())
+        })
+        .await
+    }
+
+    async fn verify_branch(&self, tip: &Cid, resolver: &(dyn PublicKeyResolver + Send + Sync)) -> Result<(), DagError> {
+        // Load the branch under the connection lock, then check signatures here so the
+        // resolver does not need to cross into the blocking pool.
+        let tip_cid = tip.clone();
+        let branch = self
+            .with_conn(move |conn| {
+                let mut branch = Vec::new();
+                let mut visited = HashSet::new();
+                let mut queue = VecDeque::new();
+                queue.push_back(tip_cid.clone());
+                visited.insert(tip_cid.clone());
+
+                while let Some(cid) = queue.pop_front() {
+                    let node = match Self::load_node(conn, &cid)? {
+                        Some(node) => node,
+                        None if cid == tip_cid => return Err(DagError::NodeNotFound(cid)),
+                        None => return Err(DagError::MissingParent(cid)),
+                    };
+                    for parent_cid in &node.node.parents {
+                        if visited.insert(parent_cid.clone()) {
+                            queue.push_back(parent_cid.clone());
+                        }
+                    }
+                    branch.push((cid, node));
+                }
+                Ok(branch)
+            })
+            .await?;
+
+        for (stored_cid, node) in branch {
+            node.verify(&stored_cid, resolver)?;
+        }
+
+        Ok(())
+    }
+
+    async fn subscribe(&self, filter: DagFeedFilter, from: Option<DagCursor>) -> Result<DagSubscription, DagError> {
+        let from = from.unwrap_or(DagCursor::START);
+        // Register for live events before reading the backlog so nothing is missed
+        let live = self.feed.receiver();
+        let backlog = self
+            .with_conn(move |conn| {
+                let mut stmt = conn.prepare_cached(
+                    "SELECT cid, node, signature, seq FROM nodes WHERE seq > ?1 ORDER BY seq",
+                )?;
+                let rows = stmt.query_map(params![from.0 as i64], |row| {
+                    Ok((Self::node_from_row(row)?, row.get::<_, i64>(3)?))
+                })?;
+                let mut backlog = Vec::new();
+                for row in rows {
+                    let (columns, seq) = row?;
+                    let node = Self::decode_row(columns)?;
+                    let cid = node.cid.clone().ok_or_else(|| DagError::CidError("Stored node has no CID".to_string()))?;
+                    backlog.push(DagFeedEvent { cursor: DagCursor(seq as u64), cid, node });
+                }
+                Ok(backlog)
+            })
+            .await?;
+
+        Ok(DagSubscription::new(filter, from, backlog, live))
+    }
+}
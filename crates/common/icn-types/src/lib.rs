@@ -44,6 +44,9 @@ pub use dag::sync::*;
 #[cfg(feature = "persistence")]
 pub use dag::rocksdb::RocksDbDagStore;
 
+#[cfg(feature = "sqlite")]
+pub use dag::sqlite::SqliteDagStore;
+
 pub use governance::QuorumConfig;
 // Commented out problematic re-exports for now
 pub use receipts::{QuorumProof, ReceiptError}; // Removed ReceiptProof, VoteReceipt, SignedVoteReceipt
@@ -0,0 +1,66 @@
+//! Runs the shared `DagStore` conformance suite against every backend in this crate.
+
+use icn_types::dag::conformance;
+use icn_types::dag::memory::MemoryDagStore;
+use icn_types::SqliteDagStore;
+use proptest::prelude::*;
+
+/// Random DAG shapes: up to 40 nodes, each with up to 3 parent indexes
+fn dag_shape() -> impl Strategy<Value = Vec<Vec<usize>>> {
+    prop::collection::vec(prop::collection::vec(any::<usize>(), 0..=3), 1..40)
+}
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to build runtime")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn memory_dag_store_conforms() {
+    conformance::run_all(MemoryDagStore::new).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sqlite_dag_store_conforms() {
+    conformance::run_all(|| SqliteDagStore::open_in_memory().expect("Failed to open SQLite store")).await;
+}
+
+#[cfg(feature = "persistence")]
+#[tokio::test(flavor = "multi_thread")]
+async fn rocksdb_dag_store_conforms() {
+    use icn_types::RocksDbDagStore;
+
+    let mut dirs = Vec::new();
+    conformance::run_all(move || {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let store = RocksDbDagStore::open(dir.path()).expect("Failed to open RocksDB store");
+        dirs.push(dir);
+        store
+    })
+    .await;
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn memory_dag_store_random_shapes(shape in dag_shape()) {
+        runtime().block_on(conformance::check_random_dag(&mut MemoryDagStore::new(), &shape));
+    }
+
+    #[test]
+    fn sqlite_dag_store_random_shapes(shape in dag_shape()) {
+        let mut store = SqliteDagStore::open_in_memory().expect("Failed to open SQLite store");
+        runtime().block_on(conformance::check_random_dag(&mut store, &shape));
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn rocksdb_dag_store_random_shapes(shape in dag_shape()) {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let mut store = icn_types::RocksDbDagStore::open(dir.path()).expect("Failed to open RocksDB store");
+        runtime().block_on(conformance::check_random_dag(&mut store, &shape));
+    }
+}
@@ -0,0 +1,59 @@
+use icn_core_types::DidKey;
+use icn_types::dag::memory::MemoryDagStore;
+use icn_types::dag::{DagNodeBuilder, DagPayload, SharedDagStore, SignedDagNode};
+use icn_types::Cid;
+
+fn signed_node(key: &DidKey, parents: Vec<Cid>, seq: u32) -> SignedDagNode {
+    let node = DagNodeBuilder::new()
+        .with_payload(DagPayload::Raw(seq.to_be_bytes().to_vec()))
+        .with_parents(parents)
+        .with_author(key.did().clone())
+        .with_federation_id("test-federation".to_string())
+        .build()
+        .expect("Failed to build node");
+    let node_bytes = serde_ipld_dagcbor::to_vec(&node).unwrap();
+    SignedDagNode {
+        signature: key.sign(&node_bytes),
+        node,
+        cid: None,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_reads_alongside_writer() {
+    let key = DidKey::new();
+    let store = SharedDagStore::new(Box::new(MemoryDagStore::new()));
+    let genesis = store.add_node(signed_node(&key, vec![], 0)).await.unwrap();
+
+    let readers: Vec<_> = (0..8)
+        .map(|_| {
+            let store = store.clone();
+            let genesis = genesis.clone();
+            tokio::spawn(async move {
+                for _ in 0..50 {
+                    assert!(store.get_node(&genesis).await.is_ok());
+                    assert!(!store.get_tips().await.unwrap().is_empty());
+                }
+            })
+        })
+        .collect();
+
+    let writer = {
+        let store = store.clone();
+        let genesis = genesis.clone();
+        tokio::spawn(async move {
+            let mut parent = genesis;
+            for seq in 1..=20 {
+                parent = store.add_node(signed_node(&key, vec![parent], seq)).await.unwrap();
+            }
+            parent
+        })
+    };
+
+    for reader in readers {
+        reader.await.unwrap();
+    }
+    let last = writer.await.unwrap();
+    assert_eq!(store.get_tips().await.unwrap(), vec![last]);
+    assert_eq!(store.get_ordered_nodes().await.unwrap().len(), 21);
+}
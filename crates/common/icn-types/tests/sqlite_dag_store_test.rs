@@ -0,0 +1,106 @@
+#![cfg(feature = "sqlite")]
+
+use icn_core_types::DidKey;
+use icn_types::dag::{DagError, DagNodeBuilder, DagPayload, DagStore, PublicKeyResolver, SignedDagNode};
+use icn_types::{Cid, Did, SqliteDagStore};
+use ed25519_dalek::VerifyingKey;
+use tempfile::tempdir;
+
+fn signed_node(key: &DidKey, parents: Vec<Cid>, payload: DagPayload) -> SignedDagNode {
+    let node = DagNodeBuilder::new()
+        .with_payload(payload)
+        .with_parents(parents)
+        .with_author(key.did().clone())
+        .with_federation_id("test-federation".to_string())
+        .build()
+        .expect("Failed to build node");
+    let node_bytes = serde_ipld_dagcbor::to_vec(&node).unwrap();
+    SignedDagNode {
+        node,
+        signature: key.sign(&node_bytes),
+        cid: None,
+    }
+}
+
+struct SingleKeyResolver(DidKey);
+
+impl PublicKeyResolver for SingleKeyResolver {
+    fn resolve(&self, did: &Did) -> Result<VerifyingKey, DagError> {
+        if did == self.0.did() {
+            Ok(*self.0.verifying_key())
+        } else {
+            Err(DagError::PublicKeyResolutionError(did.clone(), "Unknown DID".to_string()))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_sqlite_add_get_and_tips() {
+    let key = DidKey::new();
+    let mut store = SqliteDagStore::open_in_memory().unwrap();
+
+    let genesis = signed_node(&key, vec![], DagPayload::Raw(b"genesis".to_vec()));
+    let genesis_cid = store.add_node(genesis.clone()).await.unwrap();
+    assert_eq!(store.get_node(&genesis_cid).await.unwrap().node, genesis.node);
+    assert_eq!(store.get_tips().await.unwrap(), vec![genesis_cid.clone()]);
+
+    let child = signed_node(&key, vec![genesis_cid.clone()], DagPayload::Json(serde_json::json!({"n": 1})));
+    let child_cid = store.add_node(child).await.unwrap();
+    assert_eq!(store.get_tips().await.unwrap(), vec![child_cid.clone()]);
+
+    let ordered: Vec<Cid> = store
+        .get_ordered_nodes()
+        .await
+        .unwrap()
+        .iter()
+        .map(|n| n.calculate_cid().unwrap())
+        .collect();
+    assert_eq!(ordered, vec![genesis_cid.clone(), child_cid.clone()]);
+
+    let path = store.find_path(&child_cid, &genesis_cid).await.unwrap();
+    assert_eq!(path.len(), 2);
+
+    assert_eq!(store.get_nodes_by_author(key.did()).await.unwrap().len(), 2);
+    assert_eq!(store.get_nodes_by_payload_type("json").await.unwrap().len(), 1);
+    assert!(store.get_data(&child_cid).await.unwrap().is_some());
+
+    let resolver = SingleKeyResolver(key);
+    store.verify_branch(&child_cid, &resolver).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_sqlite_rejects_missing_parent_and_ignores_duplicates() {
+    let key = DidKey::new();
+    let mut store = SqliteDagStore::open_in_memory().unwrap();
+
+    let genesis = signed_node(&key, vec![], DagPayload::Raw(b"genesis".to_vec()));
+    let genesis_cid = store.add_node(genesis.clone()).await.unwrap();
+    assert_eq!(store.add_node(genesis).await.unwrap(), genesis_cid);
+    assert_eq!(store.get_ordered_nodes().await.unwrap().len(), 1);
+
+    let dangling_parent = Cid::from_bytes(b"not in the store").unwrap();
+    let orphan = signed_node(&key, vec![dangling_parent], DagPayload::Raw(b"orphan".to_vec()));
+    assert!(matches!(
+        store.add_node(orphan).await,
+        Err(DagError::ParentNotFound { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_sqlite_persists_across_reopen() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("dag.sqlite");
+    let key = DidKey::new();
+
+    let genesis_cid = {
+        let mut store = SqliteDagStore::open(&db_path).unwrap();
+        store
+            .add_node(signed_node(&key, vec![], DagPayload::Raw(b"genesis".to_vec())))
+            .await
+            .unwrap()
+    };
+
+    let store = SqliteDagStore::open(&db_path).unwrap();
+    assert_eq!(store.get_tips().await.unwrap(), vec![genesis_cid.clone()]);
+    assert!(store.get_node(&genesis_cid).await.is_ok());
+}
@@ -1,40 +1,32 @@
-use wasmtime::{Linker, Caller, Memory, AsContextMut};
+use wasmtime::{Linker, Caller};
 use crate::abi::context::HostContext;
-use std::sync::Arc;
-use anyhow::anyhow;
-use log;
-
-// Helper function to read a string from WASM memory
-fn read_string_from_memory(
-    mut caller: impl AsContextMut,
-    memory: &Memory,
-    ptr: i32,
-    len: i32,
-) -> anyhow::Result<String> {
-    let mem_slice = memory
-        .data_mut(&mut caller)
-        .get_mut(ptr as usize..(ptr + len) as usize)
-        .ok_or_else(|| anyhow!("Pointer/length out of bounds"))?;
-    String::from_utf8(mem_slice.to_vec())
-        .map_err(|_| anyhow!("Invalid UTF-8 sequence in memory"))
-}
+use crate::engine::{ContextExtension, StoreData};
+use crate::host::guest::trace_call;
+use crate::host::{dag, economics, environment, logging, policy, signature};
 
 /// Registers the ICN host functions with the Wasmtime linker.
-pub fn register_host_functions<T: HostContext + 'static>(
-    linker: &mut Linker<Arc<T>>,
+pub fn register_host_functions<T: HostContext + ContextExtension + 'static>(
+    linker: &mut Linker<StoreData<T>>,
 ) -> anyhow::Result<()> {
-    // Register log function
-    linker.func_wrap("env", "log", |mut caller: Caller<'_, Arc<T>>, ptr: i32, len: i32| {
-        if let Some(memory) = caller.get_export("memory").and_then(|e| e.into_memory()) {
-            if let Ok(message) = read_string_from_memory(&mut caller, &memory, ptr, len) {
-                caller.data().log_message(&message);
-            }
-        }
+    // Register logging functions
+    linker.func_wrap("env", "log", |mut caller: Caller<'_, StoreData<T>>, ptr: i32, len: i32| {
+        trace_call(&mut caller, "log", &[ptr.into(), len.into()]);
+        logging::host_log(&mut caller, ptr, len)
     })?;
     
+    linker.func_wrap(
+        "env",
+        "log_structured",
+        |mut caller: Caller<'_, StoreData<T>>, level: i32, msg_ptr: i32, msg_len: i32, fields_ptr: i32, fields_len: i32| -> i32 {
+            trace_call(&mut caller, "log_structured", &[level.into(), msg_ptr.into(), msg_len.into(), fields_ptr.into(), fields_len.into()]);
+            logging::host_log_structured(&mut caller, level, msg_ptr, msg_len, fields_ptr, fields_len)
+        },
+    )?;
+    
     // Register get_caller_did function
-    linker.func_wrap("env", "get_caller_did", |mut caller: Caller<'_, Arc<T>>, ptr: i32, len: i32| -> i32 {
-        let caller_did = caller.data().get_caller_did().to_string();
+    linker.func_wrap("env", "get_caller_did", |mut caller: Caller<'_, StoreData<T>>, ptr: i32, len: i32| -> i32 {
+        trace_call(&mut caller, "get_caller_did", &[ptr.into(), len.into()]);
+        let caller_did = caller.data().ctx.get_caller_did().to_string();
         
         if let Some(memory) = caller.get_export("memory").and_then(|e| e.into_memory()) {
             let memory_data = memory.data_mut(&mut caller);
@@ -54,90 +46,216 @@ pub fn register_host_functions<T: HostContext + 'static>(
     linker.func_wrap(
         "env", 
         "check_policy_authorization", 
-        move |mut caller: Caller<'_, Arc<T>>, scope_type_ptr: i32, scope_type_len: i32, 
+        |mut caller: Caller<'_, StoreData<T>>, scope_type_ptr: i32, scope_type_len: i32, 
          scope_id_ptr: i32, scope_id_len: i32, action_ptr: i32, action_len: i32,
          did_ptr: i32, did_len: i32| -> i32 {
-            // Extract the host context (dereference and clone)
-            let context = &**caller.data();
-            let cloned_context = context.clone();
-            
-            // Read strings from memory
-            let scope_type = match read_wasm_string_with_context(
-                &cloned_context, &mut caller, scope_type_ptr, scope_type_len) 
-            {
-                Ok(s) => s,
-                Err(code) => return code - 1,
-            };
-            
-            let scope_id = match read_wasm_string_with_context(
-                &cloned_context, &mut caller, scope_id_ptr, scope_id_len) 
-            {
-                Ok(s) => s,
-                Err(code) => return code - 2,
-            };
-            
-            let action = match read_wasm_string_with_context(
-                &cloned_context, &mut caller, action_ptr, action_len) 
-            {
-                Ok(s) => s,
-                Err(code) => return code - 3,
-            };
-            
-            let did_str = match read_wasm_string_with_context(
-                &cloned_context, &mut caller, did_ptr, did_len) 
-            {
-                Ok(s) => s,
-                Err(code) => return code - 4,
-            };
-            
-            // Parse the DID
-            let did = match icn_types::Did::try_from(did_str) {
-                Ok(d) => d,
-                Err(e) => {
-                    log::error!("Invalid DID format: {}", e);
-                    return -5;
-                }
-            };
-            
-            // Get the policy loader
-            let policy_loader = match cloned_context.policy_loader() {
-                Some(loader) => loader,
-                None => {
-                    log::error!("Policy loader not available");
-                    return -6;
-                }
-            };
-            
-            log::debug!("Checking authorization for {} to perform {} in {}/{}", 
-                     did, action, scope_type, scope_id);
-            
-            // Perform the policy check
-            match policy_loader.check_authorization(&scope_type, &scope_id, &action, &did) {
-                Ok(()) => 0, // Authorized
-                Err(icn_types::PolicyError::ActionNotPermitted) => 1,
-                Err(icn_types::PolicyError::UnauthorizedScopeAccess) => 2,
-                Err(icn_types::PolicyError::DidNotInAllowlist) => 3,
-                Err(icn_types::PolicyError::PolicyNotFound) => 4,
-                Err(icn_types::PolicyError::InternalError(_)) => 5,
+            trace_call(&mut caller, "check_policy_authorization", &[
+                scope_type_ptr.into(), scope_type_len.into(), scope_id_ptr.into(), scope_id_len.into(),
+                action_ptr.into(), action_len.into(), did_ptr.into(), did_len.into(),
+            ]);
+            policy::host_check_policy_authorization(
+                &mut caller, scope_type_ptr, scope_type_len, scope_id_ptr, scope_id_len,
+                action_ptr, action_len, did_ptr, did_len,
+            )
+        }
+    )?;
+    
+    // Register signature verification; the host context may resolve keys remotely,
+    // so it runs as an async host call
+    linker.func_wrap6_async(
+        "env",
+        "verify_signature",
+        |mut caller: Caller<'_, StoreData<T>>, did_ptr: i32, did_len: i32, msg_ptr: i32, msg_len: i32,
+         sig_ptr: i32, sig_len: i32| {
+            trace_call(&mut caller, "verify_signature", &[did_ptr.into(), did_len.into(), msg_ptr.into(), msg_len.into(), sig_ptr.into(), sig_len.into()]);
+            Box::new(async move {
+                signature::host_verify_signature(&mut caller, did_ptr, did_len, msg_ptr, msg_len, sig_ptr, sig_len).await
+            })
+        },
+    )?;
+    
+    // Register input/output functions
+    linker.func_wrap("env", "get_input_len", |mut caller: Caller<'_, StoreData<T>>| -> i32 {
+        trace_call(&mut caller, "get_input_len", &[]);
+        caller.data().input.len() as i32
+    })?;
+    
+    // Copies up to `len` input bytes to `ptr`; returns the number copied or -1
+    linker.func_wrap("env", "read_input", |mut caller: Caller<'_, StoreData<T>>, ptr: i32, len: i32| -> i32 {
+        trace_call(&mut caller, "read_input", &[ptr.into(), len.into()]);
+        let memory = match caller.get_export("memory").and_then(|e| e.into_memory()) {
+            Some(memory) => memory,
+            None => return -1,
+        };
+        if ptr < 0 {
+            return -1;
+        }
+        let (memory_data, data) = memory.data_and_store_mut(&mut caller);
+        let count = data.input.len().min(len.max(0) as usize);
+        match memory_data.get_mut(ptr as usize..(ptr as usize) + count) {
+            Some(dst) => {
+                dst.copy_from_slice(&data.input[..count]);
+                count as i32
+            }
+            None => -1,
+        }
+    })?;
+    
+    // Appends `len` bytes at `ptr` to the execution output; returns 0 or -1
+    linker.func_wrap("env", "write_output", |mut caller: Caller<'_, StoreData<T>>, ptr: i32, len: i32| -> i32 {
+        trace_call(&mut caller, "write_output", &[ptr.into(), len.into()]);
+        let memory = match caller.get_export("memory").and_then(|e| e.into_memory()) {
+            Some(memory) => memory,
+            None => return -1,
+        };
+        if ptr < 0 || len < 0 {
+            return -1;
+        }
+        let (memory_data, data) = memory.data_and_store_mut(&mut caller);
+        match memory_data.get(ptr as usize..(ptr as usize) + (len as usize)) {
+            Some(src) => {
+                data.output.extend_from_slice(src);
+                0
             }
+            None => -1,
         }
+    })?;
+    
+    // Register clock and randomness functions
+    linker.func_wrap("env", "clock_now_ms", |mut caller: Caller<'_, StoreData<T>>| {
+        trace_call(&mut caller, "clock_now_ms", &[]);
+        environment::host_clock_now_ms(&mut caller)
+    })?;
+    
+    linker.func_wrap("env", "random_bytes", |mut caller: Caller<'_, StoreData<T>>, out_ptr: i32, out_len: i32| {
+        trace_call(&mut caller, "random_bytes", &[out_ptr.into(), out_len.into()]);
+        environment::host_random_bytes(&mut caller, out_ptr, out_len)
+    })?;
+    
+    // Register DAG functions; these await the store, so they run as async host calls
+    linker.func_wrap4_async(
+        "env",
+        "dag_get_node",
+        |mut caller: Caller<'_, StoreData<T>>, cid_ptr: i32, cid_len: i32, out_ptr: i32, out_len: i32| {
+            trace_call(&mut caller, "dag_get_node", &[cid_ptr.into(), cid_len.into(), out_ptr.into(), out_len.into()]);
+            Box::new(async move { dag::host_dag_get_node(&mut caller, cid_ptr, cid_len, out_ptr, out_len).await })
+        },
     )?;
     
-    Ok(())
-}
+    linker.func_wrap4_async(
+        "env",
+        "dag_get_payload",
+        |mut caller: Caller<'_, StoreData<T>>, cid_ptr: i32, cid_len: i32, out_ptr: i32, out_len: i32| {
+            trace_call(&mut caller, "dag_get_payload", &[cid_ptr.into(), cid_len.into(), out_ptr.into(), out_len.into()]);
+            Box::new(async move { dag::host_dag_get_payload(&mut caller, cid_ptr, cid_len, out_ptr, out_len).await })
+        },
+    )?;
+    
+    linker.func_wrap6_async(
+        "env",
+        "dag_anchor",
+        |mut caller: Caller<'_, StoreData<T>>, payload_ptr: i32, payload_len: i32, label_ptr: i32, label_len: i32,
+         out_ptr: i32, out_len: i32| {
+            trace_call(&mut caller, "dag_anchor", &[payload_ptr.into(), payload_len.into(), label_ptr.into(), label_len.into(), out_ptr.into(), out_len.into()]);
+            Box::new(async move {
+                dag::host_dag_anchor(&mut caller, payload_ptr, payload_len, label_ptr, label_len, out_ptr, out_len).await
+            })
+        },
+    )?;
+    
+    linker.func_wrap4_async(
+        "env",
+        "dag_query_by_label",
+        |mut caller: Caller<'_, StoreData<T>>, label_ptr: i32, label_len: i32, out_ptr: i32, out_len: i32| {
+            trace_call(&mut caller, "dag_query_by_label", &[label_ptr.into(), label_len.into(), out_ptr.into(), out_len.into()]);
+            Box::new(async move { dag::host_dag_query_by_label(&mut caller, label_ptr, label_len, out_ptr, out_len).await })
+        },
+    )?;
+    
+    // Register token ledger functions
+    linker.func_wrap2_async(
+        "env",
+        "token_balance",
+        |mut caller: Caller<'_, StoreData<T>>, resource_ptr: i32, resource_len: i32| {
+            trace_call(&mut caller, "token_balance", &[resource_ptr.into(), resource_len.into()]);
+            Box::new(async move { economics::host_token_balance(&mut caller, resource_ptr, resource_len).await })
+        },
+    )?;
+    
+    linker.func_wrap3_async(
+        "env",
+        "token_debit",
+        |mut caller: Caller<'_, StoreData<T>>, resource_ptr: i32, resource_len: i32, amount: i64| {
+            trace_call(&mut caller, "token_debit", &[resource_ptr.into(), resource_len.into(), amount]);
+            Box::new(async move { economics::host_token_debit(&mut caller, resource_ptr, resource_len, amount).await })
+        },
+    )?;
+    
+    linker.func_wrap3_async(
+        "env",
+        "token_credit",
+        |mut caller: Caller<'_, StoreData<T>>, resource_ptr: i32, resource_len: i32, amount: i64| {
+            trace_call(&mut caller, "token_credit", &[resource_ptr.into(), resource_len.into(), amount]);
+            Box::new(async move { economics::host_token_credit(&mut caller, resource_ptr, resource_len, amount).await })
+        },
+    )?;
+    
+    linker.func_wrap5_async(
+        "env",
+        "token_transfer",
+        |mut caller: Caller<'_, StoreData<T>>, resource_ptr: i32, resource_len: i32, dest_ptr: i32, dest_len: i32,
+         amount: i64| {
+            trace_call(&mut caller, "token_transfer", &[resource_ptr.into(), resource_len.into(), dest_ptr.into(), dest_len.into(), amount]);
+            Box::new(async move {
+                economics::host_token_transfer(&mut caller, resource_ptr, resource_len, dest_ptr, dest_len, amount).await
+            })
+        },
+    )?;
+    
+    // Register policy update function
+    linker.func_wrap2_async(
+        "env",
+        "policy_update",
+        |mut caller: Caller<'_, StoreData<T>>, policy_ptr: i32, policy_len: i32| {
+            trace_call(&mut caller, "policy_update", &[policy_ptr.into(), policy_len.into()]);
+            Box::new(async move { policy::host_policy_update(&mut caller, policy_ptr, policy_len).await })
+        },
+    )?;
+    
+    // Functions imported by compiled CCL
+    linker.func_wrap("icn", "host_log_message", |mut caller: Caller<'_, StoreData<T>>, ptr: i32, len: i32| {
+        trace_call(&mut caller, "host_log_message", &[ptr.into(), len.into()]);
+        logging::host_log(&mut caller, ptr, len)
+    })?;
 
-// Helper function to read a string using a cloned context
-fn read_wasm_string_with_context<T: HostContext>(
-    ctx: &T,
-    caller: &mut impl AsContextMut,
-    ptr: i32,
-    len: i32,
-) -> Result<String, i32> {
-    match ctx.read_string(caller, ptr, len) {
-        Ok(s) => Ok(s),
-        Err(e) => {
-            log::error!("Failed to read string: {}", e);
-            Err(-1)
-        }
-    }
+    linker.func_wrap3_async(
+        "icn",
+        "host_anchor_to_dag",
+        |mut caller: Caller<'_, StoreData<T>>, cid_ptr: i32, cid_len: i32, bytes: i32| {
+            trace_call(&mut caller, "host_anchor_to_dag", &[cid_ptr.into(), cid_len.into(), bytes.into()]);
+            Box::new(async move { dag::host_anchor_to_dag(&mut caller, cid_ptr, cid_len, bytes).await })
+        },
+    )?;
+
+    linker.func_wrap3_async(
+        "icn",
+        "host_check_resource_authorization",
+        |mut caller: Caller<'_, StoreData<T>>, token_ptr: i32, token_len: i32, amount: i64| {
+            trace_call(&mut caller, "host_check_resource_authorization", &[token_ptr.into(), token_len.into(), amount]);
+            Box::new(async move {
+                economics::host_check_resource_authorization(&mut caller, token_ptr, token_len, amount).await
+            })
+        },
+    )?;
+    
+    linker.func_wrap3_async(
+        "icn",
+        "host_record_resource_usage",
+        |mut caller: Caller<'_, StoreData<T>>, token_ptr: i32, token_len: i32, amount: i64| {
+            trace_call(&mut caller, "host_record_resource_usage", &[token_ptr.into(), token_len.into(), amount]);
+            Box::new(async move { economics::host_record_resource_usage(&mut caller, token_ptr, token_len, amount).await })
+        },
+    )?;
+    
+    Ok(())
 }
@@ -28,7 +28,9 @@ pub trait HostContext: Send + Sync + Clone {
     /// Log message
     fn log_message(&self, message: &str);
     
-    /// Verify signature
+    /// Verify a signature by `did` over `message`; backs the `verify_signature` host
+    /// function. Implementations typically delegate to
+    /// `host::signature::verify_with_resolver`.
     async fn verify_signature(&self, did: &Did, message: &[u8], signature: &[u8]) -> bool;
     
     /// Set error message
@@ -1,6 +1,10 @@
 pub mod context;
 pub mod bindings;
 
+/// Version of the host function ABI, recorded in execution receipts. Bumped when a
+/// host function is removed or changes signature or semantics.
+pub const HOST_ABI_VERSION: u32 = 1;
+
 // Keep HostSyscall enum for potential future direct interaction/planning?
 // Or remove if only using Wasmtime bindings directly.
 use icn_types::Did;
@@ -0,0 +1,106 @@
+use icn_types::Cid;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum BlockStoreError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to compute CID: {0}")]
+    Cid(String),
+    #[error("Block store lock poisoned")]
+    LockPoisoned,
+}
+
+/// Content-addressed storage for raw blocks such as execution outputs.
+///
+/// Blocks are keyed by their raw-codec CID (`Cid::from_bytes`), so the CID a
+/// receipt records for a result can always be used to fetch that result.
+pub trait BlockStore {
+    /// Store a block and return its CID
+    fn put(&self, data: &[u8]) -> Result<Cid, BlockStoreError>;
+
+    /// Retrieve a block by its CID
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>, BlockStoreError>;
+}
+
+/// Compute the CID a block is stored under
+pub fn block_cid(data: &[u8]) -> Result<Cid, BlockStoreError> {
+    Cid::from_bytes(data).map_err(|e| BlockStoreError::Cid(e.to_string()))
+}
+
+/// In-memory block store, mainly for tests and ephemeral nodes
+#[derive(Debug, Default)]
+pub struct MemoryBlockStore {
+    blocks: RwLock<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryBlockStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl BlockStore for MemoryBlockStore {
+    fn put(&self, data: &[u8]) -> Result<Cid, BlockStoreError> {
+        let cid = block_cid(data)?;
+        self.blocks
+            .write()
+            .map_err(|_| BlockStoreError::LockPoisoned)?
+            .insert(cid.to_string(), data.to_vec());
+        Ok(cid)
+    }
+
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>, BlockStoreError> {
+        Ok(self
+            .blocks
+            .read()
+            .map_err(|_| BlockStoreError::LockPoisoned)?
+            .get(&cid.to_string())
+            .cloned())
+    }
+}
+
+/// Block store keeping one file per block, named by its CID
+#[derive(Debug, Clone)]
+pub struct FsBlockStore {
+    dir: PathBuf,
+}
+
+impl FsBlockStore {
+    /// Open a block store rooted at `dir`, creating the directory if needed
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, BlockStoreError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn block_path(&self, cid: &Cid) -> PathBuf {
+        self.dir.join(cid.to_string())
+    }
+}
+
+impl BlockStore for FsBlockStore {
+    fn put(&self, data: &[u8]) -> Result<Cid, BlockStoreError> {
+        let cid = block_cid(data)?;
+        let path = self.block_path(&cid);
+        // Content addressing makes an existing file with this name identical
+        if !path.exists() {
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, data)?;
+            fs::rename(&tmp, &path)?;
+        }
+        Ok(cid)
+    }
+
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>, BlockStoreError> {
+        match fs::read(self.block_path(cid)) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
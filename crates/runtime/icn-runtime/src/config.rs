@@ -1,6 +1,56 @@
+use crate::policy::ScopeType;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 
+/// Resource limits enforced on a single WASM execution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// Wall-clock budget for the execution, in milliseconds.
+    #[serde(default = "default_max_execution_time_ms")]
+    pub max_execution_time_ms: u64,
+
+    /// Maximum size of the module's linear memory, in 64 KiB pages.
+    #[serde(default = "default_max_memory_pages")]
+    pub max_memory_pages: u32,
+
+    /// Maximum number of elements in any table.
+    #[serde(default = "default_max_table_elements")]
+    pub max_table_elements: u32,
+
+    /// Fuel granted when the caller does not pass an explicit fuel limit.
+    #[serde(default = "default_max_fuel")]
+    pub max_fuel: u64,
+}
+
+fn default_max_execution_time_ms() -> u64 {
+    5_000
+}
+
+fn default_max_memory_pages() -> u32 {
+    256 // 16 MiB
+}
+
+fn default_max_table_elements() -> u32 {
+    10_000
+}
+
+fn default_max_fuel() -> u64 {
+    10_000_000
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            max_execution_time_ms: default_max_execution_time_ms(),
+            max_memory_pages: default_max_memory_pages(),
+            max_table_elements: default_max_table_elements(),
+            max_fuel: default_max_fuel(),
+        }
+    }
+}
+
 /// Configuration related to WASM execution within the runtime.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionConfig {
@@ -16,10 +66,24 @@ pub struct ExecutionConfig {
     /// If None, receipts are not exported to the filesystem.
     #[serde(default = "default_receipt_export_dir")]
     pub receipt_export_dir: Option<PathBuf>,
-    
-    // Placeholder for other execution-related configurations
-    // pub max_execution_time_ms: u64,
-    // pub max_memory_pages: u32,
+
+    /// Resource limits for executions whose scope has no entry in `scope_limits`.
+    #[serde(default)]
+    pub limits: ResourceLimits,
+
+    /// Per-scope resource limits, e.g. tighter limits for community jobs.
+    #[serde(default)]
+    pub scope_limits: HashMap<ScopeType, ResourceLimits>,
+
+    /// If true, record every host call with its arguments and fuel in the
+    /// execution trace linked from the receipt.
+    #[serde(default)]
+    pub trace_host_calls: bool,
+
+    /// Module CIDs allowed to run, keyed by scope ID ("_" for the federation).
+    /// Scopes without an entry may run any module their policy permits.
+    #[serde(default)]
+    pub module_allowlist: HashMap<String, Vec<String>>,
 }
 
 fn default_true() -> bool {
@@ -36,12 +100,23 @@ impl Default for ExecutionConfig {
             auto_issue_receipts: default_true(),
             anchor_receipts: default_true(),
             receipt_export_dir: default_receipt_export_dir(),
-            // max_execution_time_ms: 5000, // example
-            // max_memory_pages: 256,      // example (16MB)
+            limits: ResourceLimits::default(),
+            scope_limits: HashMap::new(),
+            trace_host_calls: false,
+            module_allowlist: HashMap::new(),
         }
     }
 }
 
+impl ExecutionConfig {
+    /// Resource limits that apply to an execution in the given scope.
+    pub fn limits_for(&self, scope: Option<&ScopeType>) -> &ResourceLimits {
+        scope
+            .and_then(|scope| self.scope_limits.get(scope))
+            .unwrap_or(&self.limits)
+    }
+}
+
 /// Main runtime configuration, potentially encompassing more than just execution.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RuntimeConfig {
@@ -50,6 +125,9 @@ pub struct RuntimeConfig {
 
     #[serde(default = "default_dag_index_path")]
     pub dag_index_path: PathBuf,
+
+    #[serde(default)]
+    pub daemon: DaemonConfig,
     // pub networking: NetworkingConfig, // Example for other configs
     // pub storage: StorageConfig,       // Example for other configs
 }
@@ -58,6 +136,45 @@ fn default_dag_index_path() -> PathBuf {
     PathBuf::from("runtime_data/dag_index") // Default path for the index
 }
 
+/// Configuration of the long-running runtime service.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonConfig {
+    /// Address the local job API listens on. Keep it on loopback: the API is not
+    /// authenticated.
+    #[serde(default = "default_listen_addr")]
+    pub listen_addr: SocketAddr,
+
+    /// Directory of the persistent job queue.
+    #[serde(default = "default_queue_path")]
+    pub queue_path: PathBuf,
+
+    /// Maximum number of jobs executing at the same time.
+    #[serde(default = "default_max_concurrent_jobs")]
+    pub max_concurrent_jobs: usize,
+}
+
+fn default_listen_addr() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 7420))
+}
+
+fn default_queue_path() -> PathBuf {
+    PathBuf::from("runtime_data/jobs")
+}
+
+fn default_max_concurrent_jobs() -> usize {
+    4
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            listen_addr: default_listen_addr(),
+            queue_path: default_queue_path(),
+            max_concurrent_jobs: default_max_concurrent_jobs(),
+        }
+    }
+}
+
 // Example for other config structs if needed in the future
 // #[derive(Debug, Clone, Serialize, Deserialize)]
 // pub struct NetworkingConfig { /* ... */ }
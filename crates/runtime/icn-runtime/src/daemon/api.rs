@@ -0,0 +1,165 @@
+//! Local HTTP API of the runtime daemon.
+//!
+//! | Method | Path                | Body / response                                   |
+//! |--------|---------------------|---------------------------------------------------|
+//! | POST   | `/jobs`             | `SubmitRequest` → `JobRecord`                     |
+//! | GET    | `/jobs`             | all `JobRecord`s, oldest first                    |
+//! | GET    | `/jobs/{id}`        | `JobRecord`                                       |
+//! | POST   | `/jobs/{id}/cancel` | `JobRecord` of the canceled job                   |
+//! | GET    | `/jobs/{id}/result` | raw output bytes of a completed job               |
+//!
+//! Errors are returned as `{"error": "..."}`. The API is unauthenticated and meant
+//! to listen on loopback only. Request bodies over `MAX_REQUEST_BYTES` are refused
+//! with 413.
+
+use super::{DaemonError, QueueError, RuntimeDaemon};
+use crate::abi::context::HostContext;
+use crate::engine::ContextExtension;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use hyper::body::HttpBody;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// Largest request body accepted, enough for a 24 MiB module once base64 encoded
+pub const MAX_REQUEST_BYTES: usize = 32 * 1024 * 1024;
+
+/// Body of a job submission
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitRequest {
+    /// WASM module, base64 encoded
+    pub module: String,
+    /// Input passed to the module, base64 encoded
+    #[serde(default)]
+    pub input: String,
+    #[serde(default)]
+    pub fuel_limit: Option<u64>,
+}
+
+/// Serve the daemon's API on `addr` until the server fails
+pub async fn serve<T>(daemon: Arc<RuntimeDaemon<T>>, addr: SocketAddr) -> Result<(), hyper::Error>
+where
+    T: HostContext + ContextExtension + Send + Sync + 'static,
+{
+    let make_service = make_service_fn(move |_| {
+        let daemon = daemon.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let daemon = daemon.clone();
+                async move { Ok::<_, Infallible>(handle(&daemon, req).await) }
+            }))
+        }
+    });
+    let server = Server::try_bind(&addr)?.serve(make_service);
+    info!("Runtime API listening on {}", addr);
+    server.await
+}
+
+/// Route a single request
+pub async fn handle<T>(daemon: &RuntimeDaemon<T>, req: Request<Body>) -> Response<Body>
+where
+    T: HostContext + ContextExtension + Send + Sync + 'static,
+{
+    let segments: Vec<String> = req
+        .uri()
+        .path()
+        .trim_matches('/')
+        .split('/')
+        .map(str::to_string)
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+    match (req.method().clone(), segments.as_slice()) {
+        (Method::POST, ["jobs"]) => {
+            let body = match read_body(req.into_body()).await {
+                Ok(body) => body,
+                Err(response) => return response,
+            };
+            let (module, input, fuel_limit) = match decode_submission(&body) {
+                Ok(submission) => submission,
+                Err(message) => return error(StatusCode::BAD_REQUEST, message),
+            };
+            reply(daemon.submit(module, input, fuel_limit), StatusCode::CREATED)
+        }
+        (Method::GET, ["jobs"]) => reply(daemon.list(), StatusCode::OK),
+        (Method::GET, ["jobs", id]) => reply(daemon.status(id), StatusCode::OK),
+        (Method::POST, ["jobs", id, "cancel"]) => reply(daemon.cancel(id), StatusCode::OK),
+        (Method::GET, ["jobs", id, "result"]) => match daemon.result(id) {
+            Ok(output) => Response::builder()
+                .header(header::CONTENT_TYPE, "application/octet-stream")
+                .body(Body::from(output))
+                .unwrap(),
+            Err(e) => daemon_error(e),
+        },
+        _ => error(StatusCode::NOT_FOUND, format!("No route for {} {}", req.method(), req.uri().path())),
+    }
+}
+
+/// Read a request body, stopping as soon as it exceeds `MAX_REQUEST_BYTES`
+async fn read_body(mut body: Body) -> Result<Vec<u8>, Response<Body>> {
+    let too_large = || {
+        error(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("Request body exceeds {} bytes", MAX_REQUEST_BYTES),
+        )
+    };
+    if body.size_hint().lower() > MAX_REQUEST_BYTES as u64 {
+        return Err(too_large());
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| error(StatusCode::BAD_REQUEST, e.to_string()))?;
+        if bytes.len() + chunk.len() > MAX_REQUEST_BYTES {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+fn decode_submission(body: &[u8]) -> Result<(Vec<u8>, Vec<u8>, Option<u64>), String> {
+    let request: SubmitRequest = serde_json::from_slice(body).map_err(|e| e.to_string())?;
+    let module = STANDARD
+        .decode(&request.module)
+        .map_err(|e| format!("Invalid module encoding: {}", e))?;
+    let input = STANDARD
+        .decode(&request.input)
+        .map_err(|e| format!("Invalid input encoding: {}", e))?;
+    Ok((module, input, request.fuel_limit))
+}
+
+fn reply<S: Serialize>(result: Result<S, DaemonError>, status: StatusCode) -> Response<Body> {
+    match result {
+        Ok(value) => match serde_json::to_vec(&value) {
+            Ok(body) => json(status, body),
+            Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        },
+        Err(e) => daemon_error(e),
+    }
+}
+
+fn daemon_error(e: DaemonError) -> Response<Body> {
+    let status = match &e {
+        DaemonError::Queue(QueueError::NotFound(_)) => StatusCode::NOT_FOUND,
+        DaemonError::AlreadyFinished(_) | DaemonError::NotCompleted(_) => StatusCode::CONFLICT,
+        DaemonError::InvalidModule(_) => StatusCode::BAD_REQUEST,
+        DaemonError::Queue(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    error(status, e.to_string())
+}
+
+fn error(status: StatusCode, message: String) -> Response<Body> {
+    json(status, serde_json::json!({ "error": message }).to_string().into_bytes())
+}
+
+fn json(status: StatusCode, body: Vec<u8>) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
@@ -0,0 +1,206 @@
+//! Long-running runtime service.
+//!
+//! A `RuntimeDaemon` takes jobs from a persistent `JobQueue` and runs up to
+//! `max_concurrent_jobs` of them at a time on one shared executor and host context.
+//! Job state is tracked with `planetary_mesh::JobStatus`, so a mesh worker node can
+//! report it as is. `api::serve` exposes submit, status, cancel and result over
+//! local HTTP.
+
+pub mod api;
+pub mod queue;
+
+pub use queue::{JobPayload, JobQueue, JobRecord, QueueError};
+
+use queue::unix_ts;
+
+use crate::abi::context::HostContext;
+use crate::config::DaemonConfig;
+use crate::engine::{ContextExtension, ModernWasmExecutor};
+use icn_types::Cid;
+use log::{info, warn};
+use planetary_mesh::JobStatus;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+use tokio::task::AbortHandle;
+
+#[derive(Error, Debug)]
+pub enum DaemonError {
+    #[error("Queue error: {0}")]
+    Queue(#[from] QueueError),
+    #[error("Job {0} has already finished")]
+    AlreadyFinished(String),
+    #[error("Job {0} has not completed")]
+    NotCompleted(String),
+    #[error("Invalid module: {0}")]
+    InvalidModule(String),
+}
+
+/// Runs queued jobs with a bounded number of concurrent executions
+pub struct RuntimeDaemon<T> {
+    executor: Arc<ModernWasmExecutor>,
+    ctx: Arc<T>,
+    queue: JobQueue,
+    permits: Arc<Semaphore>,
+    // Abort handles of running jobs; also serializes status changes between the
+    // scheduler, finishing jobs and cancellations
+    running: Mutex<HashMap<String, AbortHandle>>,
+    submitted: Notify,
+}
+
+impl<T> RuntimeDaemon<T>
+where
+    T: HostContext + ContextExtension + Send + Sync + 'static,
+{
+    /// Open the job queue at `config.queue_path`. Jobs start once `run` is called.
+    pub fn new(
+        config: &DaemonConfig,
+        executor: Arc<ModernWasmExecutor>,
+        ctx: Arc<T>,
+    ) -> Result<Arc<Self>, DaemonError> {
+        Ok(Arc::new(Self {
+            executor,
+            ctx,
+            queue: JobQueue::open(&config.queue_path)?,
+            permits: Arc::new(Semaphore::new(config.max_concurrent_jobs.max(1))),
+            running: Mutex::new(HashMap::new()),
+            submitted: Notify::new(),
+        }))
+    }
+
+    /// Queue a module for execution
+    pub fn submit(
+        &self,
+        module: Vec<u8>,
+        input: Vec<u8>,
+        fuel_limit: Option<u64>,
+    ) -> Result<JobRecord, DaemonError> {
+        let module_cid = Cid::from_bytes(&module)
+            .map_err(|e| DaemonError::InvalidModule(e.to_string()))?;
+        let record = self
+            .queue
+            .enqueue(module_cid.to_string(), &JobPayload { module, input }, fuel_limit)?;
+        info!("Queued job {} for module {}", record.id, record.module_cid);
+        self.submitted.notify_one();
+        Ok(record)
+    }
+
+    pub fn status(&self, id: &str) -> Result<JobRecord, DaemonError> {
+        Ok(self.queue.get(id)?)
+    }
+
+    /// All jobs known to the daemon, oldest first
+    pub fn list(&self) -> Result<Vec<JobRecord>, DaemonError> {
+        Ok(self.queue.list()?)
+    }
+
+    /// Cancel a queued or running job. A running job is dropped at its next
+    /// suspension point and whatever it produces afterwards is discarded.
+    pub fn cancel(&self, id: &str) -> Result<JobRecord, DaemonError> {
+        let mut running = self.running.lock().unwrap();
+        let record = self.queue.get(id)?;
+        if record.is_finished() {
+            return Err(DaemonError::AlreadyFinished(id.to_string()));
+        }
+        if let Some(handle) = running.remove(id) {
+            handle.abort();
+        }
+        let record = JobRecord { status: JobStatus::Canceled, finished_at: Some(unix_ts()), ..record };
+        self.queue.update(&record)?;
+        info!("Canceled job {}", id);
+        Ok(record)
+    }
+
+    /// Output of a completed job
+    pub fn result(&self, id: &str) -> Result<Vec<u8>, DaemonError> {
+        let record = self.queue.get(id)?;
+        if record.status != JobStatus::Completed {
+            return Err(DaemonError::NotCompleted(id.to_string()));
+        }
+        Ok(self.queue.result(id)?.unwrap_or_default())
+    }
+
+    /// Start queued jobs as execution slots free up. Runs until the queue fails.
+    pub async fn run(self: Arc<Self>) -> Result<(), DaemonError> {
+        loop {
+            let permit = self.permits.clone().acquire_owned().await.expect("semaphore is never closed");
+            let (record, payload) = loop {
+                let submitted = self.submitted.notified();
+                if let Some(job) = self.queue.next_pending()? {
+                    break job;
+                }
+                submitted.await;
+            };
+            self.start(record, payload, permit)?;
+        }
+    }
+
+    fn start(
+        self: &Arc<Self>,
+        record: JobRecord,
+        payload: JobPayload,
+        permit: OwnedSemaphorePermit,
+    ) -> Result<(), DaemonError> {
+        let mut running = self.running.lock().unwrap();
+        // The job may have been canceled since it was taken off the queue
+        let record = self.queue.get(&record.id)?;
+        if record.status != JobStatus::Submitted {
+            return Ok(());
+        }
+        let record = JobRecord { status: JobStatus::Running, started_at: Some(unix_ts()), ..record };
+        self.queue.update(&record)?;
+
+        let daemon = self.clone();
+        let id = record.id.clone();
+        let task = tokio::spawn(async move {
+            let _permit = permit;
+            let outcome = daemon.execute(&payload, record.fuel_limit).await;
+            daemon.finish(record, outcome);
+        });
+        running.insert(id, task.abort_handle());
+        Ok(())
+    }
+
+    async fn execute(&self, payload: &JobPayload, fuel_limit: Option<u64>) -> anyhow::Result<crate::ExecutionResult> {
+        let module_cid = Cid::from_bytes(&payload.module)?;
+        self.executor
+            .execute(&payload.module, self.ctx.clone(), module_cid, None, Some(&payload.input), fuel_limit)
+            .await
+    }
+
+    fn finish(&self, record: JobRecord, outcome: anyhow::Result<crate::ExecutionResult>) {
+        let mut running = self.running.lock().unwrap();
+        if running.remove(&record.id).is_none() {
+            // Canceled while finishing
+            return;
+        }
+        let record = match outcome {
+            Ok(result) => {
+                if let Err(e) = self.queue.store_result(&record.id, &result.output) {
+                    warn!("Failed to store output of job {}: {}", record.id, e);
+                }
+                info!("Job {} completed with result {}", record.id, result.result_cid);
+                JobRecord {
+                    status: JobStatus::Completed,
+                    result_cid: Some(result.result_cid.to_string()),
+                    fuel_consumed: result.fuel_consumed,
+                    finished_at: Some(unix_ts()),
+                    ..record
+                }
+            }
+            Err(e) => {
+                warn!("Job {} failed: {:#}", record.id, e);
+                JobRecord {
+                    status: JobStatus::Failed,
+                    error: Some(format!("{:#}", e)),
+                    finished_at: Some(unix_ts()),
+                    ..record
+                }
+            }
+        };
+        if let Err(e) = self.queue.update(&record) {
+            warn!("Failed to record the outcome of job {}: {}", record.id, e);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Persistent job queue of the runtime daemon.
+//!
+//! Jobs live in a sled database with three trees: `jobs` holds the `JobRecord`
+//! of every job, `payloads` the module and input of jobs that have not finished,
+//! and `pending` the IDs of jobs waiting to run, keyed by submission order.
+//! `results` holds the output of completed jobs.
+
+use planetary_mesh::JobStatus;
+use serde::{Deserialize, Serialize};
+use sled::{Db, Tree};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum QueueError {
+    #[error("Sled DB error: {0}")]
+    Sled(#[from] sled::Error),
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] bincode::Error),
+    #[error("Job {0} not found")]
+    NotFound(String),
+}
+
+/// State of a job as reported by the daemon's API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub id: String,
+    /// CID of the job's module
+    pub module_cid: String,
+    pub status: JobStatus,
+    /// Fuel budget requested by the submitter; the configured limit if `None`
+    pub fuel_limit: Option<u64>,
+    /// Unix timestamps of submission, start and end
+    pub submitted_at: u64,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+    /// CID of the output, once the job completed
+    pub result_cid: Option<String>,
+    pub fuel_consumed: Option<u64>,
+    /// Why the job failed
+    pub error: Option<String>,
+}
+
+impl JobRecord {
+    /// Whether the job has reached a final state
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status, JobStatus::Completed | JobStatus::Failed | JobStatus::Canceled)
+    }
+}
+
+/// Module and input of a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobPayload {
+    pub module: Vec<u8>,
+    pub input: Vec<u8>,
+}
+
+/// Job queue persisted in a sled database
+pub struct JobQueue {
+    db: Db,
+    jobs: Tree,
+    payloads: Tree,
+    pending: Tree,
+    results: Tree,
+}
+
+impl JobQueue {
+    /// Open the queue at `path`, putting jobs that were running when the daemon
+    /// stopped back in line
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, QueueError> {
+        let db = sled::open(path)?;
+        let queue = Self {
+            jobs: db.open_tree("jobs")?,
+            payloads: db.open_tree("payloads")?,
+            pending: db.open_tree("pending")?,
+            results: db.open_tree("results")?,
+            db,
+        };
+        queue.recover()?;
+        Ok(queue)
+    }
+
+    /// Record a new job and put it at the back of the line
+    pub fn enqueue(
+        &self,
+        module_cid: String,
+        payload: &JobPayload,
+        fuel_limit: Option<u64>,
+    ) -> Result<JobRecord, QueueError> {
+        let record = JobRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            module_cid,
+            status: JobStatus::Submitted,
+            fuel_limit,
+            submitted_at: unix_ts(),
+            started_at: None,
+            finished_at: None,
+            result_cid: None,
+            fuel_consumed: None,
+            error: None,
+        };
+        self.payloads.insert(record.id.as_bytes(), bincode::serialize(payload)?)?;
+        self.put(&record)?;
+        self.push_pending(&record.id)?;
+        self.db.flush()?;
+        Ok(record)
+    }
+
+    /// Take the oldest job still waiting to run, skipping jobs canceled meanwhile
+    pub fn next_pending(&self) -> Result<Option<(JobRecord, JobPayload)>, QueueError> {
+        while let Some((_, id)) = self.pending.pop_min()? {
+            let id = String::from_utf8_lossy(&id).to_string();
+            let record = match self.get(&id) {
+                Ok(record) => record,
+                Err(QueueError::NotFound(_)) => continue,
+                Err(e) => return Err(e),
+            };
+            if record.status != JobStatus::Submitted {
+                continue;
+            }
+            match self.payloads.get(id.as_bytes())? {
+                Some(payload) => return Ok(Some((record, bincode::deserialize(&payload)?))),
+                None => continue,
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn get(&self, id: &str) -> Result<JobRecord, QueueError> {
+        match self.jobs.get(id.as_bytes())? {
+            Some(bytes) => Ok(bincode::deserialize(&bytes)?),
+            None => Err(QueueError::NotFound(id.to_string())),
+        }
+    }
+
+    /// Store an updated record; payloads of finished jobs are dropped
+    pub fn update(&self, record: &JobRecord) -> Result<(), QueueError> {
+        self.put(record)?;
+        if record.is_finished() {
+            self.payloads.remove(record.id.as_bytes())?;
+        }
+        self.db.flush()?;
+        Ok(())
+    }
+
+    /// All jobs, oldest first
+    pub fn list(&self) -> Result<Vec<JobRecord>, QueueError> {
+        let mut records = self
+            .jobs
+            .iter()
+            .values()
+            .map(|bytes| Ok(bincode::deserialize(&bytes?)?))
+            .collect::<Result<Vec<JobRecord>, QueueError>>()?;
+        records.sort_by_key(|record| record.submitted_at);
+        Ok(records)
+    }
+
+    pub fn store_result(&self, id: &str, output: &[u8]) -> Result<(), QueueError> {
+        self.results.insert(id.as_bytes(), output)?;
+        Ok(())
+    }
+
+    /// Output of a completed job
+    pub fn result(&self, id: &str) -> Result<Option<Vec<u8>>, QueueError> {
+        Ok(self.results.get(id.as_bytes())?.map(|output| output.to_vec()))
+    }
+
+    fn put(&self, record: &JobRecord) -> Result<(), QueueError> {
+        self.jobs.insert(record.id.as_bytes(), bincode::serialize(record)?)?;
+        Ok(())
+    }
+
+    fn push_pending(&self, id: &str) -> Result<(), QueueError> {
+        let position = self.db.generate_id()?;
+        self.pending.insert(position.to_be_bytes(), id.as_bytes())?;
+        Ok(())
+    }
+
+    fn recover(&self) -> Result<(), QueueError> {
+        for record in self.list()? {
+            if record.status == JobStatus::Running {
+                log::warn!("Requeueing job {} interrupted by a restart", record.id);
+                self.put(&JobRecord { status: JobStatus::Submitted, started_at: None, ..record.clone() })?;
+                self.push_pending(&record.id)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn unix_ts() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
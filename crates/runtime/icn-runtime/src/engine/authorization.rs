@@ -0,0 +1,36 @@
+//! Authorization of module executions.
+//!
+//! Before a module runs, the caller must be granted `execute_module` by the policy
+//! of the execution's scope, and if `ExecutionConfig::module_allowlist` has an entry
+//! for the scope, the module's CID must be on it. Executions without a policy
+//! loader skip the policy check, like host calls do.
+
+use crate::abi::context::HostContext;
+use crate::engine::ContextExtension;
+use crate::host::guest::HostScope;
+use icn_types::Cid;
+
+/// Policy action checked before a module is executed
+pub const EXECUTE_MODULE_ACTION: &str = "execute_module";
+
+/// Check that the context's caller may run `module_cid` in the execution's scope,
+/// returning why not otherwise
+pub fn authorize_execution<T>(ctx: &T, module_cid: &Cid) -> Result<(), String>
+where
+    T: HostContext + ContextExtension,
+{
+    let scope = HostScope::of(ctx);
+    if let Some(allowed) = ctx.get_execution_config().module_allowlist.get(scope.policy_id()) {
+        let module = module_cid.to_string();
+        if !allowed.iter().any(|cid| *cid == module) {
+            return Err(format!("module {} is not allowed in scope {}", module, scope.policy_id()));
+        }
+    }
+    if let Some(policy_loader) = HostContext::policy_loader(ctx) {
+        let caller = ctx.get_caller_did();
+        policy_loader
+            .check_authorization(&format!("{:?}", scope.scope), scope.policy_id(), EXECUTE_MODULE_ACTION, &caller)
+            .map_err(|e| format!("{} may not execute modules in scope {}: {}", caller, scope.policy_id(), e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+//! Clock and randomness sources behind the `clock_now_ms` and `random_bytes` host
+//! functions.
+//!
+//! A deterministic executor seeds both from the execution itself, so any node
+//! running the same module on the same input observes the same values, produces the
+//! same output and consumes the same fuel.
+
+use icn_types::Cid;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Milliseconds the seeded clock advances on every read
+pub const SEEDED_CLOCK_TICK_MS: u64 = 1;
+
+/// Environment shared by the host functions and WASI of one execution
+pub type SharedEnvironment = Arc<Mutex<HostEnvironment>>;
+
+/// Where an execution's clock and randomness come from
+pub enum HostEnvironment {
+    /// Wall-clock time and operating system randomness
+    System,
+    /// A logical clock starting at zero and a ChaCha20 stream seeded by the execution
+    Seeded { clock_ms: u64, rng: Box<ChaCha20Rng> },
+}
+
+impl HostEnvironment {
+    /// Environment seeded by the module and its input
+    pub fn seeded(module_cid: &Cid, input: &[u8]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(module_cid.to_bytes());
+        hasher.update(Sha256::digest(input));
+        let seed: [u8; 32] = hasher.finalize().into();
+        HostEnvironment::Seeded { clock_ms: 0, rng: Box::new(ChaCha20Rng::from_seed(seed)) }
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        matches!(self, HostEnvironment::Seeded { .. })
+    }
+
+    /// Current time in milliseconds since the Unix epoch, or on the seeded clock
+    pub fn now_ms(&mut self) -> u64 {
+        match self {
+            HostEnvironment::System => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis() as u64)
+                .unwrap_or_default(),
+            HostEnvironment::Seeded { clock_ms, .. } => {
+                *clock_ms += SEEDED_CLOCK_TICK_MS;
+                *clock_ms
+            }
+        }
+    }
+
+    /// Fill `bytes` with random data
+    pub fn fill_random(&mut self, bytes: &mut [u8]) {
+        match self {
+            HostEnvironment::System => rand::thread_rng().fill_bytes(bytes),
+            HostEnvironment::Seeded { rng, .. } => rng.fill_bytes(bytes),
+        }
+    }
+}
@@ -1,10 +1,24 @@
 use wasmtime::*;
 use anyhow::{Context, Result};
 use crate::config::ExecutionConfig;
-use crate::host::receipt::issue_execution_receipt;
+use crate::host::receipt::{issue_execution_receipt, ReceiptProperties};
 use crate::abi::bindings::register_host_functions;
+use crate::abi::HOST_ABI_VERSION;
+use crate::block_store::block_cid;
+use crate::engine::StoreData;
+use crate::engine::authorization::authorize_execution;
+use crate::engine::determinism::HostEnvironment;
+use crate::engine::journal::CommittedEffects;
+use crate::engine::trace::ExecutionTrace;
+use crate::engine::limits::{epoch_ticks, EpochTicker, ExecutionLimiter, LimitExceeded};
+use crate::engine::module_cache::{ModuleCache, DEFAULT_MODULE_CACHE_CAPACITY};
+use crate::engine::validation::{self, ValidationIssue, ValidationPolicy, ValidationReport, ENTRY_POINTS};
+use icn_identity_core::vc::execution_receipt::ExecutionStatus;
+use thiserror::Error;
 use icn_types::{Cid, dag::EventId, Did};
 use icn_identity_core::did::DidKey;
+use icn_economics::ResourceTransaction;
+use sha2::{Digest, Sha256};
 use std::path::Path;
 use std::fs;
 use std::sync::Arc;
@@ -20,8 +34,57 @@ pub struct ExecutionResult {
     pub execution_time_ms: u64,
     /// Fuel consumed (if metering was enabled)
     pub fuel_consumed: Option<u64>,
-    /// CID of the result data
+    /// CID of the result data (raw codec over `output`)
     pub result_cid: Cid,
+    /// Bytes the module wrote through `write_output`
+    pub output: Vec<u8>,
+    /// CID of the input data (raw codec)
+    pub input_cid: Cid,
+    /// Whether the execution ran under the deterministic profile
+    pub deterministic: bool,
+    /// Largest linear memory the module grew to, in bytes
+    pub peak_memory_bytes: u64,
+    /// Hash of the executor settings the module ran under (see `config_hash`)
+    pub config_hash: String,
+    /// CIDs of the side effects committed by host calls, in call order
+    pub side_effects: Vec<Cid>,
+    /// Ledger transactions the module applied through the token host functions
+    pub transactions: Vec<ResourceTransaction>,
+    /// Logs the module wrote and, if enabled, the host calls it made
+    pub trace: ExecutionTrace,
+    /// CID of the stored trace; `None` if the module neither logged nor was traced
+    pub log_cid: Option<Cid>,
+}
+
+/// A policy denial or resource limit violation that stopped an execution.
+///
+/// Returned (wrapped in `anyhow::Error`) by `ModernWasmExecutor::execute`; callers
+/// can recover it with `downcast_ref::<ExecutionError>()`.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionError {
+    #[error("Execution ran out of fuel after {0} units")]
+    OutOfFuel(u64),
+    #[error("Execution exceeded its time limit of {0} ms")]
+    Timeout(u64),
+    #[error("Execution exceeded its memory limit of {0} pages")]
+    MemoryLimit(u32),
+    #[error("Execution exceeded its table limit of {0} elements")]
+    TableLimit(u32),
+    #[error("Execution denied: {0}")]
+    Denied(String),
+}
+
+impl ExecutionError {
+    /// Receipt status recording this outcome
+    pub fn status(&self) -> ExecutionStatus {
+        match self {
+            ExecutionError::OutOfFuel(_) => ExecutionStatus::OutOfFuel,
+            ExecutionError::Timeout(_) => ExecutionStatus::Timeout,
+            ExecutionError::MemoryLimit(_) => ExecutionStatus::MemoryLimit,
+            ExecutionError::TableLimit(_) => ExecutionStatus::TableLimit,
+            ExecutionError::Denied(_) => ExecutionStatus::Denied,
+        }
+    }
 }
 
 /// Context extension trait for WASM execution
@@ -49,6 +112,21 @@ pub trait ContextExtension {
     
     /// Get policy loader if available
     fn policy_loader(&self) -> Option<std::sync::Arc<dyn crate::policy::PolicyLoader + Send + Sync>> { None }
+    
+    /// Get block store for execution outputs if available
+    fn block_store(&self) -> Option<std::sync::Arc<dyn crate::block_store::BlockStore + Send + Sync>> { None }
+    
+    /// Get the scope the execution runs in, used to pick resource limits
+    fn execution_scope(&self) -> Option<crate::policy::ScopeType> { None }
+    
+    /// Get the ID of the cooperative or community the execution runs in
+    fn execution_scope_id(&self) -> Option<String> { None }
+    
+    /// Get the shared DAG store backing the DAG host functions if available
+    fn dag_store(&self) -> Option<icn_types::dag::SharedDagStore> { None }
+    
+    /// Get the token store backing the ledger host functions if available
+    fn token_store(&self) -> Option<std::sync::Arc<dyn icn_economics::TokenStore>> { None }
 }
 
 // Implement ContextExtension for Arc<T> where T: ContextExtension
@@ -84,23 +162,107 @@ impl<T: ContextExtension + ?Sized> ContextExtension for Arc<T> {
     fn policy_loader(&self) -> Option<std::sync::Arc<dyn crate::policy::PolicyLoader + Send + Sync>> {
         (**self).policy_loader()
     }
+    
+    fn block_store(&self) -> Option<std::sync::Arc<dyn crate::block_store::BlockStore + Send + Sync>> {
+        (**self).block_store()
+    }
+    
+    fn execution_scope(&self) -> Option<crate::policy::ScopeType> {
+        (**self).execution_scope()
+    }
+    
+    fn execution_scope_id(&self) -> Option<String> {
+        (**self).execution_scope_id()
+    }
+    
+    fn dag_store(&self) -> Option<icn_types::dag::SharedDagStore> {
+        (**self).dag_store()
+    }
+    
+    fn token_store(&self) -> Option<std::sync::Arc<dyn icn_economics::TokenStore>> {
+        (**self).token_store()
+    }
 }
 
 /// Executes WASM modules and provides resource usage metrics
 pub struct ModernWasmExecutor {
     engine: Engine,
+    // Advances the engine epoch that execution timeouts are measured in
+    _epoch_ticker: EpochTicker,
+    deterministic: bool,
+    modules: ModuleCache,
+    #[cfg(feature = "wasi")]
+    wasi: Option<crate::engine::wasi::WasiConfig>,
 }
 
 impl ModernWasmExecutor {
     /// Create a new WASM executor
     pub fn new() -> Result<Self> {
+        Self::with_profile(false)
+    }
+    
+    /// Create an executor whose results other nodes can reproduce.
+    ///
+    /// NaNs are canonicalized, relaxed SIMD and threads are disabled, and modules see
+    /// a seeded clock and randomness, so the same module and input always give the
+    /// same output and fuel consumption. Receipts record the determinism flag and
+    /// the input CID, and the input is kept in the block store for re-execution.
+    pub fn deterministic() -> Result<Self> {
+        Self::with_profile(true)
+    }
+    
+    fn with_profile(deterministic: bool) -> Result<Self> {
         let mut config = Config::new();
         config.async_support(true);
         config.consume_fuel(true); // Enable fuel for execution metering
+        config.epoch_interruption(true); // Enable wall-clock timeouts
+        if deterministic {
+            config.cranelift_nan_canonicalization(true);
+            config.wasm_relaxed_simd(false);
+            config.relaxed_simd_deterministic(true);
+            config.wasm_threads(false);
+        }
         
         let engine = Engine::new(&config)?;
+        let epoch_ticker = EpochTicker::start(engine.clone());
+        let modules = ModuleCache::new(engine.clone(), DEFAULT_MODULE_CACHE_CAPACITY, None);
         
-        Ok(Self { engine })
+        Ok(Self {
+            engine,
+            _epoch_ticker: epoch_ticker,
+            deterministic,
+            modules,
+            #[cfg(feature = "wasi")]
+            wasi: None,
+        })
+    }
+    
+    /// Keep up to `capacity` compiled modules in memory and, if `cache_dir` is
+    /// given, persist precompiled artifacts there for later runs and restarts
+    pub fn with_module_cache(mut self, capacity: usize, cache_dir: Option<&Path>) -> Self {
+        self.modules = ModuleCache::new(self.engine.clone(), capacity, cache_dir);
+        self
+    }
+    
+    /// Compiled modules of this executor, keyed by module CID
+    pub fn module_cache(&self) -> &ModuleCache {
+        &self.modules
+    }
+    
+    /// Link WASI preview 1 so modules built for WASI can run.
+    ///
+    /// Every execution gets its own sandbox: a scratch directory under
+    /// `config.scratch_root` preopened at `/scratch`, no network and no environment.
+    /// stdout is appended to the execution output and stderr is logged.
+    #[cfg(feature = "wasi")]
+    pub fn with_wasi(mut self, config: crate::engine::wasi::WasiConfig) -> Self {
+        self.wasi = Some(config);
+        self
+    }
+    
+    /// Whether this executor runs modules reproducibly
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
     }
     
     /// Load WASM module from a file path
@@ -111,21 +273,28 @@ impl ModernWasmExecutor {
         Ok(wasm_bytes)
     }
     
-    /// Validate a WASM module against ICN requirements
-    pub fn validate_module(&self, wasm_bytes: &[u8]) -> Result<bool> {
-        // Create a module and check for compatibility
-        let _ = Module::new(&self.engine, wasm_bytes)
-            .with_context(|| "Failed to validate WASM module")?;
-        
-        // TODO: Add additional validation specific to ICN requirements
-        // 1. Check for approved import namespaces
-        // 2. Verify module doesn't use restricted features
-        // 3. Enforce memory limits
-            
-        Ok(true)
+    /// Validate a WASM module against ICN requirements: import namespaces, enabled
+    /// features, declared memory and the presence of an entry point.
+    pub fn validate_module(&self, wasm_bytes: &[u8], policy: &ValidationPolicy) -> ValidationReport {
+        let mut report = validation::validate_module(wasm_bytes, policy);
+        // The policy checks pass on bytes wasmtime may still refuse to compile
+        if report.is_valid() {
+            if let Err(e) = Module::new(&self.engine, wasm_bytes) {
+                report.issues.push(ValidationIssue::InvalidModule { message: format!("{:#}", e) });
+            }
+        }
+        report
     }
     
-    /// Execute a WASM module with the given context and input
+    /// Execute a WASM module with the given context and input.
+    ///
+    /// `module_cid` must be the CID of `wasm_bytes`, and the caller must be
+    /// authorized to execute the module in the context's scope; a denied attempt
+    /// is recorded in a receipt and returned as `ExecutionError::Denied` without
+    /// running the module. Memory, table and time
+    /// limits come from the context's `ExecutionConfig` for its scope; `fuel_limit`
+    /// overrides the configured fuel budget. A violated limit is recorded in the
+    /// receipt and returned as an `ExecutionError`.
     pub async fn execute<T>(&self, 
         wasm_bytes: &[u8], 
         ctx: Arc<T>,
@@ -134,86 +303,309 @@ impl ModernWasmExecutor {
         input_data: Option<&[u8]>,
         fuel_limit: Option<u64>
     ) -> Result<ExecutionResult> 
+    where 
+        T: crate::abi::context::HostContext + ContextExtension + Send + Sync + 'static 
+    {
+        self.run(wasm_bytes, ctx, module_cid, event_id, input_data, fuel_limit, false).await
+    }
+    
+    /// Re-run a module to check a previous execution's result.
+    ///
+    /// The module sees the same host functions as under `execute`, but it is not
+    /// authorized again, the side effects it stages are discarded and no receipt is
+    /// issued. `module_cid` must still be the CID of `wasm_bytes`.
+    pub async fn replay<T>(&self, 
+        wasm_bytes: &[u8], 
+        ctx: Arc<T>,
+        module_cid: Cid,
+        input_data: Option<&[u8]>,
+        fuel_limit: Option<u64>
+    ) -> Result<ExecutionResult> 
+    where 
+        T: crate::abi::context::HostContext + ContextExtension + Send + Sync + 'static 
+    {
+        self.run(wasm_bytes, ctx, module_cid, None, input_data, fuel_limit, true).await
+    }
+    
+    #[allow(clippy::too_many_arguments)]
+    async fn run<T>(&self, 
+        wasm_bytes: &[u8], 
+        ctx: Arc<T>,
+        module_cid: Cid,
+        event_id: Option<EventId>,
+        input_data: Option<&[u8]>,
+        fuel_limit: Option<u64>,
+        replay: bool
+    ) -> Result<ExecutionResult> 
     where 
         T: crate::abi::context::HostContext + ContextExtension + Send + Sync + 'static 
     {
         let start_time = Instant::now();
         
-        // Create module from wasm bytes
-        let module = Module::new(&self.engine, wasm_bytes)
-            .with_context(|| "Failed to create WASM module")?;
+        // Allowlists, the module cache and receipts all trust `module_cid` to name these bytes
+        let actual_cid = block_cid(wasm_bytes)?;
+        if actual_cid != module_cid {
+            let reason = format!("module bytes have CID {}, not the claimed {}", actual_cid, module_cid);
+            if replay {
+                return Err(ExecutionError::Denied(reason).into());
+            }
+            return self.deny(ctx, module_cid, event_id, input_data, fuel_limit, reason, start_time).await;
+        }
+        
+        if !replay {
+            if let Err(reason) = authorize_execution(&*ctx, &module_cid) {
+                return self.deny(ctx, module_cid, event_id, input_data, fuel_limit, reason, start_time).await;
+            }
+        }
+        
+        // Reuse the compiled module if this CID ran before
+        let module = self.modules.get_or_compile(&module_cid, wasm_bytes)?;
         
         // Create linker and register host functions
         let mut linker = Linker::new(&self.engine);
         register_host_functions(&mut linker)?;
-        
-        // Create store with context
-        let mut store = Store::new(&self.engine, ctx);
-        
-        // Configure fuel if limit is specified
-        if let Some(limit) = fuel_limit {
-            store.add_fuel(limit)
-                .with_context(|| "Failed to add fuel to store")?;
+        #[cfg(feature = "wasi")]
+        if self.wasi.is_some() {
+            crate::engine::wasi::add_to_linker(&mut linker)?;
         }
         
-        // Instantiate module
-        let instance = linker.instantiate_async(&mut store, &module).await
-            .with_context(|| "Failed to instantiate WASM module")?;
+        // Resolve the resource limits for this execution's scope
+        let limits = ctx.get_execution_config().limits_for(ctx.execution_scope().as_ref()).clone();
+        let fuel = fuel_limit.unwrap_or(limits.max_fuel);
         
-        // Find entry point
-        let entry_func = self.find_entry_point(&instance, &mut store)?;
-        
-        // Set input data in the context if provided
-        if let Some(data) = input_data {
-            // This would ideally use a context method to set input data
-            // For now we're just demonstrating the flow
-            debug!("Setting input data of {} bytes", data.len());
-        }
+        // Create store with context, the input the module can read and its limits
+        let input = input_data.map(<[u8]>::to_vec).unwrap_or_default();
+        debug!("Setting input data of {} bytes", input.len());
+        let input_cid = block_cid(&input)?;
+        let environment = if self.deterministic {
+            // Re-executions need the module and input, so keep them alongside the output
+            match ctx.block_store() {
+                Some(block_store) if !replay => {
+                    block_store.put(wasm_bytes).with_context(|| "Failed to store execution module")?;
+                    block_store.put(&input).with_context(|| "Failed to store execution input")?;
+                }
+                Some(_) => {}
+                None => warn!("Block store not available. Execution module and input not persisted."),
+            }
+            HostEnvironment::seeded(&module_cid, &input)
+        } else {
+            HostEnvironment::System
+        };
+        let trace = ExecutionTrace::new(ctx.get_execution_config().trace_host_calls);
+        let mut store = Store::new(
+            &self.engine,
+            StoreData::new(ctx, input, ExecutionLimiter::new(&limits), environment, trace),
+        );
+        store.limiter(|data| &mut data.limiter);
+        store.add_fuel(fuel)
+            .with_context(|| "Failed to add fuel to store")?;
+        store.set_epoch_deadline(epoch_ticks(limits.max_execution_time_ms));
+        #[cfg(feature = "wasi")]
+        let sandbox = match &self.wasi {
+            Some(config) => {
+                let environment = store.data().environment.clone();
+                let (sandbox, wasi) = crate::engine::wasi::WasiSandbox::create(config, &module_cid, &environment)?;
+                store.data_mut().wasi = Some(wasi);
+                Some(sandbox)
+            }
+            None => None,
+        };
         
-        // Execute the WASM module
+        // Instantiate module and run its entry point
         debug!("Executing WASM module...");
-        entry_func.call_async(&mut store, ()).await
-            .with_context(|| "Failed to execute WASM module")?;
-        
-        // Get fuel consumption if enabled
-        let fuel_consumed = if fuel_limit.is_some() {
-            // store.fuel_consumed() returns Option<u64> directly
-            store.fuel_consumed()
+        let outcome = match linker.instantiate_async(&mut store, &module).await {
+            Ok(instance) => match self.find_entry_point(&instance, &mut store) {
+                Ok(entry_func) => entry_func.call_async(&mut store, ()).await
+                    .with_context(|| "Failed to execute WASM module"),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e).with_context(|| "Failed to instantiate WASM module"),
+        };
+        // A WASI module exiting with status 0 completed normally
+        #[cfg(feature = "wasi")]
+        let outcome = match outcome {
+            Err(e) => match crate::engine::wasi::exit_status(&e) {
+                Some(0) => Ok(()),
+                Some(status) => Err(e).with_context(|| format!("WASM module exited with status {}", status)),
+                None => Err(e),
+            },
+            ok => ok,
+        };
+        // Host call side effects only take effect if the module ran to completion
+        let journal = std::mem::take(&mut store.data_mut().journal);
+        if outcome.is_err() && !journal.is_empty() {
+            warn!("Discarding {} side effects staged by the failed execution", journal.effects().len());
+        }
+        let violation = match outcome {
+            Ok(()) => None,
+            Err(e) => match Self::limit_violation(&e, store.data().limiter.exceeded(), &limits, fuel) {
+                Some(violation) => {
+                    warn!("WASM execution stopped: {}", violation);
+                    Some(violation)
+                }
+                None => return Err(e),
+            },
+        };
+        let committed = if violation.is_none() && !replay {
+            journal.commit(&*store.data().ctx.clone()).await
+                .with_context(|| "Failed to commit execution side effects")?
         } else {
-            None
+            CommittedEffects::default()
         };
         
+        // Get fuel consumption
+        let fuel_consumed = store.fuel_consumed();
+        
         // Compute execution time
         let execution_time = start_time.elapsed();
         info!("WASM module executed in {:?}", execution_time);
         
+        // Content-address the output and keep it so the receipt's result CID can be resolved
+        #[allow(unused_mut)]
+        let mut output = std::mem::take(&mut store.data_mut().output);
+        #[cfg(feature = "wasi")]
+        if let Some(sandbox) = &sandbox {
+            let stderr = sandbox.stderr();
+            for line in String::from_utf8_lossy(&stderr).lines() {
+                info!(target: "wasm_stderr", "[{}] {}", module_cid, line);
+            }
+            output.extend(sandbox.stdout());
+        }
+        let result_cid = match store.block_store() {
+            Some(block_store) => block_store.put(&output)
+                .with_context(|| "Failed to store execution output")?,
+            None => {
+                warn!("Block store not available. Execution output not persisted.");
+                block_cid(&output)?
+            }
+        };
+        debug!("Execution output of {} bytes has CID {}", output.len(), result_cid);
+        
+        // Keep the trace as an auditable artifact next to the output
+        let trace = std::mem::take(&mut store.data_mut().trace);
+        let log_cid = if trace.is_empty() {
+            None
+        } else {
+            let bytes = serde_json::to_vec(&trace).with_context(|| "Failed to serialize execution trace")?;
+            Some(match store.block_store() {
+                Some(block_store) => block_store.put(&bytes)
+                    .with_context(|| "Failed to store execution trace")?,
+                None => block_cid(&bytes)?,
+            })
+        };
+        
         // Create result
         let result = ExecutionResult {
             module_cid,
             execution_time_ms: execution_time.as_millis() as u64,
             fuel_consumed,
-            // In a real implementation, we'd capture the actual output
-            result_cid: Cid::from_bytes(&[0u8; 32]).unwrap_or_else(|_| {
-                Cid::from_bytes(&[1u8; 32]).unwrap()
-            }),
+            result_cid,
+            output,
+            input_cid,
+            deterministic: self.deterministic,
+            peak_memory_bytes: store.data().limiter.peak_memory_bytes() as u64,
+            config_hash: self.config_hash(&limits, fuel),
+            side_effects: committed.cids,
+            transactions: committed.transactions,
+            trace,
+            log_cid,
         };
         
         // Handle receipt generation if configured
-        self.handle_receipt_generation(&mut store, &result, event_id).await?;
+        let status = violation.as_ref().map_or(ExecutionStatus::Success, ExecutionError::status);
+        if !replay {
+            self.handle_receipt_generation(&mut store, &result, status, event_id).await?;
+        }
         
-        Ok(result)
+        match violation {
+            Some(violation) => Err(violation.into()),
+            None => Ok(result),
+        }
+    }
+    
+    /// Record a denied execution in a receipt and fail with `ExecutionError::Denied`
+    #[allow(clippy::too_many_arguments)]
+    async fn deny<T>(&self,
+        mut ctx: Arc<T>,
+        module_cid: Cid,
+        event_id: Option<EventId>,
+        input_data: Option<&[u8]>,
+        fuel_limit: Option<u64>,
+        reason: String,
+        start_time: Instant
+    ) -> Result<ExecutionResult>
+    where
+        T: crate::abi::context::HostContext + ContextExtension + Send + Sync + 'static
+    {
+        warn!("Execution of {} denied: {}", module_cid, reason);
+        let limits = ctx.get_execution_config().limits_for(ctx.execution_scope().as_ref()).clone();
+        let fuel = fuel_limit.unwrap_or(limits.max_fuel);
+        let result = ExecutionResult {
+            module_cid,
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+            fuel_consumed: None,
+            result_cid: block_cid(&[])?,
+            output: Vec::new(),
+            input_cid: block_cid(input_data.unwrap_or_default())?,
+            deterministic: self.deterministic,
+            peak_memory_bytes: 0,
+            config_hash: self.config_hash(&limits, fuel),
+            side_effects: Vec::new(),
+            transactions: Vec::new(),
+            trace: ExecutionTrace::default(),
+            log_cid: None,
+        };
+        self.handle_receipt_generation(&mut ctx, &result, ExecutionStatus::Denied, event_id).await?;
+        Err(ExecutionError::Denied(reason).into())
+    }
+    
+    /// Hash of the settings that shape an execution's outcome: host ABI version,
+    /// profile, WASI, resource limits and fuel budget. Executions with equal hashes
+    /// ran under equivalent executors.
+    pub fn config_hash(&self, limits: &crate::config::ResourceLimits, fuel: u64) -> String {
+        #[cfg(feature = "wasi")]
+        let wasi = self.wasi.is_some();
+        #[cfg(not(feature = "wasi"))]
+        let wasi = false;
+        let settings = serde_json::json!({
+            "host_abi_version": HOST_ABI_VERSION,
+            "deterministic": self.deterministic,
+            "wasi": wasi,
+            "limits": limits,
+            "fuel": fuel,
+        });
+        format!("{:x}", Sha256::digest(settings.to_string().as_bytes()))
+    }
+    
+    /// Map an execution failure to the resource limit it violated, if any
+    fn limit_violation(
+        error: &anyhow::Error,
+        exceeded: Option<LimitExceeded>,
+        limits: &crate::config::ResourceLimits,
+        fuel: u64,
+    ) -> Option<ExecutionError> {
+        match exceeded {
+            Some(LimitExceeded::Memory) => return Some(ExecutionError::MemoryLimit(limits.max_memory_pages)),
+            Some(LimitExceeded::Table) => return Some(ExecutionError::TableLimit(limits.max_table_elements)),
+            None => {}
+        }
+        match error.downcast_ref::<Trap>() {
+            Some(Trap::OutOfFuel) => Some(ExecutionError::OutOfFuel(fuel)),
+            Some(Trap::Interrupt) => Some(ExecutionError::Timeout(limits.max_execution_time_ms)),
+            _ => None,
+        }
     }
     
     /// Find a valid entry point in the WASM module
     fn find_entry_point<T>(&self, 
         instance: &Instance, 
-        store: &mut Store<Arc<T>>
+        store: &mut Store<StoreData<T>>
     ) -> Result<TypedFunc<(), ()>> 
     where 
         T: crate::abi::context::HostContext + Send + Sync + 'static 
     {
         // Try standard entry points in order of preference
-        for name in &["_start", "main", "run", "execute"] {
+        for name in ENTRY_POINTS {
             if let Ok(func) = instance.get_typed_func::<(), ()>(&mut *store, name) {
                 debug!("Found entry point: {}", name);
                 return Ok(func);
@@ -224,14 +616,15 @@ impl ModernWasmExecutor {
     }
     
     /// Handle receipt generation based on execution configuration
-    async fn handle_receipt_generation<T>(
+    async fn handle_receipt_generation<C>(
         &self,
-        store: &mut Store<Arc<T>>,
+        store: &mut C,
         result: &ExecutionResult,
+        status: ExecutionStatus,
         event_id: Option<EventId>
     ) -> Result<Option<String>>
     where 
-        T: crate::abi::context::HostContext + ContextExtension + Send + Sync + 'static 
+        C: ContextExtension + Send
     {
         // Check if receipt generation is enabled in the execution config
         // We can access this directly through the ContextExtension implementation on Store
@@ -248,7 +641,19 @@ impl ModernWasmExecutor {
             store, // Pass the store directly instead of ctx_ref.as_ref()
             &result.module_cid,
             &result.result_cid,
-            event_id.as_ref()
+            status,
+            event_id.as_ref(),
+            &ReceiptProperties {
+                side_effects: result.side_effects.clone(),
+                deterministic: result.deterministic,
+                input_cid: result.input_cid.clone(),
+                fuel_consumed: result.fuel_consumed,
+                execution_time_ms: result.execution_time_ms,
+                peak_memory_bytes: result.peak_memory_bytes,
+                host_abi_version: HOST_ABI_VERSION,
+                executor_config_hash: result.config_hash.clone(),
+                log_cid: result.log_cid.clone(),
+            }
         ) {
             Ok(receipt) => {
                 info!("🔏 ExecutionReceipt issued: {}", receipt.id);
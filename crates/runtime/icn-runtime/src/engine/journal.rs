@@ -0,0 +1,230 @@
+//! Side effects staged by host calls during an execution.
+//!
+//! Host functions never write to the DAG, the token ledger or the policy store
+//! directly. They stage their effect in the execution's journal, which the executor
+//! commits once the module has run to completion and drops when it traps, times out
+//! or runs out of fuel, so a failed execution leaves no trace.
+
+use crate::abi::context::HostContext;
+use crate::block_store::block_cid;
+use crate::engine::ContextExtension;
+use crate::policy::DefaultPolicyLoader;
+use icn_economics::{ResourceTransaction, ResourceType, TransactionType};
+use icn_types::dag::{DagError, SignedDagNode};
+use icn_types::{Cid, ScopePolicyConfig};
+use log::{debug, error, warn};
+use thiserror::Error;
+
+/// Errors committing a journal
+#[derive(Error, Debug)]
+pub enum JournalError {
+    #[error("{0} not available to commit staged side effects")]
+    Unavailable(&'static str),
+    #[error("Failed to apply ledger transaction: {0}")]
+    Ledger(String),
+    #[error("Failed to anchor DAG nodes: {0}")]
+    Dag(#[from] DagError),
+    #[error("Failed to identify side effect: {0}")]
+    Cid(String),
+}
+
+/// A host call's effect, held back until the execution completes
+#[derive(Debug, Clone)]
+pub enum SideEffect {
+    /// A signed node to add to the DAG
+    DagAnchor(SignedDagNode),
+    /// A transaction to apply to the token ledger
+    Transaction(ResourceTransaction),
+    /// A policy replacing its scope's current one
+    PolicyUpdate(ScopePolicyConfig),
+}
+
+impl SideEffect {
+    /// Content identifier of the effect: the node's CID for anchors, and the raw CID
+    /// of the JSON encoding for transactions and policies
+    pub fn cid(&self) -> Result<Cid, JournalError> {
+        let bytes = match self {
+            SideEffect::DagAnchor(node) => return node.calculate_cid().map_err(JournalError::Dag),
+            SideEffect::Transaction(transaction) => serde_json::to_vec(transaction),
+            SideEffect::PolicyUpdate(policy) => serde_json::to_vec(policy),
+        };
+        let bytes = bytes.map_err(|e| JournalError::Cid(e.to_string()))?;
+        block_cid(&bytes).map_err(|e| JournalError::Cid(e.to_string()))
+    }
+}
+
+/// Outcome of a committed journal
+#[derive(Debug, Clone, Default)]
+pub struct CommittedEffects {
+    /// CIDs of all committed side effects, in the order they were staged
+    pub cids: Vec<Cid>,
+    /// Ledger transactions applied to the token store
+    pub transactions: Vec<ResourceTransaction>,
+}
+
+/// Side effects staged by one execution, in call order
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionJournal {
+    effects: Vec<(Cid, SideEffect)>,
+}
+
+impl ExecutionJournal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stage an effect, returning its CID
+    pub fn stage(&mut self, effect: SideEffect) -> Result<Cid, JournalError> {
+        let cid = effect.cid()?;
+        debug!("Staged side effect {}", cid);
+        self.effects.push((cid.clone(), effect));
+        Ok(cid)
+    }
+
+    /// Staged effects with their CIDs, in call order
+    pub fn effects(&self) -> &[(Cid, SideEffect)] {
+        &self.effects
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Staged DAG nodes with their CIDs, in call order
+    pub fn staged_nodes(&self) -> impl Iterator<Item = (&Cid, &SignedDagNode)> {
+        self.effects.iter().filter_map(|(cid, effect)| match effect {
+            SideEffect::DagAnchor(node) => Some((cid, node)),
+            _ => None,
+        })
+    }
+
+    /// A staged DAG node by CID
+    pub fn staged_node(&self, cid: &Cid) -> Option<&SignedDagNode> {
+        self.staged_nodes().find(|(staged, _)| *staged == cid).map(|(_, node)| node)
+    }
+
+    /// CID of the most recently staged DAG node, the parent of the next one
+    pub fn last_anchor(&self) -> Option<Cid> {
+        self.staged_nodes().last().map(|(cid, _)| cid.clone())
+    }
+
+    /// Net change staged transactions make to an account's balance
+    pub fn balance_delta(&self, account: &str, resource_type: &ResourceType) -> i128 {
+        let mut delta = 0i128;
+        for (_, effect) in &self.effects {
+            let SideEffect::Transaction(transaction) = effect else {
+                continue;
+            };
+            if &transaction.resource_type != resource_type {
+                continue;
+            }
+            let amount = i128::from(transaction.amount);
+            let (debits, credits) = match transaction.transaction_type {
+                TransactionType::Debit | TransactionType::Burn => (true, false),
+                TransactionType::Credit | TransactionType::Mint => (false, true),
+                TransactionType::Transfer => (true, true),
+            };
+            if debits && transaction.source_id.as_deref() == Some(account) {
+                delta -= amount;
+            }
+            if credits && transaction.destination_id.as_deref() == Some(account) {
+                delta += amount;
+            }
+        }
+        delta
+    }
+
+    /// Apply every staged effect.
+    ///
+    /// Ledger transactions go first, then the DAG nodes as one batch, then policy
+    /// updates, which cannot fail. If a step fails, the transactions already applied
+    /// are reversed so nothing of the journal remains.
+    pub async fn commit<T>(self, ctx: &T) -> Result<CommittedEffects, JournalError>
+    where
+        T: HostContext + ContextExtension,
+    {
+        if self.effects.is_empty() {
+            return Ok(CommittedEffects::default());
+        }
+        let mut transactions = Vec::new();
+        let mut nodes = Vec::new();
+        let mut policies = Vec::new();
+        for (_, effect) in &self.effects {
+            match effect {
+                SideEffect::Transaction(transaction) => transactions.push(transaction.clone()),
+                SideEffect::DagAnchor(node) => nodes.push(node.clone()),
+                SideEffect::PolicyUpdate(policy) => policies.push(policy.clone()),
+            }
+        }
+        let policy_loader = match policies.is_empty() {
+            true => None,
+            false => Some(HostContext::policy_loader(ctx).ok_or(JournalError::Unavailable("Policy loader"))?),
+        };
+        let policy_store = match &policy_loader {
+            Some(loader) => Some(
+                loader
+                    .as_any()
+                    .downcast_ref::<DefaultPolicyLoader>()
+                    .ok_or(JournalError::Unavailable("Writable policy store"))?,
+            ),
+            None => None,
+        };
+        let dag_store = match nodes.is_empty() {
+            true => None,
+            false => Some(ctx.dag_store().ok_or(JournalError::Unavailable("DAG store"))?),
+        };
+
+        let applied = Self::apply_transactions(ctx, &transactions).await?;
+        if let Some(dag_store) = dag_store {
+            if let Err(e) = dag_store.add_nodes(nodes).await {
+                Self::reverse_transactions(ctx, &applied).await;
+                return Err(e.into());
+            }
+        }
+        if let Some(policy_store) = policy_store {
+            for policy in policies {
+                policy_store.set_policy(policy);
+            }
+        }
+
+        debug!("Committed {} staged side effects", self.effects.len());
+        Ok(CommittedEffects {
+            cids: self.effects.into_iter().map(|(cid, _)| cid).collect(),
+            transactions: applied,
+        })
+    }
+
+    async fn apply_transactions<T: ContextExtension>(
+        ctx: &T,
+        transactions: &[ResourceTransaction],
+    ) -> Result<Vec<ResourceTransaction>, JournalError> {
+        if transactions.is_empty() {
+            return Ok(Vec::new());
+        }
+        let store = ctx.token_store().ok_or(JournalError::Unavailable("Token store"))?;
+        let mut applied = Vec::with_capacity(transactions.len());
+        for transaction in transactions {
+            if let Err(e) = store.apply_transaction(transaction).await {
+                Self::reverse_transactions(ctx, &applied).await;
+                return Err(JournalError::Ledger(e.to_string()));
+            }
+            applied.push(transaction.clone());
+        }
+        Ok(applied)
+    }
+
+    /// Undo applied transactions, newest first
+    async fn reverse_transactions<T: ContextExtension>(ctx: &T, applied: &[ResourceTransaction]) {
+        let Some(store) = ctx.token_store() else {
+            return;
+        };
+        if !applied.is_empty() {
+            warn!("Reversing {} ledger transactions of a failed commit", applied.len());
+        }
+        for transaction in applied.iter().rev() {
+            if let Err(e) = store.apply_transaction(&transaction.reversal()).await {
+                error!("Failed to reverse {:?} transaction: {}", transaction.transaction_type, e);
+            }
+        }
+    }
+}
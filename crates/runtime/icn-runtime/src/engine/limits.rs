@@ -0,0 +1,102 @@
+use crate::config::ResourceLimits;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use wasmtime::{Engine, ResourceLimiter};
+
+/// Interval between epoch increments; execution timeouts are rounded up to it
+pub const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// Which limit an execution ran into while growing memory or tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitExceeded {
+    Memory,
+    Table,
+}
+
+/// `ResourceLimiter` enforcing the memory and table limits of one execution.
+///
+/// Growth beyond a limit traps, and the limit that was hit is recorded so the
+/// executor can report it as a typed error.
+#[derive(Debug)]
+pub struct ExecutionLimiter {
+    max_memory_bytes: usize,
+    max_table_elements: u32,
+    exceeded: Option<LimitExceeded>,
+    peak_memory_bytes: usize,
+}
+
+impl ExecutionLimiter {
+    pub fn new(limits: &ResourceLimits) -> Self {
+        Self {
+            max_memory_bytes: limits.max_memory_pages as usize * 65_536,
+            max_table_elements: limits.max_table_elements,
+            exceeded: None,
+            peak_memory_bytes: 0,
+        }
+    }
+
+    /// The limit this execution ran into, if any
+    pub fn exceeded(&self) -> Option<LimitExceeded> {
+        self.exceeded
+    }
+
+    /// Largest size any linear memory was allowed to grow to, in bytes
+    pub fn peak_memory_bytes(&self) -> usize {
+        self.peak_memory_bytes
+    }
+}
+
+impl ResourceLimiter for ExecutionLimiter {
+    fn memory_growing(&mut self, _current: usize, desired: usize, _maximum: Option<usize>) -> anyhow::Result<bool> {
+        if desired > self.max_memory_bytes {
+            self.exceeded = Some(LimitExceeded::Memory);
+            anyhow::bail!("memory limit of {} bytes exceeded", self.max_memory_bytes);
+        }
+        self.peak_memory_bytes = self.peak_memory_bytes.max(desired);
+        Ok(true)
+    }
+
+    fn table_growing(&mut self, _current: u32, desired: u32, _maximum: Option<u32>) -> anyhow::Result<bool> {
+        if desired > self.max_table_elements {
+            self.exceeded = Some(LimitExceeded::Table);
+            anyhow::bail!("table limit of {} elements exceeded", self.max_table_elements);
+        }
+        Ok(true)
+    }
+}
+
+/// Number of epoch ticks covering a timeout
+pub fn epoch_ticks(timeout_ms: u64) -> u64 {
+    let tick_ms = EPOCH_TICK.as_millis() as u64;
+    timeout_ms.div_ceil(tick_ms).max(1)
+}
+
+/// Background thread advancing an engine's epoch every `EPOCH_TICK`.
+///
+/// Stores set their deadline in ticks, so all executions sharing the engine can
+/// use a single ticker. The thread stops when the ticker is dropped.
+pub struct EpochTicker {
+    stop: Arc<AtomicBool>,
+}
+
+impl EpochTicker {
+    pub fn start(engine: Engine) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                thread::sleep(EPOCH_TICK);
+                engine.increment_epoch();
+            }
+        });
+        Self { stop }
+    }
+}
+
+impl Drop for EpochTicker {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
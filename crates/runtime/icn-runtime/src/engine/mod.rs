@@ -1,9 +1,29 @@
 // Export the executor module
+pub mod authorization;
+pub mod determinism;
 pub mod executor;
+pub mod journal;
+pub mod limits;
+pub mod module_cache;
+pub mod store_data;
+pub mod trace;
+pub mod validation;
+#[cfg(feature = "wasi")]
+pub mod wasi;
 // Re-export types from the executor module
 pub use executor::ModernWasmExecutor;
 pub use executor::ExecutionResult;
 pub use executor::ContextExtension;
+pub use executor::ExecutionError;
+pub use authorization::{authorize_execution, EXECUTE_MODULE_ACTION};
+pub use determinism::{HostEnvironment, SharedEnvironment};
+pub use journal::{ExecutionJournal, JournalError, SideEffect};
+pub use module_cache::{ModuleCache, ModuleCacheStats};
+pub use store_data::StoreData;
+pub use trace::{ExecutionTrace, HostCall, LogLevel, LogRecord};
+pub use validation::{ValidationIssue, ValidationPolicy, ValidationReport};
+#[cfg(feature = "wasi")]
+pub use wasi::{WasiConfig, WasiSandbox};
 // Type alias for backward compatibility
 pub type WasmExecutor = ModernWasmExecutor;
 
@@ -26,16 +46,16 @@ impl<T: ContextExtension + ?Sized> crate::host::receipt::ReceiptContextExt for T
     }
 }
 
-// Implement ContextExtension for Arc<T> wrapped in a Store
+// Implement ContextExtension for the per-execution StoreData wrapped in a Store
 // This allows easier access to the context from wasmtime store operations
-impl<T: ContextExtension + Send + Sync + 'static> ContextExtension for wasmtime::Store<std::sync::Arc<T>> {
+impl<T: ContextExtension + Send + Sync + 'static> ContextExtension for wasmtime::Store<StoreData<T>> {
     fn get_execution_config(&self) -> &crate::config::ExecutionConfig {
-        self.data().get_execution_config()
+        self.data().ctx.get_execution_config()
     }
     
     fn get_dag_store_mut(&mut self) -> Option<&mut (dyn icn_types::dag::DagStore + Send + Sync)> {
-        // Convert &mut Store<Arc<T>> to a mutable reference to Arc<T>
-        let arc_mut = self.data_mut();
+        // Get a mutable reference to the Arc<T> held in the store data
+        let arc_mut = &mut self.data_mut().ctx;
         
         // Try to get a mutable reference to T through the Arc
         // This will only succeed if the Arc is uniquely owned
@@ -43,34 +63,54 @@ impl<T: ContextExtension + Send + Sync + 'static> ContextExtension for wasmtime:
     }
     
     fn node_did(&self) -> Option<&icn_types::Did> {
-        self.data().node_did()
+        self.data().ctx.node_did()
     }
     
     fn federation_did(&self) -> Option<&icn_types::Did> {
-        self.data().federation_did()
+        self.data().ctx.federation_did()
     }
     
     fn caller_did(&self) -> Option<&icn_types::Did> {
-        self.data().caller_did()
+        self.data().ctx.caller_did()
     }
     
     fn federation_keypair(&self) -> Option<icn_identity_core::did::DidKey> {
-        self.data().federation_keypair()
+        self.data().ctx.federation_keypair()
     }
     
     fn membership_index(&self) -> Option<std::sync::Arc<dyn crate::policy::MembershipIndex + Send + Sync>> {
-        self.data().membership_index()
+        self.data().ctx.membership_index()
     }
     
     fn policy_loader(&self) -> Option<std::sync::Arc<dyn crate::policy::PolicyLoader + Send + Sync>> {
-        self.data().policy_loader()
+        self.data().ctx.policy_loader()
+    }
+    
+    fn block_store(&self) -> Option<std::sync::Arc<dyn crate::block_store::BlockStore + Send + Sync>> {
+        self.data().ctx.block_store()
+    }
+    
+    fn execution_scope(&self) -> Option<crate::policy::ScopeType> {
+        self.data().ctx.execution_scope()
+    }
+    
+    fn execution_scope_id(&self) -> Option<String> {
+        self.data().ctx.execution_scope_id()
+    }
+    
+    fn dag_store(&self) -> Option<icn_types::dag::SharedDagStore> {
+        self.data().ctx.dag_store()
+    }
+    
+    fn token_store(&self) -> Option<std::sync::Arc<dyn icn_economics::TokenStore>> {
+        self.data().ctx.token_store()
     }
 }
 
 // Implement ContextExtension for StoreContext
-impl<'a, T: ContextExtension + Send + Sync + 'static> ContextExtension for wasmtime::StoreContext<'a, std::sync::Arc<T>> {
+impl<'a, T: ContextExtension + Send + Sync + 'static> ContextExtension for wasmtime::StoreContext<'a, StoreData<T>> {
     fn get_execution_config(&self) -> &crate::config::ExecutionConfig {
-        self.data().get_execution_config()
+        self.data().ctx.get_execution_config()
     }
     
     fn get_dag_store_mut(&mut self) -> Option<&mut (dyn icn_types::dag::DagStore + Send + Sync)> {
@@ -79,39 +119,59 @@ impl<'a, T: ContextExtension + Send + Sync + 'static> ContextExtension for wasmt
     }
     
     fn node_did(&self) -> Option<&icn_types::Did> {
-        self.data().node_did()
+        self.data().ctx.node_did()
     }
     
     fn federation_did(&self) -> Option<&icn_types::Did> {
-        self.data().federation_did()
+        self.data().ctx.federation_did()
     }
     
     fn caller_did(&self) -> Option<&icn_types::Did> {
-        self.data().caller_did()
+        self.data().ctx.caller_did()
     }
     
     fn federation_keypair(&self) -> Option<icn_identity_core::did::DidKey> {
-        self.data().federation_keypair()
+        self.data().ctx.federation_keypair()
     }
     
     fn membership_index(&self) -> Option<std::sync::Arc<dyn crate::policy::MembershipIndex + Send + Sync>> {
-        self.data().membership_index()
+        self.data().ctx.membership_index()
     }
     
     fn policy_loader(&self) -> Option<std::sync::Arc<dyn crate::policy::PolicyLoader + Send + Sync>> {
-        self.data().policy_loader()
+        self.data().ctx.policy_loader()
+    }
+    
+    fn block_store(&self) -> Option<std::sync::Arc<dyn crate::block_store::BlockStore + Send + Sync>> {
+        self.data().ctx.block_store()
+    }
+    
+    fn execution_scope(&self) -> Option<crate::policy::ScopeType> {
+        self.data().ctx.execution_scope()
+    }
+    
+    fn execution_scope_id(&self) -> Option<String> {
+        self.data().ctx.execution_scope_id()
+    }
+    
+    fn dag_store(&self) -> Option<icn_types::dag::SharedDagStore> {
+        self.data().ctx.dag_store()
+    }
+    
+    fn token_store(&self) -> Option<std::sync::Arc<dyn icn_economics::TokenStore>> {
+        self.data().ctx.token_store()
     }
 }
 
 // Implement ContextExtension for StoreContextMut
-impl<'a, T: ContextExtension + Send + Sync + 'static> ContextExtension for wasmtime::StoreContextMut<'a, std::sync::Arc<T>> {
+impl<'a, T: ContextExtension + Send + Sync + 'static> ContextExtension for wasmtime::StoreContextMut<'a, StoreData<T>> {
     fn get_execution_config(&self) -> &crate::config::ExecutionConfig {
-        self.data().get_execution_config()
+        self.data().ctx.get_execution_config()
     }
     
     fn get_dag_store_mut(&mut self) -> Option<&mut (dyn icn_types::dag::DagStore + Send + Sync)> {
-        // Convert &mut StoreContextMut<Arc<T>> to a mutable reference to Arc<T>
-        let arc_mut = self.data_mut();
+        // Get a mutable reference to the Arc<T> held in the store data
+        let arc_mut = &mut self.data_mut().ctx;
         
         // Try to get a mutable reference to T through the Arc
         // This will only succeed if the Arc is uniquely owned
@@ -119,26 +179,46 @@ impl<'a, T: ContextExtension + Send + Sync + 'static> ContextExtension for wasmt
     }
     
     fn node_did(&self) -> Option<&icn_types::Did> {
-        self.data().node_did()
+        self.data().ctx.node_did()
     }
     
     fn federation_did(&self) -> Option<&icn_types::Did> {
-        self.data().federation_did()
+        self.data().ctx.federation_did()
     }
     
     fn caller_did(&self) -> Option<&icn_types::Did> {
-        self.data().caller_did()
+        self.data().ctx.caller_did()
     }
     
     fn federation_keypair(&self) -> Option<icn_identity_core::did::DidKey> {
-        self.data().federation_keypair()
+        self.data().ctx.federation_keypair()
     }
     
     fn membership_index(&self) -> Option<std::sync::Arc<dyn crate::policy::MembershipIndex + Send + Sync>> {
-        self.data().membership_index()
+        self.data().ctx.membership_index()
     }
     
     fn policy_loader(&self) -> Option<std::sync::Arc<dyn crate::policy::PolicyLoader + Send + Sync>> {
-        self.data().policy_loader()
+        self.data().ctx.policy_loader()
+    }
+    
+    fn block_store(&self) -> Option<std::sync::Arc<dyn crate::block_store::BlockStore + Send + Sync>> {
+        self.data().ctx.block_store()
+    }
+    
+    fn execution_scope(&self) -> Option<crate::policy::ScopeType> {
+        self.data().ctx.execution_scope()
+    }
+    
+    fn execution_scope_id(&self) -> Option<String> {
+        self.data().ctx.execution_scope_id()
+    }
+    
+    fn dag_store(&self) -> Option<icn_types::dag::SharedDagStore> {
+        self.data().ctx.dag_store()
+    }
+    
+    fn token_store(&self) -> Option<std::sync::Arc<dyn icn_economics::TokenStore>> {
+        self.data().ctx.token_store()
     }
 }
@@ -0,0 +1,264 @@
+//! Cache of compiled modules keyed by module CID.
+//!
+//! Compiling a module is by far the most expensive part of a short execution, and
+//! mesh workers run the same job modules over and over. The executor looks modules
+//! up here before compiling; the most recently used ones stay in memory, and
+//! compiled artifacts can also be written to a directory so they survive restarts.
+//!
+//! Artifacts on disk live under a subdirectory named after the engine's
+//! compatibility hash, so executors with different configurations (e.g. the
+//! deterministic profile) never load each other's code. wasmtime checks the
+//! artifact header against the engine again when deserializing, and an artifact
+//! that fails that check is discarded and recompiled.
+
+use anyhow::{Context, Result};
+use icn_types::Cid;
+use lazy_static::lazy_static;
+use log::{debug, warn};
+use prometheus::{register_int_counter, IntCounter};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use wasmtime::{Engine, Module};
+
+/// Number of compiled modules kept in memory by default
+pub const DEFAULT_MODULE_CACHE_CAPACITY: usize = 64;
+
+/// File extension of precompiled artifacts
+const ARTIFACT_EXTENSION: &str = "cwasm";
+
+lazy_static! {
+    static ref MODULE_CACHE_HITS: IntCounter = register_int_counter!(
+        "runtime_module_cache_hits_total",
+        "Compiled modules served from the in-memory cache"
+    ).unwrap();
+
+    static ref MODULE_CACHE_DISK_HITS: IntCounter = register_int_counter!(
+        "runtime_module_cache_disk_hits_total",
+        "Compiled modules loaded from precompiled artifacts on disk"
+    ).unwrap();
+
+    static ref MODULE_CACHE_MISSES: IntCounter = register_int_counter!(
+        "runtime_module_cache_misses_total",
+        "Modules compiled because no cached copy was available"
+    ).unwrap();
+
+    static ref MODULE_CACHE_EVICTIONS: IntCounter = register_int_counter!(
+        "runtime_module_cache_evictions_total",
+        "Compiled modules evicted from the in-memory cache"
+    ).unwrap();
+}
+
+/// Counters of a single cache; the process-wide totals are exported to Prometheus
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModuleCacheStats {
+    /// Lookups served from memory
+    pub hits: u64,
+    /// Lookups served from a precompiled artifact on disk
+    pub disk_hits: u64,
+    /// Lookups that had to compile the module
+    pub misses: u64,
+    /// Modules dropped from memory to stay within capacity
+    pub evictions: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    hits: AtomicU64,
+    disk_hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+struct Entry {
+    module: Module,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Entries {
+    modules: HashMap<Cid, Entry>,
+    // Monotonic use counter; the entry with the lowest `last_used` is evicted first
+    clock: u64,
+}
+
+/// LRU cache of modules compiled for one engine
+pub struct ModuleCache {
+    engine: Engine,
+    capacity: usize,
+    artifact_dir: Option<PathBuf>,
+    entries: Mutex<Entries>,
+    counters: Counters,
+}
+
+impl ModuleCache {
+    /// Create a cache holding up to `capacity` modules compiled by `engine` in
+    /// memory, and persisting artifacts under `cache_dir` if one is given
+    pub fn new(engine: Engine, capacity: usize, cache_dir: Option<&Path>) -> Self {
+        let artifact_dir = cache_dir.map(|dir| dir.join(compatibility_key(&engine)));
+        Self {
+            engine,
+            capacity,
+            artifact_dir,
+            entries: Mutex::new(Entries::default()),
+            counters: Counters::default(),
+        }
+    }
+
+    /// Directory precompiled artifacts of this engine are written to
+    pub fn artifact_dir(&self) -> Option<&Path> {
+        self.artifact_dir.as_deref()
+    }
+
+    /// Return the compiled module for `module_cid`, compiling `wasm_bytes` if it is
+    /// neither in memory nor on disk.
+    ///
+    /// Fails without touching the cache if `module_cid` is not the CID of
+    /// `wasm_bytes`, so a wrong CID can neither be served nor poison the entry of
+    /// the module it names.
+    pub fn get_or_compile(&self, module_cid: &Cid, wasm_bytes: &[u8]) -> Result<Module> {
+        let actual_cid = Cid::from_bytes(wasm_bytes).context("Failed to compute module CID")?;
+        if actual_cid != *module_cid {
+            anyhow::bail!("Module bytes have CID {}, not the claimed {}", actual_cid, module_cid);
+        }
+
+        if let Some(module) = self.lookup(module_cid) {
+            self.counters.hits.fetch_add(1, Ordering::Relaxed);
+            MODULE_CACHE_HITS.inc();
+            return Ok(module);
+        }
+
+        let module = match self.load_artifact(module_cid) {
+            Some(module) => {
+                self.counters.disk_hits.fetch_add(1, Ordering::Relaxed);
+                MODULE_CACHE_DISK_HITS.inc();
+                module
+            }
+            None => {
+                self.counters.misses.fetch_add(1, Ordering::Relaxed);
+                MODULE_CACHE_MISSES.inc();
+                let module = Module::new(&self.engine, wasm_bytes).with_context(|| "Failed to create WASM module")?;
+                self.store_artifact(module_cid, &module);
+                module
+            }
+        };
+        self.insert(module_cid.clone(), module.clone());
+        Ok(module)
+    }
+
+    /// Whether `module_cid` is compiled and held in memory
+    pub fn contains(&self, module_cid: &Cid) -> bool {
+        self.entries.lock().unwrap().modules.contains_key(module_cid)
+    }
+
+    /// Number of modules held in memory
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().modules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop all modules held in memory; artifacts on disk are kept
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().modules.clear();
+    }
+
+    pub fn stats(&self) -> ModuleCacheStats {
+        ModuleCacheStats {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            disk_hits: self.counters.disk_hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            evictions: self.counters.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    fn lookup(&self, module_cid: &Cid) -> Option<Module> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.clock += 1;
+        let now = entries.clock;
+        entries.modules.get_mut(module_cid).map(|entry| {
+            entry.last_used = now;
+            entry.module.clone()
+        })
+    }
+
+    fn insert(&self, module_cid: Cid, module: Module) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        entries.clock += 1;
+        let last_used = entries.clock;
+        entries.modules.insert(module_cid, Entry { module, last_used });
+        while entries.modules.len() > self.capacity {
+            let oldest = entries
+                .modules
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(cid, _)| cid.clone());
+            let Some(oldest) = oldest else { break };
+            entries.modules.remove(&oldest);
+            self.counters.evictions.fetch_add(1, Ordering::Relaxed);
+            MODULE_CACHE_EVICTIONS.inc();
+            debug!("Evicted compiled module {} from the cache", oldest);
+        }
+    }
+
+    fn artifact_path(&self, module_cid: &Cid) -> Option<PathBuf> {
+        self.artifact_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.{}", module_cid, ARTIFACT_EXTENSION)))
+    }
+
+    // Loading precompiled code is the one place the runtime needs `unsafe`
+    #[allow(unsafe_code)]
+    fn load_artifact(&self, module_cid: &Cid) -> Option<Module> {
+        let path = self.artifact_path(module_cid)?;
+        if !path.exists() {
+            return None;
+        }
+        // SAFETY: artifacts are only written by `store_artifact` from modules this
+        // engine's configuration compiled, and wasmtime rejects artifacts whose
+        // header does not match the engine.
+        match unsafe { Module::deserialize_file(&self.engine, &path) } {
+            Ok(module) => {
+                debug!("Loaded precompiled module {} from {}", module_cid, path.display());
+                Some(module)
+            }
+            Err(e) => {
+                warn!("Discarding incompatible precompiled module {}: {:#}", path.display(), e);
+                let _ = fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    /// Persist a compiled module; failures only cost a recompilation later
+    fn store_artifact(&self, module_cid: &Cid, module: &Module) {
+        let Some(path) = self.artifact_path(module_cid) else { return };
+        let result = module.serialize().and_then(|bytes| {
+            fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))?;
+            // Write to a temporary file first so readers never see a partial artifact
+            let partial = path.with_extension(format!("{}.partial", ARTIFACT_EXTENSION));
+            fs::write(&partial, bytes)?;
+            fs::rename(&partial, &path)?;
+            Ok(())
+        });
+        if let Err(e) = result {
+            warn!("Failed to write precompiled module {}: {:#}", path.display(), e);
+        }
+    }
+}
+
+/// Directory name identifying the engine configuration artifacts are compiled for
+fn compatibility_key(engine: &Engine) -> String {
+    let mut hasher = DefaultHasher::new();
+    engine.precompile_compatibility_hash().hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
@@ -0,0 +1,52 @@
+use crate::engine::limits::ExecutionLimiter;
+use crate::engine::determinism::{HostEnvironment, SharedEnvironment};
+use crate::engine::journal::ExecutionJournal;
+use crate::engine::trace::ExecutionTrace;
+use std::sync::{Arc, Mutex};
+
+/// Data held by the wasmtime `Store` for a single execution.
+///
+/// The host context is shared with the caller; everything else lives only as long
+/// as the execution it belongs to.
+pub struct StoreData<T> {
+    /// Host context supplied by the caller
+    pub ctx: Arc<T>,
+    /// Input bytes the module can read through `read_input`
+    pub input: Vec<u8>,
+    /// Output bytes the module has written through `write_output`
+    pub output: Vec<u8>,
+    /// Memory and table limits for this execution
+    pub limiter: ExecutionLimiter,
+    /// Side effects staged by host calls, committed only if the module completes
+    pub journal: ExecutionJournal,
+    /// Clock and randomness the module observes through host calls and WASI
+    pub environment: SharedEnvironment,
+    /// Logs written by the module and, if enabled, its host calls
+    pub trace: ExecutionTrace,
+    /// WASI state, present when the executor links WASI
+    #[cfg(feature = "wasi")]
+    pub wasi: Option<wasmtime_wasi::WasiCtx>,
+}
+
+impl<T> StoreData<T> {
+    /// Create store data for an execution with the given input, limits and environment
+    pub fn new(
+        ctx: Arc<T>,
+        input: Vec<u8>,
+        limiter: ExecutionLimiter,
+        environment: HostEnvironment,
+        trace: ExecutionTrace,
+    ) -> Self {
+        Self {
+            ctx,
+            input,
+            output: Vec::new(),
+            limiter,
+            journal: ExecutionJournal::new(),
+            environment: Arc::new(Mutex::new(environment)),
+            trace,
+            #[cfg(feature = "wasi")]
+            wasi: None,
+        }
+    }
+}
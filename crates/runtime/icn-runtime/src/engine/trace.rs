@@ -0,0 +1,106 @@
+//! Logs and host call traces captured during an execution.
+//!
+//! Modules log through `env::log` (at info level) and `env::log_structured`, which
+//! takes a level and an optional JSON object of fields. With
+//! `ExecutionConfig::trace_host_calls` set, every ICN host call is also recorded
+//! with its arguments and the fuel consumed when it was made. The executor stores
+//! the whole trace as one JSON block and links it from the receipt as `log_cid`, so
+//! what a contract did can be audited after the fact.
+
+use serde::{Deserialize, Serialize};
+
+/// Severity of a guest log record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    /// Level for the code a guest passes to `log_structured`: 0 (trace) to 4 (error)
+    pub fn from_code(code: i32) -> Option<Self> {
+        match code {
+            0 => Some(LogLevel::Trace),
+            1 => Some(LogLevel::Debug),
+            2 => Some(LogLevel::Info),
+            3 => Some(LogLevel::Warn),
+            4 => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+}
+
+impl From<LogLevel> for log::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Trace => log::Level::Trace,
+            LogLevel::Debug => log::Level::Debug,
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Warn => log::Level::Warn,
+            LogLevel::Error => log::Level::Error,
+        }
+    }
+}
+
+/// A message logged by the guest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogRecord {
+    pub level: LogLevel,
+    pub message: String,
+    /// Structured fields passed alongside the message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<serde_json::Value>,
+    /// Fuel consumed by the execution when the record was logged
+    pub fuel_consumed: u64,
+}
+
+/// A host function call made by the guest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostCall {
+    /// Name of the imported function, e.g. `dag_anchor`
+    pub name: String,
+    /// Arguments as passed by the guest
+    pub args: Vec<i64>,
+    /// Fuel consumed by the execution before the call
+    pub fuel_consumed: u64,
+}
+
+/// Everything an execution logged, and optionally the host calls it made
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionTrace {
+    pub logs: Vec<LogRecord>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub host_calls: Vec<HostCall>,
+    #[serde(skip)]
+    capture_calls: bool,
+}
+
+impl ExecutionTrace {
+    /// An empty trace; host calls are recorded only if `capture_calls` is set
+    pub fn new(capture_calls: bool) -> Self {
+        Self { capture_calls, ..Self::default() }
+    }
+
+    pub fn captures_calls(&self) -> bool {
+        self.capture_calls
+    }
+
+    pub fn log(&mut self, record: LogRecord) {
+        self.logs.push(record);
+    }
+
+    pub fn record_call(&mut self, name: &str, args: &[i64], fuel_consumed: u64) {
+        if self.capture_calls {
+            self.host_calls.push(HostCall { name: name.to_string(), args: args.to_vec(), fuel_consumed });
+        }
+    }
+
+    /// Whether there is nothing worth storing
+    pub fn is_empty(&self) -> bool {
+        self.logs.is_empty() && self.host_calls.is_empty()
+    }
+}
@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use wasmparser::{ExternalKind, FuncType, Parser, Payload, StructuralType, TypeRef, Validator, WasmFeatures};
+
+/// Exported function names the executor accepts as entry points, in order of preference
+pub const ENTRY_POINTS: &[&str] = &["_start", "main", "run", "execute"];
+
+/// What a module may import and use to be accepted by the runtime.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationPolicy {
+    /// Import namespaces served by ICN host functions
+    #[serde(default = "default_allowed_namespaces")]
+    pub allowed_namespaces: Vec<String>,
+
+    /// Whether the SIMD proposal may be used
+    #[serde(default)]
+    pub allow_simd: bool,
+
+    /// Whether the threads proposal (shared memory, atomics) may be used
+    #[serde(default)]
+    pub allow_threads: bool,
+
+    /// Largest initial memory a module may declare or import, in 64 KiB pages
+    #[serde(default = "default_max_memory_pages")]
+    pub max_memory_pages: u32,
+}
+
+fn default_allowed_namespaces() -> Vec<String> {
+    vec!["env".to_string(), "icn".to_string()]
+}
+
+fn default_max_memory_pages() -> u32 {
+    crate::config::ResourceLimits::default().max_memory_pages
+}
+
+impl Default for ValidationPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_namespaces: default_allowed_namespaces(),
+            allow_simd: false,
+            allow_threads: false,
+            max_memory_pages: default_max_memory_pages(),
+        }
+    }
+}
+
+impl ValidationPolicy {
+    /// Also accept imports from the WASI namespaces, for executors that link WASI
+    #[cfg(feature = "wasi")]
+    pub fn with_wasi(mut self) -> Self {
+        for namespace in crate::engine::wasi::WASI_NAMESPACES {
+            if !self.allowed_namespaces.iter().any(|allowed| allowed == namespace) {
+                self.allowed_namespaces.push(namespace.to_string());
+            }
+        }
+        self
+    }
+
+    fn features(&self) -> WasmFeatures {
+        WasmFeatures {
+            simd: self.allow_simd,
+            relaxed_simd: self.allow_simd,
+            threads: self.allow_threads,
+            ..WasmFeatures::default()
+        }
+    }
+}
+
+/// A reason a module was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ValidationIssue {
+    /// The bytes are not a valid WebAssembly module
+    InvalidModule { message: String },
+    /// The module imports from a namespace outside the allowlist
+    ForbiddenImport { module: String, name: String },
+    /// The module uses a WebAssembly feature the policy disallows
+    ForbiddenFeature { message: String },
+    /// A memory is declared larger than the policy allows
+    MemoryTooLarge { declared_pages: u64, max_pages: u32 },
+    /// None of the supported entry points is exported as a function
+    MissingEntryPoint,
+    /// An entry point is exported, but not as a `() -> ()` function the executor can call
+    EntryPointSignature { name: String, signature: String },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationIssue::InvalidModule { message } => write!(f, "invalid module: {}", message),
+            ValidationIssue::ForbiddenImport { module, name } => {
+                write!(f, "import {}::{} is outside the allowed namespaces", module, name)
+            }
+            ValidationIssue::ForbiddenFeature { message } => write!(f, "forbidden feature: {}", message),
+            ValidationIssue::MemoryTooLarge { declared_pages, max_pages } => {
+                write!(f, "memory declares {} pages, limit is {}", declared_pages, max_pages)
+            }
+            ValidationIssue::MissingEntryPoint => {
+                write!(f, "no entry point exported (expected one of {})", ENTRY_POINTS.join(", "))
+            }
+            ValidationIssue::EntryPointSignature { name, signature } => {
+                write!(f, "entry point {} has type {}, expected () -> ()", name, signature)
+            }
+        }
+    }
+}
+
+/// An import declared by a module
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleImport {
+    pub module: String,
+    pub name: String,
+}
+
+/// Result of validating a module against a `ValidationPolicy`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    /// Problems found; the module is accepted if this is empty
+    pub issues: Vec<ValidationIssue>,
+    /// Everything the module imports
+    pub imports: Vec<ModuleImport>,
+    /// Names of everything the module exports
+    pub exports: Vec<String>,
+    /// Largest initial memory size declared or imported, in pages
+    pub memory_pages: Option<u64>,
+    /// Entry point the executor would call
+    pub entry_point: Option<String>,
+}
+
+impl ValidationReport {
+    /// Whether the module passed every check
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Validate a module's bytes against a policy, collecting every issue found
+pub fn validate_module(wasm_bytes: &[u8], policy: &ValidationPolicy) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    // Structural validity with every supported proposal enabled, then the policy's features
+    let all_features = WasmFeatures {
+        simd: true,
+        relaxed_simd: true,
+        threads: true,
+        ..WasmFeatures::default()
+    };
+    if let Err(e) = Validator::new_with_features(all_features).validate_all(wasm_bytes) {
+        report.issues.push(ValidationIssue::InvalidModule { message: e.to_string() });
+        return report;
+    }
+    if let Err(e) = Validator::new_with_features(policy.features()).validate_all(wasm_bytes) {
+        report.issues.push(ValidationIssue::ForbiddenFeature { message: e.to_string() });
+    }
+
+    // Function signatures, and the type index of every function, imported ones first
+    let mut types: Vec<Option<FuncType>> = Vec::new();
+    let mut function_types: Vec<u32> = Vec::new();
+    let mut exported_functions = Vec::new();
+    for payload in Parser::new(0).parse_all(wasm_bytes) {
+        // Parsing cannot fail here: the module has already been validated
+        let Ok(payload) = payload else { break };
+        match payload {
+            Payload::TypeSection(reader) => {
+                for ty in reader.into_iter().flatten() {
+                    types.push(match ty.structural_type {
+                        StructuralType::Func(func) => Some(func),
+                        _ => None,
+                    });
+                }
+            }
+            Payload::FunctionSection(reader) => function_types.extend(reader.into_iter().flatten()),
+            Payload::ImportSection(reader) => {
+                for import in reader.into_iter().flatten() {
+                    if !policy.allowed_namespaces.iter().any(|ns| ns == import.module) {
+                        report.issues.push(ValidationIssue::ForbiddenImport {
+                            module: import.module.to_string(),
+                            name: import.name.to_string(),
+                        });
+                    }
+                    match import.ty {
+                        TypeRef::Memory(memory) => check_memory(&mut report, memory.initial, policy),
+                        TypeRef::Func(ty) => function_types.push(ty),
+                        _ => {}
+                    }
+                    report.imports.push(ModuleImport {
+                        module: import.module.to_string(),
+                        name: import.name.to_string(),
+                    });
+                }
+            }
+            Payload::MemorySection(reader) => {
+                for memory in reader.into_iter().flatten() {
+                    check_memory(&mut report, memory.initial, policy);
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader.into_iter().flatten() {
+                    if export.kind == ExternalKind::Func {
+                        exported_functions.push((export.name.to_string(), export.index));
+                    }
+                    report.exports.push(export.name.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Entry points that are exported, in order of preference, with their types
+    let entry_points: Vec<(&str, &FuncType)> = ENTRY_POINTS
+        .iter()
+        .filter_map(|name| {
+            let (_, index) = exported_functions.iter().find(|(export, _)| export == name)?;
+            let ty = function_types.get(*index as usize)?;
+            Some((*name, types.get(*ty as usize)?.as_ref()?))
+        })
+        .collect();
+    // The executor calls the first one that takes and returns nothing
+    report.entry_point = entry_points
+        .iter()
+        .find(|(_, ty)| ty.params().is_empty() && ty.results().is_empty())
+        .map(|(name, _)| name.to_string());
+    if report.entry_point.is_none() {
+        report.issues.push(match entry_points.first() {
+            Some((name, ty)) => ValidationIssue::EntryPointSignature { name: name.to_string(), signature: signature(ty) },
+            None => ValidationIssue::MissingEntryPoint,
+        });
+    }
+
+    report
+}
+
+/// `(i32, i32) -> (i64)`
+fn signature(ty: &FuncType) -> String {
+    let list = |types: &[wasmparser::ValType]| types.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+    format!("({}) -> ({})", list(ty.params()), list(ty.results()))
+}
+
+fn check_memory(report: &mut ValidationReport, initial_pages: u64, policy: &ValidationPolicy) {
+    report.memory_pages = Some(report.memory_pages.map_or(initial_pages, |pages| pages.max(initial_pages)));
+    if initial_pages > u64::from(policy.max_memory_pages) {
+        report.issues.push(ValidationIssue::MemoryTooLarge {
+            declared_pages: initial_pages,
+            max_pages: policy.max_memory_pages,
+        });
+    }
+}
@@ -0,0 +1,202 @@
+//! WASI preview 1 support for modules built by standard toolchains.
+//!
+//! Each execution gets a sandbox with a fresh scratch directory preopened at
+//! `/scratch` and nothing else: no other filesystem access, no sockets, no
+//! environment variables. stdout and stderr are captured in memory; the executor
+//! appends stdout to the execution output and logs stderr.
+//!
+//! Under the deterministic profile the WASI clocks and `random_get` read the
+//! execution's seeded `HostEnvironment`, the same source as `clock_now_ms` and
+//! `random_bytes`. File timestamps in the scratch directory still come from the
+//! host filesystem.
+
+use crate::engine::determinism::SharedEnvironment;
+use crate::engine::StoreData;
+use anyhow::{Context, Result};
+use cap_std::time::{Instant, SystemTime};
+use icn_types::Cid;
+use log::{debug, warn};
+use rand::RngCore;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, UNIX_EPOCH};
+use wasi_common::clocks::{WasiClocks, WasiMonotonicClock, WasiMonotonicOffsetClock, WasiSystemClock};
+use wasi_common::pipe::WritePipe;
+use wasi_common::Table;
+use wasmtime::Linker;
+use wasmtime_wasi::sync::{ambient_authority, Dir};
+use wasmtime_wasi::WasiCtx;
+
+/// Guest path of the per-job scratch directory
+pub const SCRATCH_GUEST_PATH: &str = "/scratch";
+
+/// Import namespaces served by WASI
+pub const WASI_NAMESPACES: &[&str] = &["wasi_snapshot_preview1", "wasi_unstable"];
+
+/// Where WASI sandboxes keep their scratch directories
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasiConfig {
+    /// Directory the per-job scratch directories are created in
+    pub scratch_root: PathBuf,
+    /// Keep scratch directories after the job finishes, e.g. for debugging
+    pub keep_scratch: bool,
+}
+
+impl Default for WasiConfig {
+    fn default() -> Self {
+        Self {
+            scratch_root: std::env::temp_dir().join("icn-wasi"),
+            keep_scratch: false,
+        }
+    }
+}
+
+/// WASI resources of a single execution; the scratch directory is removed on drop
+pub struct WasiSandbox {
+    scratch_dir: PathBuf,
+    keep_scratch: bool,
+    stdout: Arc<RwLock<Vec<u8>>>,
+    stderr: Arc<RwLock<Vec<u8>>>,
+}
+
+impl WasiSandbox {
+    /// Create the scratch directory for a run of `module_cid` and the WASI context
+    /// the module sees
+    pub fn create(config: &WasiConfig, module_cid: &Cid, environment: &SharedEnvironment) -> Result<(Self, WasiCtx)> {
+        let scratch_dir = config.scratch_root.join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&scratch_dir)
+            .with_context(|| format!("Failed to create WASI scratch directory {}", scratch_dir.display()))?;
+        let sandbox = Self {
+            scratch_dir,
+            keep_scratch: config.keep_scratch,
+            stdout: Arc::new(RwLock::new(Vec::new())),
+            stderr: Arc::new(RwLock::new(Vec::new())),
+        };
+
+        let mut ctx = if environment.lock().unwrap().is_deterministic() {
+            WasiCtx::new(
+                Box::new(SeededRandom(environment.clone())),
+                seeded_clocks(environment),
+                wasmtime_wasi::sync::sched_ctx(),
+                Table::new(),
+            )
+        } else {
+            wasmtime_wasi::sync::WasiCtxBuilder::new().build()
+        };
+        // Modules see their CID as the program name and no other arguments
+        ctx.push_arg(&module_cid.to_string())?;
+        ctx.set_stdout(Box::new(WritePipe::from_shared(sandbox.stdout.clone())));
+        ctx.set_stderr(Box::new(WritePipe::from_shared(sandbox.stderr.clone())));
+        let dir = Dir::open_ambient_dir(&sandbox.scratch_dir, ambient_authority())
+            .with_context(|| "Failed to open WASI scratch directory")?;
+        ctx.push_preopened_dir(Box::new(wasmtime_wasi::sync::dir::Dir::from_cap_std(dir)), SCRATCH_GUEST_PATH)?;
+        debug!("Created WASI sandbox in {}", sandbox.scratch_dir.display());
+
+        Ok((sandbox, ctx))
+    }
+
+    /// Host path of the scratch directory preopened at `/scratch`
+    pub fn scratch_dir(&self) -> &Path {
+        &self.scratch_dir
+    }
+
+    /// Bytes the module wrote to stdout
+    pub fn stdout(&self) -> Vec<u8> {
+        self.stdout.read().unwrap().clone()
+    }
+
+    /// Bytes the module wrote to stderr
+    pub fn stderr(&self) -> Vec<u8> {
+        self.stderr.read().unwrap().clone()
+    }
+}
+
+impl Drop for WasiSandbox {
+    fn drop(&mut self) {
+        if self.keep_scratch {
+            return;
+        }
+        if let Err(e) = std::fs::remove_dir_all(&self.scratch_dir) {
+            warn!("Failed to remove WASI scratch directory {}: {}", self.scratch_dir.display(), e);
+        }
+    }
+}
+
+/// Link the WASI preview 1 (and legacy preview 0) functions, served from the
+/// store's `WasiCtx`
+pub fn add_to_linker<T: 'static>(linker: &mut Linker<StoreData<T>>) -> Result<()> {
+    wasmtime_wasi::sync::add_to_linker(linker, |data: &mut StoreData<T>| {
+        data.wasi.as_mut().expect("WASI functions are only linked for executions with a WASI context")
+    })
+}
+
+/// Exit status of a module that called `proc_exit`, if that is why it stopped
+pub fn exit_status(error: &anyhow::Error) -> Option<i32> {
+    error.downcast_ref::<wasmtime_wasi::I32Exit>().map(|exit| exit.0)
+}
+
+fn seeded_clocks(environment: &SharedEnvironment) -> WasiClocks {
+    // The monotonic clock counts seeded milliseconds from an arbitrary origin
+    let origin = std::time::Instant::now();
+    WasiClocks {
+        system: Some(Box::new(SeededSystemClock(environment.clone()))),
+        monotonic: Some(WasiMonotonicOffsetClock {
+            creation_time: Instant::from_std(origin),
+            abs_clock: Box::new(SeededMonotonicClock { environment: environment.clone(), origin }),
+        }),
+    }
+}
+
+struct SeededSystemClock(SharedEnvironment);
+
+impl WasiSystemClock for SeededSystemClock {
+    fn resolution(&self) -> Duration {
+        Duration::from_millis(1)
+    }
+
+    fn now(&self, _precision: Duration) -> SystemTime {
+        let now_ms = self.0.lock().unwrap().now_ms();
+        SystemTime::from_std(UNIX_EPOCH + Duration::from_millis(now_ms))
+    }
+}
+
+struct SeededMonotonicClock {
+    environment: SharedEnvironment,
+    origin: std::time::Instant,
+}
+
+impl WasiMonotonicClock for SeededMonotonicClock {
+    fn resolution(&self) -> Duration {
+        Duration::from_millis(1)
+    }
+
+    fn now(&self, _precision: Duration) -> Instant {
+        let now_ms = self.environment.lock().unwrap().now_ms();
+        Instant::from_std(self.origin + Duration::from_millis(now_ms))
+    }
+}
+
+struct SeededRandom(SharedEnvironment);
+
+impl RngCore for SeededRandom {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.lock().unwrap().fill_random(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
@@ -0,0 +1,305 @@
+//! DAG host functions: scoped reads and anchoring from inside WASM modules.
+//!
+//! Every call is confined to the execution's `HostScope`, authorized against the
+//! scope's policy (when a `PolicyLoader` is available) and charged fuel on top of
+//! the guest's own instructions. Anchored nodes are staged in the execution journal
+//! and reach the store only when the module completes; reads already see them.
+//!
+//! Results are copied into a guest buffer `(out_ptr, out_len)`. A call returns the
+//! length of its result; if that exceeds `out_len` nothing is written, so the guest
+//! can retry with a larger buffer. Negative return values are the `DAG_ERR_*` codes.
+
+use crate::abi::context::HostContext;
+use crate::engine::{ContextExtension, SideEffect, StoreData};
+use crate::host::guest::{
+    authorize, charge, read_guest, read_guest_string, write_guest, HostScope, ERR_INVALID_ARGUMENT, ERR_MEMORY,
+    ERR_UNAUTHORIZED,
+};
+use icn_types::dag::{DagError, DagNode, DagNodeBuilder, DagPayload, SignedDagNode};
+use icn_types::Cid;
+use log::{debug, error};
+use wasmtime::Caller;
+
+/// Fuel charged for every DAG host call
+pub const DAG_CALL_FUEL: u64 = 1_000;
+/// Fuel charged on top of `DAG_CALL_FUEL` for anchoring a node
+pub const DAG_ANCHOR_FUEL: u64 = 10_000;
+/// Fuel charged per byte moved between the DAG and guest memory
+pub const DAG_BYTE_FUEL: u64 = 1;
+/// Fuel charged per node examined by a label query
+pub const DAG_SCAN_FUEL: u64 = 100;
+
+/// Guest memory could not be read or written
+pub const DAG_ERR_MEMORY: i32 = ERR_MEMORY;
+/// An argument (CID or label) could not be parsed
+pub const DAG_ERR_INVALID_ARGUMENT: i32 = ERR_INVALID_ARGUMENT;
+/// The node does not exist or lies outside the execution's scope
+pub const DAG_ERR_NOT_FOUND: i32 = -3;
+/// The scope's policy does not authorize the caller
+pub const DAG_ERR_UNAUTHORIZED: i32 = ERR_UNAUTHORIZED;
+/// No DAG store, signing identity or scope ID is available to the execution
+pub const DAG_ERR_UNAVAILABLE: i32 = -5;
+/// The DAG store failed
+pub const DAG_ERR_STORE: i32 = -6;
+
+/// Policy action checked before reading nodes
+pub const DAG_READ_ACTION: &str = "dag_read";
+/// Policy action checked before anchoring a node
+pub const DAG_ANCHOR_ACTION: &str = "dag_anchor";
+
+/// `dag_get_node(cid_ptr, cid_len, out_ptr, out_len)`: the node as JSON
+pub async fn host_dag_get_node<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    cid_ptr: i32,
+    cid_len: i32,
+    out_ptr: i32,
+    out_len: i32,
+) -> anyhow::Result<i32>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    charge(caller, DAG_CALL_FUEL)?;
+    let node = match read_scoped_node(caller, cid_ptr, cid_len).await {
+        Ok(node) => node,
+        Err(code) => return Ok(code),
+    };
+    let bytes = match serde_json::to_vec(&node) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!("Failed to serialize DAG node: {}", e);
+            return Ok(DAG_ERR_STORE);
+        }
+    };
+    charge(caller, DAG_BYTE_FUEL * bytes.len() as u64)?;
+    Ok(write_guest(caller, out_ptr, out_len, &bytes).unwrap_or_else(|code| code))
+}
+
+/// `dag_get_payload(cid_ptr, cid_len, out_ptr, out_len)`: the node's payload bytes.
+///
+/// Raw payloads are returned as-is, JSON payloads serialized, and CID payloads as
+/// their string form.
+pub async fn host_dag_get_payload<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    cid_ptr: i32,
+    cid_len: i32,
+    out_ptr: i32,
+    out_len: i32,
+) -> anyhow::Result<i32>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    charge(caller, DAG_CALL_FUEL)?;
+    let node = match read_scoped_node(caller, cid_ptr, cid_len).await {
+        Ok(node) => node,
+        Err(code) => return Ok(code),
+    };
+    let bytes = match node.payload {
+        DagPayload::Raw(bytes) => bytes,
+        DagPayload::Json(value) => value.to_string().into_bytes(),
+        DagPayload::Reference(cid) | DagPayload::TrustBundle(cid) | DagPayload::ExecutionReceipt(cid) => {
+            cid.to_string().into_bytes()
+        }
+    };
+    charge(caller, DAG_BYTE_FUEL * bytes.len() as u64)?;
+    Ok(write_guest(caller, out_ptr, out_len, &bytes).unwrap_or_else(|code| code))
+}
+
+/// `dag_anchor(payload_ptr, payload_len, label_ptr, label_len, out_ptr, out_len)`:
+/// anchor a raw payload in the execution's scope and return the new node's CID.
+///
+/// The node is authored and signed by the federation keypair. Its parents are the
+/// store's current tips, or the node this execution anchored last. A zero
+/// `label_len` anchors the node without a label.
+pub async fn host_dag_anchor<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    payload_ptr: i32,
+    payload_len: i32,
+    label_ptr: i32,
+    label_len: i32,
+    out_ptr: i32,
+    out_len: i32,
+) -> anyhow::Result<i32>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    charge(caller, DAG_CALL_FUEL + DAG_ANCHOR_FUEL)?;
+    let payload = match read_guest(caller, payload_ptr, payload_len) {
+        Ok(payload) => payload,
+        Err(code) => return Ok(code),
+    };
+    charge(caller, DAG_BYTE_FUEL * payload.len() as u64)?;
+    let label = match label_len {
+        0 => None,
+        _ => match read_guest_string(caller, label_ptr, label_len) {
+            Ok(label) => Some(label),
+            Err(code) => return Ok(code),
+        },
+    };
+    let cid = match anchor_node(caller, DagPayload::Raw(payload), label).await {
+        Ok(cid) => cid,
+        Err(code) => return Ok(code),
+    };
+    Ok(write_guest(caller, out_ptr, out_len, cid.to_string().as_bytes()).unwrap_or_else(|code| code))
+}
+
+/// `icn.host_anchor_to_dag(cid_ptr, cid_len, bytes)`, as imported by compiled CCL for
+/// `anchor_data`: anchor a node referencing the CID in the execution's scope, charged
+/// as `bytes` bytes of storage. The node is built and staged as by `dag_anchor`.
+///
+/// The import has no return value, so a negative size, an invalid CID or a failed
+/// anchor traps the module.
+pub async fn host_anchor_to_dag<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    cid_ptr: i32,
+    cid_len: i32,
+    bytes: i32,
+) -> anyhow::Result<()>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    let bytes = u64::try_from(bytes).map_err(|_| anyhow::anyhow!("Cannot anchor {} bytes", bytes))?;
+    charge(caller, DAG_CALL_FUEL + DAG_ANCHOR_FUEL + DAG_BYTE_FUEL * bytes)?;
+    let cid = read_guest_string(caller, cid_ptr, cid_len)
+        .map_err(|code| anyhow::anyhow!("Cannot read the CID to anchor (error {})", code))?;
+    let reference: Cid = cid.parse().map_err(|_| anyhow::anyhow!("Cannot anchor invalid CID {}", cid))?;
+    match anchor_node(caller, DagPayload::Reference(reference), None).await {
+        Ok(_) => Ok(()),
+        Err(code) => Err(anyhow::anyhow!("Failed to anchor {} (error {})", cid, code)),
+    }
+}
+
+/// `dag_query_by_label(label_ptr, label_len, out_ptr, out_len)`: a JSON array of the
+/// CIDs of in-scope nodes carrying the label, in topological order followed by the
+/// nodes this execution has anchored
+pub async fn host_dag_query_by_label<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    label_ptr: i32,
+    label_len: i32,
+    out_ptr: i32,
+    out_len: i32,
+) -> anyhow::Result<i32>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    charge(caller, DAG_CALL_FUEL)?;
+    let label = match read_guest_string(caller, label_ptr, label_len) {
+        Ok(label) => label,
+        Err(code) => return Ok(code),
+    };
+    let (scope, nodes) = match read_scoped_nodes(caller).await {
+        Ok(found) => found,
+        Err(code) => return Ok(code),
+    };
+    charge(caller, DAG_SCAN_FUEL * nodes.len() as u64)?;
+
+    let mut cids = Vec::new();
+    for node in nodes {
+        if node.node.metadata.label.as_deref() != Some(label.as_str()) || !scope.contains(&node.node) {
+            continue;
+        }
+        match node.cid.clone().map_or_else(|| node.calculate_cid(), Ok) {
+            Ok(cid) => cids.push(cid.to_string()),
+            Err(e) => {
+                error!("Failed to compute CID of DAG node: {}", e);
+                return Ok(DAG_ERR_STORE);
+            }
+        }
+    }
+    let bytes = serde_json::to_vec(&cids)?;
+    charge(caller, DAG_BYTE_FUEL * bytes.len() as u64)?;
+    Ok(write_guest(caller, out_ptr, out_len, &bytes).unwrap_or_else(|code| code))
+}
+
+/// Fetch a node by the CID string in guest memory, hiding nodes outside the scope
+async fn read_scoped_node<T>(caller: &mut Caller<'_, StoreData<T>>, cid_ptr: i32, cid_len: i32) -> Result<DagNode, i32>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    let cid: Cid = read_guest_string(caller, cid_ptr, cid_len)?
+        .parse()
+        .map_err(|_| DAG_ERR_INVALID_ARGUMENT)?;
+    let ctx = caller.data().ctx.clone();
+    let store = ctx.dag_store().ok_or(DAG_ERR_UNAVAILABLE)?;
+    let scope = HostScope::of(&*ctx);
+    authorize(&*ctx, &scope, DAG_READ_ACTION)?;
+
+    let staged = caller.data().journal.staged_node(&cid).map(|node| node.node.clone());
+    let node = match staged {
+        Some(node) => node,
+        None => match store.get_node(&cid).await {
+            Ok(node) => node.node,
+            Err(DagError::NodeNotFound(_)) => return Err(DAG_ERR_NOT_FOUND),
+            Err(e) => return Err(store_error(e)),
+        },
+    };
+    if !scope.contains(&node) {
+        debug!("DAG node {} is outside scope {:?}", cid, scope);
+        return Err(DAG_ERR_NOT_FOUND);
+    }
+    Ok(node)
+}
+
+/// All stored and staged nodes once reads are authorized; callers filter by scope
+async fn read_scoped_nodes<T>(caller: &mut Caller<'_, StoreData<T>>) -> Result<(HostScope, Vec<SignedDagNode>), i32>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    let ctx = caller.data().ctx.clone();
+    let store = ctx.dag_store().ok_or(DAG_ERR_UNAVAILABLE)?;
+    let scope = HostScope::of(&*ctx);
+    authorize(&*ctx, &scope, DAG_READ_ACTION)?;
+    let mut nodes = store.get_ordered_nodes().await.map_err(store_error)?;
+    nodes.extend(caller.data().journal.staged_nodes().map(|(_, node)| node.clone()));
+    Ok((scope, nodes))
+}
+
+async fn anchor_node<T>(caller: &mut Caller<'_, StoreData<T>>, payload: DagPayload, label: Option<String>) -> Result<Cid, i32>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    let ctx = caller.data().ctx.clone();
+    let store = ctx.dag_store().ok_or(DAG_ERR_UNAVAILABLE)?;
+    let scope = HostScope::of(&*ctx);
+    authorize(&*ctx, &scope, DAG_ANCHOR_ACTION)?;
+    let signer = ctx.federation_keypair().ok_or(DAG_ERR_UNAVAILABLE)?;
+    let federation_id = ctx.federation_did().unwrap_or(signer.did()).to_string();
+
+    // Nodes anchored by one execution form a chain off the store's current tips
+    let parents = match caller.data().journal.last_anchor() {
+        Some(previous) => vec![previous],
+        None => store.get_tips().await.map_err(store_error)?,
+    };
+    let mut builder = DagNodeBuilder::new()
+        .with_payload(payload)
+        .with_parents(parents)
+        .with_author(signer.did().clone())
+        .with_federation_id(federation_id)
+        .with_scope(scope.scope.clone());
+    if let Some(scope_id) = scope.scope_id.clone() {
+        builder = builder.with_scope_id(scope_id);
+    }
+    if let Some(label) = label {
+        builder = builder.with_label(label);
+    }
+    let node = builder.build().map_err(|e| {
+        error!("Failed to build DAG node for anchoring: {}", e);
+        DAG_ERR_UNAVAILABLE
+    })?;
+    let signature = signer.sign(&node.canonical_bytes().map_err(store_error)?);
+
+    let cid = caller
+        .data_mut()
+        .journal
+        .stage(SideEffect::DagAnchor(SignedDagNode { node, signature, cid: None }))
+        .map_err(|e| {
+            error!("Failed to stage DAG node: {}", e);
+            DAG_ERR_STORE
+        })?;
+    debug!("Staged DAG node {} in scope {:?}", cid, scope);
+    Ok(cid)
+}
+
+fn store_error(e: DagError) -> i32 {
+    error!("DAG store error in host call: {}", e);
+    DAG_ERR_STORE
+}
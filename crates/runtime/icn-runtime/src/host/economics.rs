@@ -0,0 +1,264 @@
+//! Token ledger host functions backed by the context's `TokenStore`.
+//!
+//! Calls act on the ledger account of the execution's scope: its scope ID, or the
+//! federation for federation-level executions. Every change is a `ResourceTransaction`
+//! authored by the caller and staged in the execution journal; it reaches the token
+//! store only when the module completes. Balances seen by the module include the
+//! changes it has staged.
+//!
+//! Resource types are passed by their snake_case name (e.g. `compute_unit`); names
+//! that are not built in refer to custom resources. Negative return values are the
+//! `TOKEN_ERR_*` codes.
+
+use crate::abi::context::HostContext;
+use crate::engine::{ContextExtension, SideEffect, StoreData};
+use crate::host::guest::{
+    authorize, charge, read_guest_string, HostScope, ERR_INVALID_ARGUMENT, ERR_MEMORY, ERR_UNAUTHORIZED,
+};
+use icn_economics::{ResourceTransaction, ResourceType, TokenStore, TransactionType};
+use icn_types::Did;
+use log::{debug, error};
+use std::sync::Arc;
+use wasmtime::Caller;
+
+/// Fuel charged for every ledger host call
+pub const TOKEN_CALL_FUEL: u64 = 1_000;
+
+/// Guest memory could not be read
+pub const TOKEN_ERR_MEMORY: i32 = ERR_MEMORY;
+/// The resource name or destination is not valid UTF-8, or the amount is not positive
+pub const TOKEN_ERR_INVALID_ARGUMENT: i32 = ERR_INVALID_ARGUMENT;
+/// The account's balance does not cover the amount
+pub const TOKEN_ERR_INSUFFICIENT_FUNDS: i32 = -3;
+/// The scope's policy does not authorize the caller
+pub const TOKEN_ERR_UNAUTHORIZED: i32 = ERR_UNAUTHORIZED;
+/// No token store or ledger account is available to the execution
+pub const TOKEN_ERR_UNAVAILABLE: i32 = -5;
+/// The token store failed
+pub const TOKEN_ERR_STORE: i32 = -6;
+
+/// Policy action checked before crediting new tokens to the scope
+pub const MINT_ACTION: &str = "mint_token";
+/// Policy action checked before transferring tokens out of the scope
+pub const TRANSFER_ACTION: &str = "transfer_resource";
+
+/// The ledger account an execution acts on
+struct Ledger {
+    store: Arc<dyn TokenStore>,
+    scope: HostScope,
+    account: String,
+    federation_id: String,
+    authority: Did,
+}
+
+impl Ledger {
+    fn of<T: HostContext + ContextExtension>(ctx: &T) -> Result<Self, i32> {
+        let store = ctx.token_store().ok_or(TOKEN_ERR_UNAVAILABLE)?;
+        let federation_id = ctx.federation_did().ok_or(TOKEN_ERR_UNAVAILABLE)?.to_string();
+        let scope = HostScope::of(ctx);
+        let account = scope.scope_id.clone().unwrap_or_else(|| federation_id.clone());
+        Ok(Self { store, scope, account, federation_id, authority: ctx.get_caller_did() })
+    }
+}
+
+/// `token_balance(resource_ptr, resource_len) -> i64`: the scope account's balance
+pub async fn host_token_balance<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    resource_ptr: i32,
+    resource_len: i32,
+) -> anyhow::Result<i64>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    charge(caller, TOKEN_CALL_FUEL)?;
+    Ok(balance(caller, resource_ptr, resource_len).await.unwrap_or_else(i64::from))
+}
+
+/// `token_debit(resource_ptr, resource_len, amount) -> i32`: take tokens from the scope account
+pub async fn host_token_debit<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    resource_ptr: i32,
+    resource_len: i32,
+    amount: i64,
+) -> anyhow::Result<i32>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    charge(caller, TOKEN_CALL_FUEL)?;
+    Ok(debit(caller, resource_ptr, resource_len, amount).await.err().unwrap_or(0))
+}
+
+/// `token_credit(resource_ptr, resource_len, amount) -> i32`: add new tokens to the scope
+/// account; requires the `mint_token` action
+pub async fn host_token_credit<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    resource_ptr: i32,
+    resource_len: i32,
+    amount: i64,
+) -> anyhow::Result<i32>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    charge(caller, TOKEN_CALL_FUEL)?;
+    let result = async {
+        let (ledger, resource_type, amount) = prepare(caller, resource_ptr, resource_len, amount)?;
+        authorize(&*caller.data().ctx, &ledger.scope, MINT_ACTION)?;
+        let transaction = ResourceTransaction::new_credit(
+            resource_type,
+            amount,
+            &ledger.account,
+            &ledger.federation_id,
+            ledger.authority.clone(),
+        );
+        stage(caller, &ledger, transaction).await
+    }
+    .await;
+    Ok(result.err().unwrap_or(0))
+}
+
+/// `token_transfer(resource_ptr, resource_len, dest_ptr, dest_len, amount) -> i32`: move
+/// tokens from the scope account to another account; requires the `transfer_resource` action
+pub async fn host_token_transfer<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    resource_ptr: i32,
+    resource_len: i32,
+    dest_ptr: i32,
+    dest_len: i32,
+    amount: i64,
+) -> anyhow::Result<i32>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    charge(caller, TOKEN_CALL_FUEL)?;
+    let result = async {
+        let destination = read_guest_string(caller, dest_ptr, dest_len)?;
+        let (ledger, resource_type, amount) = prepare(caller, resource_ptr, resource_len, amount)?;
+        authorize(&*caller.data().ctx, &ledger.scope, TRANSFER_ACTION)?;
+        let transaction = ResourceTransaction::new_transfer(
+            resource_type,
+            amount,
+            &ledger.account,
+            &destination,
+            &ledger.federation_id,
+            ledger.authority.clone(),
+        );
+        stage(caller, &ledger, transaction).await
+    }
+    .await;
+    Ok(result.err().unwrap_or(0))
+}
+
+/// `icn.host_check_resource_authorization(token_ptr, token_len, amount) -> i32`, as
+/// imported by compiled CCL: 1 if the scope account covers `amount`, 0 if not
+pub async fn host_check_resource_authorization<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    token_ptr: i32,
+    token_len: i32,
+    amount: i64,
+) -> anyhow::Result<i32>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    charge(caller, TOKEN_CALL_FUEL)?;
+    Ok(match balance(caller, token_ptr, token_len).await {
+        Ok(balance) => i32::from(amount >= 0 && balance >= amount),
+        Err(code) => code,
+    })
+}
+
+/// `icn.host_record_resource_usage(token_ptr, token_len, amount)`, as imported by
+/// compiled CCL: debit the usage from the scope account.
+///
+/// The import has no return value, so usage that cannot be recorded traps the module.
+pub async fn host_record_resource_usage<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    token_ptr: i32,
+    token_len: i32,
+    amount: i64,
+) -> anyhow::Result<()>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    charge(caller, TOKEN_CALL_FUEL)?;
+    match debit(caller, token_ptr, token_len, amount).await {
+        Ok(()) => Ok(()),
+        Err(code) => Err(anyhow::anyhow!("Failed to record resource usage of {} (error {})", amount, code)),
+    }
+}
+
+async fn balance<T>(caller: &mut Caller<'_, StoreData<T>>, resource_ptr: i32, resource_len: i32) -> Result<i64, i32>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    let resource_type = read_resource_type(caller, resource_ptr, resource_len)?;
+    let ledger = Ledger::of(&*caller.data().ctx)?;
+    let balance = staged_balance(caller, &ledger, &ledger.account, &resource_type).await?;
+    Ok(i64::try_from(balance).unwrap_or(i64::MAX))
+}
+
+/// An account's balance in the token store plus the changes staged by this execution
+async fn staged_balance<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    ledger: &Ledger,
+    account: &str,
+    resource_type: &ResourceType,
+) -> Result<i128, i32> {
+    let staged = caller.data().journal.balance_delta(account, resource_type);
+    let stored = ledger.store.get_balance(account, resource_type).await.map_err(|e| {
+        error!("Failed to read balance of {}: {}", account, e);
+        TOKEN_ERR_STORE
+    })?;
+    Ok(i128::from(stored) + staged)
+}
+
+async fn debit<T>(caller: &mut Caller<'_, StoreData<T>>, resource_ptr: i32, resource_len: i32, amount: i64) -> Result<(), i32>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    let (ledger, resource_type, amount) = prepare(caller, resource_ptr, resource_len, amount)?;
+    let transaction = ResourceTransaction::new_debit(
+        resource_type,
+        amount,
+        &ledger.account,
+        &ledger.federation_id,
+        ledger.authority.clone(),
+    );
+    stage(caller, &ledger, transaction).await
+}
+
+/// Resolve the ledger, resource type and amount shared by every ledger change
+fn prepare<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    resource_ptr: i32,
+    resource_len: i32,
+    amount: i64,
+) -> Result<(Ledger, ResourceType, u64), i32>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    let resource_type = read_resource_type(caller, resource_ptr, resource_len)?;
+    let amount = u64::try_from(amount).ok().filter(|amount| *amount > 0).ok_or(TOKEN_ERR_INVALID_ARGUMENT)?;
+    let ledger = Ledger::of(&*caller.data().ctx)?;
+    Ok((ledger, resource_type, amount))
+}
+
+/// Stage a transaction, rejecting it if the source account cannot cover it
+async fn stage<T>(caller: &mut Caller<'_, StoreData<T>>, ledger: &Ledger, transaction: ResourceTransaction) -> Result<(), i32> {
+    let debits = matches!(transaction.transaction_type, TransactionType::Debit | TransactionType::Transfer);
+    if let (true, Some(source)) = (debits, transaction.source_id.as_deref()) {
+        let available = staged_balance(caller, ledger, source, &transaction.resource_type).await?;
+        if available < i128::from(transaction.amount) {
+            return Err(TOKEN_ERR_INSUFFICIENT_FUNDS);
+        }
+    }
+    debug!("Staging {:?} of {} {:?}", transaction.transaction_type, transaction.amount, transaction.resource_type);
+    caller.data_mut().journal.stage(SideEffect::Transaction(transaction)).map(|_| ()).map_err(|e| {
+        error!("Failed to stage ledger transaction: {}", e);
+        TOKEN_ERR_STORE
+    })
+}
+
+fn read_resource_type<T>(caller: &mut Caller<'_, StoreData<T>>, ptr: i32, len: i32) -> Result<ResourceType, i32> {
+    let name = read_guest_string(caller, ptr, len)?;
+    Ok(name.parse().unwrap_or_else(|never: std::convert::Infallible| match never {}))
+}
@@ -0,0 +1,31 @@
+//! Clock and randomness host functions.
+//!
+//! Values come from the execution's `HostEnvironment`: the system clock and OS
+//! randomness normally, or a seeded clock and stream under a deterministic executor.
+
+use crate::engine::StoreData;
+use crate::host::guest::{charge, write_guest, ERR_MEMORY};
+use wasmtime::Caller;
+
+/// Fuel charged for every clock or randomness host call
+pub const ENV_CALL_FUEL: u64 = 100;
+/// Fuel charged per random byte generated
+pub const RANDOM_BYTE_FUEL: u64 = 1;
+
+/// `clock_now_ms() -> i64`: milliseconds since the Unix epoch, or on the seeded clock
+pub fn host_clock_now_ms<T>(caller: &mut Caller<'_, StoreData<T>>) -> anyhow::Result<i64> {
+    charge(caller, ENV_CALL_FUEL)?;
+    Ok(i64::try_from(caller.data().environment.lock().unwrap().now_ms()).unwrap_or(i64::MAX))
+}
+
+/// `random_bytes(out_ptr, out_len) -> i32`: fill the guest buffer with random bytes,
+/// returning `out_len` or -1 if the buffer is not in guest memory
+pub fn host_random_bytes<T>(caller: &mut Caller<'_, StoreData<T>>, out_ptr: i32, out_len: i32) -> anyhow::Result<i32> {
+    if out_len < 0 {
+        return Ok(ERR_MEMORY);
+    }
+    charge(caller, ENV_CALL_FUEL + RANDOM_BYTE_FUEL * out_len as u64)?;
+    let mut bytes = vec![0u8; out_len as usize];
+    caller.data().environment.lock().unwrap().fill_random(&mut bytes);
+    Ok(write_guest(caller, out_ptr, out_len, &bytes).unwrap_or_else(|code| code))
+}
@@ -0,0 +1,113 @@
+//! Helpers shared by host functions: the execution's scope, policy checks, fuel
+//! charges and copying data in and out of guest memory.
+
+use crate::abi::context::HostContext;
+use crate::engine::{ContextExtension, StoreData};
+use crate::policy::ScopeType;
+use icn_types::dag::{DagNode, NodeScope};
+use log::debug;
+use wasmtime::{Caller, Trap};
+
+/// Guest memory could not be read or written
+pub const ERR_MEMORY: i32 = -1;
+/// An argument could not be parsed
+pub const ERR_INVALID_ARGUMENT: i32 = -2;
+/// The scope's policy does not authorize the caller
+pub const ERR_UNAUTHORIZED: i32 = -4;
+
+/// The scope an execution's host calls act in
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostScope {
+    pub scope: NodeScope,
+    pub scope_id: Option<String>,
+}
+
+impl HostScope {
+    /// Scope of an execution; executions without a scope act at federation level
+    pub fn of<C: ContextExtension + ?Sized>(ctx: &C) -> Self {
+        let scope = match ctx.execution_scope() {
+            Some(ScopeType::Cooperative) => NodeScope::Cooperative,
+            Some(ScopeType::Community) => NodeScope::Community,
+            Some(ScopeType::Federation) | None => NodeScope::Federation,
+        };
+        Self { scope, scope_id: ctx.execution_scope_id() }
+    }
+
+    /// ID the scope's policy is stored under; federation-level policies use "_"
+    pub fn policy_id(&self) -> &str {
+        self.scope_id.as_deref().unwrap_or("_")
+    }
+
+    /// Whether a DAG node belongs to this scope
+    pub fn contains(&self, node: &DagNode) -> bool {
+        node.metadata.scope == self.scope
+            && (self.scope == NodeScope::Federation || node.metadata.scope_id == self.scope_id)
+    }
+}
+
+/// Check the scope's policy for the calling DID; without a policy loader every call is allowed
+pub(crate) fn authorize<T: HostContext>(ctx: &T, scope: &HostScope, action: &str) -> Result<(), i32> {
+    let Some(policy_loader) = HostContext::policy_loader(ctx) else {
+        return Ok(());
+    };
+    let scope_type = format!("{:?}", scope.scope);
+    let scope_id = scope.policy_id();
+    policy_loader
+        .check_authorization(&scope_type, scope_id, action, &ctx.get_caller_did())
+        .map_err(|e| {
+            debug!("{} denied in {}/{}: {}", action, scope_type, scope_id, e);
+            ERR_UNAUTHORIZED
+        })
+}
+
+/// Charge fuel for host work, trapping like the guest would when it runs out
+pub(crate) fn charge<T>(caller: &mut Caller<'_, StoreData<T>>, fuel: u64) -> anyhow::Result<()> {
+    caller.consume_fuel(fuel).map(|_| ()).map_err(|_| Trap::OutOfFuel.into())
+}
+
+/// Record a host call in the execution trace if call tracing is enabled
+pub(crate) fn trace_call<T>(caller: &mut Caller<'_, StoreData<T>>, name: &str, args: &[i64]) {
+    if caller.data().trace.captures_calls() {
+        let fuel_consumed = caller.fuel_consumed().unwrap_or_default();
+        caller.data_mut().trace.record_call(name, args, fuel_consumed);
+    }
+}
+
+pub(crate) fn read_guest<T>(caller: &mut Caller<'_, StoreData<T>>, ptr: i32, len: i32) -> Result<Vec<u8>, i32> {
+    let memory = caller.get_export("memory").and_then(|e| e.into_memory()).ok_or(ERR_MEMORY)?;
+    if ptr < 0 || len < 0 {
+        return Err(ERR_MEMORY);
+    }
+    memory
+        .data(&caller)
+        .get(ptr as usize..(ptr as usize) + (len as usize))
+        .map(<[u8]>::to_vec)
+        .ok_or(ERR_MEMORY)
+}
+
+pub(crate) fn read_guest_string<T>(caller: &mut Caller<'_, StoreData<T>>, ptr: i32, len: i32) -> Result<String, i32> {
+    String::from_utf8(read_guest(caller, ptr, len)?).map_err(|_| ERR_INVALID_ARGUMENT)
+}
+
+/// Copy a result into the guest buffer, or only report its length if it does not fit
+pub(crate) fn write_guest<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    out_ptr: i32,
+    out_len: i32,
+    bytes: &[u8],
+) -> Result<i32, i32> {
+    let needed = i32::try_from(bytes.len()).map_err(|_| ERR_MEMORY)?;
+    if needed > out_len {
+        return Ok(needed);
+    }
+    let memory = caller.get_export("memory").and_then(|e| e.into_memory()).ok_or(ERR_MEMORY)?;
+    if out_ptr < 0 {
+        return Err(ERR_MEMORY);
+    }
+    let dst = memory
+        .data_mut(&mut *caller)
+        .get_mut(out_ptr as usize..(out_ptr as usize) + bytes.len())
+        .ok_or(ERR_MEMORY)?;
+    dst.copy_from_slice(bytes);
+    Ok(needed)
+}
@@ -0,0 +1,68 @@
+//! Logging host functions.
+//!
+//! Messages are forwarded to `HostContext::log_message` and the `wasm` log target,
+//! and kept in the execution's `ExecutionTrace` so they outlive the run.
+
+use crate::abi::context::HostContext;
+use crate::engine::{LogLevel, LogRecord, StoreData};
+use crate::host::guest::{read_guest, read_guest_string, ERR_INVALID_ARGUMENT};
+use wasmtime::Caller;
+
+/// `log(ptr, len)`: log a UTF-8 message at info level. Unreadable messages are dropped.
+pub fn host_log<T: HostContext>(caller: &mut Caller<'_, StoreData<T>>, ptr: i32, len: i32) {
+    if let Ok(message) = read_guest_string(caller, ptr, len) {
+        record(caller, LogLevel::Info, message, None);
+    }
+}
+
+/// `log_structured(level, msg_ptr, msg_len, fields_ptr, fields_len) -> i32`: log a
+/// message at `level` (0 trace to 4 error) with an optional JSON object of fields.
+///
+/// A zero `fields_len` logs the message without fields. Returns 0, or a negative
+/// `ERR_*` code if the message or fields cannot be read or the level is unknown.
+pub fn host_log_structured<T: HostContext>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    level: i32,
+    msg_ptr: i32,
+    msg_len: i32,
+    fields_ptr: i32,
+    fields_len: i32,
+) -> i32 {
+    let Some(level) = LogLevel::from_code(level) else {
+        return ERR_INVALID_ARGUMENT;
+    };
+    let message = match read_guest_string(caller, msg_ptr, msg_len) {
+        Ok(message) => message,
+        Err(code) => return code,
+    };
+    let fields = if fields_len == 0 {
+        None
+    } else {
+        let bytes = match read_guest(caller, fields_ptr, fields_len) {
+            Ok(bytes) => bytes,
+            Err(code) => return code,
+        };
+        match serde_json::from_slice::<serde_json::Value>(&bytes) {
+            Ok(fields) if fields.is_object() => Some(fields),
+            _ => return ERR_INVALID_ARGUMENT,
+        }
+    };
+    record(caller, level, message, fields);
+    0
+}
+
+fn record<T: HostContext>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    level: LogLevel,
+    message: String,
+    fields: Option<serde_json::Value>,
+) {
+    let log_level = log::Level::from(level);
+    match &fields {
+        Some(fields) => log::log!(target: "wasm", log_level, "{} {}", message, fields),
+        None => log::log!(target: "wasm", log_level, "{}", message),
+    }
+    caller.data().ctx.log_message(&message);
+    let fuel_consumed = caller.fuel_consumed().unwrap_or_default();
+    caller.data_mut().trace.log(LogRecord { level, message, fields, fuel_consumed });
+}
@@ -1,5 +1,12 @@
 pub mod receipt;
 pub mod policy;
+pub mod dag;
+pub mod guest;
+pub mod economics;
+pub mod environment;
+pub mod logging;
+pub mod signature;
 
 // Re-export items from the receipt module if needed publicly from host module
-pub use receipt::{issue_execution_receipt, ReceiptError}; 
\ No newline at end of file
+pub use receipt::{issue_execution_receipt, ReceiptError, ReceiptProperties};
+pub use signature::{verify_with_resolver, DidKeyResolver}; 
\ No newline at end of file
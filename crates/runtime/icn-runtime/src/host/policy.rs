@@ -1,38 +1,30 @@
 use wasmtime::Caller;
-use icn_types::{Did, PolicyError};
+use icn_types::{Did, PolicyError, ScopePolicyConfig};
 use crate::abi::context::HostContext;
+use crate::engine::{ContextExtension, SideEffect, StoreData};
+use crate::host::guest::{
+    authorize, charge, read_guest, read_guest_string, HostScope, ERR_INVALID_ARGUMENT, ERR_UNAUTHORIZED,
+};
+use crate::policy::DefaultPolicyLoader;
 use log::{debug, error};
 
-/// Helper function to read a string from WASM memory that also handles clone to avoid borrow checker issues
-fn read_string_safe<T: HostContext + Clone>(
-    caller: &mut Caller<'_, T>,
-    ptr: i32,
-    len: i32,
-    error_code: i32
-) -> Result<String, i32> {
-    let context = caller.data().clone();
-    match context.read_string(caller, ptr, len) {
-        Ok(s) => Ok(s),
-        Err(e) => {
-            error!("Failed to read string: {}", e);
-            Err(error_code)
-        }
-    }
-}
+/// Fuel charged for a policy update host call
+pub const POLICY_UPDATE_FUEL: u64 = 10_000;
+/// No writable policy store is available to the execution
+pub const POLICY_ERR_UNAVAILABLE: i32 = -5;
+/// Policy action checked before replacing a scope's policy
+pub const POLICY_UPDATE_ACTION: &str = "update_policy";
 
-/// Host function to check if a DID is authorized to perform an action within a scope
+/// `check_policy_authorization(scope_type_ptr, scope_type_len, scope_id_ptr,
+/// scope_id_len, action_ptr, action_len, did_ptr, did_len) -> i32`: whether a DID
+/// may perform an action in a scope.
 ///
-/// # Parameters
-/// * `scope_type` - The scope type (Federation, Cooperative, Community)
-/// * `scope_id` - The ID of the scope
-/// * `action` - The action being performed
-/// * `did` - The DID to validate
-///
-/// # Returns
-/// * `0` if the action is authorized
-/// * Non-zero error code if the action is not authorized
-pub fn host_check_policy_authorization<T: HostContext + Clone>(
-    mut caller: Caller<'_, T>,
+/// Returns 0 if authorized, 1 to 5 for the `PolicyError` that denied it (action not
+/// permitted, unauthorized scope access, DID not in allowlist, policy not found,
+/// internal error), -2 to -5 if the respective string argument cannot be read,
+/// -5 for an invalid DID and -6 if no policy loader is available.
+pub fn host_check_policy_authorization<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
     scope_type_ptr: i32,
     scope_type_len: i32,
     scope_id_ptr: i32,
@@ -41,61 +33,79 @@ pub fn host_check_policy_authorization<T: HostContext + Clone>(
     action_len: i32,
     did_ptr: i32,
     did_len: i32,
-) -> i32 {
-    // Read scope_type
-    let scope_type = match read_string_safe(&mut caller, scope_type_ptr, scope_type_len, -1) {
-        Ok(s) => s,
+) -> i32
+where
+    T: HostContext + 'static,
+{
+    let args = (|| -> Result<_, i32> {
+        Ok((
+            read_guest_string(caller, scope_type_ptr, scope_type_len).map_err(|_| -2)?,
+            read_guest_string(caller, scope_id_ptr, scope_id_len).map_err(|_| -3)?,
+            read_guest_string(caller, action_ptr, action_len).map_err(|_| -4)?,
+            read_guest_string(caller, did_ptr, did_len).map_err(|_| -5)?,
+        ))
+    })();
+    let (scope_type, scope_id, action, did_str) = match args {
+        Ok(args) => args,
         Err(code) => return code,
     };
-    
-    // Read scope_id
-    let scope_id = match read_string_safe(&mut caller, scope_id_ptr, scope_id_len, -2) {
-        Ok(s) => s,
-        Err(code) => return code,
-    };
-    
-    // Read action
-    let action = match read_string_safe(&mut caller, action_ptr, action_len, -3) {
-        Ok(s) => s,
-        Err(code) => return code,
-    };
-    
-    // Read DID string
-    let did_str = match read_string_safe(&mut caller, did_ptr, did_len, -4) {
-        Ok(s) => s,
-        Err(code) => return code,
-    };
-    
-    // Parse the DID
     let did = match Did::try_from(did_str) {
-        Ok(d) => d,
+        Ok(did) => did,
         Err(e) => {
             error!("Invalid DID format: {}", e);
             return -5;
         }
     };
-    
-    // We need to clone the context to avoid borrowing issues
-    let ctx = caller.data().clone();
-    
-    // Get the policy loader
-    let policy_loader = match ctx.policy_loader() {
-        Some(loader) => loader,
-        None => {
-            error!("Policy loader not available");
-            return -6;
-        }
+    let Some(policy_loader) = HostContext::policy_loader(&*caller.data().ctx) else {
+        error!("Policy loader not available");
+        return -6;
     };
     
     debug!("Checking authorization for {} to perform {} in {}/{}", did, action, scope_type, scope_id);
-    
-    // Perform the policy check
     match policy_loader.check_authorization(&scope_type, &scope_id, &action, &did) {
-        Ok(()) => 0, // Authorized
+        Ok(()) => 0,
         Err(PolicyError::ActionNotPermitted) => 1,
         Err(PolicyError::UnauthorizedScopeAccess) => 2,
         Err(PolicyError::DidNotInAllowlist) => 3,
         Err(PolicyError::PolicyNotFound) => 4,
         Err(PolicyError::InternalError(_)) => 5,
     }
-} 
\ No newline at end of file
+}
+
+/// `policy_update(policy_ptr, policy_len) -> i32`: replace the execution scope's policy
+/// with the JSON `ScopePolicyConfig` in guest memory.
+///
+/// The update is staged in the execution journal and takes effect when the module
+/// completes. Returns 0, or a negative `guest` error code; -5 if the context has no
+/// writable policy store.
+pub async fn host_policy_update<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    policy_ptr: i32,
+    policy_len: i32,
+) -> anyhow::Result<i32>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    charge(caller, POLICY_UPDATE_FUEL)?;
+    let result = (|| {
+        let policy: ScopePolicyConfig = serde_json::from_slice(&read_guest(caller, policy_ptr, policy_len)?)
+            .map_err(|_| ERR_INVALID_ARGUMENT)?;
+        let ctx = caller.data().ctx.clone();
+        let scope = HostScope::of(&*ctx);
+        if policy.scope_type != scope.scope || policy.scope_id != scope.policy_id() {
+            debug!("Policy for {:?}/{} is outside scope {:?}", policy.scope_type, policy.scope_id, scope);
+            return Err(ERR_UNAUTHORIZED);
+        }
+        let writable = HostContext::policy_loader(&*ctx)
+            .map_or(false, |loader| loader.as_any().is::<DefaultPolicyLoader>());
+        if !writable {
+            return Err(POLICY_ERR_UNAVAILABLE);
+        }
+        authorize(&*ctx, &scope, POLICY_UPDATE_ACTION)?;
+        caller.data_mut().journal.stage(SideEffect::PolicyUpdate(policy)).map_err(|e| {
+            error!("Failed to stage policy update: {}", e);
+            POLICY_ERR_UNAVAILABLE
+        })
+    })();
+    Ok(result.map(|_| 0).unwrap_or_else(|code| code))
+}
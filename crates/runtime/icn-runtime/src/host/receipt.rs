@@ -14,6 +14,8 @@ pub enum ReceiptError {
     KeypairNotFound,
     #[error("Host error: {0}")]
     HostError(String), // Placeholder for more specific host errors
+    #[error("Invalid receipt properties: {0}")]
+    InvalidProperties(String),
 }
 
 fn unix_ts() -> u64 {
@@ -23,6 +25,101 @@ fn unix_ts() -> u64 {
         .as_secs()
 }
 
+/// Execution details recorded in a receipt's additional properties.
+///
+/// Billing reads the resource usage from these and the scheduler's reputation
+/// scoring compares them across executors; both get them back with `from_receipt`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceiptProperties {
+    /// CIDs of the side effects the execution committed
+    pub side_effects: Vec<Cid>,
+    /// Whether the execution ran under the deterministic profile
+    pub deterministic: bool,
+    /// CID of the execution's input, which re-executions start from
+    pub input_cid: Cid,
+    /// Fuel the execution consumed
+    pub fuel_consumed: Option<u64>,
+    /// Wall-clock time of the execution in milliseconds
+    pub execution_time_ms: u64,
+    /// Largest linear memory the module grew to, in bytes
+    pub peak_memory_bytes: u64,
+    /// Host ABI version the module was linked against
+    pub host_abi_version: u32,
+    /// Hash of the executor settings that shaped the execution
+    pub executor_config_hash: String,
+    /// CID of the execution's logs and host call trace, if it produced any
+    pub log_cid: Option<Cid>,
+}
+
+impl ReceiptProperties {
+    fn to_json(&self) -> serde_json::Value {
+        let mut properties = serde_json::json!({
+            "deterministic": self.deterministic,
+            "input_cid": self.input_cid.to_string(),
+            "execution_time_ms": self.execution_time_ms,
+            "peak_memory_bytes": self.peak_memory_bytes,
+            "host_abi_version": self.host_abi_version,
+            "executor_config_hash": self.executor_config_hash,
+        });
+        if let Some(fuel) = self.fuel_consumed {
+            properties["fuel_consumed"] = fuel.into();
+        }
+        if let Some(log_cid) = &self.log_cid {
+            properties["log_cid"] = log_cid.to_string().into();
+        }
+        if !self.side_effects.is_empty() {
+            properties["side_effects"] = self.side_effects.iter().map(|cid| cid.to_string()).collect();
+        }
+        properties
+    }
+
+    /// Read the execution details back from a receipt issued by this runtime
+    pub fn from_receipt(receipt: &ExecutionReceipt) -> Result<Self, ReceiptError> {
+        let properties = receipt
+            .credential_subject
+            .additional_properties
+            .as_ref()
+            .ok_or_else(|| ReceiptError::InvalidProperties("receipt has no execution details".to_string()))?;
+        let field = |key: &str| {
+            properties
+                .get(key)
+                .ok_or_else(|| ReceiptError::InvalidProperties(format!("missing {}", key)))
+        };
+        let number = |key: &str| {
+            field(key)?
+                .as_u64()
+                .ok_or_else(|| ReceiptError::InvalidProperties(format!("{} is not a number", key)))
+        };
+        let cid = |value: &serde_json::Value| {
+            value
+                .as_str()
+                .and_then(|cid| cid.parse().ok())
+                .ok_or_else(|| ReceiptError::InvalidProperties(format!("invalid CID {}", value)))
+        };
+
+        Ok(Self {
+            side_effects: match properties.get("side_effects").and_then(|cids| cids.as_array()) {
+                Some(cids) => cids.iter().map(cid).collect::<Result<_, _>>()?,
+                None => Vec::new(),
+            },
+            deterministic: field("deterministic")?
+                .as_bool()
+                .ok_or_else(|| ReceiptError::InvalidProperties("deterministic is not a boolean".to_string()))?,
+            input_cid: cid(field("input_cid")?)?,
+            fuel_consumed: properties.get("fuel_consumed").and_then(|fuel| fuel.as_u64()),
+            execution_time_ms: number("execution_time_ms")?,
+            peak_memory_bytes: number("peak_memory_bytes")?,
+            host_abi_version: u32::try_from(number("host_abi_version")?)
+                .map_err(|_| ReceiptError::InvalidProperties("host_abi_version out of range".to_string()))?,
+            executor_config_hash: field("executor_config_hash")?
+                .as_str()
+                .ok_or_else(|| ReceiptError::InvalidProperties("executor_config_hash is not a string".to_string()))?
+                .to_string(),
+            log_cid: properties.get("log_cid").map(cid).transpose()?,
+        })
+    }
+}
+
 // Define a trait as an extension to HostContext to provide the receipt-specific functionality
 pub trait ReceiptContextExt {
     fn node_did(&self) -> Option<&icn_types::Did>;
@@ -35,7 +132,9 @@ pub fn issue_execution_receipt(
     ctx: &dyn ReceiptContextExt,
     module_cid: &Cid,
     result_cid: &Cid,
+    status: ExecutionStatus,
     event_id: Option<&EventId>, // Made event_id optional as per plan
+    properties: &ReceiptProperties,
 ) -> Result<ExecutionReceipt, ReceiptError> {
     // Determine the DID of the node executing
     let executor_did = ctx.node_did().ok_or_else(|| ReceiptError::HostError("Node DID not found in context".to_string()))?;
@@ -58,8 +157,8 @@ pub fn issue_execution_receipt(
         result_cid: result_cid.to_string(),
         event_id: event_id.cloned(), // Clone if EventId is passed as a reference
         timestamp: unix_ts(),
-        status: ExecutionStatus::Success, // Assuming success for now, could be a param
-        additional_properties: None, // Can be extended later
+        status,
+        additional_properties: Some(properties.to_json()),
     };
 
     // Get the federation keypair for signing
@@ -127,8 +226,19 @@ mod tests {
         let result_cid = Cid::from_bytes(&[2u8; 32]).unwrap();
         let event_id_bytes = [3u8; 32];
         let event_id = EventId(event_id_bytes);
+        let properties = ReceiptProperties {
+            side_effects: Vec::new(),
+            deterministic: false,
+            input_cid: Cid::from_bytes(b"").unwrap(),
+            fuel_consumed: None,
+            execution_time_ms: 0,
+            peak_memory_bytes: 0,
+            host_abi_version: crate::abi::HOST_ABI_VERSION,
+            executor_config_hash: String::new(),
+            log_cid: None,
+        };
 
-        let receipt_result = issue_execution_receipt(&ctx, &module_cid, &result_cid, Some(&event_id));
+        let receipt_result = issue_execution_receipt(&ctx, &module_cid, &result_cid, ExecutionStatus::Success, Some(&event_id), &properties);
 
         assert!(receipt_result.is_ok());
         let receipt = receipt_result.unwrap();
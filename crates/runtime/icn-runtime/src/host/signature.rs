@@ -0,0 +1,81 @@
+//! Signature verification host function.
+//!
+//! `verify_signature` hands the DID, message and signature to the async
+//! `HostContext::verify_signature`, which host contexts implement with
+//! `verify_with_resolver` and the `PublicKeyResolver` of their choice.
+//! `DidKeyResolver` covers `did:key` DIDs, whose public key is part of the DID.
+
+use crate::abi::context::HostContext;
+use crate::engine::{ContextExtension, StoreData};
+use crate::host::guest::{charge, read_guest, read_guest_string, ERR_INVALID_ARGUMENT};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use icn_identity_core::did::DidKey;
+use icn_types::dag::{DagError, PublicKeyResolver};
+use icn_types::Did;
+use log::debug;
+use wasmtime::Caller;
+
+/// Fuel charged for a signature verification host call
+pub const SIGNATURE_CALL_FUEL: u64 = 5_000;
+/// Fuel charged per byte of the verified message
+pub const SIGNATURE_BYTE_FUEL: u64 = 1;
+
+/// Resolves `did:key` DIDs to the Ed25519 key they encode
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DidKeyResolver;
+
+impl PublicKeyResolver for DidKeyResolver {
+    fn resolve(&self, did: &Did) -> Result<VerifyingKey, DagError> {
+        DidKey::verifying_key_from_did(&did.to_string())
+            .map_err(|e| DagError::PublicKeyResolutionError(did.clone(), e.to_string()))
+    }
+}
+
+/// Whether `signature` is a valid Ed25519 signature of `message` by the key
+/// `resolver` returns for `did`
+pub fn verify_with_resolver(
+    resolver: &(dyn PublicKeyResolver + Send + Sync),
+    did: &Did,
+    message: &[u8],
+    signature: &[u8],
+) -> bool {
+    let Ok(signature) = Signature::from_slice(signature) else {
+        return false;
+    };
+    match resolver.resolve(did) {
+        Ok(key) => key.verify(message, &signature).is_ok(),
+        Err(e) => {
+            debug!("Cannot verify signature by {}: {}", did, e);
+            false
+        }
+    }
+}
+
+/// `verify_signature(did_ptr, did_len, msg_ptr, msg_len, sig_ptr, sig_len) -> i32`:
+/// 1 if the signature is valid for the DID and message, 0 if not, or a negative
+/// `guest` error code if an argument cannot be read or the DID cannot be parsed.
+pub async fn host_verify_signature<T>(
+    caller: &mut Caller<'_, StoreData<T>>,
+    did_ptr: i32,
+    did_len: i32,
+    msg_ptr: i32,
+    msg_len: i32,
+    sig_ptr: i32,
+    sig_len: i32,
+) -> anyhow::Result<i32>
+where
+    T: HostContext + ContextExtension + 'static,
+{
+    charge(caller, SIGNATURE_CALL_FUEL)?;
+    let args = (|| -> Result<_, i32> {
+        let did = Did::try_from(read_guest_string(caller, did_ptr, did_len)?).map_err(|_| ERR_INVALID_ARGUMENT)?;
+        Ok((did, read_guest(caller, msg_ptr, msg_len)?, read_guest(caller, sig_ptr, sig_len)?))
+    })();
+    let (did, message, signature) = match args {
+        Ok(args) => args,
+        Err(code) => return Ok(code),
+    };
+    charge(caller, SIGNATURE_BYTE_FUEL * message.len() as u64)?;
+    let ctx = caller.data().ctx.clone();
+    Ok(ctx.verify_signature(&did, &message, &signature).await as i32)
+}
@@ -9,19 +9,30 @@ pub mod config;
 pub mod policy;
 pub mod dag_processor;
 pub mod dag_indexing;
+pub mod block_store;
+pub mod verification;
+pub mod daemon;
 
 // Re-export the main executor types directly
 pub use engine::ModernWasmExecutor;
 pub use engine::ExecutionResult;
 pub use engine::ContextExtension;
 pub use engine::WasmExecutor;
+pub use engine::StoreData;
+pub use engine::ExecutionError;
+pub use engine::{ExecutionJournal, SideEffect};
+pub use engine::{ExecutionTrace, LogLevel, LogRecord};
+pub use engine::{ValidationIssue, ValidationPolicy, ValidationReport};
 
 // Other re-exports
-pub use host::receipt::{issue_execution_receipt, ReceiptError, ReceiptContextExt};
+pub use host::receipt::{issue_execution_receipt, ReceiptError, ReceiptContextExt, ReceiptProperties};
 pub use dag_anchor::{anchor_execution_receipt, AnchorError};
-pub use config::{RuntimeConfig, ExecutionConfig};
+pub use config::{RuntimeConfig, ExecutionConfig, ResourceLimits, DaemonConfig};
 pub use policy::{evaluate_policy, MembershipIndex, PolicyLoader, ScopeType};
 pub use dag_processor::{DagProcessor, ValidationResult};
+pub use block_store::{BlockStore, BlockStoreError, FsBlockStore, MemoryBlockStore};
+pub use verification::{ReceiptVerifier, Verdict, VerificationAttestation, VerificationError};
+pub use daemon::{DaemonError, JobRecord, RuntimeDaemon};
 
 /// Initialize runtime components (logging, etc.)
 pub fn init_runtime() {
@@ -1,4 +1,5 @@
 use icn_types::{Did, ScopePolicyConfig, PolicyError};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use std::any::Any;
@@ -44,7 +45,7 @@ pub struct DefaultMembershipIndex {
     did_to_communities: Arc<RwLock<HashMap<Did, Vec<String>>>>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ScopeType {
     Federation,
     Cooperative,
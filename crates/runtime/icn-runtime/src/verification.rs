@@ -0,0 +1,218 @@
+//! Re-execution based verification of execution receipts.
+//!
+//! A `ReceiptVerifier` checks a deterministic receipt by fetching its module and
+//! input from the block store, replaying the module, and comparing the result CID
+//! and fuel with the receipt's claims. The verdict is a `VerificationAttestation`
+//! that the verifier signs and anchors in the DAG under the
+//! `VerificationAttestation` label, where wallets and schedulers pick it up.
+//!
+//! Replays read the current DAG and ledger. A module whose output depends on state
+//! that changed since the original execution is disputed.
+
+use crate::abi::context::HostContext;
+use crate::block_store::{BlockStore, BlockStoreError};
+use crate::engine::{ContextExtension, ExecutionError, ModernWasmExecutor};
+use crate::host::receipt::ReceiptProperties;
+use icn_identity_core::did::DidKey;
+use icn_identity_core::vc::execution_receipt::{ExecutionReceipt, ExecutionReceiptError, ExecutionStatus};
+use icn_types::dag::{DagError, DagNodeBuilder, DagPayload, NodeScope, SharedDagStore, SignedDagNode};
+use icn_types::{Cid, Did};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// DAG label of anchored attestations
+pub const ATTESTATION_LABEL: &str = "VerificationAttestation";
+
+#[derive(Error, Debug)]
+pub enum VerificationError {
+    #[error("Receipt error: {0}")]
+    Receipt(#[from] ExecutionReceiptError),
+    #[error("Receipt signature is invalid")]
+    InvalidSignature,
+    #[error("Receipt cannot be re-executed: {0}")]
+    NotReproducible(String),
+    #[error("Invalid CID in receipt: {0}")]
+    InvalidCid(String),
+    #[error("Block {0} not found")]
+    BlockNotFound(Cid),
+    #[error("Block store error: {0}")]
+    BlockStore(#[from] BlockStoreError),
+    #[error("Replay failed: {0}")]
+    Replay(String),
+    #[error("DAG store error: {0}")]
+    Dag(#[from] DagError),
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// Whether a re-execution agrees with a receipt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// The replay reproduced the receipt's result and fuel
+    Concur,
+    /// The replay produced a different result or fuel, or failed
+    Dispute,
+}
+
+/// A verifier's verdict on an execution receipt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VerificationAttestation {
+    /// ID of the verified receipt
+    pub receipt_id: String,
+    /// CID of the verified receipt
+    pub receipt_cid: Cid,
+    /// DID of the node that issued the receipt
+    pub executor: String,
+    /// DID of the verifying node
+    pub verifier: Did,
+    pub module_cid: Cid,
+    pub input_cid: Cid,
+    /// Result CID claimed by the receipt
+    pub claimed_result_cid: Cid,
+    /// Result CID of the replay, if it completed
+    pub observed_result_cid: Option<Cid>,
+    /// Fuel claimed by the receipt
+    pub claimed_fuel: u64,
+    /// Fuel consumed by the replay, if it completed
+    pub observed_fuel: Option<u64>,
+    pub verdict: Verdict,
+    /// Why the verifier disputes the receipt
+    pub reason: Option<String>,
+    /// Unix timestamp of the verification
+    pub timestamp: u64,
+}
+
+/// Replays deterministic receipts and anchors signed attestations of the outcome
+pub struct ReceiptVerifier {
+    executor: ModernWasmExecutor,
+    blocks: Arc<dyn BlockStore + Send + Sync>,
+    dag: SharedDagStore,
+    key: DidKey,
+}
+
+impl ReceiptVerifier {
+    /// Create a verifier that fetches modules and inputs from `blocks` and anchors
+    /// attestations signed by `key` in `dag`
+    pub fn new(blocks: Arc<dyn BlockStore + Send + Sync>, dag: SharedDagStore, key: DidKey) -> anyhow::Result<Self> {
+        Ok(Self { executor: ModernWasmExecutor::deterministic()?, blocks, dag, key })
+    }
+
+    /// Re-execute a receipt's module in `ctx` and judge the receipt.
+    ///
+    /// Only signed receipts of successful deterministic executions can be verified;
+    /// others are rejected with `NotReproducible` rather than disputed.
+    pub async fn verify<T>(&self, receipt: &ExecutionReceipt, ctx: Arc<T>) -> Result<VerificationAttestation, VerificationError>
+    where
+        T: HostContext + ContextExtension + Send + Sync + 'static,
+    {
+        if !matches!(receipt.verify(), Ok(true)) {
+            return Err(VerificationError::InvalidSignature);
+        }
+        let subject = &receipt.credential_subject;
+        if subject.status != ExecutionStatus::Success {
+            return Err(VerificationError::NotReproducible(format!("status is {:?}", subject.status)));
+        }
+        let properties = ReceiptProperties::from_receipt(receipt)
+            .map_err(|e| VerificationError::NotReproducible(e.to_string()))?;
+        if !properties.deterministic {
+            return Err(VerificationError::NotReproducible("execution was not deterministic".to_string()));
+        }
+        let claimed_fuel = properties
+            .fuel_consumed
+            .ok_or_else(|| VerificationError::NotReproducible("fuel consumption not recorded".to_string()))?;
+        let module_cid = parse_cid(&subject.module_cid)?;
+        let input_cid = properties.input_cid;
+        let claimed_result_cid = parse_cid(&subject.result_cid)?;
+
+        let module = self.fetch(&module_cid)?;
+        let input = self.fetch(&input_cid)?;
+        debug!("Replaying module {} on input {} for receipt {}", module_cid, input_cid, receipt.id);
+        // The receipt's fuel is the budget: a faithful replay needs exactly that much
+        let replay = self
+            .executor
+            .replay(&module, ctx, module_cid.clone(), Some(&input), Some(claimed_fuel))
+            .await;
+
+        let (observed_result_cid, observed_fuel, reason) = match replay {
+            Ok(result) => {
+                let reason = if result.result_cid != claimed_result_cid {
+                    Some(format!("result CID {} differs from claimed {}", result.result_cid, claimed_result_cid))
+                } else if result.fuel_consumed != Some(claimed_fuel) {
+                    Some(format!("consumed {:?} fuel, receipt claims {}", result.fuel_consumed, claimed_fuel))
+                } else {
+                    None
+                };
+                (Some(result.result_cid), result.fuel_consumed, reason)
+            }
+            Err(e) => match e.downcast_ref::<ExecutionError>() {
+                Some(violation) => (None, None, Some(format!("replay stopped: {}", violation))),
+                None => (None, None, Some(format!("replay failed: {:#}", e))),
+            },
+        };
+        let verdict = match reason {
+            None => Verdict::Concur,
+            Some(ref reason) => {
+                warn!("Disputing receipt {}: {}", receipt.id, reason);
+                Verdict::Dispute
+            }
+        };
+
+        Ok(VerificationAttestation {
+            receipt_id: receipt.id.clone(),
+            receipt_cid: receipt.to_cid()?,
+            executor: subject.id.clone(),
+            verifier: self.key.did().clone(),
+            module_cid,
+            input_cid,
+            claimed_result_cid,
+            observed_result_cid,
+            claimed_fuel,
+            observed_fuel,
+            verdict,
+            reason,
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        })
+    }
+
+    /// Sign an attestation into a federation-scoped DAG node and anchor it
+    pub async fn anchor(&self, attestation: &VerificationAttestation, federation_id: &str) -> Result<Cid, VerificationError> {
+        let node = DagNodeBuilder::new()
+            .with_payload(DagPayload::Json(serde_json::to_value(attestation)?))
+            .with_parents(self.dag.get_tips().await?)
+            .with_author(self.key.did().clone())
+            .with_federation_id(federation_id.to_string())
+            .with_scope(NodeScope::Federation)
+            .with_label(ATTESTATION_LABEL.to_string())
+            .build()?;
+        let signature = self.key.sign(&node.canonical_bytes()?);
+        let cid = self.dag.add_node(SignedDagNode { node, signature, cid: None }).await?;
+        info!("Anchored {:?} attestation for receipt {} as {}", attestation.verdict, attestation.receipt_id, cid);
+        Ok(cid)
+    }
+
+    /// Verify a receipt and anchor the attestation in its issuer's federation
+    pub async fn verify_and_anchor<T>(
+        &self,
+        receipt: &ExecutionReceipt,
+        ctx: Arc<T>,
+    ) -> Result<(VerificationAttestation, Cid), VerificationError>
+    where
+        T: HostContext + ContextExtension + Send + Sync + 'static,
+    {
+        let attestation = self.verify(receipt, ctx).await?;
+        let cid = self.anchor(&attestation, &receipt.issuer).await?;
+        Ok((attestation, cid))
+    }
+
+    fn fetch(&self, cid: &Cid) -> Result<Vec<u8>, VerificationError> {
+        self.blocks.get(cid)?.ok_or_else(|| VerificationError::BlockNotFound(cid.clone()))
+    }
+}
+
+fn parse_cid(cid: &str) -> Result<Cid, VerificationError> {
+    cid.parse().map_err(|_| VerificationError::InvalidCid(cid.to_string()))
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use common::{module_cid, TestContext};
+use icn_ccl_compiler::compile_proposals;
+use icn_runtime::config::ExecutionConfig;
+use icn_runtime::ModernWasmExecutor;
+use icn_types::dag::memory::MemoryDagStore;
+use icn_types::dag::{DagPayload, SharedDagStore};
+use icn_types::Cid;
+use std::sync::Arc;
+
+fn proposal(cid: &str) -> String {
+    format!(
+        r#"
+        proposal "Archive the minutes" {{
+            threshold = majority
+            duration  = 2d
+            scope     = "coop-x"
+            on_pass {{
+                for i in 0..2 {{
+                    log("archiving")
+                }}
+                anchor_data {{
+                    cid   = "{}"
+                    bytes = 4096
+                }}
+            }}
+        }}
+        "#,
+        cid
+    )
+}
+
+#[tokio::test]
+async fn test_compiled_on_pass_runs_in_runtime() {
+    let minutes = Cid::from_bytes(b"minutes").unwrap();
+    let proposal = compile_proposals(&proposal(&minutes.to_string())).expect("CCL should compile").remove(0);
+    let wasm = proposal.on_pass.wasm;
+    let mut ctx = TestContext::with_config(ExecutionConfig {
+        auto_issue_receipts: false,
+        anchor_receipts: false,
+        receipt_export_dir: None,
+        trace_host_calls: true,
+        ..ExecutionConfig::default()
+    });
+    let dag = SharedDagStore::new(Box::new(MemoryDagStore::new()));
+    ctx.dag = Some(dag.clone());
+    let ctx = Arc::new(ctx);
+
+    let result = ModernWasmExecutor::new()
+        .unwrap()
+        .execute(&wasm, ctx.clone(), module_cid(&wasm), None, None, Some(1_000_000))
+        .await
+        .expect("Execution failed");
+
+    assert_eq!(
+        *ctx.logs.lock().unwrap(),
+        vec!["archiving".to_string(), "archiving".to_string(), format!("AnchorData for cid: {}", minutes)]
+    );
+    let calls: Vec<&str> = result.trace.host_calls.iter().map(|call| call.name.as_str()).collect();
+    assert_eq!(calls, vec!["host_log_message", "host_log_message", "host_log_message", "host_anchor_to_dag"]);
+    assert_eq!(result.trace.host_calls[3].args[2], 4096);
+
+    // The statement stages a node referencing the CID, committed with the execution
+    let nodes = dag.get_ordered_nodes().await.unwrap();
+    assert_eq!(nodes.len(), 1);
+    assert_eq!(nodes[0].node.payload, DagPayload::Reference(minutes));
+    assert_eq!(result.side_effects, vec![nodes[0].calculate_cid().unwrap()]);
+}
+
+#[tokio::test]
+async fn test_anchor_data_with_an_invalid_cid_traps() {
+    let wasm = compile_proposals(&proposal("not-a-cid")).expect("CCL should compile").remove(0).on_pass.wasm;
+    let mut ctx = TestContext::new();
+    ctx.dag = Some(SharedDagStore::new(Box::new(MemoryDagStore::new())));
+
+    let result = ModernWasmExecutor::new()
+        .unwrap()
+        .execute(&wasm, Arc::new(ctx), module_cid(&wasm), None, None, None)
+        .await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,168 @@
+//! Shared test context for executor integration tests.
+#![allow(dead_code)]
+
+use icn_identity_core::did::DidKey;
+use icn_runtime::abi::context::HostContext;
+use icn_runtime::block_store::{BlockStore, MemoryBlockStore};
+use icn_runtime::config::ExecutionConfig;
+use icn_runtime::engine::ContextExtension;
+use icn_runtime::host::signature::{verify_with_resolver, DidKeyResolver};
+use icn_runtime::policy::{MembershipIndex, PolicyLoader, ScopeType};
+use icn_economics::{InMemoryTokenStore, TokenStore};
+use icn_types::dag::{DagStore, SharedDagStore};
+use icn_types::Did;
+use std::sync::{Arc, Mutex};
+
+/// Host context that records logs and keeps outputs in memory
+#[derive(Clone)]
+pub struct TestContext {
+    pub config: ExecutionConfig,
+    pub logs: Arc<Mutex<Vec<String>>>,
+    pub error: Arc<Mutex<Option<String>>>,
+    pub caller: Did,
+    pub node: Did,
+    pub federation: DidKey,
+    pub scope: Option<ScopeType>,
+    pub scope_id: Option<String>,
+    pub blocks: Arc<MemoryBlockStore>,
+    pub dag: Option<SharedDagStore>,
+    pub policy: Option<Arc<dyn PolicyLoader + Send + Sync>>,
+    pub tokens: Option<Arc<InMemoryTokenStore>>,
+}
+
+impl TestContext {
+    /// Context with receipts disabled, so executions need no federation identity
+    pub fn new() -> Self {
+        let config = ExecutionConfig {
+            auto_issue_receipts: false,
+            anchor_receipts: false,
+            receipt_export_dir: None,
+            ..ExecutionConfig::default()
+        };
+        Self::with_config(config)
+    }
+
+    pub fn with_config(config: ExecutionConfig) -> Self {
+        Self {
+            config,
+            logs: Arc::new(Mutex::new(Vec::new())),
+            error: Arc::new(Mutex::new(None)),
+            caller: DidKey::new().did().clone(),
+            node: DidKey::new().did().clone(),
+            federation: DidKey::new(),
+            scope: None,
+            scope_id: None,
+            blocks: Arc::new(MemoryBlockStore::new()),
+            dag: None,
+            policy: None,
+            tokens: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl HostContext for TestContext {
+    fn read_string(&self, _caller: &mut impl wasmtime::AsContextMut, _ptr: i32, _len: i32) -> anyhow::Result<String> {
+        unimplemented!("TestContext::read_string")
+    }
+
+    fn write_string(&self, _caller: &mut impl wasmtime::AsContextMut, _ptr: i32, _max_len: i32, _s: &str) -> anyhow::Result<i32> {
+        unimplemented!("TestContext::write_string")
+    }
+
+    fn malloc(&self, _caller: &mut impl wasmtime::AsContextMut, _size: i32) -> anyhow::Result<i32> {
+        unimplemented!("TestContext::malloc")
+    }
+
+    fn free(&self, _caller: &mut impl wasmtime::AsContextMut, _ptr: i32) -> anyhow::Result<()> {
+        unimplemented!("TestContext::free")
+    }
+
+    fn get_caller_did(&self) -> Did {
+        self.caller.clone()
+    }
+
+    fn log_message(&self, message: &str) {
+        self.logs.lock().unwrap().push(message.to_string());
+    }
+
+    async fn verify_signature(&self, did: &Did, message: &[u8], signature: &[u8]) -> bool {
+        verify_with_resolver(&DidKeyResolver, did, message, signature)
+    }
+
+    fn set_error(&self, message: String) {
+        *self.error.lock().unwrap() = Some(message);
+    }
+
+    fn get_error(&self) -> Option<String> {
+        self.error.lock().unwrap().clone()
+    }
+
+    fn clear_error(&self) {
+        *self.error.lock().unwrap() = None;
+    }
+
+    fn policy_loader(&self) -> Option<Arc<dyn PolicyLoader + Send + Sync>> {
+        self.policy.clone()
+    }
+
+    fn membership_index(&self) -> Option<Arc<dyn MembershipIndex + Send + Sync>> {
+        None
+    }
+}
+
+impl ContextExtension for TestContext {
+    fn get_execution_config(&self) -> &ExecutionConfig {
+        &self.config
+    }
+
+    fn get_dag_store_mut(&mut self) -> Option<&mut (dyn DagStore + Send + Sync)> {
+        None
+    }
+
+    fn node_did(&self) -> Option<&Did> {
+        Some(&self.node)
+    }
+
+    fn federation_did(&self) -> Option<&Did> {
+        Some(self.federation.did())
+    }
+
+    fn caller_did(&self) -> Option<&Did> {
+        Some(&self.caller)
+    }
+
+    fn federation_keypair(&self) -> Option<DidKey> {
+        Some(self.federation.clone())
+    }
+
+    fn execution_scope(&self) -> Option<ScopeType> {
+        self.scope.clone()
+    }
+
+    fn execution_scope_id(&self) -> Option<String> {
+        self.scope_id.clone()
+    }
+
+    fn dag_store(&self) -> Option<SharedDagStore> {
+        self.dag.clone()
+    }
+
+    fn block_store(&self) -> Option<Arc<dyn BlockStore + Send + Sync>> {
+        Some(self.blocks.clone())
+    }
+
+    fn token_store(&self) -> Option<Arc<dyn TokenStore>> {
+        self.tokens.clone().map(|tokens| tokens as Arc<dyn TokenStore>)
+    }
+}
+
+/// Parse a WAT module for a test
+pub fn wasm(wat: &str) -> Vec<u8> {
+    wat::parse_str(wat).expect("Failed to parse WAT")
+}
+
+/// Placeholder CID for test modules
+pub fn module_cid(wasm_bytes: &[u8]) -> icn_types::Cid {
+    icn_types::Cid::from_bytes(wasm_bytes).unwrap()
+}
@@ -0,0 +1,171 @@
+mod common;
+
+use common::{wasm, TestContext};
+use icn_runtime::config::DaemonConfig;
+use icn_runtime::daemon::{api, DaemonError, JobPayload, JobQueue, JobRecord, RuntimeDaemon};
+use icn_runtime::engine::ModernWasmExecutor;
+use hyper::{Body, Request, StatusCode};
+use planetary_mesh::JobStatus;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Writes its input back as output
+const ECHO: &str = r#"
+    (module
+      (import "env" "get_input_len" (func $get_input_len (result i32)))
+      (import "env" "read_input" (func $read_input (param i32 i32) (result i32)))
+      (import "env" "write_output" (func $write_output (param i32 i32) (result i32)))
+      (memory (export "memory") 1)
+      (func (export "_start")
+        (local $len i32)
+        (local.set $len (call $get_input_len))
+        (drop (call $read_input (i32.const 0) (local.get $len)))
+        (drop (call $write_output (i32.const 0) (local.get $len)))
+      )
+    )
+"#;
+
+fn daemon(dir: &tempfile::TempDir) -> Arc<RuntimeDaemon<TestContext>> {
+    let config = DaemonConfig {
+        queue_path: dir.path().join("jobs"),
+        max_concurrent_jobs: 2,
+        ..DaemonConfig::default()
+    };
+    let executor = Arc::new(ModernWasmExecutor::new().unwrap());
+    RuntimeDaemon::new(&config, executor, Arc::new(TestContext::new())).expect("Failed to open daemon")
+}
+
+async fn wait_until_finished(daemon: &RuntimeDaemon<TestContext>, id: &str) -> JobRecord {
+    for _ in 0..200 {
+        let record = daemon.status(id).unwrap();
+        if record.is_finished() {
+            return record;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("Job {} did not finish", id);
+}
+
+#[tokio::test]
+async fn test_submitted_jobs_run_to_completion() {
+    let dir = tempfile::tempdir().unwrap();
+    let daemon = daemon(&dir);
+    tokio::spawn(daemon.clone().run());
+
+    let jobs: Vec<JobRecord> = (0..4)
+        .map(|i| daemon.submit(wasm(ECHO), format!("job-{}", i).into_bytes(), Some(1_000_000)).unwrap())
+        .collect();
+
+    for (i, job) in jobs.iter().enumerate() {
+        assert_eq!(job.status, JobStatus::Submitted);
+        let record = wait_until_finished(&daemon, &job.id).await;
+        assert_eq!(record.status, JobStatus::Completed);
+        assert!(record.result_cid.is_some());
+        assert!(record.fuel_consumed.unwrap() > 0);
+        assert_eq!(daemon.result(&job.id).unwrap(), format!("job-{}", i).into_bytes());
+    }
+    assert_eq!(daemon.list().unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn test_failing_job_records_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let daemon = daemon(&dir);
+    tokio::spawn(daemon.clone().run());
+
+    let job = daemon
+        .submit(wasm(r#"(module (func (export "_start") unreachable))"#), Vec::new(), None)
+        .unwrap();
+    let record = wait_until_finished(&daemon, &job.id).await;
+
+    assert_eq!(record.status, JobStatus::Failed);
+    assert!(record.error.is_some());
+    assert!(matches!(daemon.result(&job.id), Err(DaemonError::NotCompleted(_))));
+}
+
+#[tokio::test]
+async fn test_canceled_job_never_runs() {
+    let dir = tempfile::tempdir().unwrap();
+    let daemon = daemon(&dir);
+
+    let job = daemon.submit(wasm(ECHO), b"never".to_vec(), None).unwrap();
+    let canceled = daemon.cancel(&job.id).unwrap();
+    assert_eq!(canceled.status, JobStatus::Canceled);
+    assert!(matches!(daemon.cancel(&job.id), Err(DaemonError::AlreadyFinished(_))));
+
+    tokio::spawn(daemon.clone().run());
+    let next = daemon.submit(wasm(ECHO), Vec::new(), None).unwrap();
+    wait_until_finished(&daemon, &next.id).await;
+
+    assert_eq!(daemon.status(&job.id).unwrap().status, JobStatus::Canceled);
+}
+
+#[test]
+fn test_interrupted_jobs_are_requeued_on_open() {
+    let dir = tempfile::tempdir().unwrap();
+    let payload = JobPayload { module: wasm(ECHO), input: b"again".to_vec() };
+    let id = {
+        let queue = JobQueue::open(dir.path()).unwrap();
+        let record = queue.enqueue("module".to_string(), &payload, None).unwrap();
+        let (record, _) = queue.next_pending().unwrap().unwrap();
+        queue.update(&JobRecord { status: JobStatus::Running, ..record }).unwrap();
+        assert!(queue.next_pending().unwrap().is_none());
+        record.id
+    };
+
+    let queue = JobQueue::open(dir.path()).unwrap();
+    let (record, requeued) = queue.next_pending().unwrap().expect("Job was not requeued");
+    assert_eq!(record.id, id);
+    assert_eq!(record.status, JobStatus::Submitted);
+    assert_eq!(requeued.input, b"again");
+}
+
+#[tokio::test]
+async fn test_api_routes() {
+    let dir = tempfile::tempdir().unwrap();
+    let daemon = daemon(&dir);
+
+    let submission = serde_json::json!({ "module": base64_encode(&wasm(ECHO)), "input": "aWNu" });
+    let response = api::handle(&daemon, post("/jobs", submission.to_string())).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let job: JobRecord = serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await.unwrap()).unwrap();
+
+    let response = api::handle(&daemon, get(&format!("/jobs/{}", job.id))).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = api::handle(&daemon, get(&format!("/jobs/{}/result", job.id))).await;
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = api::handle(&daemon, post(&format!("/jobs/{}/cancel", job.id), String::new())).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = api::handle(&daemon, get("/jobs/unknown")).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = api::handle(&daemon, post("/jobs", "{\"module\": \"not base64!\"}".to_string())).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_oversized_submission_is_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let daemon = daemon(&dir);
+
+    let body = "a".repeat(api::MAX_REQUEST_BYTES + 1);
+    let response = api::handle(&daemon, post("/jobs", body)).await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert!(daemon.list().unwrap().is_empty());
+}
+
+fn get(path: &str) -> Request<Body> {
+    Request::get(path).body(Body::empty()).unwrap()
+}
+
+fn post(path: &str, body: String) -> Request<Body> {
+    Request::post(path).body(Body::from(body)).unwrap()
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    use base64::Engine as _;
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
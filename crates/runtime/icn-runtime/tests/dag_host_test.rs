@@ -0,0 +1,207 @@
+mod common;
+
+use common::{module_cid, wasm, TestContext};
+use icn_identity_core::did::DidKey;
+use icn_runtime::engine::{ExecutionError, ModernWasmExecutor, EXECUTE_MODULE_ACTION};
+use icn_runtime::host::dag::{
+    DAG_CALL_FUEL, DAG_ERR_NOT_FOUND, DAG_ERR_UNAUTHORIZED, DAG_ERR_UNAVAILABLE, DAG_READ_ACTION,
+};
+use icn_runtime::policy::{DefaultPolicyLoader, ScopeType};
+use icn_types::dag::memory::MemoryDagStore;
+use icn_types::dag::{DagNode, DagNodeBuilder, DagPayload, NodeScope, SharedDagStore, SignedDagNode};
+use icn_types::{Cid, PolicyRule, ScopePolicyConfig};
+use std::sync::Arc;
+
+/// Calls the 4-argument DAG function `name` with the input as its first argument
+/// and writes the 4-byte return code followed by any result bytes to the output
+fn call_module(name: &str) -> Vec<u8> {
+    wasm(&format!(
+        r#"(module
+             (import "env" "get_input_len" (func $input_len (result i32)))
+             (import "env" "read_input" (func $read_input (param i32 i32) (result i32)))
+             (import "env" "write_output" (func $write_output (param i32 i32) (result i32)))
+             (import "env" "{name}" (func $call (param i32 i32 i32 i32) (result i32)))
+             (memory (export "memory") 1)
+             (func (export "_start")
+               (local $len i32) (local $ret i32)
+               (local.set $len (call $input_len))
+               (drop (call $read_input (i32.const 0) (local.get $len)))
+               (local.set $ret (call $call (i32.const 0) (local.get $len) (i32.const 1024) (i32.const 8192)))
+               (i32.store (i32.const 512) (local.get $ret))
+               (drop (call $write_output (i32.const 512) (i32.const 4)))
+               (if (i32.gt_s (local.get $ret) (i32.const 0))
+                 (then (drop (call $write_output (i32.const 1024) (local.get $ret))))))
+           )"#
+    ))
+}
+
+/// Anchors the input under the label "tally" and outputs the return code and CID
+const ANCHOR: &str = r#"
+    (module
+      (import "env" "get_input_len" (func $input_len (result i32)))
+      (import "env" "read_input" (func $read_input (param i32 i32) (result i32)))
+      (import "env" "write_output" (func $write_output (param i32 i32) (result i32)))
+      (import "env" "dag_anchor" (func $anchor (param i32 i32 i32 i32 i32 i32) (result i32)))
+      (memory (export "memory") 1)
+      (data (i32.const 256) "tally")
+      (func (export "_start")
+        (local $len i32) (local $ret i32)
+        (local.set $len (call $input_len))
+        (drop (call $read_input (i32.const 0) (local.get $len)))
+        (local.set $ret (call $anchor (i32.const 0) (local.get $len) (i32.const 256) (i32.const 5)
+                                      (i32.const 1024) (i32.const 8192)))
+        (i32.store (i32.const 512) (local.get $ret))
+        (drop (call $write_output (i32.const 512) (i32.const 4)))
+        (if (i32.gt_s (local.get $ret) (i32.const 0))
+          (then (drop (call $write_output (i32.const 1024) (local.get $ret))))))
+    )
+"#;
+
+async fn run(ctx: &TestContext, module: &[u8], input: &[u8]) -> (i32, Vec<u8>) {
+    let executor = ModernWasmExecutor::new().expect("Failed to create executor");
+    let result = executor
+        .execute(module, Arc::new(ctx.clone()), module_cid(module), None, Some(input), None)
+        .await
+        .expect("Execution failed");
+    let code = i32::from_le_bytes(result.output[..4].try_into().unwrap());
+    (code, result.output[4..].to_vec())
+}
+
+/// Context for a run in cooperative "coop-a" with a fresh in-memory DAG
+fn coop_context() -> TestContext {
+    let mut ctx = TestContext::new();
+    ctx.scope = Some(ScopeType::Cooperative);
+    ctx.scope_id = Some("coop-a".to_string());
+    ctx.dag = Some(SharedDagStore::new(Box::new(MemoryDagStore::new())));
+    ctx
+}
+
+async fn seed(ctx: &TestContext, scope_id: &str, payload: DagPayload, label: &str) -> Cid {
+    let key = DidKey::new();
+    let node = DagNodeBuilder::new()
+        .with_payload(payload)
+        .with_author(key.did().clone())
+        .with_federation_id("test-federation".to_string())
+        .with_scope(NodeScope::Cooperative)
+        .with_scope_id(scope_id.to_string())
+        .with_label(label.to_string())
+        .build()
+        .unwrap();
+    let signature = key.sign(&node.canonical_bytes().unwrap());
+    ctx.dag.as_ref().unwrap().add_node(SignedDagNode { node, signature, cid: None }).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_reads_node_and_payload_in_scope() {
+    let ctx = coop_context();
+    let cid = seed(&ctx, "coop-a", DagPayload::Raw(b"proposal-1".to_vec()), "proposal").await;
+
+    let (code, payload) = run(&ctx, &call_module("dag_get_payload"), cid.to_string().as_bytes()).await;
+    assert_eq!(code, 10);
+    assert_eq!(payload, b"proposal-1");
+
+    let (_, node_json) = run(&ctx, &call_module("dag_get_node"), cid.to_string().as_bytes()).await;
+    let node: DagNode = serde_json::from_slice(&node_json).unwrap();
+    assert_eq!(node, ctx.dag.as_ref().unwrap().get_node(&cid).await.unwrap().node);
+}
+
+#[tokio::test]
+async fn test_nodes_outside_scope_are_not_visible() {
+    let ctx = coop_context();
+    let foreign = seed(&ctx, "coop-b", DagPayload::Raw(b"secret".to_vec()), "proposal").await;
+    let own = seed(&ctx, "coop-a", DagPayload::Raw(b"public".to_vec()), "proposal").await;
+
+    let (code, _) = run(&ctx, &call_module("dag_get_payload"), foreign.to_string().as_bytes()).await;
+    assert_eq!(code, DAG_ERR_NOT_FOUND);
+
+    let (_, cids) = run(&ctx, &call_module("dag_query_by_label"), b"proposal").await;
+    let cids: Vec<String> = serde_json::from_slice(&cids).unwrap();
+    assert_eq!(cids, vec![own.to_string()]);
+}
+
+#[tokio::test]
+async fn test_anchor_writes_signed_node_into_scope() {
+    let ctx = coop_context();
+    let parent = seed(&ctx, "coop-a", DagPayload::Json(serde_json::json!({"votes": 3})), "vote").await;
+
+    let (code, cid) = run(&ctx, &wasm(ANCHOR), b"yes=3").await;
+    assert!(code > 0, "Anchor failed with {}", code);
+    let cid: Cid = String::from_utf8(cid).unwrap().parse().unwrap();
+
+    let anchored = ctx.dag.as_ref().unwrap().get_node(&cid).await.unwrap();
+    assert_eq!(anchored.node.payload, DagPayload::Raw(b"yes=3".to_vec()));
+    assert_eq!(anchored.node.author, ctx.federation.did().clone());
+    assert_eq!(anchored.node.metadata.scope, NodeScope::Cooperative);
+    assert_eq!(anchored.node.metadata.scope_id.as_deref(), Some("coop-a"));
+    assert_eq!(anchored.node.metadata.label.as_deref(), Some("tally"));
+    assert!(anchored.node.parents.contains(&parent));
+    ctx.federation
+        .verify(&anchored.node.canonical_bytes().unwrap(), &anchored.signature)
+        .expect("Anchored node should be signed by the federation key");
+
+    let (_, cids) = run(&ctx, &call_module("dag_query_by_label"), b"tally").await;
+    let cids: Vec<String> = serde_json::from_slice(&cids).unwrap();
+    assert_eq!(cids, vec![cid.to_string()]);
+}
+
+#[tokio::test]
+async fn test_policy_gates_dag_access() {
+    let mut ctx = coop_context();
+    let cid = seed(&ctx, "coop-a", DagPayload::Raw(b"proposal".to_vec()), "proposal").await;
+
+    let policy = DefaultPolicyLoader::new();
+    policy.set_policy(ScopePolicyConfig {
+        scope_type: NodeScope::Cooperative,
+        scope_id: "coop-a".to_string(),
+        allowed_actions: vec![
+            PolicyRule {
+                action_type: EXECUTE_MODULE_ACTION.to_string(),
+                required_membership: None,
+                allowed_dids: None,
+            },
+            PolicyRule {
+                action_type: DAG_READ_ACTION.to_string(),
+                required_membership: None,
+                allowed_dids: Some(vec![ctx.caller.clone()]),
+            },
+        ],
+    });
+    ctx.policy = Some(Arc::new(policy));
+
+    let (code, _) = run(&ctx, &call_module("dag_get_payload"), cid.to_string().as_bytes()).await;
+    assert_eq!(code, 8);
+
+    // The policy allows reads by the caller only, and no anchoring at all
+    let (code, _) = run(&ctx, &wasm(ANCHOR), b"yes=3").await;
+    assert_eq!(code, DAG_ERR_UNAUTHORIZED);
+    ctx.caller = DidKey::new().did().clone();
+    let (code, _) = run(&ctx, &call_module("dag_get_payload"), cid.to_string().as_bytes()).await;
+    assert_eq!(code, DAG_ERR_UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_dag_calls_without_store_are_unavailable() {
+    let mut ctx = coop_context();
+    ctx.dag = None;
+    let (code, _) = run(&ctx, &call_module("dag_query_by_label"), b"proposal").await;
+    assert_eq!(code, DAG_ERR_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_dag_calls_are_metered() {
+    let ctx = coop_context();
+    let module = call_module("dag_query_by_label");
+    let executor = ModernWasmExecutor::new().expect("Failed to create executor");
+
+    let result = executor
+        .execute(&module, Arc::new(ctx.clone()), module_cid(&module), None, Some(b"proposal"), None)
+        .await
+        .expect("Execution failed");
+    assert!(result.fuel_consumed.unwrap() >= DAG_CALL_FUEL);
+
+    let error = executor
+        .execute(&module, Arc::new(ctx), module_cid(&module), None, Some(b"proposal"), Some(DAG_CALL_FUEL / 2))
+        .await
+        .expect_err("Host call should exhaust the fuel");
+    assert_eq!(error.downcast_ref::<ExecutionError>(), Some(&ExecutionError::OutOfFuel(DAG_CALL_FUEL / 2)));
+}
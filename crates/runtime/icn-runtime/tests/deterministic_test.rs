@@ -0,0 +1,90 @@
+mod common;
+
+use common::{module_cid, wasm, TestContext};
+use icn_identity_core::vc::execution_receipt::ExecutionReceipt;
+use icn_runtime::block_store::{block_cid, BlockStore};
+use icn_runtime::config::ExecutionConfig;
+use icn_runtime::engine::{ExecutionResult, ModernWasmExecutor};
+use std::sync::Arc;
+
+/// Writes two clock readings followed by 32 random bytes and a NaN-producing division
+const CLOCK_AND_RANDOM: &str = r#"
+    (module
+      (import "env" "clock_now_ms" (func $now (result i64)))
+      (import "env" "random_bytes" (func $random (param i32 i32) (result i32)))
+      (import "env" "write_output" (func $write_output (param i32 i32) (result i32)))
+      (memory (export "memory") 1)
+      (func (export "_start")
+        (i64.store (i32.const 0) (call $now))
+        (i64.store (i32.const 8) (call $now))
+        (drop (call $random (i32.const 16) (i32.const 32)))
+        (f32.store (i32.const 48) (f32.div (f32.const 0) (f32.const 0)))
+        (drop (call $write_output (i32.const 0) (i32.const 52))))
+    )
+"#;
+
+async fn run(executor: &ModernWasmExecutor, ctx: TestContext, input: &[u8]) -> ExecutionResult {
+    let module = wasm(CLOCK_AND_RANDOM);
+    executor
+        .execute(&module, Arc::new(ctx), module_cid(&module), None, Some(input), None)
+        .await
+        .expect("Execution failed")
+}
+
+#[tokio::test]
+async fn test_deterministic_runs_are_identical() {
+    let first = run(&ModernWasmExecutor::deterministic().unwrap(), TestContext::new(), b"job-1").await;
+    let second = run(&ModernWasmExecutor::deterministic().unwrap(), TestContext::new(), b"job-1").await;
+
+    assert!(first.deterministic);
+    assert_eq!(first.output, second.output);
+    assert_eq!(first.result_cid, second.result_cid);
+    assert_eq!(first.fuel_consumed, second.fuel_consumed);
+
+    // The seeded clock starts at zero and ticks on every read
+    let now = |at: usize| u64::from_le_bytes(first.output[at..at + 8].try_into().unwrap());
+    assert_eq!((now(0), now(8)), (1, 2));
+    // NaNs are canonicalized
+    assert_eq!(&first.output[48..52], &0x7fc0_0000u32.to_le_bytes());
+}
+
+#[tokio::test]
+async fn test_seed_depends_on_input() {
+    let executor = ModernWasmExecutor::deterministic().unwrap();
+    let first = run(&executor, TestContext::new(), b"job-1").await;
+    let second = run(&executor, TestContext::new(), b"job-2").await;
+    assert_ne!(first.output[16..48], second.output[16..48]);
+}
+
+#[tokio::test]
+async fn test_default_executor_uses_system_clock() {
+    let executor = ModernWasmExecutor::new().unwrap();
+    assert!(!executor.is_deterministic());
+    let result = run(&executor, TestContext::new(), b"job-1").await;
+    assert!(!result.deterministic);
+    let now = u64::from_le_bytes(result.output[0..8].try_into().unwrap());
+    assert!(now > 1_600_000_000_000, "Expected a Unix timestamp, got {}", now);
+}
+
+#[tokio::test]
+async fn test_receipt_records_determinism_and_input() {
+    let receipts_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let ctx = TestContext::with_config(ExecutionConfig {
+        auto_issue_receipts: true,
+        anchor_receipts: false,
+        receipt_export_dir: Some(receipts_dir.path().to_path_buf()),
+        ..ExecutionConfig::default()
+    });
+    let blocks = ctx.blocks.clone();
+
+    let result = run(&ModernWasmExecutor::deterministic().unwrap(), ctx, b"job-1").await;
+    assert_eq!(result.input_cid, block_cid(b"job-1").unwrap());
+    assert_eq!(blocks.get(&result.input_cid).unwrap().as_deref(), Some(&b"job-1"[..]));
+
+    let entries: Vec<_> = std::fs::read_dir(receipts_dir.path()).unwrap().collect();
+    let receipt: ExecutionReceipt =
+        serde_json::from_slice(&std::fs::read(entries[0].as_ref().unwrap().path()).unwrap()).unwrap();
+    let properties = receipt.credential_subject.additional_properties.unwrap();
+    assert_eq!(properties["deterministic"], true);
+    assert_eq!(properties["input_cid"], result.input_cid.to_string());
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use common::{module_cid, wasm, TestContext};
+use icn_identity_core::did::DidKey;
+use icn_identity_core::vc::execution_receipt::{ExecutionReceipt, ExecutionStatus};
+use icn_runtime::block_store::{block_cid, BlockStore};
+use icn_runtime::config::ExecutionConfig;
+use icn_runtime::engine::{ExecutionError, ModernWasmExecutor, EXECUTE_MODULE_ACTION};
+use icn_runtime::policy::{DefaultPolicyLoader, ScopeType};
+use icn_types::dag::NodeScope;
+use icn_types::{PolicyRule, ScopePolicyConfig};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Writes a single byte of output
+const PING: &str = r#"
+    (module
+      (import "env" "write_output" (func $write_output (param i32 i32) (result i32)))
+      (memory (export "memory") 1)
+      (data (i32.const 0) "!")
+      (func (export "_start")
+        (drop (call $write_output (i32.const 0) (i32.const 1))))
+    )
+"#;
+
+/// Context in "coop-a" whose policy lets only the test caller execute modules
+fn coop_context(receipts_dir: &Path) -> TestContext {
+    let mut ctx = TestContext::with_config(ExecutionConfig {
+        auto_issue_receipts: true,
+        anchor_receipts: false,
+        receipt_export_dir: Some(receipts_dir.to_path_buf()),
+        ..ExecutionConfig::default()
+    });
+    ctx.scope = Some(ScopeType::Cooperative);
+    ctx.scope_id = Some("coop-a".to_string());
+    let policy = DefaultPolicyLoader::new();
+    policy.set_policy(ScopePolicyConfig {
+        scope_type: NodeScope::Cooperative,
+        scope_id: "coop-a".to_string(),
+        allowed_actions: vec![PolicyRule {
+            action_type: EXECUTE_MODULE_ACTION.to_string(),
+            required_membership: None,
+            allowed_dids: Some(vec![ctx.caller.clone()]),
+        }],
+    });
+    ctx.policy = Some(Arc::new(policy));
+    ctx
+}
+
+async fn execute(ctx: TestContext) -> anyhow::Result<icn_runtime::ExecutionResult> {
+    let module = wasm(PING);
+    ModernWasmExecutor::new()
+        .unwrap()
+        .execute(&module, Arc::new(ctx), module_cid(&module), None, None, None)
+        .await
+}
+
+fn receipts(dir: &Path) -> Vec<ExecutionReceipt> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| serde_json::from_slice(&std::fs::read(entry.unwrap().path()).unwrap()).unwrap())
+        .collect()
+}
+
+fn denial(result: anyhow::Result<icn_runtime::ExecutionResult>) -> ExecutionError {
+    let error = result.expect_err("Execution should be denied");
+    error.downcast_ref::<ExecutionError>().cloned().unwrap_or_else(|| panic!("Untyped error: {:?}", error))
+}
+
+#[tokio::test]
+async fn test_authorized_caller_executes() {
+    let dir = tempfile::tempdir().unwrap();
+    let result = execute(coop_context(dir.path())).await.expect("Execution failed");
+
+    assert_eq!(result.output, b"!");
+    assert_eq!(receipts(dir.path())[0].credential_subject.status, ExecutionStatus::Success);
+}
+
+#[tokio::test]
+async fn test_unauthorized_caller_is_denied_with_receipt() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut ctx = coop_context(dir.path());
+    ctx.caller = DidKey::new().did().clone();
+    let blocks = ctx.blocks.clone();
+
+    assert!(matches!(denial(execute(ctx).await), ExecutionError::Denied(_)));
+
+    let receipts = receipts(dir.path());
+    assert_eq!(receipts.len(), 1);
+    assert_eq!(receipts[0].credential_subject.status, ExecutionStatus::Denied);
+    assert!(receipts[0].verify().unwrap());
+    // The module never ran, so its output was never stored
+    assert_eq!(blocks.get(&block_cid(b"!").unwrap()).unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_module_allowlist_restricts_scope() {
+    let dir = tempfile::tempdir().unwrap();
+    let module = wasm(PING);
+
+    let mut ctx = coop_context(dir.path());
+    ctx.config.module_allowlist.insert("coop-a".to_string(), vec![module_cid(&module).to_string()]);
+    execute(ctx).await.expect("Allowlisted module should run");
+
+    let mut ctx = coop_context(dir.path());
+    ctx.config.module_allowlist.insert("coop-a".to_string(), vec![module_cid(b"other").to_string()]);
+    assert!(matches!(denial(execute(ctx).await), ExecutionError::Denied(_)));
+
+    // Allowlists of other scopes do not apply
+    let mut ctx = coop_context(dir.path());
+    ctx.config.module_allowlist.insert("coop-b".to_string(), Vec::new());
+    execute(ctx).await.expect("Other scopes' allowlists should not apply");
+}
+
+#[tokio::test]
+async fn test_module_must_match_its_claimed_cid() {
+    let dir = tempfile::tempdir().unwrap();
+    let allowed = wasm(PING);
+    let other = wasm(r#"(module (func (export "_start")))"#);
+
+    // Naming an allowlisted CID does not let other code run under it
+    let mut ctx = coop_context(dir.path());
+    ctx.config.module_allowlist.insert("coop-a".to_string(), vec![module_cid(&allowed).to_string()]);
+    let result = ModernWasmExecutor::new()
+        .unwrap()
+        .execute(&other, Arc::new(ctx), module_cid(&allowed), None, None, None)
+        .await;
+
+    assert!(matches!(denial(result), ExecutionError::Denied(reason) if reason.contains("not the claimed")));
+    let receipts = receipts(dir.path());
+    assert_eq!(receipts.len(), 1);
+    assert_eq!(receipts[0].credential_subject.status, ExecutionStatus::Denied);
+}
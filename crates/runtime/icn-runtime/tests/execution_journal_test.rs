@@ -0,0 +1,187 @@
+mod common;
+
+use common::{module_cid, wasm, TestContext};
+use icn_economics::{InMemoryTokenStore, ResourceType, TokenStore};
+use icn_identity_core::vc::execution_receipt::ExecutionReceipt;
+use icn_runtime::config::ExecutionConfig;
+use icn_runtime::engine::{ExecutionError, ExecutionResult, ModernWasmExecutor, EXECUTE_MODULE_ACTION};
+use icn_runtime::host::economics::TOKEN_ERR_INSUFFICIENT_FUNDS;
+use icn_runtime::host::policy::POLICY_UPDATE_ACTION;
+use icn_runtime::policy::{DefaultPolicyLoader, PolicyLoader, ScopeType};
+use icn_types::dag::memory::MemoryDagStore;
+use icn_types::dag::{NodeScope, SharedDagStore};
+use icn_types::{Cid, PolicyRule, ScopePolicyConfig};
+use std::sync::Arc;
+
+/// Module whose `_start` copies the input to offset 1024 and then runs `body`.
+/// "credit" is at offset 0, "coop-b" at 16 and "tally" at 32; `$emit` appends a
+/// return code to the output and `$anchor` anchors `len` bytes at `ptr` as "tally".
+fn journal_module(body: &str) -> Vec<u8> {
+    wasm(&format!(
+        r#"(module
+             (import "env" "get_input_len" (func $input_len (result i32)))
+             (import "env" "read_input" (func $read_input (param i32 i32) (result i32)))
+             (import "env" "write_output" (func $write_output (param i32 i32) (result i32)))
+             (import "env" "dag_anchor" (func $dag_anchor (param i32 i32 i32 i32 i32 i32) (result i32)))
+             (import "env" "token_debit" (func $debit (param i32 i32 i64) (result i32)))
+             (import "env" "token_transfer" (func $transfer (param i32 i32 i32 i32 i64) (result i32)))
+             (import "env" "policy_update" (func $policy_update (param i32 i32) (result i32)))
+             (memory (export "memory") 1)
+             (data (i32.const 0) "credit")
+             (data (i32.const 16) "coop-b")
+             (data (i32.const 32) "tally")
+             (func $emit (param i32)
+               (i32.store (i32.const 512) (local.get 0))
+               (drop (call $write_output (i32.const 512) (i32.const 4))))
+             (func $anchor (param i32 i32) (result i32)
+               (call $dag_anchor (local.get 0) (local.get 1) (i32.const 32) (i32.const 5)
+                                 (i32.const 8192) (i32.const 1024)))
+             (func (export "_start")
+               (drop (call $read_input (i32.const 1024) (call $input_len)))
+               {body})
+           )"#
+    ))
+}
+
+const ANCHOR_AND_TRANSFER: &str = r#"
+    (call $emit (call $anchor (i32.const 0) (i32.const 6)))
+    (call $emit (call $transfer (i32.const 0) (i32.const 6) (i32.const 16) (i32.const 6) (i64.const 40)))"#;
+
+/// Context for a run in cooperative "coop-a" with an empty DAG and 100 credits
+async fn coop_context() -> TestContext {
+    let mut ctx = TestContext::new();
+    ctx.scope = Some(ScopeType::Cooperative);
+    ctx.scope_id = Some("coop-a".to_string());
+    ctx.dag = Some(SharedDagStore::new(Box::new(MemoryDagStore::new())));
+    let tokens = InMemoryTokenStore::new();
+    tokens.credit("coop-a", ResourceType::Credit, 100).await.unwrap();
+    ctx.tokens = Some(Arc::new(tokens));
+    ctx
+}
+
+async fn execute(ctx: &TestContext, module: &[u8], input: &[u8], fuel: Option<u64>) -> anyhow::Result<ExecutionResult> {
+    let executor = ModernWasmExecutor::new().expect("Failed to create executor");
+    executor.execute(module, Arc::new(ctx.clone()), module_cid(module), None, Some(input), fuel).await
+}
+
+fn codes(output: &[u8]) -> Vec<i32> {
+    output.chunks(4).map(|chunk| i32::from_le_bytes(chunk.try_into().unwrap())).collect()
+}
+
+async fn balance(ctx: &TestContext, account: &str) -> u64 {
+    ctx.tokens.as_ref().unwrap().get_balance(account, &ResourceType::Credit).await.unwrap()
+}
+
+async fn node_count(ctx: &TestContext) -> usize {
+    ctx.dag.as_ref().unwrap().get_ordered_nodes().await.unwrap().len()
+}
+
+/// Policy for "coop-a" allowing the test caller the given actions
+fn coop_policy(ctx: &TestContext, actions: &[&str]) -> ScopePolicyConfig {
+    ScopePolicyConfig {
+        scope_type: NodeScope::Cooperative,
+        scope_id: "coop-a".to_string(),
+        allowed_actions: actions
+            .iter()
+            .map(|action| PolicyRule {
+                action_type: action.to_string(),
+                required_membership: None,
+                allowed_dids: Some(vec![ctx.caller.clone()]),
+            })
+            .collect(),
+    }
+}
+
+#[tokio::test]
+async fn test_side_effects_commit_when_module_completes() {
+    let receipts_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let mut ctx = coop_context().await;
+    ctx.config = ExecutionConfig {
+        auto_issue_receipts: true,
+        anchor_receipts: false,
+        receipt_export_dir: Some(receipts_dir.path().to_path_buf()),
+        ..ExecutionConfig::default()
+    };
+    let module = journal_module(&format!(
+        "{ANCHOR_AND_TRANSFER}
+         (call $emit (call $anchor (i32.const 16) (i32.const 6)))"
+    ));
+
+    let result = execute(&ctx, &module, b"", None).await.expect("Execution failed");
+    assert!(codes(&result.output).iter().all(|code| *code >= 0));
+    assert_eq!(result.side_effects.len(), 3);
+    assert_eq!(balance(&ctx, "coop-a").await, 60);
+    assert_eq!(balance(&ctx, "coop-b").await, 40);
+
+    // Both anchors are stored, the second chained onto the first
+    let dag = ctx.dag.as_ref().unwrap();
+    let first = dag.get_node(&result.side_effects[0]).await.unwrap();
+    let second = dag.get_node(&result.side_effects[2]).await.unwrap();
+    assert_eq!(first.node.metadata.label.as_deref(), Some("tally"));
+    assert_eq!(second.node.parents, vec![result.side_effects[0].clone()]);
+
+    let entries: Vec<_> = std::fs::read_dir(receipts_dir.path()).unwrap().collect();
+    let receipt: ExecutionReceipt =
+        serde_json::from_slice(&std::fs::read(entries[0].as_ref().unwrap().path()).unwrap()).unwrap();
+    let listed: Vec<String> = serde_json::from_value(
+        receipt.credential_subject.additional_properties.unwrap()["side_effects"].clone(),
+    )
+    .unwrap();
+    let listed: Vec<Cid> = listed.iter().map(|cid| cid.parse().unwrap()).collect();
+    assert_eq!(listed, result.side_effects);
+}
+
+#[tokio::test]
+async fn test_trap_discards_staged_effects() {
+    let ctx = coop_context().await;
+    let module = journal_module(&format!("{ANCHOR_AND_TRANSFER} unreachable"));
+
+    execute(&ctx, &module, b"", None).await.expect_err("Module should trap");
+    assert_eq!(node_count(&ctx).await, 0);
+    assert_eq!(balance(&ctx, "coop-a").await, 100);
+    assert_eq!(balance(&ctx, "coop-b").await, 0);
+}
+
+#[tokio::test]
+async fn test_fuel_exhaustion_discards_staged_effects() {
+    let ctx = coop_context().await;
+    let module = journal_module(&format!("{ANCHOR_AND_TRANSFER} (loop $spin (br $spin))"));
+
+    let error = execute(&ctx, &module, b"", Some(100_000)).await.expect_err("Module should run out of fuel");
+    assert_eq!(error.downcast_ref::<ExecutionError>(), Some(&ExecutionError::OutOfFuel(100_000)));
+    assert_eq!(node_count(&ctx).await, 0);
+    assert_eq!(balance(&ctx, "coop-a").await, 100);
+}
+
+#[tokio::test]
+async fn test_staged_debits_count_against_balance() {
+    let ctx = coop_context().await;
+    let module = journal_module(
+        r#"(call $emit (call $debit (i32.const 0) (i32.const 6) (i64.const 60)))
+           (call $emit (call $debit (i32.const 0) (i32.const 6) (i64.const 60)))"#,
+    );
+
+    let result = execute(&ctx, &module, b"", None).await.expect("Execution failed");
+    assert_eq!(codes(&result.output), vec![0, TOKEN_ERR_INSUFFICIENT_FUNDS]);
+    assert_eq!(balance(&ctx, "coop-a").await, 40);
+}
+
+#[tokio::test]
+async fn test_policy_update_is_staged() {
+    let mut ctx = coop_context().await;
+    let loader = Arc::new(DefaultPolicyLoader::new());
+    loader.set_policy(coop_policy(&ctx, &[EXECUTE_MODULE_ACTION, POLICY_UPDATE_ACTION]));
+    ctx.policy = Some(loader.clone());
+    let updated = coop_policy(&ctx, &[EXECUTE_MODULE_ACTION, POLICY_UPDATE_ACTION, "vote"]);
+    let input = serde_json::to_vec(&updated).unwrap();
+
+    let trapping = journal_module("(drop (call $policy_update (i32.const 1024) (call $input_len))) unreachable");
+    execute(&ctx, &trapping, &input, None).await.expect_err("Module should trap");
+    assert!(loader.check_authorization("Cooperative", "coop-a", "vote", &ctx.caller).is_err());
+
+    let module = journal_module("(call $emit (call $policy_update (i32.const 1024) (call $input_len)))");
+    let result = execute(&ctx, &module, &input, None).await.expect("Execution failed");
+    assert_eq!(codes(&result.output), vec![0]);
+    assert_eq!(result.side_effects.len(), 1);
+    assert!(loader.check_authorization("Cooperative", "coop-a", "vote", &ctx.caller).is_ok());
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::{module_cid, wasm, TestContext};
+use icn_identity_core::vc::execution_receipt::ExecutionReceipt;
+use icn_runtime::block_store::BlockStore;
+use icn_runtime::config::ExecutionConfig;
+use icn_runtime::engine::{ExecutionTrace, LogLevel, ModernWasmExecutor};
+use icn_runtime::ReceiptProperties;
+use std::sync::Arc;
+
+/// Logs a plain message, a warning with fields, and a message at an unknown level
+const LOGGER: &str = r#"
+    (module
+      (import "env" "log" (func $log (param i32 i32)))
+      (import "env" "log_structured" (func $log_structured (param i32 i32 i32 i32 i32) (result i32)))
+      (import "env" "write_output" (func $write_output (param i32 i32) (result i32)))
+      (memory (export "memory") 1)
+      (data (i32.const 0) "started")
+      (data (i32.const 16) "low balance")
+      (data (i32.const 32) "{\"balance\":3}")
+      (func (export "_start")
+        (call $log (i32.const 0) (i32.const 7))
+        (i32.store (i32.const 64)
+          (call $log_structured (i32.const 3) (i32.const 16) (i32.const 11) (i32.const 32) (i32.const 13)))
+        (i32.store (i32.const 68)
+          (call $log_structured (i32.const 9) (i32.const 16) (i32.const 11) (i32.const 0) (i32.const 0)))
+        (drop (call $write_output (i32.const 64) (i32.const 8)))
+      )
+    )
+"#;
+
+fn codes(output: &[u8]) -> (i32, i32) {
+    (
+        i32::from_le_bytes(output[0..4].try_into().unwrap()),
+        i32::from_le_bytes(output[4..8].try_into().unwrap()),
+    )
+}
+
+#[tokio::test]
+async fn test_logs_are_captured_and_stored() {
+    let module = wasm(LOGGER);
+    let ctx = Arc::new(TestContext::new());
+
+    let result = ModernWasmExecutor::new()
+        .unwrap()
+        .execute(&module, ctx.clone(), module_cid(&module), None, None, Some(1_000_000))
+        .await
+        .expect("Execution failed");
+
+    assert_eq!(codes(&result.output), (0, -2));
+    assert_eq!(result.trace.logs.len(), 2);
+    assert_eq!(result.trace.logs[0].level, LogLevel::Info);
+    assert_eq!(result.trace.logs[0].message, "started");
+    assert_eq!(result.trace.logs[1].level, LogLevel::Warn);
+    assert_eq!(result.trace.logs[1].fields, Some(serde_json::json!({ "balance": 3 })));
+    assert!(result.trace.logs[1].fuel_consumed > result.trace.logs[0].fuel_consumed);
+    assert!(result.trace.host_calls.is_empty(), "Host calls are only traced on request");
+    assert_eq!(*ctx.logs.lock().unwrap(), vec!["started".to_string(), "low balance".to_string()]);
+
+    let stored = ctx.blocks.get(result.log_cid.as_ref().unwrap()).unwrap().expect("Trace not stored");
+    let trace: ExecutionTrace = serde_json::from_slice(&stored).unwrap();
+    assert_eq!(trace.logs, result.trace.logs);
+}
+
+#[tokio::test]
+async fn test_host_calls_are_traced_and_linked_from_receipt() {
+    let receipts_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let module = wasm(LOGGER);
+    let ctx = Arc::new(TestContext::with_config(ExecutionConfig {
+        auto_issue_receipts: true,
+        anchor_receipts: false,
+        receipt_export_dir: Some(receipts_dir.path().to_path_buf()),
+        trace_host_calls: true,
+        ..ExecutionConfig::default()
+    }));
+
+    let result = ModernWasmExecutor::new()
+        .unwrap()
+        .execute(&module, ctx, module_cid(&module), None, None, Some(1_000_000))
+        .await
+        .expect("Execution failed");
+
+    let calls: Vec<&str> = result.trace.host_calls.iter().map(|call| call.name.as_str()).collect();
+    assert_eq!(calls, vec!["log", "log_structured", "log_structured", "write_output"]);
+    assert_eq!(result.trace.host_calls[0].args, vec![0, 7]);
+    assert_eq!(result.trace.host_calls[3].args, vec![64, 8]);
+
+    let entries: Vec<_> = std::fs::read_dir(receipts_dir.path()).unwrap().collect();
+    let receipt: ExecutionReceipt =
+        serde_json::from_slice(&std::fs::read(entries[0].as_ref().unwrap().path()).unwrap()).unwrap();
+    let properties = ReceiptProperties::from_receipt(&receipt).unwrap();
+    assert_eq!(properties.log_cid, result.log_cid);
+}
+
+#[tokio::test]
+async fn test_silent_execution_has_no_trace() {
+    let module = wasm(r#"(module (memory (export "memory") 1) (func (export "_start")))"#);
+
+    let result = ModernWasmExecutor::new()
+        .unwrap()
+        .execute(&module, Arc::new(TestContext::new()), module_cid(&module), None, None, None)
+        .await
+        .expect("Execution failed");
+
+    assert!(result.trace.is_empty());
+    assert_eq!(result.log_cid, None);
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::{module_cid, wasm, TestContext};
+use icn_runtime::engine::{ModuleCacheStats, ModernWasmExecutor};
+use std::sync::Arc;
+
+/// Module that writes `value` as its output
+fn constant(value: i32) -> Vec<u8> {
+    wasm(&format!(
+        r#"(module
+             (import "env" "write_output" (func $write_output (param i32 i32) (result i32)))
+             (memory (export "memory") 1)
+             (func (export "_start")
+               (i32.store (i32.const 0) (i32.const {value}))
+               (drop (call $write_output (i32.const 0) (i32.const 4)))))"#
+    ))
+}
+
+async fn run(executor: &ModernWasmExecutor, module: &[u8]) -> i32 {
+    let result = executor
+        .execute(module, Arc::new(TestContext::new()), module_cid(module), None, None, None)
+        .await
+        .expect("Execution failed");
+    i32::from_le_bytes(result.output[..4].try_into().unwrap())
+}
+
+#[tokio::test]
+async fn test_repeated_runs_reuse_compiled_module() {
+    let executor = ModernWasmExecutor::new().unwrap();
+    let module = constant(7);
+
+    assert_eq!(run(&executor, &module).await, 7);
+    assert_eq!(run(&executor, &module).await, 7);
+    assert!(executor.module_cache().contains(&module_cid(&module)));
+    assert_eq!(executor.module_cache().stats(), ModuleCacheStats { hits: 1, misses: 1, ..Default::default() });
+}
+
+#[tokio::test]
+async fn test_least_recently_used_module_is_evicted() {
+    let executor = ModernWasmExecutor::new().unwrap().with_module_cache(2, None);
+    let (a, b, c) = (constant(1), constant(2), constant(3));
+
+    run(&executor, &a).await;
+    run(&executor, &b).await;
+    run(&executor, &a).await;
+    run(&executor, &c).await;
+
+    let cache = executor.module_cache();
+    assert_eq!(cache.len(), 2);
+    assert!(cache.contains(&module_cid(&a)));
+    assert!(!cache.contains(&module_cid(&b)));
+    assert!(cache.contains(&module_cid(&c)));
+    assert_eq!(cache.stats(), ModuleCacheStats { hits: 1, disk_hits: 0, misses: 3, evictions: 1 });
+}
+
+#[tokio::test]
+async fn test_precompiled_artifacts_survive_restart() {
+    let cache_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let module = constant(42);
+
+    let first = ModernWasmExecutor::new().unwrap().with_module_cache(8, Some(cache_dir.path()));
+    assert_eq!(run(&first, &module).await, 42);
+    let artifact_dir = first.module_cache().artifact_dir().unwrap().to_path_buf();
+    assert_eq!(std::fs::read_dir(&artifact_dir).unwrap().count(), 1);
+
+    let restarted = ModernWasmExecutor::new().unwrap().with_module_cache(8, Some(cache_dir.path()));
+    assert_eq!(run(&restarted, &module).await, 42);
+    assert_eq!(restarted.module_cache().stats(), ModuleCacheStats { disk_hits: 1, ..Default::default() });
+
+    // A differently configured engine keeps its artifacts apart
+    let deterministic = ModernWasmExecutor::deterministic().unwrap().with_module_cache(8, Some(cache_dir.path()));
+    assert_ne!(deterministic.module_cache().artifact_dir(), Some(artifact_dir.as_path()));
+    assert_eq!(run(&deterministic, &module).await, 42);
+    assert_eq!(deterministic.module_cache().stats().misses, 1);
+}
+
+#[tokio::test]
+async fn test_corrupt_artifact_is_recompiled() {
+    let cache_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let module = constant(5);
+    let executor = ModernWasmExecutor::new().unwrap().with_module_cache(8, Some(cache_dir.path()));
+    run(&executor, &module).await;
+
+    let artifact = std::fs::read_dir(executor.module_cache().artifact_dir().unwrap())
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    std::fs::write(&artifact, b"not a compiled module").unwrap();
+
+    let restarted = ModernWasmExecutor::new().unwrap().with_module_cache(8, Some(cache_dir.path()));
+    assert_eq!(run(&restarted, &module).await, 5);
+    assert_eq!(restarted.module_cache().stats(), ModuleCacheStats { misses: 1, ..Default::default() });
+    // The recompiled artifact replaced the corrupt one
+    assert_ne!(std::fs::read(&artifact).unwrap(), b"not a compiled module");
+}
+
+#[tokio::test]
+async fn test_mismatched_cid_neither_hits_nor_populates_cache() {
+    let cache_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let executor = ModernWasmExecutor::new().unwrap().with_module_cache(8, Some(cache_dir.path()));
+    let cache = executor.module_cache();
+    let (real, impostor) = (constant(1), constant(2));
+
+    // A wrong CID for code that was never cached is not cached under it
+    assert!(cache.get_or_compile(&module_cid(&real), &impostor).is_err());
+    assert!(!cache.contains(&module_cid(&real)));
+    assert!(cache.artifact_dir().map_or(true, |dir| !dir.exists() || std::fs::read_dir(dir).unwrap().count() == 0));
+
+    // Nor is the cached module of the CID served for other bytes
+    assert_eq!(run(&executor, &real).await, 1);
+    assert!(cache.get_or_compile(&module_cid(&real), &impostor).is_err());
+    assert_eq!(run(&executor, &real).await, 1);
+    assert_eq!(cache.stats(), ModuleCacheStats { hits: 1, misses: 1, ..Default::default() });
+}
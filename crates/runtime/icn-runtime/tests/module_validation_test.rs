@@ -0,0 +1,127 @@
+mod common;
+
+use common::wasm;
+use icn_runtime::engine::validation::ModuleImport;
+use icn_runtime::{ModernWasmExecutor, ValidationIssue, ValidationPolicy, ValidationReport};
+
+fn validate(wat: &str, policy: &ValidationPolicy) -> ValidationReport {
+    let executor = ModernWasmExecutor::new().expect("Failed to create executor");
+    executor.validate_module(&wasm(wat), policy)
+}
+
+#[test]
+fn test_valid_module_reports_imports_and_entry_point() {
+    let report = validate(
+        r#"(module
+             (import "env" "get_input_len" (func (result i32)))
+             (memory (export "memory") 2)
+             (func (export "run")))"#,
+        &ValidationPolicy::default(),
+    );
+    assert!(report.is_valid(), "Unexpected issues: {:?}", report.issues);
+    assert_eq!(
+        report.imports,
+        vec![ModuleImport { module: "env".to_string(), name: "get_input_len".to_string() }]
+    );
+    assert_eq!(report.exports, vec!["memory".to_string(), "run".to_string()]);
+    assert_eq!(report.memory_pages, Some(2));
+    assert_eq!(report.entry_point.as_deref(), Some("run"));
+}
+
+#[test]
+fn test_import_outside_allowlist_is_rejected() {
+    let wat = r#"(module
+                   (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32)))
+                   (func (export "_start")))"#;
+    let report = validate(wat, &ValidationPolicy::default());
+    assert_eq!(
+        report.issues,
+        vec![ValidationIssue::ForbiddenImport {
+            module: "wasi_snapshot_preview1".to_string(),
+            name: "fd_write".to_string(),
+        }]
+    );
+
+    let policy = ValidationPolicy {
+        allowed_namespaces: vec!["env".to_string(), "wasi_snapshot_preview1".to_string()],
+        ..ValidationPolicy::default()
+    };
+    assert!(validate(wat, &policy).is_valid());
+}
+
+#[test]
+fn test_simd_requires_policy_opt_in() {
+    let wat = r#"(module
+                   (func (export "_start")
+                     (drop (v128.const i64x2 0 0))))"#;
+    let report = validate(wat, &ValidationPolicy::default());
+    assert!(matches!(report.issues.as_slice(), [ValidationIssue::ForbiddenFeature { .. }]), "{:?}", report.issues);
+
+    let policy = ValidationPolicy { allow_simd: true, ..ValidationPolicy::default() };
+    assert!(validate(wat, &policy).is_valid());
+}
+
+#[test]
+fn test_shared_memory_requires_threads() {
+    let report = validate(
+        r#"(module (memory 1 1 shared) (func (export "_start")))"#,
+        &ValidationPolicy::default(),
+    );
+    assert!(matches!(report.issues.as_slice(), [ValidationIssue::ForbiddenFeature { .. }]), "{:?}", report.issues);
+}
+
+#[test]
+fn test_declared_memory_over_limit_is_rejected() {
+    let policy = ValidationPolicy { max_memory_pages: 4, ..ValidationPolicy::default() };
+    let report = validate(r#"(module (memory 8) (func (export "_start")))"#, &policy);
+    assert_eq!(report.issues, vec![ValidationIssue::MemoryTooLarge { declared_pages: 8, max_pages: 4 }]);
+
+    let report = validate(r#"(module (import "env" "memory" (memory 16)) (func (export "_start")))"#, &policy);
+    assert_eq!(report.issues, vec![ValidationIssue::MemoryTooLarge { declared_pages: 16, max_pages: 4 }]);
+}
+
+#[test]
+fn test_missing_entry_point_is_rejected() {
+    let report = validate(r#"(module (func (export "helper")))"#, &ValidationPolicy::default());
+    assert_eq!(report.issues, vec![ValidationIssue::MissingEntryPoint]);
+    assert_eq!(report.entry_point, None);
+}
+
+#[test]
+fn test_entry_point_must_take_and_return_nothing() {
+    let report = validate(r#"(module (func (export "main") (param i32) (result i32) (local.get 0)))"#, &ValidationPolicy::default());
+    assert_eq!(
+        report.issues,
+        vec![ValidationIssue::EntryPointSignature { name: "main".to_string(), signature: "(i32) -> (i32)".to_string() }]
+    );
+    assert_eq!(report.entry_point, None);
+
+    // A later entry point with the right type is the one the executor calls
+    let report = validate(
+        r#"(module
+             (import "env" "get_input_len" (func (result i32)))
+             (func (export "_start") (result i32) (i32.const 0))
+             (func (export "run")))"#,
+        &ValidationPolicy::default(),
+    );
+    assert!(report.is_valid(), "Unexpected issues: {:?}", report.issues);
+    assert_eq!(report.entry_point.as_deref(), Some("run"));
+}
+
+#[test]
+fn test_malformed_bytes_are_reported() {
+    let executor = ModernWasmExecutor::new().expect("Failed to create executor");
+    let report = executor.validate_module(b"not wasm", &ValidationPolicy::default());
+    assert!(matches!(report.issues.as_slice(), [ValidationIssue::InvalidModule { .. }]), "{:?}", report.issues);
+}
+
+#[test]
+fn test_report_and_policy_serialize() {
+    let report = validate(r#"(module (func (export "helper")))"#, &ValidationPolicy::default());
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["issues"][0]["kind"], "missing_entry_point");
+
+    // Omitted policy fields fall back to the defaults
+    let policy: ValidationPolicy = serde_json::from_str(r#"{"allow_simd": true}"#).unwrap();
+    assert_eq!(policy, ValidationPolicy { allow_simd: true, ..ValidationPolicy::default() });
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::{module_cid, wasm, TestContext};
+use icn_identity_core::vc::execution_receipt::{ExecutionReceipt, ExecutionScope, ExecutionStatus, ExecutionSubject};
+use icn_runtime::abi::HOST_ABI_VERSION;
+use icn_runtime::config::ExecutionConfig;
+use icn_runtime::engine::{ExecutionResult, ModernWasmExecutor};
+use icn_runtime::ReceiptProperties;
+use std::sync::Arc;
+
+/// Grows its single page of memory by two pages and echoes nothing
+const GROW: &str = r#"
+    (module
+      (memory (export "memory") 1)
+      (func (export "_start")
+        (drop (memory.grow (i32.const 2))))
+    )
+"#;
+
+async fn execute(executor: &ModernWasmExecutor, ctx: TestContext, fuel: Option<u64>) -> ExecutionResult {
+    let module = wasm(GROW);
+    executor
+        .execute(&module, Arc::new(ctx), module_cid(&module), None, Some(b"job-1"), fuel)
+        .await
+        .expect("Execution failed")
+}
+
+#[tokio::test]
+async fn test_receipt_records_resource_usage() {
+    let receipts_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let ctx = TestContext::with_config(ExecutionConfig {
+        auto_issue_receipts: true,
+        anchor_receipts: false,
+        receipt_export_dir: Some(receipts_dir.path().to_path_buf()),
+        ..ExecutionConfig::default()
+    });
+
+    let result = execute(&ModernWasmExecutor::new().unwrap(), ctx, None).await;
+    assert_eq!(result.peak_memory_bytes, 3 * 65_536);
+
+    let entries: Vec<_> = std::fs::read_dir(receipts_dir.path()).unwrap().collect();
+    let receipt: ExecutionReceipt =
+        serde_json::from_slice(&std::fs::read(entries[0].as_ref().unwrap().path()).unwrap()).unwrap();
+    let properties = ReceiptProperties::from_receipt(&receipt).expect("Receipt should carry execution details");
+    assert_eq!(
+        properties,
+        ReceiptProperties {
+            side_effects: Vec::new(),
+            deterministic: false,
+            input_cid: result.input_cid.clone(),
+            fuel_consumed: result.fuel_consumed,
+            execution_time_ms: result.execution_time_ms,
+            peak_memory_bytes: 3 * 65_536,
+            host_abi_version: HOST_ABI_VERSION,
+            executor_config_hash: result.config_hash.clone(),
+            log_cid: None,
+        }
+    );
+    assert!(properties.fuel_consumed.unwrap() > 0);
+}
+
+#[tokio::test]
+async fn test_config_hash_identifies_executor_settings() {
+    let default = execute(&ModernWasmExecutor::new().unwrap(), TestContext::new(), None).await;
+    let again = execute(&ModernWasmExecutor::new().unwrap(), TestContext::new(), None).await;
+    let deterministic = execute(&ModernWasmExecutor::deterministic().unwrap(), TestContext::new(), None).await;
+    let less_fuel = execute(&ModernWasmExecutor::new().unwrap(), TestContext::new(), Some(50_000)).await;
+
+    assert_eq!(default.config_hash, again.config_hash);
+    assert_ne!(default.config_hash, deterministic.config_hash);
+    assert_ne!(default.config_hash, less_fuel.config_hash);
+}
+
+#[test]
+fn test_receipt_without_details_is_rejected() {
+    let ctx = TestContext::new();
+    let subject = ExecutionSubject {
+        id: ctx.node.to_string(),
+        scope: ExecutionScope::Federation {
+            federation_id: ctx.federation.did().to_string(),
+        },
+        submitter: None,
+        module_cid: module_cid(b"module").to_string(),
+        result_cid: module_cid(b"result").to_string(),
+        event_id: None,
+        timestamp: 0,
+        status: ExecutionStatus::Success,
+        additional_properties: None,
+    };
+    let receipt = ExecutionReceipt::new("urn:uuid:test".to_string(), ctx.federation.did().to_string(), subject);
+    assert!(ReceiptProperties::from_receipt(&receipt).is_err());
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use common::{module_cid, wasm, TestContext};
+use icn_identity_core::did::DidKey;
+use icn_identity_core::vc::execution_receipt::ExecutionReceipt;
+use icn_runtime::config::ExecutionConfig;
+use icn_runtime::engine::ModernWasmExecutor;
+use icn_runtime::verification::{ReceiptVerifier, Verdict, VerificationAttestation, VerificationError, ATTESTATION_LABEL};
+use icn_types::dag::memory::MemoryDagStore;
+use icn_types::dag::{DagPayload, SharedDagStore};
+use std::sync::Arc;
+
+/// Echoes its input followed by 8 random bytes
+const ECHO_RANDOM: &str = r#"
+    (module
+      (import "env" "get_input_len" (func $input_len (result i32)))
+      (import "env" "read_input" (func $read_input (param i32 i32) (result i32)))
+      (import "env" "write_output" (func $write_output (param i32 i32) (result i32)))
+      (import "env" "random_bytes" (func $random (param i32 i32) (result i32)))
+      (memory (export "memory") 1)
+      (func (export "_start")
+        (local $len i32)
+        (local.set $len (call $input_len))
+        (drop (call $read_input (i32.const 0) (local.get $len)))
+        (drop (call $random (local.get $len) (i32.const 8)))
+        (drop (call $write_output (i32.const 0) (i32.add (local.get $len) (i32.const 8)))))
+    )
+"#;
+
+/// Run the module and return the context and the receipt it exported
+async fn execute(executor: ModernWasmExecutor, input: &[u8]) -> (TestContext, ExecutionReceipt) {
+    let receipts_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let ctx = TestContext::with_config(ExecutionConfig {
+        auto_issue_receipts: true,
+        anchor_receipts: false,
+        receipt_export_dir: Some(receipts_dir.path().to_path_buf()),
+        ..ExecutionConfig::default()
+    });
+    let module = wasm(ECHO_RANDOM);
+    executor
+        .execute(&module, Arc::new(ctx.clone()), module_cid(&module), None, Some(input), None)
+        .await
+        .expect("Execution failed");
+
+    let entries: Vec<_> = std::fs::read_dir(receipts_dir.path()).unwrap().collect();
+    let receipt = serde_json::from_slice(&std::fs::read(entries[0].as_ref().unwrap().path()).unwrap()).unwrap();
+    (ctx, receipt)
+}
+
+fn verifier(ctx: &TestContext) -> (ReceiptVerifier, SharedDagStore, DidKey) {
+    let dag = SharedDagStore::new(Box::new(MemoryDagStore::new()));
+    let key = DidKey::new();
+    let verifier = ReceiptVerifier::new(ctx.blocks.clone(), dag.clone(), key.clone()).expect("Failed to create verifier");
+    (verifier, dag, key)
+}
+
+/// Change a receipt's claims and re-sign it with the executor's federation key
+fn forge(ctx: &TestContext, mut receipt: ExecutionReceipt, edit: impl FnOnce(&mut ExecutionReceipt)) -> ExecutionReceipt {
+    edit(&mut receipt);
+    receipt.proof = None;
+    receipt.sign(&ctx.federation).unwrap()
+}
+
+#[tokio::test]
+async fn test_honest_receipt_is_concurred_and_anchored() {
+    let (ctx, receipt) = execute(ModernWasmExecutor::deterministic().unwrap(), b"job-1").await;
+    let (verifier, dag, key) = verifier(&ctx);
+
+    let (attestation, cid) = verifier.verify_and_anchor(&receipt, Arc::new(ctx.clone())).await.unwrap();
+    assert_eq!(attestation.verdict, Verdict::Concur, "Unexpected dispute: {:?}", attestation.reason);
+    assert_eq!(attestation.receipt_id, receipt.id);
+    assert_eq!(attestation.observed_result_cid, Some(attestation.claimed_result_cid.clone()));
+    assert_eq!(attestation.observed_fuel, Some(attestation.claimed_fuel));
+
+    let node = dag.get_node(&cid).await.unwrap();
+    assert_eq!(node.node.metadata.label.as_deref(), Some(ATTESTATION_LABEL));
+    assert_eq!(node.node.author, key.did().clone());
+    key.verify(&node.node.canonical_bytes().unwrap(), &node.signature).expect("Attestation should be signed");
+    let DagPayload::Json(payload) = node.node.payload else {
+        panic!("Expected a JSON payload");
+    };
+    assert_eq!(serde_json::from_value::<VerificationAttestation>(payload).unwrap(), attestation);
+}
+
+#[tokio::test]
+async fn test_wrong_result_is_disputed() {
+    let (ctx, receipt) = execute(ModernWasmExecutor::deterministic().unwrap(), b"job-1").await;
+    let receipt = forge(&ctx, receipt, |receipt| {
+        receipt.credential_subject.result_cid = icn_types::Cid::from_bytes(b"forged").unwrap().to_string();
+    });
+    let (verifier, _, _) = verifier(&ctx);
+
+    let attestation = verifier.verify(&receipt, Arc::new(ctx)).await.unwrap();
+    assert_eq!(attestation.verdict, Verdict::Dispute);
+    assert!(attestation.reason.unwrap().contains("result CID"));
+}
+
+#[tokio::test]
+async fn test_understated_fuel_is_disputed() {
+    let (ctx, receipt) = execute(ModernWasmExecutor::deterministic().unwrap(), b"job-1").await;
+    let receipt = forge(&ctx, receipt, |receipt| {
+        let properties = receipt.credential_subject.additional_properties.as_mut().unwrap();
+        properties["fuel_consumed"] = (properties["fuel_consumed"].as_u64().unwrap() / 2).into();
+    });
+    let (verifier, _, _) = verifier(&ctx);
+
+    let attestation = verifier.verify(&receipt, Arc::new(ctx)).await.unwrap();
+    assert_eq!(attestation.verdict, Verdict::Dispute);
+    assert_eq!(attestation.observed_result_cid, None);
+}
+
+#[tokio::test]
+async fn test_unverifiable_receipts_are_rejected() {
+    let (ctx, receipt) = execute(ModernWasmExecutor::new().unwrap(), b"job-1").await;
+    let (verifier, _, _) = verifier(&ctx);
+    let error = verifier.verify(&receipt, Arc::new(ctx.clone())).await.unwrap_err();
+    assert!(matches!(error, VerificationError::NotReproducible(_)), "Unexpected error: {}", error);
+
+    let (ctx, mut receipt) = execute(ModernWasmExecutor::deterministic().unwrap(), b"job-1").await;
+    receipt.credential_subject.result_cid = icn_types::Cid::from_bytes(b"forged").unwrap().to_string();
+    let error = verifier.verify(&receipt, Arc::new(ctx)).await.unwrap_err();
+    assert!(matches!(error, VerificationError::InvalidSignature), "Unexpected error: {}", error);
+}
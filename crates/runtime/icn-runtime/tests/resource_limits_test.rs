@@ -0,0 +1,124 @@
+mod common;
+
+use common::{module_cid, wasm, TestContext};
+use icn_identity_core::vc::execution_receipt::{ExecutionReceipt, ExecutionStatus};
+use icn_runtime::config::{ExecutionConfig, ResourceLimits};
+use icn_runtime::engine::{ExecutionError, ModernWasmExecutor};
+use icn_runtime::policy::ScopeType;
+use std::sync::Arc;
+
+const INFINITE_LOOP: &str = r#"
+    (module
+      (func (export "_start")
+        (loop $forever (br $forever))
+      )
+    )
+"#;
+
+/// Grows memory by the number of pages passed as input length, trapping if growth fails
+const GROW_MEMORY: &str = r#"
+    (module
+      (import "env" "get_input_len" (func $get_input_len (result i32)))
+      (memory (export "memory") 1)
+      (func (export "_start")
+        (if (i32.eq (memory.grow (call $get_input_len)) (i32.const -1))
+          (then unreachable))
+      )
+    )
+"#;
+
+fn limits(max_execution_time_ms: u64, max_memory_pages: u32, max_fuel: u64) -> ResourceLimits {
+    ResourceLimits {
+        max_execution_time_ms,
+        max_memory_pages,
+        max_fuel,
+        ..ResourceLimits::default()
+    }
+}
+
+fn context_with_limits(limits: ResourceLimits) -> TestContext {
+    let mut ctx = TestContext::new();
+    ctx.config.limits = limits;
+    ctx
+}
+
+async fn run(ctx: TestContext, wat: &str, input: &[u8], fuel: Option<u64>) -> anyhow::Result<icn_runtime::ExecutionResult> {
+    let wasm_bytes = wasm(wat);
+    let executor = ModernWasmExecutor::new().expect("Failed to create executor");
+    executor.execute(&wasm_bytes, Arc::new(ctx), module_cid(&wasm_bytes), None, Some(input), fuel).await
+}
+
+fn violation(result: anyhow::Result<icn_runtime::ExecutionResult>) -> ExecutionError {
+    let error = result.expect_err("Execution should have been stopped");
+    error.downcast_ref::<ExecutionError>().cloned().unwrap_or_else(|| panic!("Untyped error: {:?}", error))
+}
+
+#[tokio::test]
+async fn test_fuel_exhaustion_is_reported() {
+    let ctx = context_with_limits(limits(60_000, 16, u64::MAX / 2));
+    let result = run(ctx, INFINITE_LOOP, b"", Some(10_000)).await;
+    assert_eq!(violation(result), ExecutionError::OutOfFuel(10_000));
+}
+
+#[tokio::test]
+async fn test_configured_fuel_applies_without_explicit_limit() {
+    let ctx = context_with_limits(limits(60_000, 16, 5_000));
+    let result = run(ctx, INFINITE_LOOP, b"", None).await;
+    assert_eq!(violation(result), ExecutionError::OutOfFuel(5_000));
+}
+
+#[tokio::test]
+async fn test_wall_clock_timeout_interrupts_execution() {
+    let ctx = context_with_limits(limits(50, 16, u64::MAX / 2));
+    let result = run(ctx, INFINITE_LOOP, b"", None).await;
+    assert_eq!(violation(result), ExecutionError::Timeout(50));
+}
+
+#[tokio::test]
+async fn test_memory_growth_beyond_limit_is_reported() {
+    let ctx = context_with_limits(limits(5_000, 4, 1_000_000));
+    // Growing by 2 pages stays within the limit, growing by 8 does not
+    run(ctx.clone(), GROW_MEMORY, &[0u8; 2], None).await.expect("Growth within the limit should succeed");
+    let result = run(ctx, GROW_MEMORY, &[0u8; 8], None).await;
+    assert_eq!(violation(result), ExecutionError::MemoryLimit(4));
+}
+
+#[tokio::test]
+async fn test_declared_memory_beyond_limit_is_reported() {
+    let ctx = context_with_limits(limits(5_000, 4, 1_000_000));
+    let result = run(ctx, r#"(module (memory 8) (func (export "_start")))"#, b"", None).await;
+    assert_eq!(violation(result), ExecutionError::MemoryLimit(4));
+}
+
+#[tokio::test]
+async fn test_scope_limits_override_defaults() {
+    let mut ctx = context_with_limits(limits(5_000, 64, 1_000_000));
+    ctx.config.scope_limits.insert(ScopeType::Community, limits(5_000, 2, 1_000_000));
+
+    run(ctx.clone(), GROW_MEMORY, &[0u8; 8], None).await.expect("Default limits should allow the growth");
+
+    ctx.scope = Some(ScopeType::Community);
+    let result = run(ctx, GROW_MEMORY, &[0u8; 8], None).await;
+    assert_eq!(violation(result), ExecutionError::MemoryLimit(2));
+}
+
+#[tokio::test]
+async fn test_violation_is_recorded_in_receipt() {
+    let receipts_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let ctx = TestContext::with_config(ExecutionConfig {
+        auto_issue_receipts: true,
+        anchor_receipts: false,
+        receipt_export_dir: Some(receipts_dir.path().to_path_buf()),
+        limits: limits(60_000, 16, 1_000),
+        ..ExecutionConfig::default()
+    });
+
+    let result = run(ctx, INFINITE_LOOP, b"", None).await;
+    assert_eq!(violation(result), ExecutionError::OutOfFuel(1_000));
+
+    let entries: Vec<_> = std::fs::read_dir(receipts_dir.path()).unwrap().collect();
+    assert_eq!(entries.len(), 1, "Expected one exported receipt");
+    let receipt: ExecutionReceipt =
+        serde_json::from_slice(&std::fs::read(entries[0].as_ref().unwrap().path()).unwrap()).unwrap();
+    assert_eq!(receipt.credential_subject.status, ExecutionStatus::OutOfFuel);
+}
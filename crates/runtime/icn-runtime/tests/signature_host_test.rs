@@ -0,0 +1,84 @@
+mod common;
+
+use common::{module_cid, wasm, TestContext};
+use icn_identity_core::did::DidKey;
+use icn_runtime::host::signature::SIGNATURE_CALL_FUEL;
+use icn_runtime::ModernWasmExecutor;
+use std::sync::Arc;
+
+/// Escape bytes for a WAT data segment
+fn escape(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("\\{:02x}", b)).collect()
+}
+
+/// Module that verifies `signature` by `did` over `message` and outputs the result code
+fn verifier(did: &str, message: &[u8], signature: &[u8]) -> Vec<u8> {
+    wasm(&format!(
+        r#"
+        (module
+          (import "env" "verify_signature" (func $verify (param i32 i32 i32 i32 i32 i32) (result i32)))
+          (import "env" "write_output" (func $write_output (param i32 i32) (result i32)))
+          (memory (export "memory") 1)
+          (data (i32.const 0) "{}")
+          (data (i32.const 512) "{}")
+          (data (i32.const 1024) "{}")
+          (func (export "_start")
+            (i32.store (i32.const 2048)
+              (call $verify (i32.const 0) (i32.const {}) (i32.const 512) (i32.const {}) (i32.const 1024) (i32.const {})))
+            (drop (call $write_output (i32.const 2048) (i32.const 4))))
+        )
+        "#,
+        escape(did.as_bytes()),
+        escape(message),
+        escape(signature),
+        did.len(),
+        message.len(),
+        signature.len(),
+    ))
+}
+
+async fn verify(did: &str, message: &[u8], signature: &[u8]) -> (i32, u64) {
+    let module = verifier(did, message, signature);
+    let result = ModernWasmExecutor::new()
+        .unwrap()
+        .execute(&module, Arc::new(TestContext::new()), module_cid(&module), None, None, Some(1_000_000))
+        .await
+        .expect("Execution failed");
+    (i32::from_le_bytes(result.output[..4].try_into().unwrap()), result.fuel_consumed.unwrap())
+}
+
+#[tokio::test]
+async fn test_valid_signature_verifies() {
+    let key = DidKey::new();
+    let message = b"approve proposal 7";
+    let signature = key.sign(message).to_bytes();
+
+    let (code, fuel_consumed) = verify(&key.did().to_string(), message, &signature).await;
+    assert_eq!(code, 1);
+    assert!(fuel_consumed >= SIGNATURE_CALL_FUEL);
+}
+
+#[tokio::test]
+async fn test_invalid_signatures_are_rejected() {
+    let key = DidKey::new();
+    let signature = key.sign(b"approve proposal 7").to_bytes();
+
+    let (code, _) = verify(&key.did().to_string(), b"approve proposal 8", &signature).await;
+    assert_eq!(code, 0, "Tampered message should not verify");
+
+    let other = DidKey::new();
+    let (code, _) = verify(&other.did().to_string(), b"approve proposal 7", &signature).await;
+    assert_eq!(code, 0, "Signature by another key should not verify");
+
+    let (code, _) = verify(&key.did().to_string(), b"approve proposal 7", &signature[..32]).await;
+    assert_eq!(code, 0, "Truncated signature should not verify");
+}
+
+#[tokio::test]
+async fn test_unparseable_did_is_an_argument_error() {
+    let key = DidKey::new();
+    let signature = key.sign(b"hello").to_bytes();
+
+    let (code, _) = verify("not-a-did", b"hello", &signature).await;
+    assert_eq!(code, -2);
+}
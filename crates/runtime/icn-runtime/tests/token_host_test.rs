@@ -0,0 +1,177 @@
+mod common;
+
+use common::{module_cid, wasm, TestContext};
+use icn_economics::{InMemoryTokenStore, ResourceType, TokenStore, TransactionType};
+use icn_runtime::engine::{ExecutionResult, ModernWasmExecutor, EXECUTE_MODULE_ACTION};
+use icn_runtime::host::economics::{
+    TOKEN_ERR_INSUFFICIENT_FUNDS, TOKEN_ERR_UNAUTHORIZED, TOKEN_ERR_UNAVAILABLE, TRANSFER_ACTION,
+};
+use icn_runtime::policy::{DefaultPolicyLoader, ScopeType};
+use icn_types::dag::NodeScope;
+use icn_types::{PolicyRule, ScopePolicyConfig};
+use std::sync::Arc;
+
+/// Module whose `_start` runs `body`. "credit" is at offset 0 and "coop-b" at 16;
+/// `$emit` and `$emit64` append a return value to the output.
+fn ledger_module(body: &str) -> Vec<u8> {
+    wasm(&format!(
+        r#"(module
+             (import "env" "write_output" (func $write_output (param i32 i32) (result i32)))
+             (import "env" "token_balance" (func $balance (param i32 i32) (result i64)))
+             (import "env" "token_debit" (func $debit (param i32 i32 i64) (result i32)))
+             (import "env" "token_credit" (func $credit (param i32 i32 i64) (result i32)))
+             (import "env" "token_transfer" (func $transfer (param i32 i32 i32 i32 i64) (result i32)))
+             (import "icn" "host_check_resource_authorization" (func $authorized (param i32 i32 i64) (result i32)))
+             (import "icn" "host_record_resource_usage" (func $record (param i32 i32 i64)))
+             (memory (export "memory") 1)
+             (data (i32.const 0) "credit")
+             (data (i32.const 16) "coop-b")
+             (func $emit (param i32)
+               (i32.store (i32.const 512) (local.get 0))
+               (drop (call $write_output (i32.const 512) (i32.const 4))))
+             (func $emit64 (param i64)
+               (i64.store (i32.const 512) (local.get 0))
+               (drop (call $write_output (i32.const 512) (i32.const 8))))
+             (func (export "_start") {body})
+           )"#
+    ))
+}
+
+/// Context for a run in cooperative "coop-a", whose account holds 100 credits
+async fn coop_context() -> TestContext {
+    let mut ctx = TestContext::new();
+    ctx.scope = Some(ScopeType::Cooperative);
+    ctx.scope_id = Some("coop-a".to_string());
+    let tokens = InMemoryTokenStore::new();
+    tokens.credit("coop-a", ResourceType::Credit, 100).await.unwrap();
+    ctx.tokens = Some(Arc::new(tokens));
+    ctx
+}
+
+async fn execute(ctx: &TestContext, module: &[u8]) -> anyhow::Result<ExecutionResult> {
+    let executor = ModernWasmExecutor::new().expect("Failed to create executor");
+    executor.execute(module, Arc::new(ctx.clone()), module_cid(module), None, None, None).await
+}
+
+fn codes(output: &[u8]) -> Vec<i32> {
+    output.chunks(4).map(|chunk| i32::from_le_bytes(chunk.try_into().unwrap())).collect()
+}
+
+async fn balance(ctx: &TestContext, account: &str) -> u64 {
+    ctx.tokens.as_ref().unwrap().get_balance(account, &ResourceType::Credit).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_debit_and_credit_update_scope_account() {
+    let ctx = coop_context().await;
+    let module = ledger_module(
+        r#"(call $emit (call $debit (i32.const 0) (i32.const 6) (i64.const 30)))
+           (call $emit (call $credit (i32.const 0) (i32.const 6) (i64.const 5)))
+           (call $emit64 (call $balance (i32.const 0) (i32.const 6)))"#,
+    );
+
+    let result = execute(&ctx, &module).await.expect("Execution failed");
+    assert_eq!(codes(&result.output[..8]), vec![0, 0]);
+    assert_eq!(i64::from_le_bytes(result.output[8..].try_into().unwrap()), 75);
+    assert_eq!(balance(&ctx, "coop-a").await, 75);
+
+    let types: Vec<_> = result.transactions.iter().map(|t| t.transaction_type.clone()).collect();
+    assert_eq!(types, vec![TransactionType::Debit, TransactionType::Credit]);
+    assert!(result.transactions.iter().all(|t| t.authority == ctx.caller));
+}
+
+#[tokio::test]
+async fn test_transfer_moves_tokens_to_destination() {
+    let ctx = coop_context().await;
+    let module = ledger_module(
+        r#"(call $emit (call $transfer (i32.const 0) (i32.const 6) (i32.const 16) (i32.const 6) (i64.const 40)))"#,
+    );
+
+    let result = execute(&ctx, &module).await.expect("Execution failed");
+    assert_eq!(codes(&result.output), vec![0]);
+    assert_eq!(balance(&ctx, "coop-a").await, 60);
+    assert_eq!(balance(&ctx, "coop-b").await, 40);
+}
+
+#[tokio::test]
+async fn test_debit_beyond_balance_is_rejected() {
+    let ctx = coop_context().await;
+    let module = ledger_module(r#"(call $emit (call $debit (i32.const 0) (i32.const 6) (i64.const 500)))"#);
+
+    let result = execute(&ctx, &module).await.expect("Execution failed");
+    assert_eq!(codes(&result.output), vec![TOKEN_ERR_INSUFFICIENT_FUNDS]);
+    assert!(result.transactions.is_empty());
+    assert_eq!(balance(&ctx, "coop-a").await, 100);
+}
+
+#[tokio::test]
+async fn test_policy_gates_minting() {
+    let mut ctx = coop_context().await;
+    let policy = DefaultPolicyLoader::new();
+    policy.set_policy(ScopePolicyConfig {
+        scope_type: NodeScope::Cooperative,
+        scope_id: "coop-a".to_string(),
+        allowed_actions: [EXECUTE_MODULE_ACTION, TRANSFER_ACTION]
+            .iter()
+            .map(|action| PolicyRule {
+                action_type: action.to_string(),
+                required_membership: None,
+                allowed_dids: Some(vec![ctx.caller.clone()]),
+            })
+            .collect(),
+    });
+    ctx.policy = Some(Arc::new(policy));
+    let module = ledger_module(
+        r#"(call $emit (call $credit (i32.const 0) (i32.const 6) (i64.const 5)))
+           (call $emit (call $transfer (i32.const 0) (i32.const 6) (i32.const 16) (i32.const 6) (i64.const 5)))"#,
+    );
+
+    let result = execute(&ctx, &module).await.expect("Execution failed");
+    assert_eq!(codes(&result.output), vec![TOKEN_ERR_UNAUTHORIZED, 0]);
+    assert_eq!(balance(&ctx, "coop-a").await, 95);
+}
+
+#[tokio::test]
+async fn test_trap_rolls_back_ledger_changes() {
+    let ctx = coop_context().await;
+    let module = ledger_module(
+        r#"(drop (call $transfer (i32.const 0) (i32.const 6) (i32.const 16) (i32.const 6) (i64.const 40)))
+           (drop (call $debit (i32.const 0) (i32.const 6) (i64.const 10)))
+           unreachable"#,
+    );
+
+    execute(&ctx, &module).await.expect_err("Module should trap");
+    assert_eq!(balance(&ctx, "coop-a").await, 100);
+    assert_eq!(balance(&ctx, "coop-b").await, 0);
+}
+
+#[tokio::test]
+async fn test_ccl_resource_imports() {
+    let ctx = coop_context().await;
+    let module = ledger_module(
+        r#"(call $emit (call $authorized (i32.const 0) (i32.const 6) (i64.const 50)))
+           (call $emit (call $authorized (i32.const 0) (i32.const 6) (i64.const 500)))
+           (call $record (i32.const 0) (i32.const 6) (i64.const 20))"#,
+    );
+    let result = execute(&ctx, &module).await.expect("Execution failed");
+    assert_eq!(codes(&result.output), vec![1, 0]);
+    assert_eq!(balance(&ctx, "coop-a").await, 80);
+
+    // Usage the account cannot cover traps, undoing the usage recorded before it
+    let module = ledger_module(
+        r#"(call $record (i32.const 0) (i32.const 6) (i64.const 20))
+           (call $record (i32.const 0) (i32.const 6) (i64.const 500))"#,
+    );
+    execute(&ctx, &module).await.expect_err("Unrecorded usage should trap");
+    assert_eq!(balance(&ctx, "coop-a").await, 80);
+}
+
+#[tokio::test]
+async fn test_ledger_calls_without_store_are_unavailable() {
+    let mut ctx = coop_context().await;
+    ctx.tokens = None;
+    let module = ledger_module(r#"(call $emit (call $debit (i32.const 0) (i32.const 6) (i64.const 1)))"#);
+
+    let result = execute(&ctx, &module).await.expect("Execution failed");
+    assert_eq!(codes(&result.output), vec![TOKEN_ERR_UNAVAILABLE]);
+}
@@ -0,0 +1,158 @@
+#![cfg(feature = "wasi")]
+
+mod common;
+
+use common::{module_cid, wasm, TestContext};
+use icn_runtime::engine::{ExecutionResult, ModernWasmExecutor, WasiConfig};
+use icn_runtime::ValidationPolicy;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Writes "hello\n" to stdout and "oops\n" to stderr
+const HELLO: &str = r#"
+    (module
+      (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+      (memory (export "memory") 1)
+      (data (i32.const 100) "hello\n")
+      (data (i32.const 110) "oops\n")
+      (func (export "_start")
+        (i32.store (i32.const 0) (i32.const 100))
+        (i32.store (i32.const 4) (i32.const 6))
+        (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))
+        (i32.store (i32.const 0) (i32.const 110))
+        (i32.store (i32.const 4) (i32.const 5))
+        (drop (call $fd_write (i32.const 2) (i32.const 0) (i32.const 1) (i32.const 8))))
+    )
+"#;
+
+/// Creates and writes "data.txt" in the preopened scratch directory (fd 3), then
+/// tries to escape it and to use a descriptor beyond the preopens. Each errno is
+/// appended to the output through `write_output`.
+const SCRATCH: &str = r#"
+    (module
+      (import "wasi_snapshot_preview1" "path_open"
+        (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))
+      (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+      (import "wasi_snapshot_preview1" "fd_prestat_get" (func $prestat (param i32 i32) (result i32)))
+      (import "env" "write_output" (func $write_output (param i32 i32) (result i32)))
+      (memory (export "memory") 1)
+      (data (i32.const 100) "data.txt")
+      (data (i32.const 120) "../escape.txt")
+      (data (i32.const 140) "stored")
+      (func $emit (param i32)
+        (i32.store (i32.const 512) (local.get 0))
+        (drop (call $write_output (i32.const 512) (i32.const 4))))
+      (func $open (param i32 i32) (result i32)
+        (call $path_open (i32.const 3) (i32.const 0) (local.get 0) (local.get 1)
+                         (i32.const 1) (i64.const 66) (i64.const 0) (i32.const 0) (i32.const 200)))
+      (func (export "_start")
+        (call $emit (call $open (i32.const 100) (i32.const 8)))
+        (i32.store (i32.const 0) (i32.const 140))
+        (i32.store (i32.const 4) (i32.const 6))
+        (call $emit (call $fd_write (i32.load (i32.const 200)) (i32.const 0) (i32.const 1) (i32.const 204)))
+        (call $emit (call $open (i32.const 120) (i32.const 13)))
+        (call $emit (call $prestat (i32.const 4) (i32.const 300))))
+    )
+"#;
+
+/// Writes a realtime clock reading and 16 random bytes to stdout
+const CLOCK_AND_RANDOM: &str = r#"
+    (module
+      (import "wasi_snapshot_preview1" "clock_time_get" (func $clock (param i32 i64 i32) (result i32)))
+      (import "wasi_snapshot_preview1" "random_get" (func $random (param i32 i32) (result i32)))
+      (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+      (memory (export "memory") 1)
+      (func (export "_start")
+        (drop (call $clock (i32.const 0) (i64.const 1) (i32.const 104)))
+        (drop (call $random (i32.const 112) (i32.const 16)))
+        (i32.store (i32.const 0) (i32.const 104))
+        (i32.store (i32.const 4) (i32.const 24))
+        (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8))))
+    )
+"#;
+
+fn exiting(status: i32) -> String {
+    format!(
+        r#"(module
+             (import "wasi_snapshot_preview1" "proc_exit" (func $exit (param i32)))
+             (memory (export "memory") 1)
+             (func (export "_start") (call $exit (i32.const {status}))))"#
+    )
+}
+
+fn sandboxed(executor: ModernWasmExecutor, scratch_root: &Path, keep_scratch: bool) -> ModernWasmExecutor {
+    executor.with_wasi(WasiConfig { scratch_root: scratch_root.to_path_buf(), keep_scratch })
+}
+
+async fn run(executor: &ModernWasmExecutor, wat: &str) -> anyhow::Result<ExecutionResult> {
+    let module = wasm(wat);
+    executor
+        .execute(&module, Arc::new(TestContext::new()), module_cid(&module), None, Some(b"job-1"), None)
+        .await
+}
+
+fn codes(output: &[u8]) -> Vec<i32> {
+    output.chunks(4).map(|chunk| i32::from_le_bytes(chunk.try_into().unwrap())).collect()
+}
+
+#[tokio::test]
+async fn test_stdout_is_captured_into_output() {
+    let scratch_root = tempfile::tempdir().expect("Failed to create temp dir");
+    let executor = sandboxed(ModernWasmExecutor::new().unwrap(), scratch_root.path(), false);
+
+    let result = run(&executor, HELLO).await.expect("Execution failed");
+    assert_eq!(result.output, b"hello\n");
+    // The scratch directory is removed once the job finishes
+    assert_eq!(std::fs::read_dir(scratch_root.path()).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn test_filesystem_is_confined_to_scratch_directory() {
+    let scratch_root = tempfile::tempdir().expect("Failed to create temp dir");
+    let executor = sandboxed(ModernWasmExecutor::new().unwrap(), scratch_root.path(), true);
+
+    let result = run(&executor, SCRATCH).await.expect("Execution failed");
+    let codes = codes(&result.output);
+    assert_eq!(codes[..2], [0, 0]);
+    assert_ne!(codes[2], 0, "Escaping the scratch directory should fail");
+    assert_ne!(codes[3], 0, "Only the scratch directory should be preopened");
+
+    let scratch = std::fs::read_dir(scratch_root.path()).unwrap().next().unwrap().unwrap().path();
+    assert_eq!(std::fs::read(scratch.join("data.txt")).unwrap(), b"stored");
+    assert!(!scratch_root.path().join("escape.txt").exists());
+}
+
+#[tokio::test]
+async fn test_deterministic_profile_seeds_wasi_clock_and_random() {
+    let scratch_root = tempfile::tempdir().expect("Failed to create temp dir");
+    let first = run(&sandboxed(ModernWasmExecutor::deterministic().unwrap(), scratch_root.path(), false), CLOCK_AND_RANDOM)
+        .await
+        .expect("Execution failed");
+    let second = run(&sandboxed(ModernWasmExecutor::deterministic().unwrap(), scratch_root.path(), false), CLOCK_AND_RANDOM)
+        .await
+        .expect("Execution failed");
+
+    assert_eq!(first.output, second.output);
+    assert_eq!(first.result_cid, second.result_cid);
+    // The seeded clock's first reading is 1 ms, reported in nanoseconds
+    assert_eq!(u64::from_le_bytes(first.output[..8].try_into().unwrap()), 1_000_000);
+}
+
+#[tokio::test]
+async fn test_exit_status() {
+    let scratch_root = tempfile::tempdir().expect("Failed to create temp dir");
+    let executor = sandboxed(ModernWasmExecutor::new().unwrap(), scratch_root.path(), false);
+
+    run(&executor, &exiting(0)).await.expect("Exit status 0 should succeed");
+    let error = run(&executor, &exiting(3)).await.expect_err("Exit status 3 should fail");
+    assert!(format!("{:#}", error).contains("exited with status 3"), "Unexpected error: {:#}", error);
+}
+
+#[tokio::test]
+async fn test_wasi_requires_opt_in() {
+    let executor = ModernWasmExecutor::new().unwrap();
+    run(&executor, HELLO).await.expect_err("WASI imports should not link");
+
+    let report = executor.validate_module(&wasm(HELLO), &ValidationPolicy::default().with_wasi());
+    assert!(report.is_valid(), "Unexpected issues: {:?}", report.issues);
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::{module_cid, wasm, TestContext};
+use icn_runtime::block_store::BlockStore;
+use icn_runtime::engine::ModernWasmExecutor;
+use icn_types::Cid;
+use std::sync::Arc;
+
+const FUEL: Option<u64> = Some(1_000_000);
+
+/// Reads its input and writes it back twice
+const ECHO_TWICE: &str = r#"
+    (module
+      (import "env" "get_input_len" (func $get_input_len (result i32)))
+      (import "env" "read_input" (func $read_input (param i32 i32) (result i32)))
+      (import "env" "write_output" (func $write_output (param i32 i32) (result i32)))
+      (memory (export "memory") 1)
+      (func (export "_start")
+        (local $len i32)
+        (local.set $len (call $get_input_len))
+        (drop (call $read_input (i32.const 0) (local.get $len)))
+        (drop (call $write_output (i32.const 0) (local.get $len)))
+        (drop (call $write_output (i32.const 0) (local.get $len)))
+      )
+    )
+"#;
+
+#[tokio::test]
+async fn test_output_is_content_addressed_and_stored() {
+    let wasm_bytes = wasm(ECHO_TWICE);
+    let executor = ModernWasmExecutor::new().expect("Failed to create executor");
+    let ctx = Arc::new(TestContext::new());
+
+    let result = executor
+        .execute(&wasm_bytes, ctx.clone(), module_cid(&wasm_bytes), None, Some(b"icn"), FUEL)
+        .await
+        .expect("Execution failed");
+
+    assert_eq!(result.output, b"icnicn");
+    assert_eq!(result.result_cid, Cid::from_bytes(b"icnicn").unwrap());
+    assert_eq!(ctx.blocks.get(&result.result_cid).unwrap(), Some(b"icnicn".to_vec()));
+}
+
+#[tokio::test]
+async fn test_different_inputs_give_different_result_cids() {
+    let wasm_bytes = wasm(ECHO_TWICE);
+    let executor = ModernWasmExecutor::new().expect("Failed to create executor");
+    let ctx = Arc::new(TestContext::new());
+    let cid = module_cid(&wasm_bytes);
+
+    let first = executor.execute(&wasm_bytes, ctx.clone(), cid.clone(), None, Some(b"a"), FUEL).await.unwrap();
+    let second = executor.execute(&wasm_bytes, ctx.clone(), cid.clone(), None, Some(b"b"), FUEL).await.unwrap();
+    let empty = executor.execute(&wasm_bytes, ctx, cid, None, None, FUEL).await.unwrap();
+
+    assert_ne!(first.result_cid, second.result_cid);
+    assert!(empty.output.is_empty());
+    assert_eq!(empty.result_cid, Cid::from_bytes(&[]).unwrap());
+}
+
+#[tokio::test]
+async fn test_out_of_bounds_output_is_rejected() {
+    let wasm_bytes = wasm(r#"
+        (module
+          (import "env" "write_output" (func $write_output (param i32 i32) (result i32)))
+          (import "env" "log" (func $log (param i32 i32)))
+          (memory (export "memory") 1)
+          (data (i32.const 0) "rejected")
+          (func (export "_start")
+            ;; One page is 65536 bytes, so this range is out of bounds
+            (if (i32.eq (call $write_output (i32.const 65530) (i32.const 16)) (i32.const -1))
+              (then (call $log (i32.const 0) (i32.const 8))))
+          )
+        )
+    "#);
+    let executor = ModernWasmExecutor::new().expect("Failed to create executor");
+    let ctx = Arc::new(TestContext::new());
+
+    let result = executor
+        .execute(&wasm_bytes, ctx.clone(), module_cid(&wasm_bytes), None, None, FUEL)
+        .await
+        .expect("Execution failed");
+
+    assert!(result.output.is_empty());
+    assert_eq!(*ctx.logs.lock().unwrap(), vec!["rejected".to_string()]);
+}
@@ -134,10 +134,10 @@ async fn test_host_log_invocation() {
     let executor = ModernWasmExecutor::new().expect("Failed to create executor");
     let mock_ctx = std::sync::Arc::new(MockContext::new());
 
-    let dummy_module_cid = Cid::from_bytes(b"test_module_cid_0123456789abcdef").unwrap(); // Dummy CID
+    let module_cid = Cid::from_bytes(&wasm_bytes).unwrap();
 
     // Run the module with the context
-    executor.execute(&wasm_bytes, mock_ctx.clone(), dummy_module_cid, None, None, None).await
+    executor.execute(&wasm_bytes, mock_ctx.clone(), module_cid, None, None, None).await
         .expect("Failed to run WASM module");
 
     // Assert that the log_message method was called with the correct message
@@ -21,6 +21,7 @@ pub mod commands {
 
     // This is a simplified view. The actual modules must be made public.
     pub use crate::commands::bundle;
+    pub use crate::commands::ccl;
     pub use crate::commands::coop;
     pub use crate::commands::community;
     pub use crate::commands::dag;
@@ -113,5 +114,9 @@ pub enum Commands {
     /// Observability commands for federation transparency
     #[command(subcommand)]
     Observe(commands::observability::ObservabilityCommands),
+
+    /// CCL source tooling
+    #[command(subcommand)]
+    Ccl(commands::ccl::CclCommands),
     Doctor,
 } 
\ No newline at end of file
@@ -0,0 +1,53 @@
+use clap::Subcommand;
+use crate::context::CliContext;
+use crate::error::{CliError, CliResult};
+use icn_ccl_parser::format_ccl;
+use std::fs;
+use std::path::PathBuf;
+
+/// CCL source tooling
+#[derive(Subcommand, Debug, Clone)]
+pub enum CclCommands {
+    /// Rewrite CCL files in canonical form, keeping their comments
+    Fmt {
+        /// CCL files to format
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Only report files that are not formatted, without changing them
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+pub async fn handle_ccl_command(_ctx: &mut CliContext, cmd: &CclCommands) -> CliResult {
+    match cmd {
+        CclCommands::Fmt { files, check } => handle_fmt(files, *check),
+    }
+}
+
+fn handle_fmt(files: &[PathBuf], check: bool) -> CliResult {
+    let mut unformatted = 0;
+    for file in files {
+        let source = fs::read_to_string(file)?;
+        let origin = file.display().to_string();
+        let formatted = format_ccl(&source).map_err(|e| {
+            eprintln!("{}", e.diagnostic().render(&source, &origin));
+            CliError::InvalidInput(format!("could not format {}", origin))
+        })?;
+        if formatted == source {
+            continue;
+        }
+        if check {
+            println!("{} is not formatted", origin);
+            unformatted += 1;
+        } else {
+            fs::write(file, formatted)?;
+            println!("Formatted {}", origin);
+        }
+    }
+    if unformatted > 0 {
+        return Err(CliError::InvalidInput(format!("{} file(s) would be reformatted", unformatted)));
+    }
+    Ok(())
+}
@@ -10,7 +10,7 @@ use icn_types::dag::NodeScope;
 use icn_runtime::dag_indexing::{SledDagIndex, DagIndex, IndexError};
 use std::str::FromStr;
 use anyhow::Result;
-use icn_ccl_compiler::compile;
+use icn_ccl_compiler::{check, compile};
 use chrono::Utc;
 use serde_json::json;
 
@@ -310,7 +310,16 @@ async fn handle_propose_ccl(file: PathBuf, scope: String, title: String, out: Op
     let source = std::fs::read_to_string(&file)
         .map_err(|e| anyhow::anyhow!("Failed to read CCL file {}: {}", file.display(), e))?;
 
-    // 2. compile CCL ➜ Wasm + hash
+    // 2. check, printing every diagnostic rustc-style, then compile CCL ➜ Wasm + hash
+    let origin = file.display().to_string();
+    let diagnostics = check(&source);
+    for diagnostic in &diagnostics {
+        eprintln!("{}", diagnostic.render(&source, &origin));
+    }
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    if errors > 0 {
+        return Err(CliError::InvalidInput(format!("could not compile {} due to {} previous error(s)", origin, errors)));
+    }
     let art = compile(&source, &scope)
         .map_err(|e| anyhow::anyhow!("CCL compilation error: {}", e))?;
 
@@ -14,6 +14,7 @@ pub mod community;
 pub mod scope;
 pub mod observability;
 pub mod doctor;
+pub mod ccl;
 
 // Re-export handlers for main.rs
 pub use dag::handle_dag_command;
@@ -28,6 +29,7 @@ pub use sync_p2p::handle_dag_sync_command;
 pub use policy::handle_policy_command;
 pub use keygen::handle_key_gen;
 pub use observability::handle_dag_view;
+pub use ccl::handle_ccl_command;
 
 // Export coop module components
 pub use coop::CoopCommands;
@@ -2,6 +2,10 @@ use clap::{Subcommand, Args, ValueHint};
 use crate::error::CliResult;
 use crate::context::CliContext;
 use crate::error::CliError;
+use crate::config;
+use crate::commands::runtime::{LedgerFile, RuntimeExecutionContext};
+use icn_ccl_compiler::{compile_proposals, CclDuration, CclThreshold, ProposalArtifact};
+use icn_runtime::{config::ExecutionConfig, ModernWasmExecutor};
 use icn_identity_core::{
     did::DidKey,
     vc::{
@@ -19,10 +23,11 @@ use icn_identity_core::{
     QuorumEngine,
     QuorumOutcome,
 };
-use icn_types::dag::{DagStore, EventId, EventType, EventPayload, DagEvent};
+use icn_types::dag::{DagStore, EventId, EventType, EventPayload, DagEvent, SharedDagStore};
 use icn_core_types::{Cid, Did};
 use icn_identity_core::ExecutionStatus;
-use std::path::PathBuf;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
 use std::fs;
 use std::io::{self, Read};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -126,9 +131,9 @@ pub struct SubmitProposalArgs {
     #[clap(long)]
     federation: String,
     
-    /// Proposal title
-    #[clap(long)]
-    title: String,
+    /// Proposal title. With --ccl, selects the proposal block to submit when the file has several.
+    #[clap(long, required_unless_present = "ccl")]
+    title: Option<String>,
     
     /// Proposal description
     #[clap(long)]
@@ -158,6 +163,11 @@ pub struct SubmitProposalArgs {
     #[clap(long)]
     parameters: Option<String>,
     
+    /// CCL file with a `proposal` block. Its title, threshold, duration and scope are used
+    /// instead of the flags, and its `on_pass` body becomes the code executed once it passes.
+    #[clap(long, value_parser, value_hint = ValueHint::FilePath)]
+    ccl: Option<PathBuf>,
+    
     /// Output file for the proposal credential (JSON format). If not provided, prints to stdout.
     #[clap(long, short, value_parser, value_hint = ValueHint::FilePath)]
     output: Option<PathBuf>,
@@ -253,18 +263,44 @@ pub async fn handle_proposal_commands(
             let submitter_did = submitter_key.did().to_string();
             
             // Parse voting threshold and duration
-            let voting_threshold = parse_voting_threshold(&args.voting_threshold)?;
-            let voting_duration = parse_voting_duration(&args.voting_duration)?;
-            let proposal_type = parse_proposal_type(&args.proposal_type)?;
+            let mut voting_threshold = parse_voting_threshold(&args.voting_threshold)?;
+            let mut voting_duration = parse_voting_duration(&args.voting_duration)?;
+            let mut proposal_type = parse_proposal_type(&args.proposal_type)?;
+            let mut execution_cid = args.execution_cid;
+            let mut title = args.title;
             
             // Parse optional parameters if provided
-            let parameters = if let Some(params_str) = args.parameters {
+            let mut parameters = if let Some(params_str) = args.parameters {
                 Some(serde_json::from_str::<Value>(&params_str)
                     .map_err(|e| CliError::InvalidArgument(format!("Invalid JSON parameters: {}", e)))?)
             } else {
                 None
             };
             
+            // A CCL proposal block supplies the voting rules and the code to execute
+            if let Some(ccl_path) = &args.ccl {
+                let compiled = load_ccl_proposal(ccl_path, title.as_deref())?;
+                let wasm_path = store_proposal_wasm(&compiled)?;
+                println!("Compiled on_pass of '{}' to {}", compiled.title, wasm_path.display());
+                
+                match parameters.get_or_insert_with(|| json!({})) {
+                    Value::Object(params) => {
+                        params.insert("scope".to_string(), json!(compiled.scope));
+                    }
+                    _ => {
+                        return Err(CliError::InvalidArgument(
+                            "--parameters must be a JSON object when --ccl is used".to_string()
+                        ));
+                    }
+                }
+                voting_threshold = voting_threshold_from_ccl(compiled.threshold);
+                voting_duration = voting_duration_from_ccl(compiled.duration);
+                proposal_type = ProposalType::CodeExecution;
+                execution_cid = Some(compiled.on_pass.hash_hex);
+                title = Some(compiled.title);
+            }
+            let title = title.ok_or_else(|| CliError::InvalidArgument("--title is required without --ccl".to_string()))?;
+            
             // Create timestamps
             let now = current_time();
             let voting_end_time = match voting_duration {
@@ -274,7 +310,7 @@ pub async fn handle_proposal_commands(
             
             // Validate proposal type specific requirements
             if matches!(proposal_type, ProposalType::CodeExecution | ProposalType::CodeUpgrade) {
-                if args.execution_cid.is_none() {
+                if execution_cid.is_none() {
                     return Err(CliError::InvalidArgument(
                         "execution_cid is required for CodeExecution and CodeUpgrade proposal types".to_string()
                     ));
@@ -284,7 +320,7 @@ pub async fn handle_proposal_commands(
             // Create proposal subject
             let subject = ProposalSubject {
                 id: args.federation.clone(),
-                title: args.title,
+                title,
                 description: args.description,
                 proposal_type,
                 status: ProposalStatus::Draft,
@@ -293,7 +329,7 @@ pub async fn handle_proposal_commands(
                 voting_duration,
                 voting_start_time: now,
                 voting_end_time,
-                execution_cid: args.execution_cid,
+                execution_cid,
                 thread_cid: args.thread_cid,
                 parameters,
                 previous_version: None,
@@ -418,17 +454,16 @@ pub async fn handle_proposal_commands(
             println!("\nProceeding with execution...");
             println!("Execution CID: {}", execution_cid);
             
-            // 6. Create execution context
-            // This would setup the VM context with the right DID, etc.
-            // TODO: Replace this with actual execution
-            println!("Initializing execution environment...");
-            
-            // 7. Execute proposal code (simplified for now)
+            // 6. Execute the proposal's compiled code in the runtime
             println!("Executing proposal code...");
-            let result_cid = execute_proposal_stub(&execution_cid);
+            let federation = Did::from_string(&proposal.issuer)
+                .map_err(|e| CliError::InvalidDidFormat(format!("Proposal issuer {}: {}", proposal.issuer, e)))?;
+            let dag_store = SharedDagStore::new(Box::new(ctx.get_dag_store(None)?));
+            let ledger = LedgerFile::open(ledger_path()?).await?;
+            let result_cid = execute_proposal_module(&wasm_dir()?, &execution_cid, federation, executor_key.clone(), dag_store, &ledger).await?;
             println!("Execution completed. Result CID: {}", result_cid);
             
-            // 8. Generate execution receipt
+            // 7. Generate execution receipt
             println!("Generating execution receipt...");
             let receipt = generate_execution_receipt_stub(
                 &executor_key,
@@ -437,7 +472,7 @@ pub async fn handle_proposal_commands(
                 &result_cid,
             );
             
-            // 9. Output receipt
+            // 8. Output receipt
             let receipt_json = receipt.to_json()
                 .map_err(|e| CliError::SerializationError(format!("Failed to serialize receipt: {}", e)))?;
             
@@ -451,7 +486,7 @@ pub async fn handle_proposal_commands(
                 println!("{}", receipt_json);
             }
             
-            // 10. Anchor receipt to DAG
+            // 9. Anchor receipt to DAG
             println!("Anchoring receipt to DAG...");
             anchor_receipt_to_dag(&receipt, ctx, None).await?;
             
@@ -548,16 +583,113 @@ async fn load_votes_for_proposal(
     Ok(votes)
 }
 
-// Stub for executing a proposal
-fn execute_proposal_stub(execution_cid: &str) -> String {
-    // In a real implementation, we would:
-    // 1. Load the WASM module from the execution_cid
-    // 2. Create a VM context with appropriate permissions
-    // 3. Execute the module
-    // 4. Gather the results
+/// Directory holding compiled CCL modules as `<sha256 hex>.wasm`, shared with `dag propose-ccl`
+fn wasm_dir() -> Result<PathBuf, CliError> {
+    Ok(config::data_dir()?.join("wasm"))
+}
+
+/// Token ledger of the proposals the CLI has executed
+fn ledger_path() -> Result<PathBuf, CliError> {
+    Ok(config::data_dir()?.join("ledger.jsonl"))
+}
+
+/// Compile a CCL file and pick its proposal block titled `title`, or its only one
+fn load_ccl_proposal(path: &Path, title: Option<&str>) -> Result<ProposalArtifact, CliError> {
+    let source = fs::read_to_string(path)
+        .map_err(|e| CliError::IoError(format!("Failed to read CCL file {}: {}", path.display(), e)))?;
+    let mut proposals = compile_proposals(&source)
+        .map_err(|e| CliError::InvalidInput(format!("CCL compilation error: {}", e)))?;
+    
+    let titles = proposals.iter().map(|p| format!("'{}'", p.title)).collect::<Vec<_>>().join(", ");
+    let index = match title {
+        Some(title) => proposals.iter().position(|p| p.title == title).ok_or_else(|| {
+            CliError::NotFound(format!("No proposal '{}' in {} (found: {})", title, path.display(), titles))
+        })?,
+        None if proposals.len() == 1 => 0,
+        None if proposals.is_empty() => {
+            return Err(CliError::InvalidInput(format!("{} has no proposal blocks", path.display())));
+        }
+        None => {
+            return Err(CliError::InvalidArgument(format!(
+                "{} has several proposals ({}); choose one with --title", path.display(), titles
+            )));
+        }
+    };
+    Ok(proposals.swap_remove(index))
+}
+
+/// Persist the compiled `on_pass` module so `proposal execute` can find it by hash
+fn store_proposal_wasm(proposal: &ProposalArtifact) -> Result<PathBuf, CliError> {
+    let dir = wasm_dir()?;
+    fs::create_dir_all(&dir)
+        .map_err(|e| CliError::IoError(format!("Failed to create wasm directory {}: {}", dir.display(), e)))?;
+    let path = dir.join(format!("{}.wasm", proposal.on_pass.hash_hex));
+    fs::write(&path, &proposal.on_pass.wasm)
+        .map_err(|e| CliError::IoError(format!("Failed to write Wasm file {}: {}", path.display(), e)))?;
+    Ok(path)
+}
+
+fn voting_threshold_from_ccl(threshold: CclThreshold) -> VotingThreshold {
+    match threshold {
+        CclThreshold::Majority => VotingThreshold::Majority,
+        CclThreshold::Percentage(percent) => VotingThreshold::Percentage(percent),
+        CclThreshold::Unanimous => VotingThreshold::Unanimous,
+    }
+}
+
+fn voting_duration_from_ccl(duration: CclDuration) -> VotingDuration {
+    match duration {
+        CclDuration::TimeBased(seconds) => VotingDuration::TimeBased(seconds),
+        CclDuration::BlockBased(blocks) => VotingDuration::BlockBased(blocks),
+        CclDuration::OpenEnded => VotingDuration::OpenEnded,
+    }
+}
+
+/// Run a passed proposal's module, stored under its hash in `dir` by `proposal submit --ccl`,
+/// for `federation`, anchoring as `signer`; record its ledger transactions and return
+/// the CID of its result
+async fn execute_proposal_module(
+    dir: &Path,
+    execution_cid: &str,
+    federation: Did,
+    signer: DidKey,
+    dag_store: SharedDagStore,
+    ledger: &LedgerFile,
+) -> Result<String, CliError> {
+    let wasm_path = dir.join(format!("{}.wasm", execution_cid));
+    let wasm = fs::read(&wasm_path).map_err(|e| {
+        CliError::NotFound(format!("Module {} not found at {}: {}", execution_cid, wasm_path.display(), e))
+    })?;
+    // The file is only named by its hash; refuse one that was replaced or corrupted
+    let digest = format!("{:x}", Sha256::digest(&wasm));
+    if !digest.eq_ignore_ascii_case(execution_cid) {
+        return Err(CliError::InvalidInput(format!(
+            "Module at {} has SHA-256 {}, not the proposal's execution CID {}",
+            wasm_path.display(),
+            digest,
+            execution_cid
+        )));
+    }
     
-    // For now, just return a fake result CID
-    format!("result-{}", Uuid::new_v4())
+    // The CLI issues and anchors its own receipt for the execution
+    let config = ExecutionConfig {
+        auto_issue_receipts: false,
+        anchor_receipts: false,
+        receipt_export_dir: None,
+        ..ExecutionConfig::default()
+    };
+    let ctx = Arc::new(RuntimeExecutionContext::new(config, true)?.with_federation(federation, signer, dag_store, ledger.store()));
+    let module_cid = icn_types::Cid::from_bytes(&wasm)
+        .map_err(|e| CliError::InvalidCidFormat(e.to_string()))?;
+    let executor = ModernWasmExecutor::new()
+        .map_err(|e| CliError::Config(format!("Failed to create WASM executor: {}", e)))?;
+    let result = executor
+        .execute(&wasm, ctx, module_cid, None, None, None)
+        .await
+        .map_err(|e| CliError::WasmExecError(e.to_string()))?;
+    ledger.record(&result.transactions)?;
+    
+    Ok(result.result_cid.to_string())
 }
 
 // Stub for generating an execution receipt
@@ -733,4 +865,83 @@ impl<T: DagStore + Send + Sync> DagStoreExt for T {
             _ => Err(icn_types::dag::DagError::NodeNotFound(Cid::from("stub-cid")))
         }
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use icn_economics::{ResourceTransaction, ResourceType, TokenStore};
+    use icn_types::dag::memory::MemoryDagStore;
+    use icn_types::dag::DagPayload;
+    use tempfile::tempdir;
+
+    fn metered_proposal(minutes: &Cid) -> String {
+        format!(
+            r#"
+            proposal "Pay for the build" {{
+                threshold = majority
+                duration  = 1d
+                scope     = "coop-x"
+                on_pass {{
+                    let jobs = 3
+                    perform_metered_action {{
+                        resource_type = "compute_unit"
+                        amount        = jobs * 10
+                    }}
+                    anchor_data {{
+                        cid   = "{}"
+                        bytes = 512
+                    }}
+                }}
+            }}
+            "#,
+            minutes
+        )
+    }
+
+    #[tokio::test]
+    async fn test_on_pass_debits_the_ledger_and_anchors() {
+        let dir = tempdir().unwrap();
+        let minutes = Cid::from_bytes(b"minutes").unwrap();
+        let proposal = compile_proposals(&metered_proposal(&minutes)).unwrap().remove(0);
+        fs::write(dir.path().join(format!("{}.wasm", proposal.on_pass.hash_hex)), &proposal.on_pass.wasm).unwrap();
+
+        let federation = DidKey::new().did().clone();
+        let account = federation.to_string();
+        let ledger_path = dir.path().join("ledger.jsonl");
+        let funding = ResourceTransaction::new_credit(ResourceType::ComputeUnit, 100, &account, &account, federation.clone());
+        LedgerFile::open(ledger_path.clone()).await.unwrap().record(&[funding]).unwrap();
+
+        let ledger = LedgerFile::open(ledger_path.clone()).await.unwrap();
+        let dag_store = SharedDagStore::new(Box::new(MemoryDagStore::new()));
+        execute_proposal_module(dir.path(), &proposal.on_pass.hash_hex, federation, DidKey::new(), dag_store.clone(), &ledger)
+            .await
+            .expect("on_pass should run");
+
+        // The debit is applied and survives reopening the ledger
+        let reopened = LedgerFile::open(ledger_path).await.unwrap();
+        let balance = reopened.store().get_balance(&account, &ResourceType::ComputeUnit).await.unwrap();
+        assert_eq!(balance, 70);
+
+        let nodes = dag_store.get_ordered_nodes().await.unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].node.payload, DagPayload::Reference(minutes));
+    }
+
+    #[tokio::test]
+    async fn test_module_must_match_the_execution_cid() {
+        let dir = tempdir().unwrap();
+        let proposal = compile_proposals(&metered_proposal(&Cid::from_bytes(b"minutes").unwrap())).unwrap().remove(0);
+        let mut tampered = proposal.on_pass.wasm.clone();
+        tampered.push(0);
+        fs::write(dir.path().join(format!("{}.wasm", proposal.on_pass.hash_hex)), &tampered).unwrap();
+
+        let ledger = LedgerFile::open(dir.path().join("ledger.jsonl")).await.unwrap();
+        let dag_store = SharedDagStore::new(Box::new(MemoryDagStore::new()));
+        let federation = DidKey::new().did().clone();
+        let err = execute_proposal_module(dir.path(), &proposal.on_pass.hash_hex, federation, DidKey::new(), dag_store.clone(), &ledger)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not the proposal's execution CID"));
+        assert!(dag_store.get_ordered_nodes().await.unwrap().is_empty());
+    }
+}
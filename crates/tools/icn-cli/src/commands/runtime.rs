@@ -1,14 +1,20 @@
 use clap::{Arg, Args, Subcommand, ArgMatches, ValueHint};
 use crate::context::CliContext;
 use crate::error::{CliError, CliResult};
+use icn_identity_core::did::DidKey;
 use icn_runtime::{
     abi::context::HostContext,
+    host::{verify_with_resolver, DidKeyResolver},
     policy::{MembershipIndex, PolicyLoader},
-    config::ExecutionConfig,
+    config::{ExecutionConfig, RuntimeConfig},
+    daemon::api,
     ModernWasmExecutor,
+    RuntimeDaemon,
     ContextExtension,
+    ValidationPolicy,
 };
-use icn_types::{Cid, Did, dag::{EventId, DagStore}};
+use icn_types::{Cid, Did, dag::{EventId, DagStore, SharedDagStore}};
+use icn_economics::{InMemoryTokenStore, ResourceTransaction, TokenStore};
 use std::path::{PathBuf, Path};
 use std::sync::Arc;
 use std::str::FromStr;
@@ -18,10 +24,6 @@ use std::sync::OnceLock;
 use std::sync::Mutex;
 use anyhow::Result;
 
-// Comment out wasmtime imports - we won't use them
-// use wasmtime;
-// use wasmtime::AsContextMut;
-
 #[derive(Subcommand, Debug)]
 pub enum RuntimeCommands {
     /// Execute a WASM module in the ICN runtime.
@@ -30,6 +32,8 @@ pub enum RuntimeCommands {
     Inspect(InspectModuleArgs),
     /// Validate a WASM module against ICN runtime expectations.
     Validate(ValidateModuleArgs),
+    /// Run the job daemon and serve its local API until interrupted.
+    Daemon(DaemonArgs),
 }
 
 #[derive(Args, Debug, Clone)]
@@ -73,33 +77,128 @@ pub struct ValidateModuleArgs {
     /// Path to the WASM module file to validate.
     #[clap(long, value_hint = ValueHint::FilePath)]
     pub module_path: String,
-    /// Optional path to a schema or policy file for validation.
+    /// Optional path to a JSON validation policy; omitted fields use the runtime defaults.
     #[clap(long, value_hint = ValueHint::FilePath)]
     pub policy_file: Option<PathBuf>,
+    /// Print the full validation report as JSON.
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    pub json: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DaemonArgs {
+    /// Path to a TOML runtime configuration; omitted fields use the runtime defaults.
+    #[clap(long, value_hint = ValueHint::FilePath)]
+    pub config: Option<PathBuf>,
+    /// Print the log output of executed modules.
+    #[clap(long, action = clap::ArgAction::SetTrue)]
+    pub verbose: bool,
 }
 
+/// Context for modules the CLI runs itself, such as passed proposals
 #[derive(Clone)]
-struct RuntimeExecutionContext {
+pub(crate) struct RuntimeExecutionContext {
     execution_config: ExecutionConfig,
     log_enabled: bool,
     errors: Arc<Mutex<Option<String>>>,
     policy_loader: Option<Arc<dyn PolicyLoader + Send + Sync>>,
     membership_index: Option<Arc<dyn MembershipIndex + Send + Sync>>,
+    federation_did: Option<Did>,
+    signer: Option<DidKey>,
+    dag_store: Option<SharedDagStore>,
+    token_store: Option<Arc<dyn TokenStore>>,
 }
 
 // Add this static
 static DUMMY_DID: OnceLock<Did> = OnceLock::new();
 
 impl RuntimeExecutionContext {
-    fn new(config: ExecutionConfig, verbose: bool) -> Result<Self> {
+    pub(crate) fn new(config: ExecutionConfig, verbose: bool) -> Result<Self> {
         Ok(Self {
             execution_config: config,
             log_enabled: verbose,
             errors: Arc::new(Mutex::new(None)),
             policy_loader: None,
             membership_index: None,
+            federation_did: None,
+            signer: None,
+            dag_store: None,
+            token_store: None,
         })
     }
+
+    /// Run on behalf of `federation`, against its DAG and token ledger; nodes the
+    /// module anchors are signed by `signer`
+    pub(crate) fn with_federation(
+        mut self,
+        federation: Did,
+        signer: DidKey,
+        dag_store: SharedDagStore,
+        token_store: Arc<dyn TokenStore>,
+    ) -> Self {
+        self.federation_did = Some(federation);
+        self.signer = Some(signer);
+        self.dag_store = Some(dag_store);
+        self.token_store = Some(token_store);
+        self
+    }
+}
+
+/// Token ledger of the modules the CLI runs, kept as one JSON transaction per line.
+///
+/// Opening the file replays it into memory; the runtime applies a module's
+/// transactions to that store when it commits, and `record` then appends them.
+pub(crate) struct LedgerFile {
+    path: PathBuf,
+    store: Arc<InMemoryTokenStore>,
+}
+
+impl LedgerFile {
+    pub(crate) async fn open(path: PathBuf) -> Result<Self, CliError> {
+        let store = Arc::new(InMemoryTokenStore::new());
+        if path.exists() {
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|e| CliError::IoError(format!("Failed to read ledger {}: {}", path.display(), e)))?;
+            for (number, line) in contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+                let transaction: ResourceTransaction = serde_json::from_str(line).map_err(|e| {
+                    CliError::SerializationError(format!("Invalid ledger entry {}:{}: {}", path.display(), number + 1, e))
+                })?;
+                store.apply_transaction(&transaction).await.map_err(|e| {
+                    CliError::InvalidInput(format!("Cannot replay ledger entry {}:{}: {}", path.display(), number + 1, e))
+                })?;
+            }
+        }
+        Ok(Self { path, store })
+    }
+
+    pub(crate) fn store(&self) -> Arc<dyn TokenStore> {
+        self.store.clone()
+    }
+
+    /// Append transactions already applied to `store()`
+    pub(crate) fn record(&self, transactions: &[ResourceTransaction]) -> Result<(), CliError> {
+        if transactions.is_empty() {
+            return Ok(());
+        }
+        let mut lines = String::new();
+        for transaction in transactions {
+            let line = serde_json::to_string(transaction)
+                .map_err(|e| CliError::SerializationError(format!("Failed to serialize transaction: {}", e)))?;
+            lines.push_str(&line);
+            lines.push('\n');
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| CliError::IoError(format!("Failed to create {}: {}", parent.display(), e)))?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| CliError::IoError(format!("Failed to open ledger {}: {}", self.path.display(), e)))?;
+        std::io::Write::write_all(&mut file, lines.as_bytes())
+            .map_err(|e| CliError::IoError(format!("Failed to write ledger {}: {}", self.path.display(), e)))
+    }
 }
 
 impl ContextExtension for RuntimeExecutionContext {
@@ -116,7 +215,7 @@ impl ContextExtension for RuntimeExecutionContext {
     }
     
     fn federation_did(&self) -> Option<&Did> {
-        None // We don't have a federation DID in this simple implementation
+        self.federation_did.as_ref()
     }
     
     fn caller_did(&self) -> Option<&Did> {
@@ -126,11 +225,72 @@ impl ContextExtension for RuntimeExecutionContext {
         });
         Some(did)
     }
+
+    fn federation_keypair(&self) -> Option<DidKey> {
+        self.signer.clone()
+    }
+
+    fn dag_store(&self) -> Option<SharedDagStore> {
+        self.dag_store.clone()
+    }
+
+    fn token_store(&self) -> Option<Arc<dyn TokenStore>> {
+        self.token_store.clone()
+    }
 }
 
-// REMOVED IMPLEMENTATION OF HOSTCONTEXT TRAIT
-// We will not implement the HostContext trait at all, since it requires
-// wasmtime types that are causing compilation issues.
+#[async_trait]
+impl HostContext for RuntimeExecutionContext {
+    fn read_string(&self, _caller: &mut impl wasmtime::AsContextMut, _ptr: i32, _len: i32) -> Result<String> {
+        anyhow::bail!("read_string is not supported by the CLI runtime context")
+    }
+
+    fn write_string(&self, _caller: &mut impl wasmtime::AsContextMut, _ptr: i32, _max_len: i32, _s: &str) -> Result<i32> {
+        anyhow::bail!("write_string is not supported by the CLI runtime context")
+    }
+
+    fn malloc(&self, _caller: &mut impl wasmtime::AsContextMut, _size: i32) -> Result<i32> {
+        anyhow::bail!("malloc is not supported by the CLI runtime context")
+    }
+
+    fn free(&self, _caller: &mut impl wasmtime::AsContextMut, _ptr: i32) -> Result<()> {
+        anyhow::bail!("free is not supported by the CLI runtime context")
+    }
+
+    fn get_caller_did(&self) -> Did {
+        self.caller_did().cloned().unwrap_or_default()
+    }
+
+    fn log_message(&self, message: &str) {
+        if self.log_enabled {
+            println!("[wasm] {}", message);
+        }
+    }
+
+    async fn verify_signature(&self, did: &Did, message: &[u8], signature: &[u8]) -> bool {
+        verify_with_resolver(&DidKeyResolver, did, message, signature)
+    }
+
+    fn set_error(&self, message: String) {
+        *self.errors.lock().unwrap() = Some(message);
+    }
+
+    fn get_error(&self) -> Option<String> {
+        self.errors.lock().unwrap().clone()
+    }
+
+    fn clear_error(&self) {
+        *self.errors.lock().unwrap() = None;
+    }
+
+    fn policy_loader(&self) -> Option<Arc<dyn PolicyLoader + Send + Sync>> {
+        self.policy_loader.clone()
+    }
+
+    fn membership_index(&self) -> Option<Arc<dyn MembershipIndex + Send + Sync>> {
+        self.membership_index.clone()
+    }
+}
 
 pub async fn handle_runtime_command(
     context: &mut CliContext, 
@@ -146,9 +306,74 @@ pub async fn handle_runtime_command(
             println!("Would inspect module: {}", args.module_ref);
             Ok(())
         },
-        RuntimeCommands::Validate(args) => {
-            println!("Would validate module: {}", args.module_path);
-            Ok(())
-        },
+        RuntimeCommands::Validate(args) => validate_module(args),
+        RuntimeCommands::Daemon(args) => run_daemon(args).await,
+    }
+}
+
+async fn run_daemon(args: &DaemonArgs) -> CliResult {
+    let config = match &args.config {
+        Some(path) => {
+            let contents = std::fs::read_to_string(path)?;
+            toml::from_str::<RuntimeConfig>(&contents)
+                .map_err(|e| CliError::Config(format!("Invalid runtime config {}: {}", path.display(), e)))?
+        }
+        None => RuntimeConfig::default(),
+    };
+    let executor = ModernWasmExecutor::new()
+        .map_err(|e| CliError::Config(format!("Failed to create WASM executor: {}", e)))?;
+    let ctx = RuntimeExecutionContext::new(config.execution.clone(), args.verbose)?;
+    let daemon = RuntimeDaemon::new(&config.daemon, Arc::new(executor), Arc::new(ctx))
+        .map_err(|e| CliError::Config(format!("Failed to start runtime daemon: {}", e)))?;
+
+    println!("Runtime daemon listening on {}", config.daemon.listen_addr);
+    tokio::select! {
+        result = daemon.clone().run() => {
+            result.map_err(|e| CliError::Any(anyhow::anyhow!("Runtime daemon stopped: {}", e)))
+        }
+        result = api::serve(daemon, config.daemon.listen_addr) => {
+            result.map_err(|e| CliError::Any(anyhow::anyhow!("Runtime API stopped: {}", e)))
+        }
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    }
+}
+
+fn validate_module(args: &ValidateModuleArgs) -> CliResult {
+    let policy = match &args.policy_file {
+        Some(path) => serde_json::from_slice::<ValidationPolicy>(&std::fs::read(path)?)?,
+        None => ValidationPolicy::default(),
+    };
+    let wasm_bytes = std::fs::read(&args.module_path)?;
+    let executor = ModernWasmExecutor::new()
+        .map_err(|e| CliError::Config(format!("Failed to create WASM executor: {}", e)))?;
+    let report = executor.validate_module(&wasm_bytes, &policy);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("Module: {}", args.module_path);
+        println!("Entry point: {}", report.entry_point.as_deref().unwrap_or("none"));
+        if let Some(pages) = report.memory_pages {
+            println!("Memory: {} pages", pages);
+        }
+        for import in &report.imports {
+            println!("Import: {}::{}", import.module, import.name);
+        }
+        for issue in &report.issues {
+            println!("Issue: {}", issue);
+        }
+        if report.is_valid() {
+            println!("Module is valid");
+        }
+    }
+
+    if report.is_valid() {
+        Ok(())
+    } else {
+        Err(CliError::VerificationFailed(format!(
+            "{} failed validation with {} issue(s)",
+            args.module_path,
+            report.issues.len()
+        )))
     }
 } 
\ No newline at end of file
@@ -1,11 +1,12 @@
 use clap::{Args, Subcommand, ValueHint};
 use crate::context::{CliContext, get_cid};
 use crate::error::{CliError, CliResult};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
 use icn_types::dag::{DagNodeBuilder, DagPayload, NodeScope};
 use icn_types::Did;
 use serde_json::json;
+use icn_ccl_compiler::{compile_policy, PolicyScope};
 use crate::commands::observability::{ObservabilityCommands, ScopeObservabilityOptions, handle_dag_view, handle_inspect_policy, handle_activity_log};
 
 /// Enumeration of supported scope types
@@ -142,7 +143,7 @@ pub enum ScopeCommands {
         #[command(flatten)]
         options: ScopeOptions,
         
-        /// Path to a JSON file containing the policy configuration
+        /// Path to a JSON policy configuration, or a `.ccl` file with a `policy` block
         #[arg(long, value_hint = ValueHint::FilePath)]
         policy_file: PathBuf,
     },
@@ -154,7 +155,7 @@ pub enum ScopeCommands {
         #[command(flatten)]
         options: ScopeOptions,
         
-        /// Path to a JSON file containing the new policy configuration
+        /// Path to the new JSON policy configuration, or a `.ccl` file with a `policy` block
         #[arg(long, value_hint = ValueHint::FilePath)]
         policy_file: PathBuf,
         
@@ -652,12 +653,7 @@ pub async fn handle_scope_command(command: &ScopeCommands, ctx: &mut CliContext)
             let did = Did::from_string(&did_key.to_did_string())?;
             
             // Read policy configuration from file
-            let policy_str = fs::read_to_string(policy_file)
-                .map_err(|e| CliError::IoError(format!("Failed to read policy file: {}", e)))?;
-            
-            // Parse policy from JSON
-            let mut policy_config: icn_types::ScopePolicyConfig = serde_json::from_str(&policy_str)
-                .map_err(|e| CliError::SerializationError(format!("Failed to parse policy: {}", e)))?;
+            let mut policy_config = read_policy_file(policy_file, &scope_type, &options.scope_id)?;
             
             // Override scope_type and scope_id to ensure they match options
             policy_config.scope_type = scope_type.to_node_scope();
@@ -730,12 +726,7 @@ pub async fn handle_scope_command(command: &ScopeCommands, ctx: &mut CliContext)
             let did = Did::from_string(&did_key.to_did_string())?;
             
             // Read policy configuration from file
-            let policy_str = fs::read_to_string(policy_file)
-                .map_err(|e| CliError::IoError(format!("Failed to read policy file: {}", e)))?;
-            
-            // Parse policy from JSON
-            let policy_config: icn_types::ScopePolicyConfig = serde_json::from_str(&policy_str)
-                .map_err(|e| CliError::SerializationError(format!("Failed to parse policy: {}", e)))?;
+            let policy_config = read_policy_file(policy_file, &scope_type, &options.scope_id)?;
             
             // Create a policy update proposal node
             let node_type = "PolicyUpdateProposal";
@@ -992,4 +983,92 @@ pub async fn handle_scope_command(command: &ScopeCommands, ctx: &mut CliContext)
 fn cid_from_string(cid_str: &str) -> CliResult<icn_types::Cid> {
     icn_types::Cid::from_bytes(cid_str.as_bytes())
         .map_err(|e| CliError::SerializationError(format!("Invalid CID: {}", e)))
-} 
\ No newline at end of file
+}
+
+/// Read a policy for `scope_id`, either as `ScopePolicyConfig` JSON or, for
+/// `.ccl` files, by compiling the file's `policy` block
+fn read_policy_file(path: &Path, scope_type: &ScopeType, scope_id: &str) -> CliResult<icn_types::ScopePolicyConfig> {
+    let policy_str = fs::read_to_string(path)
+        .map_err(|e| CliError::IoError(format!("Failed to read policy file: {}", e)))?;
+    
+    let policy_json = if path.extension().map_or(false, |ext| ext == "ccl") {
+        let policy_scope = match scope_type {
+            ScopeType::Cooperative => PolicyScope::Cooperative,
+            ScopeType::Community => PolicyScope::Community,
+        };
+        compile_policy(&policy_str, policy_scope, scope_id)
+            .map_err(|e| CliError::InvalidInput(format!("CCL policy error: {}", e)))?
+            .to_json_string()
+    } else {
+        policy_str
+    };
+    
+    serde_json::from_str(&policy_json)
+        .map_err(|e| CliError::SerializationError(format!("Failed to parse policy: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use icn_ccl_compiler::PolicyDid;
+    use icn_types::dag::EventPayload;
+    use icn_types::ScopePolicyConfig;
+
+    fn did(byte: u8) -> String {
+        PolicyDid { public_key_bytes: vec![byte; 32] }.to_string()
+    }
+
+    fn ccl_policy() -> String {
+        format!(
+            r#"policy {{
+                allow "submit_proposal" to members_of("coop-x");
+                allow "mint_token" to dids("{}", "{}");
+                allow "anchor_data" to anyone;
+            }}"#,
+            did(1),
+            did(2)
+        )
+    }
+
+    #[test]
+    fn test_ccl_policy_file_reads_as_scope_policy_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("policy.ccl");
+        fs::write(&path, ccl_policy()).unwrap();
+
+        let config = read_policy_file(&path, &ScopeType::Cooperative, "coop-x").expect("policy");
+
+        assert_eq!(config.scope_type, NodeScope::Cooperative);
+        assert_eq!(config.scope_id, "coop-x");
+        let rules: Vec<_> = config.allowed_actions.iter().map(|rule| rule.action_type.as_str()).collect();
+        assert_eq!(rules, ["submit_proposal", "mint_token", "anchor_data"]);
+        assert_eq!(config.allowed_actions[0].required_membership.as_deref(), Some("coop-x"));
+        assert_eq!(
+            config.allowed_actions[1].allowed_dids,
+            Some(vec![Did::from_string(&did(1)).unwrap(), Did::from_string(&did(2)).unwrap()])
+        );
+        assert_eq!(config.allowed_actions[2].allowed_dids, None);
+    }
+
+    #[test]
+    fn test_compiled_policy_is_a_valid_update_proposal() {
+        let compiled = compile_policy(&ccl_policy(), PolicyScope::Cooperative, "coop-x").expect("compile");
+        let payload = EventPayload::policy_update_proposal(
+            "Cooperative",
+            "coop-x",
+            compiled.to_json_string(),
+            did(1),
+            "Restrict minting",
+        );
+
+        let payload: EventPayload = serde_json::from_str(&serde_json::to_string(&payload).unwrap()).unwrap();
+        let proposed_policy = match payload {
+            EventPayload::PolicyUpdateProposal { proposed_policy, .. } => proposed_policy,
+            other => panic!("expected a policy update proposal, got {:?}", other),
+        };
+        let config: ScopePolicyConfig = serde_json::from_str(&proposed_policy).expect("ScopePolicyConfig");
+        assert_eq!(config.scope_type, NodeScope::Cooperative);
+        assert_eq!(config.allowed_actions.len(), 3);
+        assert_eq!(config.to_json_string().unwrap(), compiled.to_json_string());
+    }
+}
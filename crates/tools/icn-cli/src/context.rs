@@ -9,6 +9,7 @@ use ed25519_dalek::{VerifyingKey, SigningKey, Signer};
 use hex;
 use serde::{Deserialize, Serialize};
 use icn_types::dag::memory::MemoryDagStore;
+use icn_types::dag::sqlite::SqliteDagStore;
 use thiserror::Error;
 use std::collections::HashMap;
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
@@ -103,6 +104,65 @@ struct KeyFileJson {
     created: Option<String>,
 }
 
+/// DAG store backend selected by a `--dag-dir` value.
+///
+/// The backend is chosen by URL scheme: `sqlite://<file>`, `rocksdb://<dir>` or
+/// `memory://`. A plain path uses the default backend (RocksDB when the
+/// `persistence` feature is enabled, in-memory otherwise).
+#[derive(Debug, Clone, PartialEq)]
+pub enum DagStoreLocation {
+    Default(PathBuf),
+    RocksDb(PathBuf),
+    Sqlite(PathBuf),
+    Memory,
+}
+
+impl DagStoreLocation {
+    /// Parse a `--dag-dir` value into a store location
+    pub fn parse(path: &Path) -> Self {
+        let raw = path.to_string_lossy();
+        if let Some(rest) = raw.strip_prefix("sqlite://") {
+            DagStoreLocation::Sqlite(PathBuf::from(rest))
+        } else if let Some(rest) = raw.strip_prefix("rocksdb://") {
+            DagStoreLocation::RocksDb(PathBuf::from(rest))
+        } else if raw.starts_with("memory://") {
+            DagStoreLocation::Memory
+        } else {
+            DagStoreLocation::Default(path.to_path_buf())
+        }
+    }
+
+    /// Open the DAG store at this location
+    pub fn open(&self) -> Result<Arc<dyn DagStore + Send + Sync>, CliError> {
+        match self {
+            DagStoreLocation::Sqlite(db_path) => {
+                if let Some(parent) = db_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(parent).map_err(CliError::Io)?;
+                }
+                let store = SqliteDagStore::open(db_path).map_err(CliError::Dag)?;
+                Ok(Arc::new(store))
+            }
+            DagStoreLocation::Memory => Ok(Arc::new(MemoryDagStore::new())),
+            #[cfg(feature = "persistence")]
+            DagStoreLocation::RocksDb(store_path) | DagStoreLocation::Default(store_path) => {
+                use icn_types::dag::rocksdb::RocksDbDagStore;
+                std::fs::create_dir_all(store_path).map_err(CliError::Io)?;
+                let store = RocksDbDagStore::open(store_path).map_err(CliError::Dag)?;
+                Ok(Arc::new(store))
+            }
+            #[cfg(not(feature = "persistence"))]
+            DagStoreLocation::RocksDb(_) => Err(CliError::Config(
+                "rocksdb:// DAG stores require the `persistence` feature".to_string(),
+            )),
+            #[cfg(not(feature = "persistence"))]
+            DagStoreLocation::Default(_) => {
+                eprintln!("Warning: Persistence feature not enabled, using in-memory DAG store.");
+                Ok(Arc::new(MemoryDagStore::new()))
+            }
+        }
+    }
+}
+
 pub struct CliContext {
     _config_dir: PathBuf,
     _default_key_path: PathBuf,
@@ -148,32 +208,16 @@ impl CliContext {
 
     pub fn get_dag_store(&mut self, path_opt: Option<&Path>) -> Result<MutableDagStore, CliError> {
         if self.dag_store.is_none() {
-            let store_path = path_opt.map(|p| p.to_path_buf()).unwrap_or_else(|| {
-                self._config_dir.join("dag_store")
-            });
-            
-             if !store_path.exists() {
-                 std::fs::create_dir_all(&store_path).map_err(|e| CliError::Io(e))?;
-             }
+            let location = match path_opt {
+                Some(p) => DagStoreLocation::parse(p),
+                None => DagStoreLocation::Default(self._config_dir.join("dag_store")),
+            };
 
             if self.verbose {
-                println!("Initializing DAG store at: {:?}", store_path);
+                println!("Initializing DAG store: {:?}", location);
             }
 
-            // Correct usage of RocksDbDagStore
-            #[cfg(feature = "persistence")]
-            {
-                // Need to import RocksDbDagStore when used
-                use icn_types::dag::rocksdb::RocksDbDagStore;
-                let store = RocksDbDagStore::new(store_path).map_err(CliError::Dag)?;
-                self.dag_store = Some(Arc::new(store));
-            }
-            #[cfg(not(feature = "persistence"))]
-            {
-                 eprintln!("Warning: Persistence feature not enabled, using in-memory DAG store.");
-                 let store = MemoryDagStore::new(); // Use correct name
-                 self.dag_store = Some(Arc::new(store));
-            }
+            self.dag_store = Some(location.open()?);
         }
         
         // Return a mutable wrapper
@@ -341,6 +385,10 @@ impl icn_types::dag::DagStore for MutableDagStore {
     async fn verify_branch(&self, tip: &Cid, resolver: &(dyn PublicKeyResolver + Send + Sync)) -> Result<(), DagError> {
         self.inner.verify_branch(tip, resolver).await
     }
+    
+    async fn subscribe(&self, filter: icn_types::dag::DagFeedFilter, from: Option<icn_types::dag::DagCursor>) -> Result<icn_types::dag::DagSubscription, DagError> {
+        self.inner.subscribe(filter, from).await
+    }
 } 
 
 #[cfg(test)]
@@ -358,4 +406,21 @@ mod tests {
         
         assert_eq!(actual_path, expected_path, "Default DAG index path should match the expected value");
     }
+
+    #[test]
+    fn test_dag_store_location_parse() {
+        assert_eq!(
+            DagStoreLocation::parse(Path::new("sqlite:///var/icn/dag.sqlite")),
+            DagStoreLocation::Sqlite(PathBuf::from("/var/icn/dag.sqlite"))
+        );
+        assert_eq!(
+            DagStoreLocation::parse(Path::new("rocksdb://data/dag")),
+            DagStoreLocation::RocksDb(PathBuf::from("data/dag"))
+        );
+        assert_eq!(DagStoreLocation::parse(Path::new("memory://")), DagStoreLocation::Memory);
+        assert_eq!(
+            DagStoreLocation::parse(Path::new("data/dag")),
+            DagStoreLocation::Default(PathBuf::from("data/dag"))
+        );
+    }
 } 
\ No newline at end of file
@@ -57,6 +57,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Vote(cmd) => {
             icn_cli::commands::vote::handle_vote_commands(cmd.clone(), &mut ctx).await?;
         }
+        Commands::Ccl(cmd) => {
+            icn_cli::commands::ccl::handle_ccl_command(&mut ctx, cmd).await?;
+        }
         Commands::Observe(obs_cmd) => {
             match obs_cmd {
                 icn_cli::commands::observability::ObservabilityCommands::DagView(options) => {
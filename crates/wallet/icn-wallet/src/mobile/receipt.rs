@@ -156,13 +156,24 @@ fn parse_scope(scope: Option<String>) -> Option<ExecutionScope> {
 }
 
 fn parse_status(status: Option<String>) -> Option<ExecutionStatus> {
-    status.and_then(|s| match s.to_lowercase().as_str() {
+    status.and_then(|s| status_from_str(&s))
+}
+
+/// Accepts both the serialized (`out_of_fuel`) and the `Debug` (`OutOfFuel`)
+/// spelling, as `SerializedReceipt::status` uses the latter
+fn status_from_str(status: &str) -> Option<ExecutionStatus> {
+    match status.to_lowercase().replace('_', "").as_str() {
         "pending" => Some(ExecutionStatus::Pending),
         "success" => Some(ExecutionStatus::Success),
         "failed" => Some(ExecutionStatus::Failed),
         "canceled" => Some(ExecutionStatus::Canceled),
+        "outoffuel" => Some(ExecutionStatus::OutOfFuel),
+        "timeout" => Some(ExecutionStatus::Timeout),
+        "memorylimit" => Some(ExecutionStatus::MemoryLimit),
+        "tablelimit" => Some(ExecutionStatus::TableLimit),
+        "denied" => Some(ExecutionStatus::Denied),
         _ => None,
-    })
+    }
 }
 
 /// A serializable version of StoredReceipt for FFI
@@ -244,13 +255,8 @@ impl TryFrom<SerializedReceipt> for StoredReceipt {
             None
         };
         
-        let status = match ser.status.to_lowercase().as_str() {
-            "pending" => ExecutionStatus::Pending,
-            "success" => ExecutionStatus::Success,
-            "failed" => ExecutionStatus::Failed,
-            "canceled" => ExecutionStatus::Canceled,
-            _ => ExecutionStatus::Pending, // Default
-        };
+        let status = status_from_str(&ser.status)
+            .ok_or_else(|| format!("Unknown execution status: {}", ser.status))?;
         
         let scope = match ser.scope.to_lowercase().as_str() {
             "federation" => ExecutionScope::Federation {
@@ -311,4 +317,31 @@ impl TryFrom<SerializedReceipt> for StoredReceipt {
             wallet_stored_at: ser.wallet_stored_at,
         })
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_status_survives_serialization() {
+        let statuses = [
+            ExecutionStatus::Pending,
+            ExecutionStatus::Success,
+            ExecutionStatus::Failed,
+            ExecutionStatus::Canceled,
+            ExecutionStatus::OutOfFuel,
+            ExecutionStatus::Timeout,
+            ExecutionStatus::MemoryLimit,
+            ExecutionStatus::TableLimit,
+            ExecutionStatus::Denied,
+        ];
+        for status in statuses {
+            // `SerializedReceipt` spelling and the receipt's own JSON spelling
+            assert_eq!(status_from_str(&format!("{:?}", status)), Some(status.clone()));
+            let json = serde_json::to_value(&status).unwrap();
+            assert_eq!(status_from_str(json.as_str().unwrap()), Some(status));
+        }
+        assert_eq!(status_from_str("exploded"), None);
+    }
+}